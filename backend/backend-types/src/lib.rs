@@ -1,6 +1,9 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use shengji_core::{game_state, interactive};
+use shengji_mechanics::bidding::Bid;
+use shengji_mechanics::scoring::ScoreBand;
+use shengji_mechanics::trick::CompactTrick;
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
@@ -23,12 +26,47 @@ pub enum GameMessage {
         from: String,
     },
     Error(String),
+    /// An error which clients can act on, sent only to the player whose
+    /// action failed.
+    ActionError {
+        error: interactive::ActionError,
+        message: String,
+    },
     Header {
         messages: Vec<String>,
     },
     Kicked {
         target: String,
     },
+    /// The finished tricks of the current hand, sent only to the player who
+    /// asked for them.
+    TrickHistory {
+        tricks: Vec<CompactTrick>,
+    },
+    /// The last finished trick, sent only to the player who asked for it.
+    LastTrick {
+        last_trick: Option<game_state::play_phase::LastTrick>,
+    },
+    /// The bids the player could make right now, sent only to the player who
+    /// asked for them.
+    ValidBids {
+        bids: Vec<Bid>,
+    },
+    /// The results the current game could still end in, sent only to the
+    /// player who asked for them.
+    RemainingOutcomes {
+        bands: Vec<ScoreBand>,
+    },
+    /// The room's standings across every game played in it, sent only to the
+    /// player who asked for them.
+    SeasonStandings {
+        standings: Vec<game_state::score_ledger::SeasonStanding>,
+    },
+    /// Every game played in the room, oldest first, sent only to the player
+    /// who asked for it.
+    ScoreLedger {
+        entries: Vec<game_state::score_ledger::LedgerEntry>,
+    },
 }
 
 /// zstd dictionary, compressed with zstd.
@@ -29,7 +29,9 @@ use axum::{
 use tower_http::services::ServeDir;
 
 use shengji_core::settings;
-use shengji_mechanics::types::FULL_DECK;
+use shengji_mechanics::naming::CardNames;
+use shengji_mechanics::ordered_card::warm_caches;
+use shengji_mechanics::types::Card;
 use shengji_types::ZSTD_ZSTD_DICT;
 use storage::{HashMapStorage, Storage};
 
@@ -46,7 +48,8 @@ static NEXT_USER_ID: AtomicUsize = AtomicUsize::new(1);
 
 lazy_static::lazy_static! {
     static ref CARDS_JSON: CardsBlob = CardsBlob {
-        cards: FULL_DECK.iter().map(|c| c.as_info()).collect()
+        cards: Card::all().map(|c| c.as_info()).collect(),
+        names: CardNames::of(Card::all()),
     };
 
     static ref ROOT_LOGGER: Logger = {
@@ -82,6 +85,9 @@ lazy_static::lazy_static! {
     static ref WEBSOCKET_HOST: Option<String> = {
         std::env::var("WEBSOCKET_HOST").ok()
     };
+    static ref WARM_CACHE_CARDS: usize = {
+        std::env::var("WARM_CACHE_CARDS").ok().and_then(|v| v.parse().ok()).unwrap_or(16)
+    };
 }
 
 async fn runtime_settings() -> impl IntoResponse {
@@ -109,6 +115,13 @@ async fn main() -> Result<(), anyhow::Error> {
     })
     .unwrap();
 
+    // Precompute the play decompositions up-front, rather than stalling the
+    // first game which sees a large throw.
+    tokio::task::spawn_blocking(|| {
+        warm_caches(*WARM_CACHE_CARDS);
+        info!(ROOT_LOGGER, "Warmed decomposition caches"; "max_cards" => *WARM_CACHE_CARDS);
+    });
+
     let (backend_storage, stats) = state_dump::load_state().await?;
 
     tokio::task::spawn(periodically_dump_state(
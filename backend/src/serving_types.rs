@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 
 use shengji_core::interactive::Action;
+use shengji_mechanics::naming::CardNames;
 use shengji_mechanics::types::{CardInfo, PlayerID};
 use shengji_types::GameMessage;
 use storage::State;
@@ -52,9 +53,16 @@ pub enum UserMessage {
     Beep,
     ReadyCheck,
     Ready,
+    TrickHistory,
+    LastTrick,
+    ValidBids,
+    SeasonStandings,
+    ScoreLedger,
+    RemainingOutcomes,
 }
 
 #[derive(Clone, Serialize)]
 pub struct CardsBlob {
     pub cards: Vec<CardInfo>,
+    pub names: Vec<CardNames>,
 }
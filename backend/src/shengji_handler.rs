@@ -142,7 +142,14 @@ async fn player_subscribe_task(
                 | GameMessage::Broadcast { .. }
                 | GameMessage::Message { .. }
                 | GameMessage::Error(_)
-                | GameMessage::Header { .. } => true,
+                | GameMessage::ActionError { .. }
+                | GameMessage::Header { .. }
+                | GameMessage::TrickHistory { .. }
+                | GameMessage::LastTrick { .. }
+                | GameMessage::ValidBids { .. }
+                | GameMessage::SeasonStandings { .. }
+                | GameMessage::ScoreLedger { .. }
+                | GameMessage::RemainingOutcomes { .. } => true,
                 GameMessage::Beep { target } | GameMessage::Kicked { target } => *target == name_,
                 GameMessage::ReadyCheck { from } => *from != name_,
             };
@@ -368,6 +375,144 @@ async fn handle_user_action<S: Storage<VersionedGame, E>, E: Send>(
                 )
                 .await?;
         }
+        UserMessage::TrickHistory => {
+            let (history_tx, history_rx) = oneshot::channel();
+            execute_immutable_operation(
+                ws_id,
+                room_name,
+                backend_storage.clone(),
+                move |game, _| {
+                    let _ = history_tx.send(game.trick_history(caller)?);
+                    Ok(vec![])
+                },
+                "fetch trick history",
+            )
+            .await;
+            if let Ok(tricks) = history_rx.await {
+                backend_storage
+                    .publish_to_single_subscriber(
+                        room_name.as_bytes().to_vec(),
+                        ws_id,
+                        GameMessage::TrickHistory { tricks },
+                    )
+                    .await?;
+            }
+        }
+        UserMessage::LastTrick => {
+            let (last_trick_tx, last_trick_rx) = oneshot::channel();
+            execute_immutable_operation(
+                ws_id,
+                room_name,
+                backend_storage.clone(),
+                move |game, _| {
+                    let _ = last_trick_tx.send(game.last_trick(caller)?);
+                    Ok(vec![])
+                },
+                "fetch last trick",
+            )
+            .await;
+            if let Ok(last_trick) = last_trick_rx.await {
+                backend_storage
+                    .publish_to_single_subscriber(
+                        room_name.as_bytes().to_vec(),
+                        ws_id,
+                        GameMessage::LastTrick { last_trick },
+                    )
+                    .await?;
+            }
+        }
+        UserMessage::ValidBids => {
+            let (bids_tx, bids_rx) = oneshot::channel();
+            execute_immutable_operation(
+                ws_id,
+                room_name,
+                backend_storage.clone(),
+                move |game, _| {
+                    let _ = bids_tx.send(game.valid_bids(caller)?);
+                    Ok(vec![])
+                },
+                "fetch valid bids",
+            )
+            .await;
+            if let Ok(bids) = bids_rx.await {
+                backend_storage
+                    .publish_to_single_subscriber(
+                        room_name.as_bytes().to_vec(),
+                        ws_id,
+                        GameMessage::ValidBids { bids },
+                    )
+                    .await?;
+            }
+        }
+        UserMessage::RemainingOutcomes => {
+            let (bands_tx, bands_rx) = oneshot::channel();
+            execute_immutable_operation(
+                ws_id,
+                room_name,
+                backend_storage.clone(),
+                move |game, _| {
+                    let _ = bands_tx.send(game.remaining_outcomes()?);
+                    Ok(vec![])
+                },
+                "fetch remaining outcomes",
+            )
+            .await;
+            if let Ok(bands) = bands_rx.await {
+                backend_storage
+                    .publish_to_single_subscriber(
+                        room_name.as_bytes().to_vec(),
+                        ws_id,
+                        GameMessage::RemainingOutcomes { bands },
+                    )
+                    .await?;
+            }
+        }
+        UserMessage::SeasonStandings => {
+            let (standings_tx, standings_rx) = oneshot::channel();
+            execute_immutable_operation(
+                ws_id,
+                room_name,
+                backend_storage.clone(),
+                move |game, _| {
+                    let _ = standings_tx.send(game.season_standings());
+                    Ok(vec![])
+                },
+                "fetch season standings",
+            )
+            .await;
+            if let Ok(standings) = standings_rx.await {
+                backend_storage
+                    .publish_to_single_subscriber(
+                        room_name.as_bytes().to_vec(),
+                        ws_id,
+                        GameMessage::SeasonStandings { standings },
+                    )
+                    .await?;
+            }
+        }
+        UserMessage::ScoreLedger => {
+            let (ledger_tx, ledger_rx) = oneshot::channel();
+            execute_immutable_operation(
+                ws_id,
+                room_name,
+                backend_storage.clone(),
+                move |game, _| {
+                    let _ = ledger_tx.send(game.score_ledger());
+                    Ok(vec![])
+                },
+                "fetch score ledger",
+            )
+            .await;
+            if let Ok(entries) = ledger_rx.await {
+                backend_storage
+                    .publish_to_single_subscriber(
+                        room_name.as_bytes().to_vec(),
+                        ws_id,
+                        GameMessage::ScoreLedger { entries },
+                    )
+                    .await?;
+            }
+        }
         UserMessage::Kick(id) => {
             info!(logger, "Kicking user"; "other" => id.0);
             execute_operation(
@@ -3,7 +3,7 @@ use std::io::{self, ErrorKind};
 
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-use shengji_core::interactive::InteractiveGame;
+use shengji_core::interactive::{ActionError, InteractiveGame};
 use shengji_mechanics::types::PlayerID;
 use shengji_types::GameMessage;
 use storage::Storage;
@@ -81,8 +81,8 @@ where
                 .await;
             false
         }
-        Err(EitherError::E2(msg)) => {
-            let err = GameMessage::Error(format!("Failed to {action_description}: {msg}"));
+        Err(EitherError::E2(e)) => {
+            let err = error_message(action_description, &e);
             let _ = backend_storage
                 .publish_to_single_subscriber(room_name_, ws_id, err)
                 .await;
@@ -149,8 +149,8 @@ where
                 .await;
             false
         }
-        Err(EitherError::E2(msg)) => {
-            let err = GameMessage::Error(format!("Failed to {action_description}: {msg}"));
+        Err(EitherError::E2(e)) => {
+            let err = error_message(action_description, &e);
             let _ = backend_storage
                 .publish_to_single_subscriber(room_name_, ws_id, err)
                 .await;
@@ -159,6 +159,14 @@ where
     }
 }
 
+fn error_message(action_description: &str, e: &anyhow::Error) -> GameMessage {
+    let message = format!("Failed to {action_description}: {e}");
+    match ActionError::from_error(e) {
+        Some(error) => GameMessage::ActionError { error, message },
+        None => GameMessage::Error(message),
+    }
+}
+
 enum EitherError<E> {
     E(E),
     E2(anyhow::Error),
@@ -199,6 +199,7 @@ fn main() {
                                     ),
                                     format.iter().cloned(),
                                     s.propagated().trick_draw_policy(),
+                                    trick_format.adjacency_policy(),
                                 );
 
                                 playable.next().map(|u| {
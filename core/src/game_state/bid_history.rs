@@ -0,0 +1,101 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use shengji_mechanics::bidding::Bid;
+
+use crate::game_state::now_millis;
+
+/// A bid as it was made, and what became of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BidRecord {
+    pub bid: Bid,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The number of cards left in the deck, or zero once drawing is done.
+    pub cards_left: usize,
+    /// The index of the bid which outbid this one, if any.
+    pub superseded_by: Option<usize>,
+    pub taken_back: bool,
+}
+
+/// Every bid made this hand in the order they were made, so the declaration
+/// can be replayed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct BidHistory(Vec<BidRecord>);
+
+impl BidHistory {
+    pub fn records(&self) -> &[BidRecord] {
+        &self.0
+    }
+
+    /// The bid which is currently winning, i.e. neither outbid nor taken back.
+    fn winning_idx(&self) -> Option<usize> {
+        self.0
+            .iter()
+            .rposition(|r| r.superseded_by.is_none() && !r.taken_back)
+    }
+
+    /// Records a bid which was just accepted, and so outbids the winning one.
+    pub fn record(&mut self, bid: Bid, cards_left: usize) {
+        let idx = self.0.len();
+        if let Some(winning_idx) = self.winning_idx() {
+            self.0[winning_idx].superseded_by = Some(idx);
+        }
+        self.0.push(BidRecord {
+            bid,
+            timestamp: now_millis(),
+            cards_left,
+            superseded_by: None,
+            taken_back: false,
+        });
+    }
+
+    /// Records that the bid was taken back, which puts the bid it outbid back
+    /// in the lead.
+    pub fn record_takeback(&mut self, bid: Bid) {
+        if let Some(idx) = self.0.iter().rposition(|r| r.bid == bid && !r.taken_back) {
+            self.0[idx].taken_back = true;
+            for r in &mut self.0 {
+                if r.superseded_by == Some(idx) {
+                    r.superseded_by = None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shengji_mechanics::bidding::Bid;
+    use shengji_mechanics::types::{cards::*, PlayerID};
+
+    use super::BidHistory;
+
+    #[test]
+    fn test_bid_history() {
+        let bid = |id, card, count| Bid {
+            id: PlayerID(id),
+            card,
+            count,
+            epoch: 0,
+        };
+        let mut history = BidHistory::default();
+        history.record(bid(0, H_2, 1), 10);
+        history.record(bid(1, S_2, 2), 8);
+        history.record(bid(2, D_2, 3), 0);
+        history.record_takeback(bid(2, D_2, 3));
+
+        let records = history.records();
+        assert_eq!(
+            records
+                .iter()
+                .map(|r| (r.cards_left, r.superseded_by, r.taken_back))
+                .collect::<Vec<_>>(),
+            vec![(10, Some(1), false), (8, None, false), (0, None, true)]
+        );
+
+        history.record(bid(0, H_2, 3), 0);
+        assert_eq!(history.records()[1].superseded_by, Some(3));
+    }
+}
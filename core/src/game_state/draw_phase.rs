@@ -1,17 +1,32 @@
 use anyhow::{anyhow, bail, Error};
+use rand::seq::SliceRandom;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use shengji_mechanics::bidding::Bid;
+use shengji_mechanics::bidding::{Bid, BidPriorityPolicy, BidReinforcementPolicy, BidRules};
 use shengji_mechanics::deck::Deck;
 use shengji_mechanics::hands::Hands;
-use shengji_mechanics::types::{Card, PlayerID, Rank, Trump};
+use shengji_mechanics::types::{Card, PermanentTrump, PlayerID, Rank, Suit, Trump};
 
 use crate::message::MessageVariant;
-use crate::settings::{FirstLandlordSelectionPolicy, GameMode, KittyBidPolicy, PropagatedState};
+use crate::settings::{
+    BlindBidPolicy, FirstLandlordSelectionPolicy, GameMode, KittyBidPolicy, NoBidPolicy,
+    PropagatedState, TrumpSelectionPolicy,
+};
 
+use crate::game_state::bid_history::BidHistory;
 use crate::game_state::exchange_phase::ExchangePhase;
 use crate::game_state::initialize_phase::InitializePhase;
+use crate::game_state::{joker_counts, unreveal_bid};
+
+/// A player's sealed bid, under `TrumpSelectionPolicy::SealedBids`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SealedBid {
+    pub id: PlayerID,
+    /// The bid, or `None` for a pass. Other players' bids are hidden until
+    /// they're all revealed.
+    pub bid: Option<Bid>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DrawPhase {
@@ -21,6 +36,17 @@ pub struct DrawPhase {
     propagated: PropagatedState,
     hands: Hands,
     bids: Vec<Bid>,
+    /// The number of cards left in the deck when each of `bids` was made.
+    #[serde(default)]
+    bid_deck_sizes: Vec<usize>,
+    #[serde(default)]
+    bid_history: BidHistory,
+    /// The bids which were made blind; see `BlindBidPolicy`.
+    #[serde(default)]
+    blind_bids: Vec<Bid>,
+    /// The sealed bids submitted so far, in the order they were submitted.
+    #[serde(default)]
+    sealed_bids: Vec<SealedBid>,
     #[serde(default)]
     autobid: Option<Bid>,
     position: usize,
@@ -32,6 +58,12 @@ pub struct DrawPhase {
     removed_cards: Vec<Card>,
     #[serde(default)]
     decks: Vec<Deck>,
+    /// The deck each card in `deck` came from; see `Hands::deck_indices`.
+    #[serde(default)]
+    deck_indices: Vec<usize>,
+    /// The deck each card in `kitty` came from.
+    #[serde(default)]
+    kitty_deck_indices: Vec<usize>,
 }
 
 impl DrawPhase {
@@ -41,13 +73,23 @@ impl DrawPhase {
         position: usize,
         deck: Vec<Card>,
         kitty: Vec<Card>,
+        mut deck_indices: Vec<usize>,
         num_decks: usize,
         game_mode: GameMode,
         level: Option<Rank>,
         decks: Vec<Deck>,
         removed_cards: Vec<Card>,
     ) -> Self {
+        // `deck_indices` covers the deck followed by the kitty.
+        let kitty_deck_indices = if deck_indices.len() == deck.len() + kitty.len() {
+            deck_indices.split_off(deck.len())
+        } else {
+            deck_indices.clear();
+            vec![]
+        };
         DrawPhase {
+            deck_indices,
+            kitty_deck_indices,
             hands: Hands::new(propagated.players.iter().map(|p| p.id)),
             deck,
             kitty,
@@ -59,6 +101,10 @@ impl DrawPhase {
             level,
             removed_cards,
             bids: Vec::new(),
+            bid_deck_sizes: Vec::new(),
+            bid_history: BidHistory::default(),
+            blind_bids: Vec::new(),
+            sealed_bids: Vec::new(),
             revealed_cards: 0,
             autobid: None,
         }
@@ -84,6 +130,18 @@ impl DrawPhase {
         &self.kitty
     }
 
+    pub fn hands(&self) -> &Hands {
+        &self.hands
+    }
+
+    pub fn bids(&self) -> &[Bid] {
+        &self.bids
+    }
+
+    pub fn bid_history(&self) -> &BidHistory {
+        &self.bid_history
+    }
+
     #[cfg(test)]
     pub fn deck_mut(&mut self) -> &mut Vec<Card> {
         &mut self.deck
@@ -123,20 +181,66 @@ impl DrawPhase {
         }
     }
 
-    pub fn draw_card(&mut self, id: PlayerID) -> Result<(), Error> {
+    /// Draws the next card, which may set trump under
+    /// `TrumpSelectionPolicy::FirstRankCardDrawn`.
+    pub fn draw_card(&mut self, id: PlayerID) -> Result<Option<MessageVariant>, Error> {
         if id != self.propagated.players[self.position].id {
             bail!("not your turn!");
         }
+        // The deck indices may be missing, e.g. for games started before they
+        // were tracked.
+        let deck_index = if self.deck_indices.len() == self.deck.len() {
+            self.deck_indices.pop()
+        } else {
+            None
+        };
         if let Some(next_card) = self.deck.pop() {
-            self.hands.add(id, Some(next_card))?;
+            self.hands
+                .add_from_decks(id, Some((next_card, deck_index)))?;
             self.position = (self.position + 1) % self.propagated.players.len();
-            Ok(())
+            self.check_trump_rank_card(id, next_card)
         } else {
             bail!("no cards left in deck")
         }
     }
 
+    /// Sets trump from the card just drawn, if it's the first card of the
+    /// trump rank and trump is picked that way.
+    fn check_trump_rank_card(
+        &mut self,
+        id: PlayerID,
+        card: Card,
+    ) -> Result<Option<MessageVariant>, Error> {
+        if self.propagated.trump_selection_policy != TrumpSelectionPolicy::FirstRankCardDrawn
+            || self.autobid.is_some()
+        {
+            return Ok(None);
+        }
+        let rank = self
+            .propagated
+            .players
+            .iter()
+            .find(|p| p.id == self.propagated.landlord.unwrap_or(id))
+            .map(|p| p.rank());
+        match (rank, card.number()) {
+            (Some(Rank::Number(rank)), Some(number)) if rank == number => {
+                self.autobid = Some(Bid {
+                    id,
+                    card,
+                    count: 1,
+                    epoch: 0,
+                });
+                self.hands.reveal(id, vec![card])?;
+                Ok(Some(MessageVariant::DrewTrumpRankCard { card }))
+            }
+            _ => Ok(None),
+        }
+    }
+
     pub fn reveal_card(&mut self) -> Result<MessageVariant, Error> {
+        if self.propagated.no_bid_policy != NoBidPolicy::RevealKitty {
+            bail!("cards aren't revealed from the bottom when nobody bids")
+        }
         if !self.deck.is_empty() {
             bail!("can't reveal card until deck is fully drawn")
         }
@@ -203,6 +307,7 @@ impl DrawPhase {
                             Rank::Number(n) => Some(n),
                             Rank::NoTrump => None,
                         },
+                        permanent: PermanentTrump::None,
                     }
                     .compare(*a, *b)
                 });
@@ -222,11 +327,119 @@ impl DrawPhase {
         Ok(MessageVariant::RevealedCardFromKitty)
     }
 
+    fn check_no_bids(&self) -> Result<(), Error> {
+        if !self.deck.is_empty() {
+            bail!("deck has cards remaining")
+        }
+        if !self.bids.is_empty() || self.autobid.is_some() || self.revealed_cards > 0 {
+            bail!("trump has already been bid")
+        }
+        Ok(())
+    }
+
+    /// Deals the cards again when nobody bid, under `NoBidPolicy::Redeal`.
+    pub fn redeal(&self, id: PlayerID) -> Result<DrawPhase, Error> {
+        self.check_no_bids()?;
+        let dealer = self
+            .propagated
+            .landlord
+            .or_else(|| self.draw_order().first().copied())
+            .unwrap_or(id);
+        InitializePhase::from_propagated(self.propagated.clone()).start(dealer)
+    }
+
+    /// Picks trump when nobody bid: at random under `NoBidPolicy::RandomTrump`,
+    /// or as the landlord chooses under `NoBidPolicy::LandlordChooses`.
+    pub fn pick_trump_without_bid(
+        &mut self,
+        id: PlayerID,
+        suit: Option<Suit>,
+    ) -> Result<MessageVariant, Error> {
+        self.check_no_bids()?;
+        let (landlord, suit, random) = match self.propagated.no_bid_policy {
+            NoBidPolicy::RevealKitty | NoBidPolicy::Redeal => {
+                bail!("trump isn't picked without a bid in this game")
+            }
+            NoBidPolicy::RandomTrump => {
+                let landlord = match self.propagated.landlord {
+                    Some(landlord) => landlord,
+                    None => self
+                        .draw_order()
+                        .first()
+                        .copied()
+                        .ok_or_else(|| anyhow!("no players to pick trump"))?,
+                };
+                let suits = Suit::iter().collect::<Vec<_>>();
+                let suit = *suits
+                    .choose(&mut rand::thread_rng())
+                    .ok_or_else(|| anyhow!("no suits to pick from"))?;
+                (landlord, suit, true)
+            }
+            NoBidPolicy::LandlordChooses => {
+                let landlord = match self.propagated.landlord {
+                    Some(landlord) => landlord,
+                    None => bail!("there's no landlord from the last game to pick trump"),
+                };
+                if id != landlord {
+                    bail!("only the landlord can pick trump")
+                }
+                match suit {
+                    Some(suit) => (landlord, suit, false),
+                    None => bail!("pick a suit to be trump"),
+                }
+            }
+        };
+        let number = match self.propagated.players.iter().find(|p| p.id == landlord) {
+            Some(p) => match p.rank() {
+                Rank::Number(number) => number,
+                Rank::NoTrump => bail!("there's no trump suit when the level is no trump"),
+            },
+            None => bail!("couldn't find the landlord"),
+        };
+        let card = Card::Suited { suit, number };
+        self.autobid = Some(Bid {
+            id: landlord,
+            card,
+            count: 1,
+            epoch: 0,
+        });
+        Ok(MessageVariant::TrumpPickedWithoutBid { card, random })
+    }
+
     pub fn bid(&mut self, id: PlayerID, card: Card, count: usize) -> bool {
+        self.try_bid(id, card, count).is_ok()
+    }
+
+    /// Makes the bid, or explains why it isn't allowed. Returns the player
+    /// whose bid was replaced, if the bid took priority over an equally
+    /// strong one.
+    pub fn try_bid(
+        &mut self,
+        id: PlayerID,
+        card: Card,
+        count: usize,
+    ) -> Result<Option<PlayerID>, Error> {
         if self.revealed_cards > 0 {
-            return false;
+            bail!("Bidding is over once cards have been revealed from the bottom")
+        }
+        match self.propagated.trump_selection_policy {
+            TrumpSelectionPolicy::Bidding => (),
+            TrumpSelectionPolicy::FirstRankCardDrawn => {
+                bail!("Trump is set by the first card of the trump rank to be drawn")
+            }
+            TrumpSelectionPolicy::SealedBids => bail!("Bids are sealed and made after drawing"),
+        }
+        let displaced = self.displaced_bid(Bid {
+            id,
+            card,
+            count,
+            epoch: 0,
+        });
+        if displaced.is_some() {
+            self.bids.pop();
+            self.bid_deck_sizes.pop();
         }
-        Bid::bid(
+        let result = Bid::bid(
             id,
             card,
             count,
@@ -238,13 +451,295 @@ impl DrawPhase {
             self.propagated.bid_policy,
             self.propagated.bid_reinforcement_policy,
             self.propagated.joker_bid_policy,
-            self.num_decks,
+            joker_counts(&self.decks, self.num_decks),
+            self.propagated.permanent_trump,
+            self.propagated.bid_rules,
+            &self.propagated.bid_partners(id),
+            self.deck.is_empty(),
             0,
+        );
+        if let Err(e) = result {
+            if let Some(displaced) = displaced {
+                self.bids.push(displaced);
+                self.bid_deck_sizes.push(self.deck.len());
+            }
+            return Err(e);
+        }
+        if let Some(displaced) = displaced {
+            unreveal_bid(&mut self.hands, &self.bids, displaced);
+        }
+        self.bid_deck_sizes.push(self.deck.len());
+        if let Some(bid) = self.bids.last() {
+            self.bid_history.record(*bid, self.deck.len());
+        }
+        // The bid was checked against the hand, so the cards are all there to
+        // be shown to the table.
+        self.hands.reveal(id, vec![card; count])?;
+        Ok(displaced.map(|b| b.id))
+    }
+
+    /// Makes a bid before the player has drawn many cards, which raises the
+    /// stakes of the game if it wins; see `BlindBidPolicy`.
+    pub fn try_blind_bid(
+        &mut self,
+        id: PlayerID,
+        card: Card,
+        count: usize,
+    ) -> Result<Option<PlayerID>, Error> {
+        let max_cards_drawn = match self.propagated.blind_bid_policy {
+            BlindBidPolicy::NoBlindBids => bail!("Blind bids are not allowed"),
+            BlindBidPolicy::AllowBlindBids {
+                max_cards_drawn, ..
+            } => max_cards_drawn,
+        };
+        if self.hands.get(id).map(|h| h.len()).unwrap_or(0) > max_cards_drawn {
+            bail!(
+                "Blind bids can only be made before drawing more than {} cards",
+                max_cards_drawn
+            )
+        }
+        let displaced = self.try_bid(id, card, count)?;
+        if let Some(bid) = self.bids.last() {
+            self.blind_bids.push(*bid);
+        }
+        Ok(displaced)
+    }
+
+    /// Every bid the player could make right now, including those which would
+    /// take priority over an equally strong winning bid.
+    pub fn valid_bids(&self, id: PlayerID) -> Result<Vec<Bid>, Error> {
+        if self.revealed_cards > 0 || self.autobid.is_some() {
+            return Ok(vec![]);
+        }
+        match self.propagated.trump_selection_policy {
+            TrumpSelectionPolicy::Bidding => (),
+            TrumpSelectionPolicy::FirstRankCardDrawn => return Ok(vec![]),
+            TrumpSelectionPolicy::SealedBids => {
+                if !self.deck.is_empty()
+                    || !self.bids.is_empty()
+                    || self.sealed_bids.iter().any(|b| b.id == id)
+                {
+                    return Ok(vec![]);
+                }
+                return self.sealed_bid_options(id);
+            }
+        }
+        let valid_bids = |bids: &[Bid]| {
+            Bid::valid_bids(
+                id,
+                bids,
+                &self.hands,
+                &self.propagated.players,
+                self.propagated.landlord,
+                0,
+                self.propagated.bid_policy,
+                self.propagated.bid_reinforcement_policy,
+                self.propagated.joker_bid_policy,
+                joker_counts(&self.decks, self.num_decks),
+                self.propagated.permanent_trump,
+                self.propagated.bid_rules,
+                &self.propagated.bid_partners(id),
+                self.deck.is_empty(),
+            )
+        };
+        let mut bids = valid_bids(&self.bids)?;
+        if let Some((_, earlier_bids)) = self.bids.split_last() {
+            for bid in valid_bids(earlier_bids)? {
+                if !bids.contains(&bid) && self.displaced_bid(bid).is_some() {
+                    bids.push(bid);
+                }
+            }
+        }
+        Ok(bids)
+    }
+
+    /// The bids the player could submit as a sealed bid, which only need to
+    /// be valid on their own.
+    fn sealed_bid_options(&self, id: PlayerID) -> Result<Vec<Bid>, Error> {
+        Bid::valid_bids(
+            id,
+            &[],
+            &self.hands,
+            &self.propagated.players,
+            self.propagated.landlord,
+            0,
+            self.propagated.bid_policy,
+            self.propagated.bid_reinforcement_policy,
+            self.propagated.joker_bid_policy,
+            joker_counts(&self.decks, self.num_decks),
+            self.propagated.permanent_trump,
+            self.propagated.bid_rules,
+            &[],
+            true,
         )
     }
 
+    /// Submits the player's sealed bid, or a pass. Once everyone has
+    /// submitted, the bids are revealed and the strongest one is made.
+    pub fn submit_sealed_bid(
+        &mut self,
+        id: PlayerID,
+        bid: Option<(Card, usize)>,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if self.propagated.trump_selection_policy != TrumpSelectionPolicy::SealedBids {
+            bail!("Bids aren't sealed")
+        }
+        if !self.deck.is_empty() {
+            bail!("Sealed bids are submitted once all the cards are drawn")
+        }
+        if !self.bids.is_empty() || self.autobid.is_some() {
+            bail!("The sealed bids have already been revealed")
+        }
+        if self.sealed_bids.iter().any(|b| b.id == id) {
+            bail!("You've already submitted a sealed bid")
+        }
+        let bid = bid.map(|(card, count)| Bid {
+            id,
+            card,
+            count,
+            epoch: 0,
+        });
+        if let Some(bid) = bid {
+            if !self.sealed_bid_options(id)?.contains(&bid) {
+                bail!("bid was invalid")
+            }
+        }
+        self.sealed_bids.push(SealedBid { id, bid });
+
+        let mut msgs = vec![MessageVariant::SubmittedSealedBid];
+        if self.sealed_bids.len() == self.propagated.players.len() {
+            let winner = self.strongest_sealed_bid()?;
+            if let Some(winner) = winner {
+                self.bids.push(winner);
+                self.bid_deck_sizes.push(0);
+                self.bid_history.record(winner, 0);
+                self.hands
+                    .reveal(winner.id, vec![winner.card; winner.count])?;
+            }
+            msgs.push(MessageVariant::SealedBidsRevealed {
+                bids: self.sealed_bids.clone(),
+                winner: winner.map(|b| b.id),
+            });
+        }
+        Ok(msgs)
+    }
+
+    /// The strongest sealed bid, where equally strong bids go to whoever has
+    /// priority.
+    fn strongest_sealed_bid(&self) -> Result<Option<Bid>, Error> {
+        let draw_order = self.draw_order();
+        let dealers_team = match self.propagated.landlord {
+            Some(landlord) => {
+                let mut team = self.propagated.bid_partners(landlord);
+                team.push(landlord);
+                team
+            }
+            None => vec![],
+        };
+        let mut strongest: Option<Bid> = None;
+        for bid in self.sealed_bids.iter().filter_map(|b| b.bid) {
+            let current = match strongest {
+                Some(current) => current,
+                None => {
+                    strongest = Some(bid);
+                    continue;
+                }
+            };
+            let outbids = Bid::valid_bids(
+                bid.id,
+                &[current],
+                &self.hands,
+                &self.propagated.players,
+                self.propagated.landlord,
+                0,
+                self.propagated.bid_policy,
+                BidReinforcementPolicy::OverturnOrReinforceWhileWinning,
+                self.propagated.joker_bid_policy,
+                joker_counts(&self.decks, self.num_decks),
+                self.propagated.permanent_trump,
+                BidRules::default(),
+                &[],
+                true,
+            )?
+            .contains(&bid);
+            if outbids
+                || (bid.same_strength(&current, self.propagated.bid_policy)
+                    && self.propagated.bid_priority_policy.has_priority(
+                        bid.id,
+                        current.id,
+                        &draw_order,
+                        &dealers_team,
+                    ))
+            {
+                strongest = Some(bid);
+            }
+        }
+        Ok(strongest)
+    }
+
+    /// The winning bid, if `new_bid` is just as strong, was made before any
+    /// more cards were drawn, and takes priority over it.
+    fn displaced_bid(&self, new_bid: Bid) -> Option<Bid> {
+        let winning_bid = *self.bids.last()?;
+        let policy = self.propagated.bid_priority_policy;
+        if policy == BidPriorityPolicy::FirstClick
+            || winning_bid.id == new_bid.id
+            || self.bid_deck_sizes.len() != self.bids.len()
+            || self.bid_deck_sizes.last() != Some(&self.deck.len())
+            || !new_bid.same_strength(&winning_bid, self.propagated.bid_policy)
+        {
+            return None;
+        }
+        let dealers_team = match self.propagated.landlord {
+            Some(landlord) => {
+                let mut team = self.propagated.bid_partners(landlord);
+                team.push(landlord);
+                team
+            }
+            None => vec![],
+        };
+        if policy.has_priority(
+            new_bid.id,
+            winning_bid.id,
+            &self.draw_order(),
+            &dealers_team,
+        ) {
+            Some(winning_bid)
+        } else {
+            None
+        }
+    }
+
+    /// The players in the order they draw cards, starting from whoever drew
+    /// the first card.
+    fn draw_order(&self) -> Vec<PlayerID> {
+        let players = &self.propagated.players;
+        let drawn = players
+            .iter()
+            .map(|p| self.hands.get(p.id).map(|h| h.len()).unwrap_or(0))
+            .sum::<usize>();
+        let first = (self.position + players.len() - drawn % players.len()) % players.len();
+        (0..players.len())
+            .map(|offset| players[(first + offset) % players.len()].id)
+            .collect()
+    }
+
     pub fn take_back_bid(&mut self, id: PlayerID) -> Result<(), Error> {
-        Bid::take_back_bid(id, self.propagated.bid_takeback_policy, &mut self.bids, 0)
+        if self.propagated.trump_selection_policy == TrumpSelectionPolicy::SealedBids {
+            bail!("Sealed bids can't be taken back")
+        }
+        let bid = Bid::take_back_bid(
+            id,
+            self.propagated.bid_takeback_policy,
+            &mut self.bids,
+            self.deck.is_empty(),
+            0,
+        )?;
+        self.bid_deck_sizes.truncate(self.bids.len());
+        self.bid_history.record_takeback(bid);
+        self.blind_bids.retain(|b| *b != bid);
+        unreveal_bid(&mut self.hands, &self.bids, bid);
+        Ok(())
     }
 
     pub fn done_drawing(&self) -> bool {
@@ -281,7 +776,10 @@ impl DrawPhase {
             (landlord, landlord_level)
         };
         let trump = match landlord_level {
-            Rank::NoTrump => Trump::NoTrump { number: None },
+            Rank::NoTrump => Trump::NoTrump {
+                number: None,
+                permanent: self.propagated.permanent_trump,
+            },
             Rank::Number(landlord_level) => {
                 // Note: this is not repeated in all cases above, but it is
                 // repeated in some. It's OK because the bid calculation is
@@ -291,25 +789,36 @@ impl DrawPhase {
                     Card::Unknown => bail!("can't bid with unknown cards!"),
                     Card::SmallJoker | Card::BigJoker => Trump::NoTrump {
                         number: Some(landlord_level),
+                        permanent: self.propagated.permanent_trump,
                     },
                     Card::Suited { suit, .. } => Trump::Standard {
                         suit,
                         number: landlord_level,
+                        permanent: self.propagated.permanent_trump,
                     },
                 }
             }
         };
+        let trump = self.propagated.trump_suit_policy.apply(trump);
         let mut hands = self.hands.clone();
         hands.set_trump(trump);
+        let kitty_deck_indices = if self.kitty_deck_indices.len() == self.kitty.len() {
+            self.kitty_deck_indices.iter().map(|i| Some(*i)).collect()
+        } else {
+            vec![]
+        };
         Ok(ExchangePhase::new(
             self.propagated.clone(),
             self.num_decks,
             self.game_mode.clone(),
             self.kitty.clone(),
+            kitty_deck_indices,
             landlord,
             hands,
             trump,
             self.bids.clone(),
+            self.bid_history.clone(),
+            self.blind_bids.clone(),
             self.autobid,
             self.removed_cards.clone(),
             self.decks.clone(),
@@ -333,5 +842,12 @@ impl DrawPhase {
         for card in &mut self.deck {
             *card = Card::Unknown;
         }
+        if self.bids.is_empty() {
+            for sealed_bid in &mut self.sealed_bids {
+                if sealed_bid.id != player {
+                    sealed_bid.bid = None;
+                }
+            }
+        }
     }
 }
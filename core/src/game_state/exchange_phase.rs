@@ -11,10 +11,15 @@ use shengji_mechanics::types::{Card, Number, PlayerID, Rank, Trump};
 
 use crate::message::MessageVariant;
 use crate::settings::{
-    Friend, FriendSelection, FriendSelectionPolicy, GameMode, KittyTheftPolicy, PropagatedState,
+    BlindBidPolicy, ExchangeDelegationPolicy, Friend, FriendSelection, FriendSelectionPolicy,
+    GameMode, KittyDiggingPolicy, KittyRevisionPolicy, KittyTheftPolicy, PropagatedState,
 };
 
-use crate::game_state::{initialize_phase::InitializePhase, play_phase::PlayPhase};
+use crate::game_state::bid_history::BidHistory;
+use crate::game_state::{
+    initialize_phase::InitializePhase, joker_counts, play_phase::PlayPhase, redact_kitty,
+    unreveal_bid,
+};
 
 macro_rules! bail_unwrap {
     ($opt:expr) => {
@@ -25,6 +30,16 @@ macro_rules! bail_unwrap {
     };
 }
 
+#[derive(
+    thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+pub enum KittyBurialError {
+    #[error("{0:?} is a point card, which can't be buried while you have other cards")]
+    PointCard(Card),
+    #[error("{0:?} is trump, which can't be buried while you have other cards")]
+    Trump(Card),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExchangePhase {
     propagated: PropagatedState,
@@ -43,11 +58,30 @@ pub struct ExchangePhase {
     #[serde(default)]
     bids: Vec<Bid>,
     #[serde(default)]
+    bid_history: BidHistory,
+    /// The bids which were made blind while drawing.
+    #[serde(default)]
+    blind_bids: Vec<Bid>,
+    #[serde(default)]
     autobid: Option<Bid>,
     #[serde(default)]
     removed_cards: Vec<Card>,
     #[serde(default)]
     decks: Vec<Deck>,
+    /// The deck each card in `kitty` came from, where known; see
+    /// `Hands::deck_indices`.
+    #[serde(default)]
+    kitty_deck_indices: Vec<Option<usize>>,
+    /// Who buried each card in `kitty`, or `None` if it was dealt there.
+    #[serde(default)]
+    kitty_buried_by: Vec<Option<PlayerID>>,
+    /// Whoever dug the kitty, which can only happen once per game.
+    #[serde(default)]
+    kitty_digger: Option<PlayerID>,
+    /// Whether the room owner lifted the `KittyBurialRules` for this
+    /// exchange.
+    #[serde(default)]
+    kitty_burial_rules_waived: bool,
 }
 
 impl ExchangePhase {
@@ -57,16 +91,21 @@ impl ExchangePhase {
         num_decks: usize,
         game_mode: GameMode,
         kitty: Vec<Card>,
+        kitty_deck_indices: Vec<Option<usize>>,
         landlord: PlayerID,
         hands: Hands,
         trump: Trump,
         bids: Vec<Bid>,
+        bid_history: BidHistory,
+        blind_bids: Vec<Bid>,
         autobid: Option<Bid>,
         removed_cards: Vec<Card>,
         decks: Vec<Deck>,
     ) -> Self {
         ExchangePhase {
             kitty_size: kitty.len(),
+            kitty_buried_by: vec![None; kitty.len()],
+            kitty_deck_indices,
             num_decks,
             game_mode,
             kitty,
@@ -76,11 +115,15 @@ impl ExchangePhase {
             hands,
             trump,
             bids,
+            bid_history,
+            blind_bids,
             autobid,
             removed_cards,
             decks,
             finalized: false,
             epoch: 1,
+            kitty_digger: None,
+            kitty_burial_rules_waived: false,
         }
     }
 
@@ -99,8 +142,24 @@ impl ExchangePhase {
         if self.finalized {
             bail!("cards already finalized")
         }
-        self.hands.remove(self.exchanger, Some(card))?;
+        if let Err(e) = self.check_burial(card) {
+            if self.unrestricted_cards_in_hand() > 0 {
+                return Err(e.into());
+            }
+        }
+        if self.propagated.bid_rules.keep_bid_cards && self.bidding_open() {
+            if let Some((bid_card, count)) = Bid::cards_to_keep(&self.bids, id) {
+                let held = self.hands.counts(id).map(|c| c.count(card)).unwrap_or(0);
+                if bid_card == card && held <= count {
+                    bail!("You have to keep the cards you bid with until the bid is overturned")
+                }
+            }
+        }
+        let deck_index = self.hands.remove_from_decks(self.exchanger, Some(card))?;
+        self.sync_kitty_deck_indices();
         self.kitty.push(card);
+        self.kitty_deck_indices.extend(deck_index);
+        self.kitty_buried_by.push(Some(self.exchanger));
         Ok(())
     }
 
@@ -112,14 +171,59 @@ impl ExchangePhase {
             bail!("cards already finalized")
         }
         if let Some(index) = self.kitty.iter().position(|c| *c == card) {
+            self.sync_kitty_deck_indices();
             self.kitty.swap_remove(index);
-            self.hands.add(self.exchanger, Some(card))?;
+            let deck_index = self.kitty_deck_indices.swap_remove(index);
+            self.kitty_buried_by.swap_remove(index);
+            self.hands
+                .add_from_decks(self.exchanger, Some((card, deck_index)))?;
             Ok(())
         } else {
             bail!("card not in the kitty")
         }
     }
 
+    /// Whether anyone can still bid over the winning bid.
+    fn bidding_open(&self) -> bool {
+        self.propagated.kitty_theft_policy == KittyTheftPolicy::AllowKittyTheft
+            && self.autobid.is_none()
+            && self.kitty_digger.is_none()
+    }
+
+    /// Game states from before the kitty's deck indices and buriers were
+    /// tracked don't have any, so pad them out to line up with the kitty.
+    fn sync_kitty_deck_indices(&mut self) {
+        self.kitty_deck_indices.resize(self.kitty.len(), None);
+        self.kitty_buried_by.resize(self.kitty.len(), None);
+    }
+
+    /// Hands the exchange over to a teammate under the
+    /// `ExchangeDelegationPolicy`. The landlord can take it back at any point,
+    /// so the exchange can be split between them.
+    pub fn delegate_exchange(&mut self, id: PlayerID, to: PlayerID) -> Result<(), Error> {
+        if self.propagated.exchange_delegation_policy == ExchangeDelegationPolicy::LandlordOnly {
+            bail!("Only the leader can exchange the bottom cards")
+        }
+        if id != self.exchanger && id != self.landlord {
+            bail!("Only the leader or the exchanger can hand over the bottom cards")
+        }
+        if self.finalized {
+            bail!("cards already finalized")
+        }
+        if to == self.exchanger {
+            bail!("That player is already exchanging the bottom cards")
+        }
+        let landlords_team = self.landlords_team()?;
+        if !landlords_team.contains(&self.exchanger) || !landlords_team.contains(&to) {
+            bail!("The bottom cards can only be handed to the leader's teammates")
+        }
+        if self.kitty.len() != self.kitty_size {
+            bail!("incorrect number of cards in the bottom")
+        }
+        self.exchanger = to;
+        Ok(())
+    }
+
     pub fn num_friends(&self) -> usize {
         match self.game_mode {
             GameMode::FindingFriends { num_friends, .. } => num_friends,
@@ -149,16 +253,14 @@ impl ExchangePhase {
 
             for friend in friend_set.iter() {
                 if FriendSelectionPolicy::TrumpsIncluded != self.propagated.friend_selection_policy
+                    && self.trump.is_trump(friend.card)
                 {
-                    if friend.card.is_joker() || friend.card.number() == self.trump.number() {
-                        if let Some(n) = self.trump.number() {
-                            bail!("you can't pick a joker or a {} as your friend", n.as_str())
-                        } else {
-                            bail!("you can't pick a joker as your friend",)
-                        }
-                    }
-                    if self.trump.suit().is_some() && friend.card.suit() == self.trump.suit() {
+                    if !friend.card.is_joker() && friend.card.number() != self.trump.number() {
                         bail!("you can't pick a trump suit as your friend")
+                    } else if let Some(n) = self.trump.number() {
+                        bail!("you can't pick a joker or a {} as your friend", n.as_str())
+                    } else {
+                        bail!("you can't pick a joker as your friend",)
                     }
                 }
                 if friend.initial_skip >= self.num_decks {
@@ -188,7 +290,12 @@ impl ExchangePhase {
                         .ok_or_else(|| anyhow!("Couldn't find landlord level?"))?
                         .rank();
 
-                    match (landlord_level, friend.card.points(), friend.card.number()) {
+                    let points = self
+                        .propagated
+                        .game_scoring_parameters
+                        .point_schedule
+                        .points(friend.card);
+                    match (landlord_level, points, friend.card.number()) {
                         (Rank::Number(Number::Ace), _, Some(Number::King)) => (),
                         (_, Some(_), _) => {
                             bail!("you can't pick a point card as your friend");
@@ -211,6 +318,61 @@ impl ExchangePhase {
         }
     }
 
+    /// Lifts the `KittyBurialRules` for the rest of this exchange. Only the
+    /// room owner can do this.
+    pub fn waive_kitty_burial_rules(&mut self, id: PlayerID) -> Result<(), Error> {
+        if self.propagated.room_owner() != Some(id) {
+            bail!("Only the room owner can lift the rules for burying cards")
+        }
+        if self.finalized {
+            bail!("cards already finalized")
+        }
+        if self.kitty_burial_rules_waived {
+            bail!("The rules for burying cards have already been lifted")
+        }
+        self.kitty_burial_rules_waived = true;
+        Ok(())
+    }
+
+    /// Whether the card may be buried under the `KittyBurialRules`, ignoring
+    /// what else is in the exchanger's hand.
+    fn check_burial(&self, card: Card) -> Result<(), KittyBurialError> {
+        if self.kitty_burial_rules_waived {
+            return Ok(());
+        }
+        let rules = self.propagated.kitty_burial_rules;
+        let point_schedule = &self.propagated.game_scoring_parameters.point_schedule;
+        if !rules.allow_point_cards && point_schedule.is_point_card(card) {
+            return Err(KittyBurialError::PointCard(card));
+        }
+        if !rules.allow_trump && self.trump.is_trump(card) {
+            return Err(KittyBurialError::Trump(card));
+        }
+        Ok(())
+    }
+
+    fn unrestricted_cards_in_hand(&self) -> usize {
+        self.hands
+            .get(self.exchanger)
+            .map(|hand| {
+                hand.cards()
+                    .filter(|c| self.check_burial(*c).is_ok())
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    /// Checks that nothing in the bottom breaks the `KittyBurialRules` while
+    /// the exchanger could have buried something else instead.
+    fn check_kitty_burial(&self) -> Result<(), KittyBurialError> {
+        if self.unrestricted_cards_in_hand() == 0 {
+            return Ok(());
+        }
+        self.kitty
+            .iter()
+            .try_for_each(|card| self.check_burial(*card))
+    }
+
     pub fn finalize(&mut self, id: PlayerID) -> Result<(), Error> {
         if id != self.exchanger {
             bail!("only the exchanger can finalize their cards")
@@ -221,6 +383,7 @@ impl ExchangePhase {
         if self.kitty.len() != self.kitty_size {
             bail!("incorrect number of cards in the bottom")
         }
+        self.check_kitty_burial()?;
         self.finalized = true;
         Ok(())
     }
@@ -239,29 +402,144 @@ impl ExchangePhase {
         if id != winning_bid.id {
             bail!("Only the winner of the bid can pick up the cards")
         }
-        self.trump = match winning_bid.card {
-            Card::Unknown => bail!("can't bid with unknown cards!"),
-            Card::SmallJoker | Card::BigJoker => Trump::NoTrump {
-                number: self.trump.number(),
+        // The current trump may not have a number, depending on the trump
+        // suit policy, so use the landlord's rank directly.
+        let landlord_level = match self
+            .propagated
+            .players
+            .iter()
+            .find(|p| p.id == self.landlord)
+            .map(|p| p.rank())
+        {
+            Some(Rank::Number(n)) => n,
+            _ => bail!("Shouldn't have bids if the landlord isn't playing a numbered rank"),
+        };
+        self.trump = self
+            .propagated
+            .trump_suit_policy
+            .apply(match winning_bid.card {
+                Card::Unknown => bail!("can't bid with unknown cards!"),
+                Card::SmallJoker | Card::BigJoker => Trump::NoTrump {
+                    number: Some(landlord_level),
+                    permanent: self.propagated.permanent_trump,
+                },
+                Card::Suited { suit, .. } => Trump::Standard {
+                    suit,
+                    number: landlord_level,
+                    permanent: self.propagated.permanent_trump,
+                },
+            });
+        self.finalized = false;
+        self.epoch += 1;
+        self.exchanger = winning_bid.id;
+
+        Ok(())
+    }
+
+    /// Takes over the bottom cards under the `KittyDiggingPolicy`: the player
+    /// declares trump again with `card` and becomes the landlord.
+    pub fn dig_kitty(&mut self, id: PlayerID, card: Card) -> Result<(), Error> {
+        let jokers = match self.propagated.kitty_digging_policy {
+            KittyDiggingPolicy::NoKittyDigging => bail!("Digging the bottom cards is not allowed"),
+            KittyDiggingPolicy::DigWithJokers { jokers } => jokers,
+        };
+        if !self.finalized {
+            bail!("Can't dig the bottom cards until the exchanger is done swapping cards")
+        }
+        if self.kitty_digger.is_some() {
+            bail!("The bottom cards have already been dug this game")
+        }
+        if self.landlords_team()?.contains(&id) {
+            bail!("Only the other team can dig the bottom cards")
+        }
+        let hand = self.hands.get(id)?;
+        if hand.count(Card::SmallJoker) + hand.count(Card::BigJoker) < jokers {
+            bail!(
+                "You need at least {} jokers to dig the bottom cards",
+                jokers
+            )
+        }
+        if hand.count(card) == 0 {
+            bail!("You can only declare trump with a card in your hand")
+        }
+        let rank = self
+            .propagated
+            .players
+            .iter()
+            .find(|p| p.id == id)
+            .map(|p| p.rank())
+            .ok_or_else(|| anyhow!("Couldn't find your rank"))?;
+        let trump = match (card, rank) {
+            (Card::SmallJoker | Card::BigJoker, rank) => Trump::NoTrump {
+                number: match rank {
+                    Rank::Number(n) => Some(n),
+                    Rank::NoTrump => None,
+                },
+                permanent: self.propagated.permanent_trump,
             },
-            Card::Suited { suit, .. } => Trump::Standard {
+            (Card::Suited { suit, number }, Rank::Number(n)) if number == n => Trump::Standard {
                 suit,
-                number: self
-                    .trump
-                    .number()
-                    .expect("Shouldn't have trump number if there are bids"),
+                number,
+                permanent: self.propagated.permanent_trump,
             },
+            _ => bail!("Trump has to be declared with a joker or a card of your rank"),
         };
+        self.trump = self.propagated.trump_suit_policy.apply(trump);
+        self.hands.set_trump(self.trump);
+        self.hands.reveal(id, vec![card])?;
+        if let GameMode::FindingFriends {
+            ref mut friends, ..
+        } = self.game_mode
+        {
+            friends.clear();
+        }
+        self.kitty_digger = Some(id);
+        self.landlord = id;
+        self.exchanger = id;
         self.finalized = false;
         self.epoch += 1;
-        self.exchanger = winning_bid.id;
-
         Ok(())
     }
 
+    /// The landlord's team, as far as it's known before play starts.
+    fn landlords_team(&self) -> Result<Vec<PlayerID>, Error> {
+        let landlord_position = bail_unwrap!(self
+            .propagated
+            .players
+            .iter()
+            .position(|p| p.id == self.landlord));
+        Ok(match self.game_mode {
+            GameMode::Tractor => self
+                .propagated
+                .players
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, p)| {
+                    if idx % 2 == landlord_position % 2 {
+                        Some(p.id)
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+            GameMode::FindingFriends { .. } => vec![self.landlord],
+        })
+    }
+
     pub fn bid(&mut self, id: PlayerID, card: Card, count: usize) -> bool {
-        if !self.finalized || self.autobid.is_some() {
-            return false;
+        self.try_bid(id, card, count).is_ok()
+    }
+
+    /// Makes the bid, or explains why it isn't allowed.
+    pub fn try_bid(&mut self, id: PlayerID, card: Card, count: usize) -> Result<(), Error> {
+        if !self.finalized {
+            bail!("Can't bid until the exchanger is done swapping cards")
+        }
+        if self.autobid.is_some() {
+            bail!("Can't bid if the winning bid was automatic")
+        }
+        if self.kitty_digger.is_some() {
+            bail!("Can't bid once the bottom cards have been dug")
         }
         Bid::bid(
             id,
@@ -275,8 +553,42 @@ impl ExchangePhase {
             self.propagated.bid_policy,
             self.propagated.bid_reinforcement_policy,
             self.propagated.joker_bid_policy,
-            self.num_decks,
+            joker_counts(&self.decks, self.num_decks),
+            self.propagated.permanent_trump,
+            self.propagated.bid_rules,
+            &self.propagated.bid_partners(id),
+            true,
+            self.epoch,
+        )?;
+        if let Some(bid) = self.bids.last() {
+            self.bid_history.record(*bid, 0);
+        }
+        // The bid was checked against the hand, so the cards are all there to
+        // be shown to the table.
+        self.hands.reveal(id, vec![card; count])?;
+        Ok(())
+    }
+
+    /// Every bid the player could make right now.
+    pub fn valid_bids(&self, id: PlayerID) -> Result<Vec<Bid>, Error> {
+        if !self.finalized || self.autobid.is_some() {
+            return Ok(vec![]);
+        }
+        Bid::valid_bids(
+            id,
+            &self.bids,
+            &self.hands,
+            &self.propagated.players,
+            self.propagated.landlord,
             self.epoch,
+            self.propagated.bid_policy,
+            self.propagated.bid_reinforcement_policy,
+            self.propagated.joker_bid_policy,
+            joker_counts(&self.decks, self.num_decks),
+            self.propagated.permanent_trump,
+            self.propagated.bid_rules,
+            &self.propagated.bid_partners(id),
+            true,
         )
     }
 
@@ -287,12 +599,32 @@ impl ExchangePhase {
         if self.autobid.is_some() {
             bail!("Can't take back bid if the winning bid was automatic")
         }
-        Bid::take_back_bid(
+        let bid = Bid::take_back_bid(
             id,
             self.propagated.bid_takeback_policy,
             &mut self.bids,
+            true,
             self.epoch,
-        )
+        )?;
+        self.bid_history.record_takeback(bid);
+        unreveal_bid(&mut self.hands, &self.bids, bid);
+        Ok(())
+    }
+
+    pub fn bid_history(&self) -> &BidHistory {
+        &self.bid_history
+    }
+
+    /// Reopens a finished exchange so that the landlord can change the bottom
+    /// cards again, keeping the cards they buried last time.
+    pub(crate) fn reopen(&mut self, propagated: PropagatedState) {
+        self.propagated = propagated;
+        self.exchanger = self.landlord;
+        self.finalized = false;
+    }
+
+    pub fn exchanger(&self) -> PlayerID {
+        self.exchanger
     }
 
     pub fn landlord(&self) -> PlayerID {
@@ -316,9 +648,10 @@ impl ExchangePhase {
     }
 
     pub fn next_player(&self) -> Result<PlayerID, Error> {
-        if self.propagated.kitty_theft_policy == KittyTheftPolicy::AllowKittyTheft
-            && self.autobid.is_none()
-            && !self.finalized
+        if !self.finalized
+            && ((self.propagated.kitty_theft_policy == KittyTheftPolicy::AllowKittyTheft
+                && self.autobid.is_none())
+                || self.propagated.kitty_digging_policy != KittyDiggingPolicy::NoKittyDigging)
         {
             Ok(self.exchanger)
         } else {
@@ -333,6 +666,9 @@ impl ExchangePhase {
         if self.kitty.len() != self.kitty_size {
             bail!("incorrect number of cards in the bottom")
         }
+        if !self.finalized {
+            self.check_kitty_burial()?;
+        }
         if let GameMode::FindingFriends {
             num_friends,
             ref friends,
@@ -349,28 +685,15 @@ impl ExchangePhase {
         {
             bail!("must give other players a chance to over-bid and swap cards")
         }
+        if self.propagated.kitty_digging_policy != KittyDiggingPolicy::NoKittyDigging
+            && !self.finalized
+        {
+            bail!("must give other players a chance to dig the bottom cards")
+        }
 
-        let landlord_position = bail_unwrap!(self
-            .propagated
-            .players
-            .iter()
-            .position(|p| p.id == self.landlord));
-        let landlords_team = match self.game_mode {
-            GameMode::Tractor => self
-                .propagated
-                .players
-                .iter()
-                .enumerate()
-                .flat_map(|(idx, p)| {
-                    if idx % 2 == landlord_position % 2 {
-                        Some(p.id)
-                    } else {
-                        None
-                    }
-                })
-                .collect(),
-            GameMode::FindingFriends { .. } => vec![self.landlord],
-        };
+        let landlords_team = self.landlords_team()?;
+        let mut kitty_buried_by = self.kitty_buried_by.clone();
+        kitty_buried_by.resize(self.kitty.len(), None);
 
         PlayPhase::new(
             self.propagated.clone(),
@@ -384,9 +707,34 @@ impl ExchangePhase {
             landlords_team,
             self.removed_cards.clone(),
             self.decks.clone(),
+            self.bid_history.clone(),
+            self.blind_bid_levels()?,
+            kitty_buried_by,
+            match self.propagated.kitty_revision_policy {
+                KittyRevisionPolicy::NoRevision => None,
+                KittyRevisionPolicy::AllowRevisionBeforeFirstLead => Some(Box::new(self.clone())),
+            },
         )
     }
 
+    /// The extra levels at stake if the landlord won the bidding with a
+    /// blind bid.
+    fn blind_bid_levels(&self) -> Result<usize, Error> {
+        let levels = match self.propagated.blind_bid_policy {
+            BlindBidPolicy::NoBlindBids => return Ok(0),
+            BlindBidPolicy::AllowBlindBids { levels, .. } => levels,
+        };
+        if self.autobid.is_some() || self.bids.is_empty() {
+            return Ok(0);
+        }
+        let (_, winning_bid) = Bid::first_and_winner(&self.bids, self.autobid)?;
+        if winning_bid.id == self.landlord && self.blind_bids.contains(&winning_bid) {
+            Ok(levels)
+        } else {
+            Ok(0)
+        }
+    }
+
     pub fn return_to_initialize(&self) -> Result<(InitializePhase, Vec<MessageVariant>), Error> {
         let mut msgs = vec![MessageVariant::ResettingGame];
 
@@ -399,9 +747,12 @@ impl ExchangePhase {
     pub fn destructively_redact_for_player(&mut self, player: PlayerID) {
         self.hands.destructively_redact_except_for_player(player);
         if player != self.exchanger || self.finalized {
-            for card in &mut self.kitty {
-                *card = Card::Unknown;
-            }
+            redact_kitty(
+                &mut self.kitty,
+                &self.kitty_buried_by,
+                self.propagated.face_up_kitty_burial,
+                player,
+            );
         }
         if player != self.landlord {
             if let GameMode::FindingFriends {
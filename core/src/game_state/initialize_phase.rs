@@ -6,11 +6,12 @@ use rand::{seq::SliceRandom, RngCore};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use shengji_mechanics::types::{Card, Number, PlayerID, Rank, ALL_SUITS};
+use shengji_mechanics::deck::{Deck, DeckSpec};
+use shengji_mechanics::types::{Card, Number, PlayerID, Rank, Suit, ALL_SUITS};
 
 use crate::settings::{GameMode, GameModeSettings, GameStartPolicy, PropagatedState};
 
-use crate::game_state::DrawPhase;
+use crate::game_state::{now_millis, DrawPhase};
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct InitializePhase {
@@ -91,17 +92,20 @@ impl InitializePhase {
             bail!("need at least one deck to start the game");
         }
         let decks = self.propagated.decks()?;
-        let mut deck = Vec::with_capacity(decks.iter().map(|d| d.len()).sum::<usize>());
-        for deck_ in &decks {
-            deck.extend(deck_.cards());
+        let specs = decks.iter().map(Deck::spec).collect::<Vec<DeckSpec>>();
+        // Track which deck each card came from alongside the card itself, so
+        // that the frontend can show the deck backs.
+        let mut deck = Vec::with_capacity(specs.iter().map(|s| s.len()).sum::<usize>());
+        for (deck_index, spec) in specs.iter().enumerate() {
+            deck.extend(spec.cards().map(|c| (c, deck_index)));
         }
         // Ensure that it is possible to bid for the landlord, if set, or all players, if not.
         match level {
-            Some(Rank::Number(level)) if decks.iter().any(|d| d.includes_number(level)) => (),
+            Some(Rank::Number(level)) if specs.iter().any(|s| s.includes_number(level)) => (),
             Some(Rank::NoTrump) => (),
             None if self.players.iter().all(|p| {
-                decks.iter().any(|d| match p.level {
-                    Rank::Number(level) => d.includes_number(level),
+                specs.iter().any(|s| match p.level {
+                    Rank::Number(level) => s.includes_number(level),
                     Rank::NoTrump => true,
                 })
             }) => {}
@@ -124,9 +128,9 @@ impl InitializePhase {
                 // appropriate number of cards.
                 let num_players = self.propagated.players.len();
 
-                let min_number: Number = decks
+                let min_number: Number = specs
                     .iter()
-                    .map(|d| d.min)
+                    .filter_map(DeckSpec::min_number)
                     .min()
                     .ok_or_else(|| anyhow!("no minimum value in deck?"))?;
 
@@ -149,9 +153,12 @@ impl InitializePhase {
                                 Rank::NoTrump => None,
                             })
                             .collect::<HashSet<Number>>();
-                        bad_levels.insert(Number::Five);
-                        bad_levels.insert(Number::Ten);
-                        bad_levels.insert(Number::King);
+                        let point_schedule =
+                            &self.propagated.game_scoring_parameters.point_schedule;
+                        bad_levels.extend(
+                            std::iter::successors(Some(min_number), |n| n.successor())
+                                .filter(|n| point_schedule.is_point_number(*n)),
+                        );
                         let mut n = min_number;
                         loop {
                             if !bad_levels.contains(&n) {
@@ -168,21 +175,26 @@ impl InitializePhase {
                     }
                 };
 
-                let mut suit_idx = ALL_SUITS.len() - 1;
+                let suits = ALL_SUITS
+                    .iter()
+                    .copied()
+                    .filter(|suit| specs.iter().any(|s| s.includes_suit(*suit)))
+                    .collect::<Vec<Suit>>();
+                let mut suit_idx = suits.len() - 1;
 
                 while deck.len() % num_players != size % num_players {
                     let card_to_remove = Card::Suited {
-                        suit: ALL_SUITS[suit_idx],
+                        suit: suits[suit_idx],
                         number: removed_card_number,
                     };
                     suit_idx = if suit_idx == 0 {
-                        ALL_SUITS.len() - 1
+                        suits.len() - 1
                     } else {
                         suit_idx - 1
                     };
 
                     // Attempt to remove the card from the deck.
-                    match deck.iter().position(|c| *c == card_to_remove) {
+                    match deck.iter().position(|(c, _)| *c == card_to_remove) {
                         Some(idx) => {
                             deck.remove(idx);
                             removed_cards.push(card_to_remove);
@@ -208,13 +220,18 @@ impl InitializePhase {
             }
         };
 
-        let propagated = self.propagated.clone();
+        let mut propagated = self.propagated.clone();
+        if propagated.match_started_at.is_none() {
+            propagated.match_started_at = Some(now_millis());
+        }
+        let (deck, deck_indices): (Vec<Card>, Vec<usize>) = deck.into_iter().unzip();
 
         Ok(DrawPhase::new(
             propagated,
             position,
             deck[0..deck.len() - kitty_size].to_vec(),
             deck[deck.len() - kitty_size..].to_vec(),
+            deck_indices,
             num_decks,
             game_mode,
             level,
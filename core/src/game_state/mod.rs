@@ -1,24 +1,77 @@
 use std::ops::Deref;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{bail, Error};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use shengji_mechanics::types::PlayerID;
+use shengji_mechanics::bidding::Bid;
+use shengji_mechanics::deck::{Deck, JokerCounts};
+use shengji_mechanics::hands::Hands;
+use shengji_mechanics::sorting::SortOrder;
+use shengji_mechanics::types::{Card, PlayerID};
 
 use crate::message::MessageVariant;
 use crate::settings::PropagatedState;
 
+pub mod bid_history;
 pub mod draw_phase;
 pub mod exchange_phase;
 pub mod initialize_phase;
 pub mod play_phase;
+pub mod score_ledger;
 
 use draw_phase::DrawPhase;
 use exchange_phase::ExchangePhase;
 use initialize_phase::InitializePhase;
 use play_phase::PlayPhase;
 
+/// The jokers in play for the given decks. Game states from before the decks
+/// were recorded don't have any, so assume standard decks for those.
+fn joker_counts(decks: &[Deck], num_decks: usize) -> JokerCounts {
+    if decks.is_empty() {
+        JokerCounts::standard(num_decks)
+    } else {
+        JokerCounts::of(decks)
+    }
+}
+
+/// Hides the cards shown for a bid which was taken back, other than those
+/// still shown for the player's remaining bids.
+fn unreveal_bid(hands: &mut Hands, bids: &[Bid], taken_back: Bid) {
+    let keep = bids
+        .iter()
+        .filter(|b| b.id == taken_back.id && b.card == taken_back.card)
+        .map(|b| b.count)
+        .max()
+        .unwrap_or(0);
+    hands.unreveal(taken_back.id, taken_back.card, keep);
+}
+
+/// Milliseconds since the Unix epoch.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Hides the bottom cards from `viewer`, other than those which were buried
+/// face-up and those which `viewer` buried themselves.
+fn redact_kitty(
+    kitty: &mut [Card],
+    buried_by: &[Option<PlayerID>],
+    face_up_burial: bool,
+    viewer: PlayerID,
+) {
+    for (idx, card) in kitty.iter_mut().enumerate() {
+        let buried_by = buried_by.get(idx).copied().flatten();
+        if buried_by != Some(viewer) && (!face_up_burial || buried_by.is_none()) {
+            *card = Card::Unknown;
+        }
+    }
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum GameState {
@@ -47,6 +100,15 @@ impl GameState {
         }
     }
 
+    fn propagated_mut(&mut self) -> &'_ mut PropagatedState {
+        match self {
+            GameState::Initialize(p) => p.propagated_mut(),
+            GameState::Draw(p) => p.propagated_mut(),
+            GameState::Exchange(p) => p.propagated_mut(),
+            GameState::Play(p) => p.propagated_mut(),
+        }
+    }
+
     pub fn is_player(&self, id: PlayerID) -> bool {
         self.propagated().players.iter().any(|p| p.id == id)
     }
@@ -115,6 +177,15 @@ impl GameState {
         }
     }
 
+    pub fn set_sort_order(&mut self, id: PlayerID, sort_order: SortOrder) -> Result<(), Error> {
+        match self {
+            GameState::Initialize(ref mut p) => p.propagated_mut().set_sort_order(id, sort_order),
+            GameState::Draw(ref mut p) => p.propagated_mut().set_sort_order(id, sort_order),
+            GameState::Exchange(ref mut p) => p.propagated_mut().set_sort_order(id, sort_order),
+            GameState::Play(ref mut p) => p.propagated_mut().set_sort_order(id, sort_order),
+        }
+    }
+
     pub fn reset(&mut self) -> Result<Vec<MessageVariant>, Error> {
         match self {
             GameState::Initialize(_) => bail!("Game has not started yet!"),
@@ -150,6 +221,9 @@ impl GameState {
                 p.destructively_redact_for_player(id);
             }
         }
+        // The ledger grows with every game played in the room, so it's only
+        // sent when asked for.
+        s.propagated_mut().score_ledger = Default::default();
         s
     }
 }
@@ -165,15 +239,32 @@ impl Deref for GameState {
 #[cfg(test)]
 mod tests {
     use crate::settings::{
-        AdvancementPolicy, FriendSelection, FriendSelectionPolicy, GameMode, GameModeSettings,
-        KittyTheftPolicy,
+        AdvancementPolicy, BlindBidPolicy, ExchangeDelegationPolicy, FinalTrickBonusRung,
+        FriendSelection, FriendSelectionPolicy, GameMode, GameModeSettings, HandRevealPolicy,
+        KittyBurialRules, KittyDiggingPolicy, KittyPenalty, KittyRevisionPolicy, KittyTheftPolicy,
+        LastTrickPolicy, MatchEndReason, MatchLength, NoBidPolicy, PlayTakebackPolicy,
+        ScoringPolicy, TeamFormation, ThrowChallengePenalties, ThrowChallengePolicy, ThrowPenalty,
+        ThrowPenaltyPolicy, TrumpSelectionPolicy, TrumpSuitPolicy,
     };
 
+    use shengji_mechanics::bidding::{Bid, BidPriorityPolicy, BidRules, BidTakebackPolicy};
+    use shengji_mechanics::card_set::CardSet;
+    use shengji_mechanics::deck::Deck;
     use shengji_mechanics::player::Player;
-    use shengji_mechanics::types::{cards, Card, Number, PlayerID, Rank, FULL_DECK};
+    use shengji_mechanics::scoring::{AdvancementSchedule, GameScoringParameters};
+    use shengji_mechanics::sorting::SortOrder;
+    use shengji_mechanics::trick::PlayError;
+    use shengji_mechanics::types::{
+        cards, Card, Number, PermanentTrump, PlayerID, Rank, Suit, Trump, FULL_DECK,
+    };
 
-    use crate::game_state::{initialize_phase::InitializePhase, play_phase::PlayPhase};
-    use crate::message::MessageVariant;
+    use crate::game_state::{
+        exchange_phase::KittyBurialError, initialize_phase::InitializePhase, play_phase::PlayPhase,
+        GameState,
+    };
+    use crate::interactive::ActionError;
+    use crate::message::{CapturedPointCards, MessageVariant};
+    use crate::scoring::{compute_score, ScoreBonus, ScoreState};
 
     const R2: Rank = Rank::Number(Number::Two);
     const R3: Rank = Rank::Number(Number::Three);
@@ -197,24 +288,28 @@ mod tests {
                 name: "p1".into(),
                 level: R2,
                 metalevel: 0,
+                sort_order: Default::default(),
             },
             Player {
                 id: PlayerID(1),
                 name: "p2".into(),
                 level: R2,
                 metalevel: 0,
+                sort_order: Default::default(),
             },
             Player {
                 id: PlayerID(2),
                 name: "p3".into(),
                 level: R2,
                 metalevel: 0,
+                sort_order: Default::default(),
             },
             Player {
                 id: PlayerID(3),
                 name: "p4".into(),
                 level: R2,
                 metalevel: 0,
+                sort_order: Default::default(),
             },
         ]
     }
@@ -261,6 +356,7 @@ mod tests {
                     (PlayerID(0), starting_rank),
                     advance_policy,
                     RNT,
+                    &[],
                 );
                 let ranks = p.iter().map(|pp| pp.rank()).collect::<Vec<Rank>>();
                 assert_eq!(
@@ -312,6 +408,7 @@ mod tests {
                     (PlayerID(0), starting_rank),
                     advance_policy,
                     RA,
+                    &[],
                 );
                 let ranks = p.iter().map(|pp| pp.rank()).collect::<Vec<Rank>>();
                 assert_eq!(
@@ -384,6 +481,7 @@ mod tests {
                     (PlayerID(0), starting_rank),
                     advance_policy,
                     RNT,
+                    &[],
                 );
                 let ranks = p.iter().map(|pp| pp.rank()).collect::<Vec<Rank>>();
                 assert_eq!(
@@ -451,6 +549,7 @@ mod tests {
                     (PlayerID(0), p0_rank),
                     advance_policy,
                     RNT,
+                    &[],
                 );
                 let ranks = p.iter().map(|pp| pp.rank()).collect::<Vec<Rank>>();
                 assert_eq!(
@@ -477,6 +576,7 @@ mod tests {
             (PlayerID(0), p0_rank),
             AdvancementPolicy::Unrestricted,
             RNT,
+            &[],
         );
         let ranks = p.iter().map(|pp| pp.rank()).collect::<Vec<Rank>>();
         assert_eq!(ranks, vec![R4, R2, RNT, R2],);
@@ -494,11 +594,129 @@ mod tests {
             (PlayerID(0), p0_rank),
             AdvancementPolicy::Unrestricted,
             RNT,
+            &[],
         );
         let ranks = p.iter().map(|pp| pp.rank()).collect::<Vec<Rank>>();
         assert_eq!(ranks, vec![R3, R2, R3, R2],);
     }
 
+    #[test]
+    fn test_final_rank_policies() {
+        let won_match = |msgs: &[MessageVariant]| {
+            msgs.iter()
+                .find_map(|m| match m {
+                    MessageVariant::GameFinished { result } => {
+                        let mut names = result
+                            .iter()
+                            .filter(|(_, r)| r.won_match)
+                            .map(|(name, _)| name.clone())
+                            .collect::<Vec<_>>();
+                        names.sort();
+                        Some(names)
+                    }
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        let mut players = init_players();
+        for p in &mut players {
+            p.level = RA;
+        }
+        // Winning by two levels on A isn't enough.
+        let msgs = PlayPhase::compute_player_level_deltas(
+            players.iter_mut(),
+            0,
+            2,
+            &[PlayerID(0), PlayerID(2)],
+            true,
+            (PlayerID(0), RA),
+            AdvancementPolicy::MustWinExactly,
+            RA,
+            &[],
+        );
+        assert_eq!(players[0].rank(), RA);
+        assert!(won_match(&msgs).is_empty());
+
+        let msgs = PlayPhase::compute_player_level_deltas(
+            players.iter_mut(),
+            0,
+            1,
+            &[PlayerID(0), PlayerID(2)],
+            true,
+            (PlayerID(0), RA),
+            AdvancementPolicy::MustWinExactly,
+            RA,
+            &[],
+        );
+        assert_eq!(players[0].rank(), R2);
+        assert_eq!(won_match(&msgs), vec!["p1".to_string(), "p3".to_string()]);
+
+        // Without restrictions, the attacking team can win the match too.
+        let msgs = PlayPhase::compute_player_level_deltas(
+            players.iter_mut(),
+            2,
+            0,
+            &[PlayerID(0), PlayerID(2)],
+            false,
+            (PlayerID(0), R2),
+            AdvancementPolicy::FullyUnrestricted,
+            RA,
+            &[],
+        );
+        assert_eq!(players[1].rank(), R3);
+        assert_eq!(won_match(&msgs), vec!["p2".to_string(), "p4".to_string()]);
+    }
+
+    #[test]
+    fn test_defended_ranks() {
+        let mut players = init_players();
+        let msgs = PlayPhase::compute_player_level_deltas(
+            players.iter_mut(),
+            3,
+            3,
+            &[PlayerID(0), PlayerID(2)],
+            true,
+            (PlayerID(0), R2),
+            AdvancementPolicy::FullyUnrestricted,
+            RNT,
+            &[Number::Four, Number::Jack],
+        );
+        // Everyone is held at 4, which can only be passed while defending.
+        for p in &players {
+            assert_eq!(p.rank(), R4);
+        }
+        assert!(msgs.iter().any(|m| matches!(
+            m,
+            MessageVariant::AdvancementBlocked {
+                player: PlayerID(1),
+                rank: R4,
+            }
+        )));
+
+        let _ = PlayPhase::compute_player_level_deltas(
+            players.iter_mut(),
+            1,
+            1,
+            &[PlayerID(0), PlayerID(2)],
+            true,
+            (PlayerID(0), R4),
+            AdvancementPolicy::FullyUnrestricted,
+            RNT,
+            &[Number::Four, Number::Jack],
+        );
+        let ranks = players.iter().map(|p| p.rank()).collect::<Vec<_>>();
+        assert_eq!(ranks, vec![R5, R4, R5, R4]);
+
+        let mut init = InitializePhase::new();
+        init.set_defended_ranks(vec![Number::King, Number::Five, Number::King])
+            .unwrap();
+        assert!(init
+            .set_defended_ranks(vec![Number::Five, Number::King])
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_player_level_deltas() {
         let mut players = init_players();
@@ -513,6 +731,7 @@ mod tests {
             (PlayerID(0), R5),
             AdvancementPolicy::Unrestricted,
             RNT,
+            &[],
         );
         for p in &players {
             assert_eq!(p.rank(), Rank::Number(Number::Four));
@@ -528,6 +747,7 @@ mod tests {
             (PlayerID(0), Rank::Number(Number::Ace)),
             AdvancementPolicy::DefendPoints,
             RNT,
+            &[],
         );
         for p in &players {
             assert_eq!(p.rank(), R5);
@@ -544,6 +764,7 @@ mod tests {
             (PlayerID(0), RA),
             AdvancementPolicy::DefendPoints,
             RNT,
+            &[],
         );
         for p in &players {
             if p.id == PlayerID(0) || p.id == PlayerID(2) {
@@ -564,6 +785,7 @@ mod tests {
             (PlayerID(0), Rank::Number(Number::Ace)),
             AdvancementPolicy::DefendPoints,
             RNT,
+            &[],
         );
 
         for p in &players {
@@ -586,11 +808,18 @@ mod tests {
         for n_players in 4..10 {
             init.add_player(format!("p{n_players}")).unwrap();
             for n_decks in 1..n_players {
+                let mut init_ = init.clone();
+                init_.set_num_decks(Some(n_decks)).unwrap();
+                init_.set_kitty_size(Some(0)).unwrap_err();
+                init_
+                    .set_kitty_size(Some(n_decks * FULL_DECK.len() - 1))
+                    .unwrap_err();
                 for kitty_size in 1..30 {
                     let mut init_ = init.clone();
                     init_.set_num_decks(Some(n_decks)).unwrap();
                     if init_.set_kitty_size(Some(kitty_size)).is_ok() {
                         let draw = init_.start(p1).unwrap();
+                        assert!(!draw.deck().is_empty());
                         assert_eq!(draw.deck().len() % n_players, 0);
                         assert_eq!(draw.kitty().len(), kitty_size);
                         assert_eq!(
@@ -603,6 +832,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_short_deck() {
+        let mut init = InitializePhase::new();
+        let mut players = vec![];
+        for n in 0..4 {
+            players.push(init.add_player(format!("p{n}")).unwrap().0);
+        }
+        init.set_num_decks(Some(2)).unwrap();
+        init.set_min_deck_number(Some(Number::Six)).unwrap();
+        assert!(init.decks().unwrap().iter().all(|d| d.min == Number::Six));
+
+        // Nobody can bid on a 2 if the 2s have been removed.
+        assert!(init.start(players[0]).is_err());
+        for p in &players {
+            init.set_rank(*p, Rank::Number(Number::Six)).unwrap();
+        }
+        let draw = init.start(players[0]).unwrap();
+        let dealt = draw
+            .removed_cards()
+            .iter()
+            .chain(draw.deck())
+            .chain(draw.kitty())
+            .collect::<Vec<_>>();
+        assert_eq!(dealt.len(), 2 * 38);
+        assert!(dealt
+            .iter()
+            .all(|c| c.number().map(|n| n >= Number::Six).unwrap_or(true)));
+        assert_eq!(dealt.iter().flat_map(|c| c.points()).sum::<usize>(), 2 * 80);
+    }
+
+    #[test]
+    fn test_deck_indices_dealt() {
+        let mut init = InitializePhase::new();
+        let mut players = vec![];
+        for n in 0..4 {
+            players.push(init.add_player(format!("p{n}")).unwrap().0);
+        }
+        let mut draw = init.start(players[0]).unwrap();
+        while !draw.deck().is_empty() {
+            let next = draw.next_player().unwrap();
+            draw.draw_card(next).unwrap();
+        }
+
+        let mut from_each_deck = [0; 2];
+        for p in &players {
+            let hand_size = draw.hands().get(*p).unwrap().values().sum::<usize>();
+            let deck_indices = draw.hands().deck_indices(*p).unwrap();
+            assert_eq!(
+                deck_indices.values().map(|d| d.len()).sum::<usize>(),
+                hand_size
+            );
+            for (card, indices) in deck_indices {
+                assert_eq!(indices.len(), draw.hands().get(*p).unwrap()[card]);
+                for idx in indices {
+                    from_each_deck[*idx] += 1;
+                }
+            }
+        }
+        assert_eq!(
+            from_each_deck.iter().sum::<usize>() + draw.kitty().len(),
+            2 * FULL_DECK.len()
+        );
+    }
+
     #[test]
     fn test_bid_sequence() {
         let mut init = InitializePhase::new();
@@ -638,6 +931,21 @@ mod tests {
         assert!(draw.bid(p3, Card::SmallJoker, 2));
         assert!(draw.bid(p2, Card::BigJoker, 2));
         assert!(!draw.bid(p1, cards::H_2, 2));
+
+        // The cards used to bid stay visible to the other players.
+        let mut redacted = draw.clone();
+        redacted.destructively_redact_for_player(p4);
+        assert_eq!(
+            redacted.hands().counts(p1),
+            Some(&CardSet::from_cards(vec![cards::H_2, cards::H_2]))
+        );
+        assert_eq!(
+            redacted.hands().counts(p3),
+            Some(&CardSet::from_cards(vec![
+                Card::SmallJoker,
+                Card::SmallJoker
+            ]))
+        );
     }
 
     #[test]
@@ -688,51 +996,2031 @@ mod tests {
     }
 
     #[test]
-    fn test_tuple_protection_case() {
+    fn test_keep_bid_cards() {
+        for keep_bid_cards in [false, true] {
+            let mut init = InitializePhase::new();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            init.set_kitty_theft_policy(KittyTheftPolicy::AllowKittyTheft)
+                .unwrap();
+            init.set_bid_rules(BidRules {
+                keep_bid_cards,
+                ..Default::default()
+            })
+            .unwrap();
+            let mut draw = init.start(PlayerID(0)).unwrap();
+            *draw.deck_mut() = vec![
+                cards::S_2,
+                Card::SmallJoker,
+                Card::BigJoker,
+                cards::H_2,
+                cards::S_2,
+                Card::SmallJoker,
+                Card::BigJoker,
+                cards::H_2,
+            ];
+            *draw.position_mut() = 0;
+            for p in [p1, p2, p3, p4, p1, p2, p3, p4] {
+                draw.draw_card(p).unwrap();
+            }
+
+            assert!(draw.bid(p1, cards::H_2, 1));
+            let mut exchange = draw.advance(p1).unwrap();
+            // Only the one card that was bid has to be kept.
+            exchange.move_card_to_kitty(p1, cards::H_2).unwrap();
+            let buried = exchange.move_card_to_kitty(p1, cards::H_2).is_ok();
+            assert_eq!(buried, !keep_bid_cards);
+            exchange.move_card_to_hand(p1, cards::H_2).unwrap();
+            if buried {
+                exchange.move_card_to_hand(p1, cards::H_2).unwrap();
+            }
+            exchange.finalize(p1).unwrap();
+
+            // Once the bid is overturned, the new bidder has to keep theirs.
+            assert!(exchange.bid(p3, Card::SmallJoker, 2));
+            exchange.pick_up_cards(p3).unwrap();
+            assert_eq!(
+                exchange.move_card_to_kitty(p3, Card::SmallJoker).is_ok(),
+                !keep_bid_cards
+            );
+        }
+    }
+
+    #[test]
+    fn test_kitty_burial_rules() {
         use cards::*;
 
         let mut init = InitializePhase::new();
-        init.set_trick_draw_policy(
-            shengji_mechanics::trick::TrickDrawPolicy::LongerTuplesProtected,
-        )
+        init.set_kitty_burial_rules(KittyBurialRules {
+            allow_point_cards: false,
+            allow_trump: false,
+        })
         .unwrap();
         let p1 = init.add_player("p1".into()).unwrap().0;
         let p2 = init.add_player("p2".into()).unwrap().0;
         let p3 = init.add_player("p3".into()).unwrap().0;
         let p4 = init.add_player("p4".into()).unwrap().0;
+        // Only jacks are worth points, so the 5 can be buried.
+        let mut parameters = GameScoringParameters::default();
+        parameters.point_schedule.numbers = vec![(Number::Jack, 10)].into_iter().collect();
+        init.set_game_scoring_parameters(parameters).unwrap();
         let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, S_3, S_4, S_6, C_3, S_7, S_8, S_9];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.kitty_mut() = vec![D_3, D_4, D_5, D_6, D_7, D_8, D_9, D_J];
+        *draw.position_mut() = 0;
+        for p in [p1, p2, p3, p4, p1, p2, p3, p4] {
+            draw.draw_card(p).unwrap();
+        }
+        draw.try_bid(p1, H_2, 1).unwrap();
+        let mut exchange = draw.advance(p1).unwrap();
 
-        let p1_hand = vec![S_9, S_9, S_10, S_10, S_K, S_3, S_4, S_5, S_7, S_7, H_2];
-        let p2_hand = vec![S_3, S_3, S_5, S_5, S_7, S_8, S_J, S_Q, C_3, C_4, C_5];
-        let p3_hand = vec![S_3, S_5, S_10, S_J, S_Q, S_6, S_8, S_8, S_8, C_6, C_7];
-        let p4_hand = vec![S_6, S_6, S_6, C_8, C_9, C_10, C_J, C_Q, C_K, C_A, C_A];
+        let burial_error = |e: anyhow::Error| match ActionError::from_error(&e) {
+            Some(ActionError::KittyBurial(e)) => e,
+            _ => panic!("{}", e),
+        };
+        assert_eq!(
+            burial_error(exchange.finalize(p1).unwrap_err()),
+            KittyBurialError::PointCard(D_J)
+        );
+        assert_eq!(
+            burial_error(exchange.move_card_to_kitty(p1, H_2).unwrap_err()),
+            KittyBurialError::Trump(H_2)
+        );
+        exchange.move_card_to_hand(p1, D_J).unwrap();
+        exchange.move_card_to_kitty(p1, C_3).unwrap();
+
+        // Only restricted cards are left, so they can be buried.
+        exchange.move_card_to_kitty(p1, H_2).unwrap();
+        exchange.move_card_to_hand(p1, H_2).unwrap();
+        exchange.move_card_to_hand(p1, C_3).unwrap();
+        exchange.move_card_to_kitty(p1, H_2).unwrap_err();
+
+        // The room owner can lift the rules.
+        exchange.waive_kitty_burial_rules(p2).unwrap_err();
+        exchange.waive_kitty_burial_rules(p1).unwrap();
+        exchange.move_card_to_kitty(p1, H_2).unwrap();
+        exchange.finalize(p1).unwrap();
+        exchange.waive_kitty_burial_rules(p1).unwrap_err();
+    }
 
-        let mut deck = vec![];
-        for i in 0..11 {
-            deck.push(p1_hand[i]);
-            deck.push(p2_hand[i]);
-            deck.push(p3_hand[i]);
-            deck.push(p4_hand[i]);
-        }
+    #[test]
+    fn test_room_owner() {
+        let mut init = InitializePhase::new();
+        assert_eq!(init.room_owner(), None);
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        assert_eq!(init.room_owner(), Some(p1));
+
+        // The room goes to the next player in seating order, not to the
+        // player who joined next.
+        init.reorder_players(&[p1, p3, p2]).unwrap();
+        init.remove_player(p1).unwrap();
+        assert_eq!(init.room_owner(), Some(p3));
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        assert_eq!(init.room_owner(), Some(p3));
+
+        // Leaving the room as an observer hands it over, too.
+        init.make_observer(p3).unwrap();
+        assert_eq!(init.room_owner(), Some(p3));
+        init.remove_observer(p3).unwrap();
+        assert_eq!(init.room_owner(), Some(p2));
+        init.remove_player(p2).unwrap();
+        init.remove_player(p1).unwrap();
+        assert_eq!(init.room_owner(), None);
+    }
+
+    #[test]
+    fn test_dig_kitty() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        init.set_kitty_digging_policy(KittyDiggingPolicy::DigWithJokers { jokers: 2 })
+            .unwrap();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![
+            H_2,
+            Card::SmallJoker,
+            D_3,
+            S_5,
+            S_3,
+            Card::BigJoker,
+            D_4,
+            S_6,
+            S_4,
+            C_2,
+            D_6,
+            S_7,
+        ];
         deck.reverse();
         *draw.deck_mut() = deck;
         *draw.position_mut() = 0;
-
-        for _ in 0..11 {
-            draw.draw_card(p1).unwrap();
-            draw.draw_card(p2).unwrap();
-            draw.draw_card(p3).unwrap();
-            draw.draw_card(p4).unwrap();
+        for _ in 0..3 {
+            for p in [p1, p2, p3, p4] {
+                draw.draw_card(p).unwrap();
+            }
         }
+        draw.try_bid(p1, H_2, 1).unwrap();
+        let mut exchange = draw.advance(p1).unwrap();
 
-        assert!(draw.bid(p1, cards::H_2, 1));
+        exchange.dig_kitty(p2, C_2).unwrap_err();
+        exchange.advance(p1).unwrap_err();
+        exchange.finalize(p1).unwrap();
+        // p3 is on the landlord's team, and p4 doesn't have any jokers.
+        exchange.dig_kitty(p3, D_3).unwrap_err();
+        exchange.dig_kitty(p4, S_5).unwrap_err();
+        exchange.dig_kitty(p2, D_3).unwrap_err();
+        exchange.dig_kitty(p2, C_2).unwrap();
+        exchange.dig_kitty(p4, S_5).unwrap_err();
+
+        assert_eq!(exchange.landlord(), p2);
+        assert_eq!(exchange.trump().suit(), Some(Suit::Clubs));
+        assert!(!exchange.bid(p1, H_2, 1));
+        exchange.advance(p1).unwrap_err();
+        exchange.advance(p2).unwrap_err();
+        exchange.finalize(p2).unwrap();
+        let play = exchange.advance(p2).unwrap();
+        assert_eq!(play.landlords_team(), &[p2, p4]);
+    }
 
-        let exchange = draw.advance(p1).unwrap();
+    #[test]
+    fn test_delegate_exchange() {
+        use cards::*;
+
+        let setup_exchange = |policy| {
+            let mut init = InitializePhase::new();
+            init.set_exchange_delegation_policy(policy).unwrap();
+            init.set_should_reveal_kitty_at_end_of_game(true).unwrap();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            let mut draw = init.start(PlayerID(0)).unwrap();
+            let mut deck = vec![H_2, S_3, C_8, S_6, C_7, S_4, C_9, S_7];
+            deck.reverse();
+            *draw.deck_mut() = deck;
+            *draw.position_mut() = 0;
+            *draw.kitty_mut() = vec![D_3, D_4];
+            for _ in 0..2 {
+                for p in [p1, p2, p3, p4] {
+                    draw.draw_card(p).unwrap();
+                }
+            }
+            assert!(draw.bid(p1, H_2, 1));
+            ([p1, p2, p3, p4], draw.advance(p1).unwrap())
+        };
+
+        let ([p1, _, p3, _], mut exchange) = setup_exchange(ExchangeDelegationPolicy::LandlordOnly);
+        assert_eq!(
+            exchange.delegate_exchange(p1, p3).unwrap_err().to_string(),
+            "Only the leader can exchange the bottom cards"
+        );
+
+        let ([p1, p2, p3, _], mut exchange) =
+            setup_exchange(ExchangeDelegationPolicy::AllowTeammate);
+        exchange.delegate_exchange(p2, p3).unwrap_err();
+        exchange.delegate_exchange(p1, p2).unwrap_err();
+        exchange.delegate_exchange(p1, p3).unwrap();
+        assert_eq!(exchange.exchanger(), p3);
+        exchange.move_card_to_hand(p1, D_3).unwrap_err();
+        exchange.move_card_to_hand(p3, D_3).unwrap();
+        exchange.delegate_exchange(p1, p1).unwrap_err();
+        exchange.move_card_to_kitty(p3, C_8).unwrap();
+
+        // The leader takes the exchange back to finish it off.
+        exchange.delegate_exchange(p1, p1).unwrap();
+        exchange.move_card_to_hand(p1, D_4).unwrap();
+        exchange.move_card_to_kitty(p1, C_7).unwrap();
+        let play = exchange.advance(p1).unwrap();
+
+        let kitty = play.kitty_reveal().unwrap();
+        assert_eq!(kitty.cards, vec![C_8, C_7]);
+        assert_eq!(kitty.buried_by, vec![Some(p3), Some(p1)]);
+    }
+
+    #[test]
+    fn test_revise_kitty() {
+        use cards::*;
+
+        let setup_play = |policy| {
+            let mut init = InitializePhase::new();
+            init.set_kitty_revision_policy(policy).unwrap();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            let mut draw = init.start(PlayerID(0)).unwrap();
+            let mut deck = vec![H_2, S_3, C_8, S_6, C_7, S_4, C_9, S_7];
+            deck.reverse();
+            *draw.deck_mut() = deck;
+            *draw.position_mut() = 0;
+            *draw.kitty_mut() = vec![D_3, D_4];
+            for _ in 0..2 {
+                for p in [p1, p2, p3, p4] {
+                    draw.draw_card(p).unwrap();
+                }
+            }
+            assert!(draw.bid(p1, H_2, 1));
+            let mut exchange = draw.advance(p1).unwrap();
+            exchange.move_card_to_hand(p1, D_3).unwrap();
+            exchange.move_card_to_kitty(p1, C_7).unwrap();
+            ([p1, p2, p3, p4], exchange.advance(p1).unwrap())
+        };
+
+        let ([p1, ..], play) = setup_play(KittyRevisionPolicy::NoRevision);
+        assert_eq!(
+            play.revise_kitty(p1).unwrap_err().to_string(),
+            "The bottom cards can't be changed once play has started"
+        );
+
+        let ([p1, p2, ..], play) = setup_play(KittyRevisionPolicy::AllowRevisionBeforeFirstLead);
+        play.revise_kitty(p2).unwrap_err();
+        let mut exchange = play.revise_kitty(p1).unwrap();
+        assert_eq!(exchange.exchanger(), p1);
+        assert_eq!(exchange.hands().get(p1).unwrap().count(D_3), 1);
+        assert_eq!(exchange.hands().get(p1).unwrap().count(C_7), 0);
+
+        // The previous burial is kept, so it can be changed again.
+        exchange.move_card_to_hand(p1, C_7).unwrap();
+        exchange.move_card_to_kitty(p1, D_3).unwrap();
         let mut play = exchange.advance(p1).unwrap();
-        play.play_cards(p1, &[S_9, S_9, S_10, S_10, S_K]).unwrap();
-        play.play_cards(p2, &[S_3, S_3, S_5, S_5, S_7]).unwrap();
-        play.play_cards(p3, &[S_3, S_5, S_10, S_J, S_Q]).unwrap();
-        play.play_cards(p4, &[S_6, S_6, S_6, C_8, C_9]).unwrap();
+        play.play_cards(p1, &[C_7]).unwrap();
+        assert_eq!(
+            play.revise_kitty(p1).unwrap_err().to_string(),
+            "The bottom cards can't be changed after the first card is led"
+        );
+    }
+
+    #[test]
+    fn test_face_up_kitty_burial() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        init.set_face_up_kitty_burial(true).unwrap();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, S_3, C_8, S_6, C_7, S_4, C_9, S_7];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_3, D_4];
+        for _ in 0..2 {
+            for p in [p1, p2, p3, p4] {
+                draw.draw_card(p).unwrap();
+            }
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let mut exchange = draw.advance(p1).unwrap();
+        exchange.move_card_to_hand(p1, D_3).unwrap();
+        exchange.move_card_to_kitty(p1, C_7).unwrap();
+
+        // Only the card which was buried is shown, not the one dealt there.
+        let kitty_for = |state: GameState, player| {
+            let value = match state.for_player(player) {
+                GameState::Exchange(e) => serde_json::to_value(&e).unwrap(),
+                GameState::Play(p) => serde_json::to_value(&p).unwrap(),
+                _ => unreachable!(),
+            };
+            serde_json::from_value::<Vec<Card>>(value["kitty"].clone()).unwrap()
+        };
+        let expected = vec![Card::Unknown, C_7];
+        assert_eq!(
+            kitty_for(GameState::Exchange(exchange.clone()), p2),
+            expected
+        );
+        let play = exchange.advance(p1).unwrap();
+        assert_eq!(kitty_for(GameState::Play(play), p3), expected);
+    }
+
+    #[test]
+    fn test_kitty_redacted_after_theft() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        init.set_kitty_theft_policy(KittyTheftPolicy::AllowKittyTheft)
+            .unwrap();
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, S_2, C_8, S_6, C_7, S_2, C_9, S_7];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_3, D_4];
+        for _ in 0..2 {
+            for p in [p1, p2, p3, p4].iter() {
+                draw.draw_card(*p).unwrap();
+            }
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let mut exchange = draw.advance(p1).unwrap();
+        exchange.move_card_to_hand(p1, D_3).unwrap();
+        exchange.move_card_to_kitty(p1, C_7).unwrap();
+        exchange.finalize(p1).unwrap();
+
+        // p2 steals the bottom cards, and buries on top of what p1 left.
+        assert!(exchange.bid(p2, S_2, 2));
+        exchange.pick_up_cards(p2).unwrap();
+        exchange.move_card_to_hand(p2, D_4).unwrap();
+        exchange.move_card_to_kitty(p2, S_2).unwrap();
+        exchange.finalize(p2).unwrap();
+        let play = exchange.advance(p1).unwrap();
+
+        let kitty_for = |player| match GameState::Play(play.clone()).for_player(player) {
+            GameState::Play(p) => serde_json::from_value::<Vec<Card>>(
+                serde_json::to_value(&p).unwrap()["kitty"].clone(),
+            )
+            .unwrap(),
+            _ => unreachable!(),
+        };
+        // The previous exchanger only sees the card they buried, not what the
+        // player who stole the bottom cards buried.
+        assert_eq!(kitty_for(p1), vec![C_7, Card::Unknown]);
+        assert_eq!(kitty_for(p2), vec![C_7, S_2]);
+        assert_eq!(kitty_for(p3), vec![Card::Unknown, Card::Unknown]);
+    }
+
+    #[test]
+    fn test_kitty_penalty_multiplier() {
+        let custom = KittyPenalty::Custom {
+            single: 3,
+            pair: 5,
+            tractor: 7,
+        };
+        // (largest unit size, tractor) for a single, a pair and a tractor.
+        let cases = [(1, false), (2, false), (4, true)];
+        for (penalty, expected) in [
+            (KittyPenalty::Times, [2, 4, 8]),
+            (KittyPenalty::Power, [2, 4, 16]),
+            (KittyPenalty::Double, [2, 2, 2]),
+            (KittyPenalty::QuadrupleForTractors, [2, 2, 4]),
+            (custom, [3, 5, 7]),
+        ]
+        .iter()
+        {
+            let multipliers = cases
+                .iter()
+                .map(|(size, tractor)| penalty.multiplier(*size, *tractor))
+                .collect::<Vec<_>>();
+            assert_eq!(multipliers, expected.to_vec(), "{:?}", penalty);
+        }
+        assert_eq!(KittyPenalty::Power.multiplier(64, true), usize::MAX);
+    }
+
+    #[test]
+    fn test_trump_suit_policy() {
+        for (policy, expected) in [
+            (
+                TrumpSuitPolicy::DeclaredSuit,
+                Trump::Standard {
+                    suit: Suit::Hearts,
+                    number: Number::Two,
+                    permanent: PermanentTrump::None,
+                },
+            ),
+            (
+                TrumpSuitPolicy::JokersAndRank,
+                Trump::NoTrump {
+                    number: Some(Number::Two),
+                    permanent: PermanentTrump::None,
+                },
+            ),
+            (
+                TrumpSuitPolicy::JokersOnly,
+                Trump::NoTrump {
+                    number: None,
+                    permanent: PermanentTrump::None,
+                },
+            ),
+        ] {
+            let mut init = InitializePhase::new();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            init.set_kitty_theft_policy(KittyTheftPolicy::AllowKittyTheft)
+                .unwrap();
+            init.set_trump_suit_policy(policy).unwrap();
+            let mut draw = init.start(PlayerID(0)).unwrap();
+            *draw.deck_mut() = vec![
+                cards::S_2,
+                Card::SmallJoker,
+                cards::H_2,
+                cards::D_2,
+                cards::S_2,
+                Card::SmallJoker,
+                cards::H_2,
+                cards::H_2,
+            ];
+            *draw.position_mut() = 0;
+            for p in [p1, p2, p3, p4, p1, p2, p3, p4] {
+                draw.draw_card(p).unwrap();
+            }
+
+            assert!(draw.bid(p1, cards::H_2, 1));
+            let mut exchange = draw.advance(p1).unwrap();
+            assert_eq!(exchange.trump(), expected);
+
+            // Overbidding in the exchange phase follows the same policy.
+            exchange.finalize(p1).unwrap();
+            assert!(exchange.bid(p2, cards::H_2, 2));
+            exchange.pick_up_cards(p2).unwrap();
+            assert_eq!(exchange.trump(), expected);
+        }
+    }
+
+    #[test]
+    fn test_set_sort_order() {
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        for name in ["p2", "p3", "p4"] {
+            init.add_player(name.into()).unwrap();
+        }
+        let sort_order = SortOrder {
+            group_tuples: true,
+            ..Default::default()
+        };
+        let mut state = GameState::Draw(init.start(p1).unwrap());
+        state.set_sort_order(p1, sort_order.clone()).unwrap();
+        state
+            .set_sort_order(PlayerID(99), SortOrder::default())
+            .unwrap_err();
+        assert_eq!(state.players()[0].sort_order, sort_order);
+        assert_eq!(state.players()[1].sort_order, SortOrder::default());
+    }
+
+    #[test]
+    fn test_permanent_trump() {
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        init.set_permanent_trump(PermanentTrump::Card(Card::BigJoker))
+            .unwrap_err();
+        init.set_permanent_trump(PermanentTrump::Card(cards::S_2))
+            .unwrap();
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        *draw.deck_mut() = vec![
+            cards::C_3,
+            cards::D_3,
+            cards::H_2,
+            cards::S_2,
+            cards::C_3,
+            cards::D_3,
+            cards::H_3,
+            cards::S_2,
+        ];
+        *draw.position_mut() = 0;
+        for p in [p1, p2, p3, p4, p1, p2, p3, p4] {
+            draw.draw_card(p).unwrap();
+        }
+
+        // The general can't be used to bid.
+        assert!(!draw.bid(p1, cards::S_2, 2));
+        assert!(draw.bid(p2, cards::H_2, 1));
+        let exchange = draw.advance(p2).unwrap();
+        let trump = exchange.trump();
+        assert_eq!(
+            trump,
+            Trump::Standard {
+                suit: Suit::Hearts,
+                number: Number::Two,
+                permanent: PermanentTrump::Card(cards::S_2),
+            }
+        );
+        assert!(trump.is_trump(cards::S_2));
+        assert_eq!(
+            trump.compare(cards::S_2, cards::H_2),
+            std::cmp::Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_tuple_protection_case() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        init.set_trick_draw_policy(
+            shengji_mechanics::trick::TrickDrawPolicy::LongerTuplesProtected,
+        )
+        .unwrap();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+
+        let p1_hand = vec![S_9, S_9, S_10, S_10, S_K, S_3, S_4, S_5, S_7, S_7, H_2];
+        let p2_hand = vec![S_3, S_3, S_5, S_5, S_7, S_8, S_J, S_Q, C_3, C_4, C_5];
+        let p3_hand = vec![S_3, S_5, S_10, S_J, S_Q, S_6, S_8, S_8, S_8, C_6, C_7];
+        let p4_hand = vec![S_6, S_6, S_6, C_8, C_9, C_10, C_J, C_Q, C_K, C_A, C_A];
+
+        let mut deck = vec![];
+        for i in 0..11 {
+            deck.push(p1_hand[i]);
+            deck.push(p2_hand[i]);
+            deck.push(p3_hand[i]);
+            deck.push(p4_hand[i]);
+        }
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+
+        for _ in 0..11 {
+            draw.draw_card(p1).unwrap();
+            draw.draw_card(p2).unwrap();
+            draw.draw_card(p3).unwrap();
+            draw.draw_card(p4).unwrap();
+        }
+
+        assert!(draw.bid(p1, cards::H_2, 1));
+
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+        play.play_cards(p1, &[S_9, S_9, S_10, S_10, S_K]).unwrap();
+        play.play_cards(p2, &[S_3, S_3, S_5, S_5, S_7]).unwrap();
+        play.play_cards(p3, &[S_3, S_5, S_10, S_J, S_Q]).unwrap();
+        play.play_cards(p4, &[S_6, S_6, S_6, C_8, C_9]).unwrap();
+    }
+
+    #[test]
+    fn test_bid_rules() {
+        use cards::*;
+
+        let setup_draw = |bid_rules| {
+            let mut init = InitializePhase::new();
+            init.set_bid_rules(bid_rules).unwrap();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            let mut draw = init.start(PlayerID(0)).unwrap();
+            let mut deck = vec![H_2, S_2, H_2, C_2, H_2, S_2, H_2, C_2];
+            deck.reverse();
+            *draw.deck_mut() = deck;
+            *draw.position_mut() = 0;
+            for p in [p1, p2, p3, p4, p1, p2, p3, p4] {
+                draw.draw_card(p).unwrap();
+            }
+            ([p1, p2, p3, p4], draw)
+        };
+
+        let ([p1, _, p3, _], mut draw) = setup_draw(BidRules::default());
+        draw.try_bid(p1, H_2, 1).unwrap();
+        draw.try_bid(p1, H_2, 2).unwrap();
+        draw.try_bid(p3, C_2, 2).unwrap_err();
+
+        let ([p1, p2, p3, p4], mut draw) = setup_draw(BidRules {
+            self_outbid: false,
+            partner_reinforcement: false,
+            equal_count_outbid: false,
+            ..BidRules::default()
+        });
+        draw.try_bid(p1, H_2, 1).unwrap();
+        assert_eq!(
+            draw.try_bid(p1, H_2, 2).unwrap_err().to_string(),
+            "You can't outbid your own bid"
+        );
+        assert_eq!(
+            draw.try_bid(p3, H_2, 2).unwrap_err().to_string(),
+            "You can't reinforce your partner's bid"
+        );
+        draw.try_bid(p2, S_2, 2).unwrap();
+        assert_eq!(
+            draw.try_bid(p3, H_2, 2).unwrap_err().to_string(),
+            "A bid must have more cards than the winning bid"
+        );
+        assert!(!draw.bid(p4, C_2, 2));
+    }
+
+    #[test]
+    fn test_bid_takeback_while_drawing() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        init.set_bid_takeback_policy(BidTakebackPolicy::AllowBidTakebackWhileDrawing)
+            .unwrap();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, S_3, S_4, S_5, H_2, S_6, S_7, S_8];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        for p in [p1, p2, p3, p4, p1] {
+            draw.draw_card(p).unwrap();
+        }
+
+        draw.try_bid(p1, H_2, 1).unwrap();
+        draw.try_bid(p1, H_2, 2).unwrap();
+        draw.take_back_bid(p1).unwrap();
+        assert_eq!(draw.bids().len(), 1);
+        assert_eq!(draw.hands().revealed(p1).unwrap().count(H_2), 1);
+        draw.take_back_bid(p1).unwrap();
+        assert!(draw.bids().is_empty());
+        assert_eq!(draw.hands().revealed(p1).unwrap().count(H_2), 0);
+        // There's nothing left to take back.
+        draw.take_back_bid(p1).unwrap_err();
+
+        draw.try_bid(p1, H_2, 1).unwrap();
+        for p in [p2, p3, p4] {
+            draw.draw_card(p).unwrap();
+        }
+        assert_eq!(
+            draw.take_back_bid(p1).unwrap_err().to_string(),
+            "Bids can't be taken back once all the cards have been drawn"
+        );
+    }
+
+    #[test]
+    fn test_bid_priority() {
+        use cards::*;
+
+        let setup_draw = |policy| {
+            let mut init = InitializePhase::new();
+            init.set_bid_priority_policy(policy).unwrap();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            let mut draw = init.start(PlayerID(0)).unwrap();
+            let mut deck = vec![H_2, S_2, C_2, D_3, S_4, S_5, S_6, S_7];
+            deck.reverse();
+            *draw.deck_mut() = deck;
+            *draw.position_mut() = 0;
+            for p in [p1, p2, p3] {
+                draw.draw_card(p).unwrap();
+            }
+            ([p1, p2, p3, p4], draw)
+        };
+
+        let ([p1, p2, ..], mut draw) = setup_draw(BidPriorityPolicy::FirstClick);
+        assert_eq!(draw.try_bid(p2, S_2, 1).unwrap(), None);
+        assert!(draw.valid_bids(p1).unwrap().is_empty());
+        draw.try_bid(p1, H_2, 1).unwrap_err();
+
+        let ([p1, p2, p3, p4], mut draw) = setup_draw(BidPriorityPolicy::DrawOrder);
+        assert_eq!(
+            draw.valid_bids(p2).unwrap(),
+            vec![Bid {
+                id: p2,
+                card: S_2,
+                count: 1,
+                epoch: 0,
+            }]
+        );
+        assert_eq!(draw.try_bid(p2, S_2, 1).unwrap(), None);
+        assert!(draw.valid_bids(p3).unwrap().is_empty());
+        assert_eq!(
+            draw.valid_bids(p1).unwrap(),
+            vec![Bid {
+                id: p1,
+                card: H_2,
+                count: 1,
+                epoch: 0,
+            }]
+        );
+        // p3 draws after p2, so can't take priority.
+        draw.try_bid(p3, C_2, 1).unwrap_err();
+        assert_eq!(draw.try_bid(p1, H_2, 1).unwrap(), Some(p2));
+        assert_eq!(draw.bids().len(), 1);
+        assert_eq!(draw.bids()[0].id, p1);
+        assert_eq!(draw.hands().revealed(p2).unwrap().count(S_2), 0);
+        draw.take_back_bid(p1).unwrap();
+
+        // Once another card is drawn, the first bid stands.
+        draw.try_bid(p2, S_2, 1).unwrap();
+        draw.draw_card(p4).unwrap();
+        draw.try_bid(p1, H_2, 1).unwrap_err();
+    }
+
+    #[test]
+    fn test_no_bid_policy() {
+        use cards::*;
+
+        let setup_draw = |policy, landlord: bool| {
+            let mut init = InitializePhase::new();
+            init.set_no_bid_policy(policy).unwrap();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            if landlord {
+                init.set_landlord(Some(p1)).unwrap();
+            }
+            let mut draw = init.start(p1).unwrap();
+            *draw.deck_mut() = vec![S_3, S_4, S_5, S_6];
+            *draw.position_mut() = 0;
+            for p in [p1, p2, p3, p4] {
+                draw.draw_card(p).unwrap();
+            }
+            ([p1, p2, p3, p4], draw)
+        };
+
+        let ([p1, ..], mut draw) = setup_draw(NoBidPolicy::RevealKitty, true);
+        draw.pick_trump_without_bid(p1, Some(Suit::Spades))
+            .unwrap_err();
+        draw.redeal(p1).unwrap();
+        draw.reveal_card().unwrap();
+
+        let ([p1, ..], draw) = setup_draw(NoBidPolicy::Redeal, false);
+        let redealt = draw.redeal(p1).unwrap();
+        assert!(!redealt.deck().is_empty());
+        assert!(redealt.bids().is_empty());
+
+        let ([p1, p2, ..], mut draw) = setup_draw(NoBidPolicy::LandlordChooses, true);
+        draw.reveal_card().unwrap_err();
+        draw.pick_trump_without_bid(p2, Some(Suit::Spades))
+            .unwrap_err();
+        draw.pick_trump_without_bid(p1, None).unwrap_err();
+        match draw.pick_trump_without_bid(p1, Some(Suit::Spades)).unwrap() {
+            MessageVariant::TrumpPickedWithoutBid {
+                card,
+                random: false,
+            } => assert_eq!(card, S_2),
+            m => panic!("unexpected message {:?}", m),
+        }
+        draw.pick_trump_without_bid(p1, Some(Suit::Hearts))
+            .unwrap_err();
+        assert_eq!(
+            draw.advance(p1).unwrap().trump(),
+            Trump::Standard {
+                suit: Suit::Spades,
+                number: Number::Two,
+                permanent: PermanentTrump::None,
+            }
+        );
+
+        let ([p1, ..], mut draw) = setup_draw(NoBidPolicy::LandlordChooses, false);
+        draw.pick_trump_without_bid(p1, Some(Suit::Spades))
+            .unwrap_err();
+
+        let ([p1, p2, ..], mut draw) = setup_draw(NoBidPolicy::RandomTrump, false);
+        match draw.pick_trump_without_bid(p2, None).unwrap() {
+            MessageVariant::TrumpPickedWithoutBid { random: true, .. } => (),
+            m => panic!("unexpected message {:?}", m),
+        }
+        // Without a landlord, whoever drew first gets the bid.
+        draw.advance(p1).unwrap();
+    }
+
+    #[test]
+    fn test_blind_bid() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        init.set_blind_bid_policy(BlindBidPolicy::AllowBlindBids {
+            max_cards_drawn: 1,
+            levels: 2,
+        })
+        .unwrap();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![S_2, H_3, D_4, C_5, S_3, H_2, D_5, C_6];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        for p in [p1, p2, p3, p4] {
+            draw.draw_card(p).unwrap();
+        }
+        draw.try_blind_bid(p1, S_2, 1).unwrap();
+        for p in [p1, p2, p3, p4] {
+            draw.draw_card(p).unwrap();
+        }
+        assert_eq!(
+            draw.try_blind_bid(p2, H_2, 1).unwrap_err().to_string(),
+            "Blind bids can only be made before drawing more than 1 cards"
+        );
+
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+        for cards in [[S_2, H_2, D_4, C_5], [S_3, H_3, D_5, C_6]] {
+            for (p, card) in [p1, p2, p3, p4].iter().zip(cards.iter()) {
+                play.play_cards(*p, &[*card]).unwrap();
+            }
+            play.finish_trick().unwrap();
+        }
+        let (_, landlord_won, msgs) = play.finish_game().unwrap();
+        assert!(landlord_won);
+        assert!(msgs.iter().any(|m| matches!(
+            m,
+            MessageVariant::BlindBidResolved {
+                landlord_won: true,
+                levels: 2
+            }
+        )));
+        assert!(msgs.iter().any(|m| matches!(
+            m,
+            MessageVariant::RankAdvanced {
+                player,
+                new_rank: Rank::Number(Number::Seven),
+            } if *player == p1
+        )));
+    }
+
+    #[test]
+    fn test_trump_from_first_rank_card_drawn() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        init.set_trump_selection_policy(TrumpSelectionPolicy::FirstRankCardDrawn)
+            .unwrap();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![S_3, H_2, D_2, C_4];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+
+        assert!(draw.draw_card(p1).unwrap().is_none());
+        match draw.draw_card(p2).unwrap() {
+            Some(MessageVariant::DrewTrumpRankCard { card }) => assert_eq!(card, H_2),
+            m => panic!("unexpected message {:?}", m),
+        }
+        assert!(draw.draw_card(p3).unwrap().is_none());
+        assert!(draw.valid_bids(p3).unwrap().is_empty());
+        draw.try_bid(p3, D_2, 1).unwrap_err();
+        draw.draw_card(p4).unwrap();
+
+        draw.advance(p1).unwrap_err();
+        let exchange = draw.advance(p2).unwrap();
+        assert_eq!(exchange.landlord(), p2);
+        assert_eq!(
+            exchange.trump(),
+            Trump::Standard {
+                suit: Suit::Hearts,
+                number: Number::Two,
+                permanent: PermanentTrump::None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_sealed_bids() {
+        use cards::*;
+
+        let setup_draw = |policy| {
+            let mut init = InitializePhase::new();
+            init.set_trump_selection_policy(TrumpSelectionPolicy::SealedBids)
+                .unwrap();
+            init.set_bid_priority_policy(policy).unwrap();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            let mut draw = init.start(PlayerID(0)).unwrap();
+            let mut deck = vec![H_2, S_2, D_2, C_3, H_2, S_5, D_2, C_4];
+            deck.reverse();
+            *draw.deck_mut() = deck;
+            *draw.position_mut() = 0;
+            for p in [p1, p2, p3, p4, p1, p2, p3] {
+                draw.draw_card(p).unwrap();
+            }
+            draw.submit_sealed_bid(p3, Some((D_2, 2))).unwrap_err();
+            draw.draw_card(p4).unwrap();
+            ([p1, p2, p3, p4], draw)
+        };
+
+        let ([p1, p2, p3, p4], mut draw) = setup_draw(BidPriorityPolicy::DrawOrder);
+        draw.try_bid(p3, D_2, 2).unwrap_err();
+        draw.submit_sealed_bid(p3, Some((D_2, 2))).unwrap();
+        draw.submit_sealed_bid(p3, None).unwrap_err();
+        draw.submit_sealed_bid(p2, Some((S_2, 2))).unwrap_err();
+        draw.submit_sealed_bid(p2, Some((S_2, 1))).unwrap();
+        draw.submit_sealed_bid(p1, Some((H_2, 2))).unwrap();
+
+        let mut redacted = draw.clone();
+        redacted.destructively_redact_for_player(p2);
+        let sealed_bids = serde_json::to_value(&redacted).unwrap()["sealed_bids"].clone();
+        assert_eq!(sealed_bids[0]["bid"], serde_json::Value::Null);
+        assert_eq!(sealed_bids[1]["bid"]["card"], serde_json::json!(S_2));
+
+        let msgs = draw.submit_sealed_bid(p4, None).unwrap();
+        match &msgs[..] {
+            [MessageVariant::SubmittedSealedBid, MessageVariant::SealedBidsRevealed { bids, winner }] =>
+            {
+                assert_eq!(bids.len(), 4);
+                assert_eq!(*winner, Some(p1));
+            }
+            m => panic!("unexpected messages {:?}", m),
+        }
+        assert_eq!(draw.bids()[0].card, H_2);
+        draw.take_back_bid(p1).unwrap_err();
+        let exchange = draw.advance(p1).unwrap();
+        assert_eq!(exchange.trump().suit(), Some(Suit::Hearts));
+
+        let ([p1, p2, p3, p4], mut draw) = setup_draw(BidPriorityPolicy::FirstClick);
+        draw.submit_sealed_bid(p3, Some((D_2, 2))).unwrap();
+        draw.submit_sealed_bid(p1, Some((H_2, 2))).unwrap();
+        draw.submit_sealed_bid(p2, None).unwrap();
+        draw.submit_sealed_bid(p4, None).unwrap();
+        assert_eq!(draw.bids()[0].id, p3);
+    }
+
+    #[test]
+    fn test_reveal_hand() {
+        use cards::*;
+
+        let setup_play = |policy| {
+            let mut init = InitializePhase::new();
+            init.set_hand_reveal_policy(policy).unwrap();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            let mut draw = init.start(PlayerID(0)).unwrap();
+            *draw.deck_mut() = vec![S_3, S_4, S_5, S_6, H_2, H_2, H_2, H_2];
+            *draw.position_mut() = 0;
+            for p in [p1, p2, p3, p4, p1, p2, p3, p4] {
+                draw.draw_card(p).unwrap();
+            }
+            assert!(draw.bid(p1, H_2, 1));
+            let exchange = draw.advance(p1).unwrap();
+            ([p1, p2, p3, p4], exchange.advance(p1).unwrap())
+        };
+
+        let ([p1, p2, p3, _], mut play) = setup_play(HandRevealPolicy::AllowHandReveal);
+        let hand = play.hands().counts(p2).unwrap().clone();
+        match play.reveal_hand(p2).unwrap() {
+            MessageVariant::RevealedHand { cards } => {
+                assert_eq!(CardSet::from_cards(cards), hand)
+            }
+            msg => panic!("unexpected message {:?}", msg),
+        }
+        play.reveal_hand(p2).unwrap();
+        assert_eq!(play.revealed_players(), &[p2]);
+
+        let mut redacted = play.clone();
+        redacted.destructively_redact_for_player(p3);
+        assert_eq!(redacted.hands().counts(p2), Some(&hand));
+        assert_eq!(redacted.hands().counts(p3).unwrap().count(Card::Unknown), 0);
+        // p1's bid is still on display, but the rest of their hand isn't.
+        assert_eq!(
+            redacted.hands().counts(p1),
+            Some(&CardSet::from_cards(vec![H_2, Card::Unknown]))
+        );
+
+        let ([p1, ..], mut play) = setup_play(HandRevealPolicy::NoHandReveal);
+        play.reveal_hand(p1).unwrap_err();
+        assert!(play.revealed_players().is_empty());
+    }
+
+    #[test]
+    fn test_throw_penalty_policy() {
+        use cards::*;
+
+        let setup_play = |policy, penalty| {
+            let mut init = InitializePhase::new();
+            init.set_play_takeback_policy(PlayTakebackPolicy::NoPlayTakeback)
+                .unwrap();
+            init.set_throw_penalty_policy(policy).unwrap();
+            init.set_throw_penalty(penalty).unwrap();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            let mut draw = init.start(PlayerID(0)).unwrap();
+
+            let p1_hand = [S_K, S_3, H_2];
+            let p2_hand = [S_A, S_4, C_3];
+            let p3_hand = [S_5, S_6, C_4];
+            let p4_hand = [S_7, S_8, C_5];
+            let mut deck = vec![];
+            for i in 0..3 {
+                deck.extend([p1_hand[i], p2_hand[i], p3_hand[i], p4_hand[i]]);
+            }
+            deck.reverse();
+            *draw.deck_mut() = deck;
+            *draw.position_mut() = 0;
+            for _ in 0..3 {
+                for p in [p1, p2, p3, p4] {
+                    draw.draw_card(p).unwrap();
+                }
+            }
+            assert!(draw.bid(p1, H_2, 1));
+            let exchange = draw.advance(p1).unwrap();
+            ([p1, p2, p3, p4], exchange.advance(p1).unwrap())
+        };
+
+        // The throw fails because S_A beats S_K, so S_3 is played instead.
+        let ([p1, ..], mut play) =
+            setup_play(ThrowPenaltyPolicy::ForceSmallestUnit, ThrowPenalty::None);
+        play.play_cards(p1, &[S_K, S_3]).unwrap();
+        assert_eq!(play.trick().played_cards()[0].cards, vec![S_3]);
+        play.take_back_cards(p1).unwrap_err();
+
+        let ([p1, ..], mut play) =
+            setup_play(ThrowPenaltyPolicy::AllowTakeBack, ThrowPenalty::None);
+        play.play_cards(p1, &[S_K, S_3]).unwrap();
+        play.take_back_cards(p1).unwrap();
+        assert!(play.trick().played_cards().is_empty());
+        assert_eq!(play.hands().get(p1).unwrap().len(), 3);
+        // Ordinary plays still can't be taken back, and neither can a failed
+        // throw once the next player has played.
+        play.play_cards(p1, &[S_3]).unwrap();
+        play.take_back_cards(p1).unwrap_err();
+
+        let ([p1, p2, ..], mut play) =
+            setup_play(ThrowPenaltyPolicy::AllowTakeBack, ThrowPenalty::None);
+        play.play_cards(p1, &[S_K, S_3]).unwrap();
+        play.play_cards(p2, &[S_4]).unwrap();
+        play.take_back_cards(p1).unwrap_err();
+
+        let penalties = |penalty| {
+            let ([p1, p2, p3, p4], mut play) =
+                setup_play(ThrowPenaltyPolicy::DeductPoints, penalty);
+            play.play_cards(p1, &[S_K, S_3]).unwrap();
+            play.play_cards(p2, &[S_4]).unwrap();
+            play.play_cards(p3, &[S_5]).unwrap();
+            play.play_cards(p4, &[S_7]).unwrap();
+            play.finish_trick()
+                .unwrap()
+                .into_iter()
+                .filter_map(|m| match m {
+                    MessageVariant::ThrowPenaltyDeducted { player, points } => {
+                        Some((player, points))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(penalties(ThrowPenalty::None), vec![(PlayerID(0), 10)]);
+        // Both penalties apply, and are reported together.
+        assert_eq!(
+            penalties(ThrowPenalty::TenPointsPerAttempt),
+            vec![(PlayerID(0), 20)]
+        );
+    }
+
+    #[test]
+    fn test_throw_challenge() {
+        use cards::*;
+
+        let setup_play = |others: [[Card; 3]; 3]| {
+            let mut init = InitializePhase::new();
+            init.set_throw_challenge_policy(ThrowChallengePolicy::CheckedWhenChallenged)
+                .unwrap();
+            init.set_throw_challenge_penalties(ThrowChallengePenalties {
+                thrower: 20,
+                challenger: 5,
+            })
+            .unwrap();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            let mut draw = init.start(PlayerID(0)).unwrap();
+
+            let p1_hand = [S_K, S_3, H_2];
+            let mut deck = vec![];
+            for i in 0..3 {
+                deck.extend([p1_hand[i], others[0][i], others[1][i], others[2][i]]);
+            }
+            deck.reverse();
+            *draw.deck_mut() = deck;
+            *draw.position_mut() = 0;
+            for _ in 0..3 {
+                for p in [p1, p2, p3, p4] {
+                    draw.draw_card(p).unwrap();
+                }
+            }
+            assert!(draw.bid(p1, H_2, 1));
+            let exchange = draw.advance(p1).unwrap();
+            ([p1, p2, p3, p4], exchange.advance(p1).unwrap())
+        };
+
+        // S_A beats S_K, but the throw stands until it's challenged.
+        let ([p1, p2, p3, _], mut play) =
+            setup_play([[S_A, S_4, C_3], [S_5, S_6, C_4], [S_7, S_8, C_5]]);
+        play.play_cards(p1, &[S_K, S_3]).unwrap();
+        assert_eq!(play.trick().played_cards()[0].cards.len(), 2);
+        play.challenge_throw(p1).unwrap_err();
+        play.challenge_throw(p3).unwrap_err();
+        let msgs = play.challenge_throw(p2).unwrap();
+        assert!(matches!(
+            msgs[0],
+            MessageVariant::ThrowChallenged { upheld: true, penalized, points: 20, .. }
+                if penalized == p1
+        ));
+        assert_eq!(play.trick().played_cards()[0].cards, vec![S_3]);
+        play.challenge_throw(p2).unwrap_err();
+
+        let ([p1, p2, _, p4], mut play) =
+            setup_play([[D_A, D_4, C_3], [D_5, D_6, C_4], [D_7, D_8, C_5]]);
+        play.play_cards(p1, &[S_K, S_3]).unwrap();
+        let msgs = play.challenge_throw(p4).unwrap();
+        assert!(matches!(
+            msgs[0],
+            MessageVariant::ThrowChallenged { upheld: false, penalized, points: 5, .. }
+                if penalized == p4
+        ));
+        assert_eq!(play.trick().played_cards()[0].cards.len(), 2);
+        play.challenge_throw(p2).unwrap_err();
+    }
+
+    #[test]
+    fn test_trick_point_cards() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![S_K, S_5, S_10, S_3, H_2, C_4, C_5, C_6];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        for _ in 0..2 {
+            for p in [p1, p2, p3, p4] {
+                draw.draw_card(p).unwrap();
+            }
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+
+        for (p, card) in [(p1, S_K), (p2, S_5), (p3, S_10), (p4, S_3)] {
+            play.play_cards(p, &[card]).unwrap();
+        }
+        let msgs = play.finish_trick().unwrap();
+        match msgs.last() {
+            Some(MessageVariant::TrickWon {
+                winner,
+                points: 25,
+                point_cards,
+                landlords_team: true,
+                ..
+            }) => {
+                assert_eq!(*winner, p1);
+                assert_eq!(
+                    point_cards,
+                    &[
+                        CapturedPointCards {
+                            player: Some(p1),
+                            cards: vec![S_K],
+                            points: 10,
+                        },
+                        CapturedPointCards {
+                            player: Some(p2),
+                            cards: vec![S_5],
+                            points: 5,
+                        },
+                        CapturedPointCards {
+                            player: Some(p3),
+                            cards: vec![S_10],
+                            points: 10,
+                        },
+                    ]
+                );
+            }
+            m => panic!("unexpected message {:?}", m),
+        }
+    }
+
+    #[test]
+    fn test_ruff_and_capture_messages() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![S_K, H_3, S_10, S_3, H_2, C_4, C_5, C_6];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        for _ in 0..2 {
+            for p in [p1, p2, p3, p4] {
+                draw.draw_card(p).unwrap();
+            }
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+
+        assert_eq!(play.play_cards(p1, &[S_K]).unwrap().len(), 1);
+        match &play.play_cards(p2, &[H_3]).unwrap()[..] {
+            [MessageVariant::PlayedCards { .. }, MessageVariant::RuffOccurred {
+                player,
+                over,
+                overruff: false,
+            }] => assert_eq!((*player, *over), (p2, p1)),
+            m => panic!("unexpected messages {:?}", m),
+        }
+        assert_eq!(play.play_cards(p3, &[S_10]).unwrap().len(), 1);
+        assert_eq!(play.play_cards(p4, &[S_3]).unwrap().len(), 1);
+
+        let msgs = play.finish_trick().unwrap();
+        match &msgs[..] {
+            [MessageVariant::PointsCaptured { player, points: 20 }, MessageVariant::TrickWon {
+                landlords_team: false,
+                ..
+            }] => assert_eq!(*player, p2),
+            m => panic!("unexpected messages {:?}", m),
+        }
+    }
+
+    #[test]
+    fn test_end_of_game_kitty_reveal() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        init.set_should_reveal_kitty_at_end_of_game(true).unwrap();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, S_3, S_4, S_6];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_5, D_K, D_3];
+        for p in [p1, p2, p3, p4] {
+            draw.draw_card(p).unwrap();
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+        for (p, card) in [(p1, H_2), (p2, S_3), (p3, S_4), (p4, S_6)] {
+            play.play_cards(p, &[card]).unwrap();
+        }
+        let msgs = play.finish_trick().unwrap();
+        assert!(msgs.iter().any(|m| matches!(
+            m,
+            MessageVariant::EndOfGameKittyReveal { cards, points: 15 }
+                if *cards == vec![D_5, D_K, D_3]
+        )));
+
+        let (_, _, msgs) = play.finish_game().unwrap();
+        assert!(msgs.iter().any(|m| matches!(
+            m,
+            MessageVariant::EndOfGameSummary {
+                kitty: Some(kitty),
+                ..
+            } if kitty.points == 15 && kitty.cards == vec![D_5, D_K, D_3]
+        )));
+    }
+
+    #[test]
+    fn test_buried_points() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, H_3, S_4, S_6, S_K, S_A, S_7, S_8];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_3, D_4, D_5];
+        for _ in 0..2 {
+            for p in [p1, p2, p3, p4] {
+                draw.draw_card(p).unwrap();
+            }
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let mut exchange = draw.advance(p1).unwrap();
+        exchange.move_card_to_hand(p1, D_3).unwrap();
+        exchange.move_card_to_hand(p1, D_4).unwrap();
+        exchange.move_card_to_kitty(p1, H_2).unwrap();
+        exchange.move_card_to_kitty(p1, S_K).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+        for cards in [
+            [(p1, D_3), (p2, H_3), (p3, S_4), (p4, S_6)],
+            [(p2, S_A), (p3, S_7), (p4, S_8), (p1, D_4)],
+        ]
+        .iter()
+        {
+            for (p, card) in cards.iter() {
+                play.play_cards(*p, &[*card]).unwrap();
+            }
+            play.finish_trick().unwrap();
+        }
+
+        // The kitty isn't revealed, but the other team won its points.
+        let (_, _, msgs) = play.finish_game().unwrap();
+        let (kitty, buried_points, kitty_multiplier) = msgs
+            .iter()
+            .find_map(|m| match m {
+                MessageVariant::EndOfGameSummary {
+                    kitty,
+                    buried_points,
+                    kitty_multiplier,
+                    ..
+                } => Some((kitty, buried_points, kitty_multiplier)),
+                _ => None,
+            })
+            .unwrap();
+        assert!(kitty.is_none());
+        assert_eq!(
+            buried_points
+                .iter()
+                .map(|b| (b.card, b.points, b.buried_by))
+                .collect::<Vec<_>>(),
+            vec![(D_5, 5, None), (S_K, 10, Some(p1))]
+        );
+        assert_eq!(kitty_multiplier.unwrap().multiplier, 2);
+    }
+
+    #[test]
+    fn test_shutout_bonus() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let mut parameters = GameScoringParameters::default();
+        parameters.shutout_bonus_levels = 2;
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        init.set_game_scoring_parameters(parameters).unwrap();
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, S_3, S_4, S_6];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_3, D_4];
+        for p in [p1, p2, p3, p4] {
+            draw.draw_card(p).unwrap();
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+        for (p, card) in [(p1, H_2), (p2, S_3), (p3, S_4), (p4, S_6)] {
+            play.play_cards(p, &[card]).unwrap();
+        }
+        play.finish_trick().unwrap();
+
+        let (_, _, msgs) = play.finish_game().unwrap();
+        assert!(msgs.iter().any(|m| matches!(
+            m,
+            MessageVariant::EndOfGameSummary {
+                shutout_bonus_levels: 2,
+                ..
+            }
+        )));
+        assert!(msgs
+            .iter()
+            .any(|m| matches!(m, MessageVariant::ShutoutBonusEarned { levels: 2 })));
+        // Three levels for the shutout itself, and two more for the bonus.
+        assert!(msgs.iter().any(|m| matches!(
+            m,
+            MessageVariant::RankAdvanced {
+                player,
+                new_rank: R7,
+            } if *player == p1
+        )));
+    }
+
+    #[test]
+    fn test_score_ledger() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![S_3, H_2, S_4, S_6];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_5, D_4];
+        for p in [p1, p2, p3, p4].iter() {
+            draw.draw_card(*p).unwrap();
+        }
+        assert!(draw.bid(p2, H_2, 1));
+        let exchange = draw.advance(p2).unwrap();
+        let mut play = exchange.advance(p2).unwrap();
+        for (p, card) in [(p2, H_2), (p3, S_4), (p4, S_6), (p1, S_3)].iter() {
+            play.play_cards(*p, &[*card]).unwrap();
+        }
+        play.finish_trick().unwrap();
+        let (init, _, _) = play.finish_game().unwrap();
+        assert_eq!(init.score_ledger().entries().len(), 1);
+        let state = GameState::Initialize(init.clone()).for_player(p1);
+        assert!(state.score_ledger().entries().is_empty());
+
+        let entry = &init.score_ledger().entries()[0];
+        assert_eq!(entry.game, 0);
+        assert_eq!(entry.landlord, p2);
+        assert!(entry.landlord_won);
+        assert_eq!(entry.non_landlords_points, 0);
+        // The landlord's team won the last trick, so took the bottom.
+        assert_eq!(entry.kitty_points, 10);
+        let p2_result = entry.players.iter().find(|r| r.player == p2).unwrap();
+        assert_eq!(p2_result.previous_rank, R2);
+        assert_eq!(p2_result.rank, R5);
+        assert_eq!(p2_result.ranks_up, 3);
+        let p1_result = entry.players.iter().find(|r| r.player == p1).unwrap();
+        assert_eq!((p1_result.rank, p1_result.ranks_up), (R2, 0));
+    }
+
+    #[test]
+    fn test_advancement_schedule() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        assert!(init
+            .set_advancement_schedule(AdvancementSchedule {
+                landlord_levels: vec![2, 1],
+                attacking_levels: vec![],
+            })
+            .is_err());
+        let msgs = init
+            .set_advancement_schedule(AdvancementSchedule {
+                landlord_levels: vec![1, 1, 2],
+                attacking_levels: vec![],
+            })
+            .unwrap();
+        assert_eq!(msgs.len(), 1);
+
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![S_3, H_2, S_4, S_6];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_5, D_4];
+        for p in [p1, p2, p3, p4].iter() {
+            draw.draw_card(*p).unwrap();
+        }
+        assert!(draw.bid(p2, H_2, 1));
+        let exchange = draw.advance(p2).unwrap();
+        let mut play = exchange.advance(p2).unwrap();
+        for (p, card) in [(p2, H_2), (p3, S_4), (p4, S_6), (p1, S_3)].iter() {
+            play.play_cards(*p, &[*card]).unwrap();
+        }
+        play.finish_trick().unwrap();
+
+        // Keeping the other team to no points is worth two levels rather
+        // than the usual three.
+        let bands = play.remaining_outcomes().unwrap();
+        assert_eq!(bands[0].result.landlord_delta, 2);
+        let (init, landlord_won, _) = play.finish_game().unwrap();
+        assert!(landlord_won);
+        let ranks = init
+            .players()
+            .iter()
+            .map(|p| (p.id, p.rank()))
+            .collect::<Vec<_>>();
+        assert_eq!(ranks, vec![(p1, R2), (p2, R4), (p3, R2), (p4, R4)]);
+    }
+
+    #[test]
+    fn test_rotate_partners() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        init.set_team_formation(TeamFormation::RotatePartners)
+            .unwrap();
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, S_3, S_4, S_6];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_3, D_4];
+        for p in [p1, p2, p3, p4].iter() {
+            draw.draw_card(*p).unwrap();
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+        assert_eq!(play.landlords_team(), &[p1, p3]);
+        for (p, card) in [(p1, H_2), (p2, S_3), (p3, S_4), (p4, S_6)].iter() {
+            play.play_cards(*p, &[*card]).unwrap();
+        }
+        play.finish_trick().unwrap();
+        let (init, _, msgs) = play.finish_game().unwrap();
+        assert!(msgs
+            .iter()
+            .any(|m| matches!(m, MessageVariant::PartnersRotated)));
+        assert_eq!(
+            init.players().iter().map(|p| p.id).collect::<Vec<_>>(),
+            vec![p1, p4, p2, p3]
+        );
+
+        // p3 is the next landlord, and is now partnered with p4 rather than
+        // p1, so p4 is the one who advances with them.
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        *draw.deck_mut() = vec![H_5, H_5, H_5, H_5];
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_3, D_4];
+        for _ in 0..4 {
+            let next = draw.next_player().unwrap();
+            draw.draw_card(next).unwrap();
+        }
+        assert!(draw.bid(p3, H_5, 1));
+        let exchange = draw.advance(p3).unwrap();
+        let mut play = exchange.advance(p3).unwrap();
+        assert_eq!(play.landlords_team(), &[p4, p3]);
+        assert!(matches!(
+            ActionError::from_error(&play.play_cards(p1, &[H_5]).unwrap_err()),
+            Some(ActionError::Play(PlayError::OutOfTurn {
+                next_player: Some(p)
+            })) if p == p3
+        ));
+        for p in [p3, p1, p4, p2].iter() {
+            play.play_cards(*p, &[H_5]).unwrap();
+        }
+        play.finish_trick().unwrap();
+        let (init, _, _) = play.finish_game().unwrap();
+        let ranks = init
+            .players()
+            .iter()
+            .map(|p| (p.id, p.rank()))
+            .collect::<Vec<_>>();
+        assert_eq!(ranks, vec![(p1, R5), (p3, R8), (p4, R5), (p2, R2)]);
+    }
+
+    #[test]
+    fn test_final_trick_bonus() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let rung = |min_cards, kitty_multiplier, bonus_points| FinalTrickBonusRung {
+            min_cards,
+            kitty_multiplier,
+            bonus_points,
+        };
+        assert!(init
+            .set_final_trick_bonus_ladder(vec![rung(1, None, 5)])
+            .is_err());
+        assert!(init
+            .set_final_trick_bonus_ladder(vec![rung(2, None, 5), rung(2, None, 10)])
+            .is_err());
+        init.set_final_trick_bonus_ladder(vec![rung(4, Some(8), 40), rung(2, Some(3), 20)])
+            .unwrap();
+
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, Card::BigJoker, S_5, S_7, H_2, Card::BigJoker, S_6, S_8];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_5, D_10];
+        for _ in 0..2 {
+            for p in [p1, p2, p3, p4].iter() {
+                draw.draw_card(*p).unwrap();
+            }
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+        for (p, cards) in [
+            (p1, [H_2, H_2]),
+            (p2, [Card::BigJoker, Card::BigJoker]),
+            (p3, [S_5, S_6]),
+            (p4, [S_7, S_8]),
+        ]
+        .iter()
+        {
+            play.play_cards(*p, &cards[..]).unwrap();
+        }
+        let msgs = play.finish_trick().unwrap();
+        assert!(msgs
+            .iter()
+            .any(|m| matches!(m, MessageVariant::PointsInKitty { multiplier: 3, .. })));
+        assert!(msgs.iter().any(|m| matches!(
+            m,
+            MessageVariant::FinalTrickBonusEarned {
+                player,
+                points: 20,
+            } if *player == p2
+        )));
+        // The bottom is worth 15 points three times over, on top of the
+        // bonus and the 5 in the trick.
+        assert_eq!(play.calculate_points().0, 70);
+    }
+
+    #[test]
+    fn test_compute_score() {
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        init.add_player("p4".into()).unwrap();
+        let mut parameters = GameScoringParameters::default();
+        parameters.shutout_bonus_levels = 1;
+        init.set_game_scoring_parameters(parameters).unwrap();
+        let decks = [Deck::default()];
+        let state = ScoreState {
+            decks: &decks,
+            landlord: p1,
+            landlords_team: &[p1, p3],
+            smaller_landlord_team: false,
+            blind_bid_levels: 1,
+            tricks_played: 0,
+        };
+
+        let score = compute_score(0, init.propagated(), &state).unwrap();
+        assert!(score.landlord_won);
+        assert_eq!(score.threshold, None);
+        assert_eq!(score.base_levels, 3);
+        assert_eq!(
+            score.bonuses,
+            vec![
+                ScoreBonus::Shutout { levels: 1 },
+                ScoreBonus::BlindBid { levels: 1 }
+            ]
+        );
+        assert_eq!(score.bonus_levels(), 2);
+        let p1_result = score.players.iter().find(|r| r.player == p1).unwrap();
+        assert_eq!((p1_result.previous_rank, p1_result.rank), (R2, R7));
+        // Nothing is changed until the result is applied.
+        assert!(init.players().iter().all(|p| p.rank() == R2));
+
+        let score = compute_score(65, init.propagated(), &state).unwrap();
+        assert!(!score.landlord_won);
+        assert_eq!(score.threshold, Some(60));
+        assert_eq!((score.base_levels, score.levels), (1, 2));
+        assert_eq!(
+            score
+                .players
+                .iter()
+                .map(|r| (r.player, r.ranks_up))
+                .collect::<Vec<_>>(),
+            vec![(p1, 0), (p2, 2), (p3, 0), (PlayerID(3), 2)]
+        );
+    }
+
+    #[test]
+    fn test_points_per_trick() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        assert!(init
+            .set_scoring_policy(ScoringPolicy::PointsPerTrick { points: 0 })
+            .is_err());
+        init.set_scoring_policy(ScoringPolicy::PointsPerTrick { points: 10 })
+            .unwrap();
+
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, S_A, S_5, S_7, S_3, S_4, S_10, S_8];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_5, D_10];
+        for _ in 0..2 {
+            for p in [p1, p2, p3, p4].iter() {
+                draw.draw_card(*p).unwrap();
+            }
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+        for (p, card) in [(p1, H_2), (p2, S_4), (p3, S_5), (p4, S_7)].iter() {
+            play.play_cards(*p, &[*card]).unwrap();
+        }
+        play.finish_trick().unwrap();
+        assert!(play.finish_game_early().is_err());
+        for (p, card) in [(p1, S_3), (p2, S_A), (p3, S_10), (p4, S_8)].iter() {
+            play.play_cards(*p, &[*card]).unwrap();
+        }
+        let msgs = play.finish_trick().unwrap();
+        assert!(!msgs
+            .iter()
+            .any(|m| matches!(m, MessageVariant::PointsInKitty { .. })));
+        assert!(msgs.iter().any(|m| matches!(
+            m,
+            MessageVariant::PointsCaptured { player, points: 10 } if *player == p2
+        )));
+        assert!(msgs.iter().any(|m| matches!(
+            m,
+            MessageVariant::TrickWon { points: 10, point_cards, .. } if point_cards.is_empty()
+        )));
+
+        // Only the tricks count, and not the point cards in them or in the
+        // bottom.
+        assert_eq!(play.calculate_points().0, 10);
+        let (init, landlord_won, _) = play.finish_game().unwrap();
+        assert!(landlord_won);
+        assert!(init.players().iter().all(|p| p.rank() == R2));
+    }
+
+    #[test]
+    fn test_remaining_outcomes() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, S_A, S_5, S_7, S_3, S_4, S_10, S_8];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_5, D_10];
+        for _ in 0..2 {
+            for p in [p1, p2, p3, p4].iter() {
+                draw.draw_card(*p).unwrap();
+            }
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+        for (p, card) in [(p1, H_2), (p2, S_4), (p3, S_5), (p4, S_7)].iter() {
+            play.play_cards(*p, &[*card]).unwrap();
+        }
+        play.finish_trick().unwrap();
+
+        // There are 195 points left in the two decks, of which the 15 in the
+        // bottom could be doubled.
+        let bands = play.remaining_outcomes().unwrap();
+        assert_eq!((bands[0].min_points, bands[0].max_points), (0, 4));
+        assert_eq!(bands[0].result.landlord_delta, 3);
+        assert_eq!(
+            (bands[3].min_points, bands[3].result.landlord_won),
+            (80, false)
+        );
+        assert_eq!(bands.last().unwrap().max_points, 210);
+
+        for (p, card) in [(p1, S_3), (p2, S_A), (p3, S_10), (p4, S_8)].iter() {
+            play.play_cards(*p, &[*card]).unwrap();
+        }
+        play.finish_trick().unwrap();
+        let bands = play.remaining_outcomes().unwrap();
+        assert_eq!(bands.len(), 1);
+        assert_eq!((bands[0].min_points, bands[0].max_points), (40, 40));
+    }
+
+    #[test]
+    fn test_match_length() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        init.set_match_length(MatchLength {
+            max_cycles: None,
+            max_games: Some(1),
+            max_minutes: None,
+        })
+        .unwrap();
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![H_2, S_3, S_4, S_6];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        *draw.kitty_mut() = vec![D_3, D_4];
+        for p in [p1, p2, p3, p4].iter() {
+            draw.draw_card(*p).unwrap();
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+        for (p, card) in [(p1, H_2), (p2, S_3), (p3, S_4), (p4, S_6)].iter() {
+            play.play_cards(*p, &[*card]).unwrap();
+        }
+        play.finish_trick().unwrap();
+
+        let (init, _, msgs) = play.finish_game().unwrap();
+        let standings = msgs
+            .iter()
+            .find_map(|m| match m {
+                MessageVariant::MatchComplete {
+                    reason: MatchEndReason::Games,
+                    standings,
+                } => Some(standings),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(standings[0].rank, R5);
+        assert_eq!(standings[3].rank, R2);
+        assert!(init
+            .players()
+            .iter()
+            .all(|p| p.rank() == R2 && p.metalevel == 1));
+        assert_eq!(init.match_games_finished, 0);
+        assert_eq!(init.match_started_at, None);
+
+        // A player who has gone around once ends the match on cycles.
+        let mut propagated = init.propagated().clone();
+        propagated
+            .set_match_length(MatchLength {
+                max_cycles: Some(1),
+                max_games: None,
+                max_minutes: Some(60),
+            })
+            .unwrap();
+        assert!(propagated.finish_match_game(0).is_none());
+        propagated.players[1].set_meta_rank(2);
+        assert!(matches!(
+            propagated.finish_match_game(0),
+            Some(MessageVariant::MatchComplete {
+                reason: MatchEndReason::Cycles,
+                ..
+            })
+        ));
+        propagated.match_started_at = Some(0);
+        assert!(matches!(
+            propagated.finish_match_game(60 * 60 * 1000),
+            Some(MessageVariant::MatchComplete {
+                reason: MatchEndReason::TimeLimit,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_last_trick() {
+        use cards::*;
+
+        let setup_play = |policy| {
+            let mut init = InitializePhase::new();
+            init.set_last_trick_policy(policy).unwrap();
+            let p1 = init.add_player("p1".into()).unwrap().0;
+            let p2 = init.add_player("p2".into()).unwrap().0;
+            let p3 = init.add_player("p3".into()).unwrap().0;
+            let p4 = init.add_player("p4".into()).unwrap().0;
+            let mut draw = init.start(PlayerID(0)).unwrap();
+            let mut deck = vec![S_K, S_5, S_10, S_3, H_2, C_4, C_5, C_6];
+            deck.reverse();
+            *draw.deck_mut() = deck;
+            *draw.position_mut() = 0;
+            for _ in 0..2 {
+                for p in [p1, p2, p3, p4] {
+                    draw.draw_card(p).unwrap();
+                }
+            }
+            assert!(draw.bid(p1, H_2, 1));
+            let exchange = draw.advance(p1).unwrap();
+            let mut play = exchange.advance(p1).unwrap();
+            for (p, card) in [(p1, S_K), (p2, S_5), (p3, S_10), (p4, S_3)] {
+                play.play_cards(p, &[card]).unwrap();
+            }
+            play.finish_trick().unwrap();
+            ([p1, p2, p3, p4], play)
+        };
+
+        let ([p1, p2, p3, p4], mut play) = setup_play(LastTrickPolicy::AllowLastTrick);
+        let last_trick = play.last_trick(p2).unwrap().unwrap();
+        assert_eq!(last_trick.winner, Some(p1));
+        assert_eq!(last_trick.points, 25);
+        assert_eq!(
+            last_trick
+                .plays
+                .iter()
+                .map(|pc| (pc.id, pc.cards.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (p1, vec![S_K]),
+                (p2, vec![S_5]),
+                (p3, vec![S_10]),
+                (p4, vec![S_3])
+            ]
+        );
+        let observer = play.add_observer("o".into()).unwrap();
+        play.last_trick(observer).unwrap_err();
+
+        let ([_, p2, ..], play) = setup_play(LastTrickPolicy::NoLastTrick);
+        play.last_trick(p2).unwrap_err();
+        match GameState::Play(play).for_player(p2) {
+            GameState::Play(p) => {
+                assert!(serde_json::to_value(&p).unwrap()["last_trick"].is_null())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_trick_history() {
+        use cards::*;
+
+        let mut init = InitializePhase::new();
+        let p1 = init.add_player("p1".into()).unwrap().0;
+        let p2 = init.add_player("p2".into()).unwrap().0;
+        let p3 = init.add_player("p3".into()).unwrap().0;
+        let p4 = init.add_player("p4".into()).unwrap().0;
+        let mut draw = init.start(PlayerID(0)).unwrap();
+        let mut deck = vec![S_K, S_5, S_10, S_3, H_2, C_4, C_5, C_6];
+        deck.reverse();
+        *draw.deck_mut() = deck;
+        *draw.position_mut() = 0;
+        for _ in 0..2 {
+            for p in [p1, p2, p3, p4] {
+                draw.draw_card(p).unwrap();
+            }
+        }
+        assert!(draw.bid(p1, H_2, 1));
+        let exchange = draw.advance(p1).unwrap();
+        let mut play = exchange.advance(p1).unwrap();
+
+        for (p, card) in [(p1, S_K), (p2, S_5), (p3, S_10), (p4, S_3)] {
+            play.play_cards(p, &[card]).unwrap();
+        }
+        play.finish_trick().unwrap();
+        assert_eq!(play.trick_history(p1).unwrap().len(), 1);
+        assert!(play.trick_history(PlayerID(4)).is_err());
+        let trick = &play.trick_history(p1).unwrap()[0];
+        assert_eq!(trick.winner, Some(p1));
+        assert_eq!(
+            trick
+                .plays
+                .iter()
+                .map(|p| (p.id, p.cards().collect::<Vec<_>>()))
+                .collect::<Vec<_>>(),
+            vec![
+                (p1, vec![S_K]),
+                (p2, vec![S_5]),
+                (p3, vec![S_10]),
+                (p4, vec![S_3])
+            ]
+        );
+
+        match GameState::Play(play).for_player(p2) {
+            GameState::Play(p) => assert!(p.trick_history(p2).unwrap().is_empty()),
+            _ => unreachable!(),
+        }
     }
 
     #[test]
@@ -7,17 +7,26 @@ use serde::{Deserialize, Serialize};
 use shengji_mechanics::deck::Deck;
 use shengji_mechanics::hands::Hands;
 use shengji_mechanics::player::Player;
-use shengji_mechanics::scoring::{compute_level_deltas, next_threshold_reachable, GameScoreResult};
-use shengji_mechanics::trick::{PlayCards, PlayCardsMessage, Trick, TrickEnded, TrickUnit};
-use shengji_mechanics::types::{Card, PlayerID, Rank, Trump};
+use shengji_mechanics::scoring::{next_threshold_reachable, remaining_score_bands, ScoreBand};
+use shengji_mechanics::trick::{
+    BeatPolicy, CompactTrick, PlayCards, PlayCardsMessage, PlayError, PlayedCards, Trick,
+    TrickEnded, TrickFormat, TrickUnit,
+};
+use shengji_mechanics::types::{Card, EffectiveSuit, Number, PlayerID, Rank, Trump};
 
-use crate::message::MessageVariant;
+use crate::message::{CapturedPointCards, MessageVariant};
+use crate::scoring::{compute_score, score_players, PlayerScoreResult, ScoreBonus, ScoreState};
 use crate::settings::{
-    AdvancementPolicy, GameMode, KittyPenalty, MultipleJoinPolicy, PlayTakebackPolicy,
-    PropagatedState, ThrowPenalty,
+    AdvancementPolicy, FinalTrickBonusRung, GameMode, HandRevealPolicy, KittyPenalty,
+    KittyRevisionPolicy, LastTrickPolicy, MultipleJoinPolicy, PlayTakebackPolicy, PropagatedState,
+    ScoringPolicy, ThrowChallengePolicy, ThrowPenalty, ThrowPenaltyPolicy,
 };
 
+use crate::game_state::bid_history::BidHistory;
+use crate::game_state::exchange_phase::ExchangePhase;
 use crate::game_state::initialize_phase::InitializePhase;
+use crate::game_state::score_ledger::{LedgerEntry, LedgerPlayerResult};
+use crate::game_state::{now_millis, redact_kitty};
 
 macro_rules! bail_unwrap {
     ($opt:expr) => {
@@ -36,6 +45,60 @@ pub struct PlayerGameFinishedResult {
     pub ranks_up: usize,
     pub confetti: bool,
     pub rank: Rank,
+    /// Whether the player went past the final rank.
+    #[serde(default)]
+    pub won_match: bool,
+}
+
+/// The bottom cards as revealed at the end of the game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct KittyReveal {
+    pub cards: Vec<Card>,
+    /// The points buried in the bottom, before any multiplier.
+    pub points: usize,
+    /// Who buried each of the cards, or `None` if it was dealt there.
+    pub buried_by: Vec<Option<PlayerID>>,
+}
+
+/// A point card which was buried in the bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BuriedPointCard {
+    pub card: Card,
+    pub points: usize,
+    /// Who buried the card, or `None` if it was dealt there.
+    pub buried_by: Option<PlayerID>,
+}
+
+/// How the multiplier for the points in the bottom was worked out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct KittyMultiplier {
+    pub penalty: KittyPenalty,
+    /// The size of the largest unit in the last trick.
+    pub largest_unit_size: usize,
+    /// Whether the last trick was won with a tractor.
+    pub tractor: bool,
+    pub multiplier: usize,
+    /// The rung of the final trick bonus ladder which set the multiplier,
+    /// instead of the penalty.
+    #[serde(default)]
+    pub final_trick_bonus: Option<FinalTrickBonusRung>,
+}
+
+/// Bonus points earned by winning the last trick with a pair or tractor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FinalTrickBonus {
+    pub player: PlayerID,
+    pub points: usize,
+}
+
+/// The last finished trick, as seen by the players at the table.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LastTrick {
+    /// The cards played by each player, in play order.
+    pub plays: Vec<PlayedCards>,
+    pub format: Option<TrickFormat>,
+    pub winner: Option<PlayerID>,
+    pub points: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -53,11 +116,45 @@ pub struct PlayPhase {
     trump: Trump,
     trick: Trick,
     last_trick: Option<Trick>,
+    /// Every finished trick this hand, oldest first. Left out of the state
+    /// sent to players; see `trick_history`.
+    #[serde(default)]
+    trick_history: Vec<CompactTrick>,
     game_ended_early: bool,
     #[serde(default)]
     removed_cards: Vec<Card>,
     #[serde(default)]
     decks: Vec<Deck>,
+    /// Players who have shown their remaining cards to the table.
+    #[serde(default)]
+    revealed_players: Vec<PlayerID>,
+    /// Whether the throw leading the current trick has been challenged.
+    #[serde(default)]
+    throw_challenged: bool,
+    #[serde(default)]
+    bid_history: BidHistory,
+    /// The extra levels at stake because the landlord won the bidding with a
+    /// blind bid.
+    #[serde(default)]
+    blind_bid_levels: usize,
+    /// Who buried each card in `kitty`, or `None` if it was dealt there.
+    #[serde(default)]
+    kitty_buried_by: Vec<Option<PlayerID>>,
+    /// The exchange this game started from, kept under the
+    /// `KittyRevisionPolicy` until the first card is led.
+    #[serde(default)]
+    pre_lead_exchange: Option<Box<ExchangePhase>>,
+    /// How the points in the bottom were multiplied, if the other team won
+    /// them in the last trick.
+    #[serde(default)]
+    kitty_multiplier: Option<KittyMultiplier>,
+    /// The bonus for winning the last trick, which counts for the winner's
+    /// team.
+    #[serde(default)]
+    final_trick_bonus: Option<FinalTrickBonus>,
+    /// The number of tricks each player has won this hand.
+    #[serde(default)]
+    tricks_won: HashMap<PlayerID, usize>,
 }
 
 impl PlayPhase {
@@ -74,6 +171,10 @@ impl PlayPhase {
         landlords_team: Vec<PlayerID>,
         removed_cards: Vec<Card>,
         decks: Vec<Deck>,
+        bid_history: BidHistory,
+        blind_bid_levels: usize,
+        kitty_buried_by: Vec<Option<PlayerID>>,
+        pre_lead_exchange: Option<Box<ExchangePhase>>,
     ) -> Result<Self, Error> {
         let landlord_idx = bail_unwrap!(propagated.players.iter().position(|p| p.id == landlord));
         Ok(PlayPhase {
@@ -102,7 +203,17 @@ impl PlayPhase {
             removed_cards,
             decks,
             game_ended_early: false,
+            throw_challenged: false,
             last_trick: None,
+            trick_history: vec![],
+            revealed_players: vec![],
+            bid_history,
+            blind_bid_levels,
+            kitty_buried_by,
+            pre_lead_exchange,
+            kitty_multiplier: None,
+            final_trick_bonus: None,
+            tricks_won: HashMap::new(),
         })
     }
 
@@ -126,10 +237,102 @@ impl PlayPhase {
         &self.landlords_team
     }
 
+    /// The players publicly known to be on `id`'s team. Until every friend
+    /// has joined, the defenders can't be sure of their teammates.
+    pub fn known_teammates(&self, id: PlayerID) -> Vec<PlayerID> {
+        let all_friends_found = match self.game_mode {
+            GameMode::Tractor => true,
+            GameMode::FindingFriends { ref friends, .. } => {
+                friends.iter().all(|f| f.player_id.is_some())
+            }
+        };
+        let on_landlords_team = self.landlords_team.contains(&id);
+        if !on_landlords_team && !all_friends_found {
+            return vec![];
+        }
+        self.propagated
+            .players
+            .iter()
+            .map(|p| p.id)
+            .filter(|p| *p != id && self.landlords_team.contains(p) == on_landlords_team)
+            .collect()
+    }
+
     pub fn trick(&self) -> &Trick {
         &self.trick
     }
 
+    /// The last finished trick, which only the seated players may look at,
+    /// and only if the room allows it.
+    pub fn last_trick(&self, id: PlayerID) -> Result<Option<LastTrick>, Error> {
+        if self.propagated.last_trick_policy == LastTrickPolicy::NoLastTrick {
+            bail!("Looking at the last trick is not allowed")
+        }
+        if !self.propagated.players.iter().any(|p| p.id == id) {
+            bail!("Only players at the table can look at the last trick")
+        }
+        let point_schedule = &self.propagated.game_scoring_parameters.point_schedule;
+        Ok(self.last_trick.as_ref().map(|trick| LastTrick {
+            plays: trick.played_cards().to_vec(),
+            format: trick.trick_format().cloned(),
+            winner: trick.current_winner(),
+            points: point_schedule.total(
+                trick
+                    .played_cards()
+                    .iter()
+                    .flat_map(|pc| pc.cards.iter().copied()),
+            ),
+        }))
+    }
+
+    pub fn trick_history(&self, id: PlayerID) -> Result<&[CompactTrick], Error> {
+        if self.propagated.last_trick_policy == LastTrickPolicy::NoLastTrick {
+            bail!("Looking at past tricks is not allowed")
+        }
+        if !self.propagated.players.iter().any(|p| p.id == id) {
+            bail!("Only players at the table can look at past tricks")
+        }
+        Ok(&self.trick_history)
+    }
+
+    /// The bottom cards, if they're shown to everyone at the end of the game.
+    pub fn kitty_reveal(&self) -> Option<KittyReveal> {
+        if !self.propagated.should_reveal_kitty_at_end_of_game {
+            return None;
+        }
+        let point_schedule = &self.propagated.game_scoring_parameters.point_schedule;
+        Some(KittyReveal {
+            cards: self.kitty.clone(),
+            points: point_schedule.total(self.kitty.iter().copied()),
+            buried_by: self.kitty_buried_by.clone(),
+        })
+    }
+
+    /// The point cards in the bottom and who buried them. These are only
+    /// known once they're public, i.e. when the bottom cards are revealed or
+    /// the other team won them.
+    pub fn buried_points(&self) -> Vec<BuriedPointCard> {
+        if !self.propagated.should_reveal_kitty_at_end_of_game && self.kitty_multiplier.is_none() {
+            return vec![];
+        }
+        let point_schedule = &self.propagated.game_scoring_parameters.point_schedule;
+        self.kitty
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, card)| {
+                Some(BuriedPointCard {
+                    card: *card,
+                    points: point_schedule.points(*card)?,
+                    buried_by: self.kitty_buried_by.get(idx).copied().flatten(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn bid_history(&self) -> &BidHistory {
+        &self.bid_history
+    }
+
     pub fn hands(&self) -> &Hands {
         &self.hands
     }
@@ -144,11 +347,43 @@ impl PlayPhase {
 
     pub fn can_play_cards(&self, id: PlayerID, cards: &[Card]) -> Result<(), Error> {
         if self.game_ended_early {
-            bail!("Game has already ended; cards can't be played");
+            return Err(PlayError::GameEnded.into());
         }
-        Ok(self
-            .trick
-            .can_play_cards(id, &self.hands, cards, self.propagated.trick_draw_policy)?)
+        self.trick.can_play_cards_with_lead_policy(
+            id,
+            &self.hands,
+            cards,
+            self.propagated.trick_draw_policy,
+            self.propagated.tractor_requirements.wild_card,
+            self.propagated.multi_suit_lead_policy,
+        )?;
+        if self.propagated.beat_policy == BeatPolicy::MustBeatIfAble {
+            self.trick.check_must_beat(
+                id,
+                &self.hands,
+                cards,
+                self.propagated.trick_draw_policy,
+                self.propagated.throw_evaluation_policy,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Goes back to the exchange under the `KittyRevisionPolicy`, with the
+    /// bottom cards as the landlord left them.
+    pub fn revise_kitty(&self, id: PlayerID) -> Result<ExchangePhase, Error> {
+        if id != self.landlord {
+            bail!("Only the leader can change the bottom cards")
+        }
+        let mut exchange = match self.pre_lead_exchange {
+            Some(ref exchange) => (**exchange).clone(),
+            None if self.propagated.kitty_revision_policy == KittyRevisionPolicy::NoRevision => {
+                bail!("The bottom cards can't be changed once play has started")
+            }
+            None => bail!("The bottom cards can't be changed after the first card is led"),
+        };
+        exchange.reopen(self.propagated.clone());
+        Ok(exchange)
     }
 
     pub fn play_cards(
@@ -166,19 +401,110 @@ impl PlayPhase {
         format_hint: Option<&'_ [TrickUnit]>,
     ) -> Result<Vec<MessageVariant>, Error> {
         if self.game_ended_early {
-            bail!("Game has already ended; cards can't be played");
+            return Err(PlayError::GameEnded.into());
         }
 
-        let mut msgs = self.trick.play_cards(PlayCards {
+        let teammates = self.known_teammates(id);
+        let leading = self.trick.played_cards().is_empty();
+        let previous_winner = self.trick.current_winner();
+        let msgs = self.trick.play_cards(PlayCards {
             id,
             hands: &mut self.hands,
             cards,
-            trick_draw_policy: self.propagated.trick_draw_policy,
-            throw_eval_policy: self.propagated.throw_evaluation_policy,
+            rules: &self.propagated.trick_rules(),
+            teammates: &teammates,
             format_hint,
-            hide_throw_halting_player: self.propagated.hide_throw_halting_player,
-            tractor_requirements: self.propagated.tractor_requirements,
         })?;
+        if leading {
+            self.throw_challenged = false;
+        }
+        self.pre_lead_exchange = None;
+        let mut msgs = self.to_messages(msgs);
+        if let Some(ruff) = self.ruff_by(id, previous_winner) {
+            msgs.push(ruff);
+        }
+        Ok(msgs)
+    }
+
+    /// The `RuffOccurred` message for `id`'s play, if it took the trick from
+    /// `previous_winner` by trumping a non-trump lead. Ruffs aren't announced
+    /// when played cards are hidden.
+    fn ruff_by(&self, id: PlayerID, previous_winner: Option<PlayerID>) -> Option<MessageVariant> {
+        let previous_winner = previous_winner?;
+        if self.propagated.hide_played_cards
+            || previous_winner == id
+            || self.trick.current_winner() != Some(id)
+            || self.trick.trick_format()?.suit() == EffectiveSuit::Trump
+        {
+            return None;
+        }
+        let is_trump = |player: PlayerID| {
+            self.trick
+                .played_cards()
+                .iter()
+                .find(|pc| pc.id == player)
+                .and_then(|pc| pc.cards.first())
+                .map(|c| self.trump.effective_suit(*c) == EffectiveSuit::Trump)
+                .unwrap_or(false)
+        };
+        if !is_trump(id) {
+            return None;
+        }
+        Some(MessageVariant::RuffOccurred {
+            player: id,
+            over: previous_winner,
+            overruff: is_trump(previous_winner),
+        })
+    }
+
+    /// Lets one of the thrower's opponents challenge the throw which was just
+    /// led, when throws are only checked once challenged. The loser of the
+    /// challenge is penalized.
+    pub fn challenge_throw(&mut self, id: PlayerID) -> Result<Vec<MessageVariant>, Error> {
+        if self.game_ended_early {
+            bail!("Game has already ended; throws can't be challenged");
+        }
+        if self.propagated.throw_challenge_policy != ThrowChallengePolicy::CheckedWhenChallenged {
+            bail!("Throws can't be challenged");
+        }
+        let thrower = match self.trick.played_cards() {
+            [pc] => pc.id,
+            _ => bail!("Only a throw which was just led can be challenged"),
+        };
+        let teammates = self.known_teammates(thrower);
+        if id == thrower || teammates.contains(&id) {
+            bail!("Only the thrower's opponents can challenge a throw");
+        }
+        if self.throw_challenged {
+            bail!("This throw has already been challenged");
+        }
+        let msgs = self.trick.challenge_throw(
+            &mut self.hands,
+            &self.propagated.trick_rules(),
+            &teammates,
+        )?;
+        self.throw_challenged = true;
+
+        let upheld = !msgs.is_empty();
+        let penalties = self.propagated.throw_challenge_penalties;
+        let (penalized, points) = if upheld {
+            (thrower, penalties.thrower)
+        } else {
+            (id, penalties.challenger)
+        };
+        *self.penalties.entry(penalized).or_insert(0) += points;
+
+        let mut result = vec![MessageVariant::ThrowChallenged {
+            thrower,
+            upheld,
+            penalized,
+            points,
+        }];
+        result.extend(self.to_messages(msgs));
+        Ok(result)
+    }
+
+    fn to_messages(&self, mut msgs: Vec<PlayCardsMessage>) -> Vec<MessageVariant> {
         if self.propagated.hide_played_cards {
             for msg in &mut msgs {
                 match msg {
@@ -198,8 +524,7 @@ impl PlayPhase {
                 }
             }
         }
-        Ok(msgs
-            .into_iter()
+        msgs.into_iter()
             .map(|p| match p {
                 PlayCardsMessage::ThrowFailed {
                     original_cards,
@@ -210,14 +535,25 @@ impl PlayPhase {
                 },
                 PlayCardsMessage::PlayedCards { cards } => MessageVariant::PlayedCards { cards },
             })
-            .collect())
+            .collect()
     }
 
     pub fn take_back_cards(&mut self, id: PlayerID) -> Result<(), Error> {
         if self.game_ended_early {
             bail!("Game has already ended; cards can't be taken back");
         }
-        if self.propagated.play_takeback_policy == PlayTakebackPolicy::NoPlayTakeback {
+        // A failed throw can be taken back under `ThrowPenaltyPolicy::AllowTakeBack`
+        // even when taking back plays is otherwise disallowed.
+        let failed_throw = self
+            .trick
+            .played_cards()
+            .last()
+            .map(|pc| pc.id == id && !pc.bad_throw_cards.is_empty())
+            .unwrap_or(false);
+        if self.propagated.play_takeback_policy == PlayTakebackPolicy::NoPlayTakeback
+            && !(failed_throw
+                && self.propagated.throw_penalty_policy == ThrowPenaltyPolicy::AllowTakeBack)
+        {
             bail!("Taking back played cards is not allowed")
         }
         Ok(self
@@ -225,34 +561,77 @@ impl PlayPhase {
             .take_back(id, &mut self.hands, self.propagated.throw_evaluation_policy)?)
     }
 
+    pub fn reveal_hand(&mut self, id: PlayerID) -> Result<MessageVariant, Error> {
+        if self.game_ended_early {
+            bail!("Game has already ended; hands can't be revealed");
+        }
+        if self.propagated.hand_reveal_policy == HandRevealPolicy::NoHandReveal {
+            bail!("Revealing hands is not allowed")
+        }
+        let player = bail_unwrap!(self.propagated.players.iter().find(|p| p.id == id));
+        let cards = self.hands.sorted_cards(id, &player.sort_order)?;
+        if !self.revealed_players.contains(&id) {
+            self.revealed_players.push(id);
+        }
+        Ok(MessageVariant::RevealedHand { cards })
+    }
+
+    pub fn revealed_players(&self) -> &'_ [PlayerID] {
+        &self.revealed_players
+    }
+
     pub fn finish_trick(&mut self) -> Result<Vec<MessageVariant>, Error> {
         if self.game_ended_early {
             bail!("Game has already ended; trick can't be finished");
         }
         let TrickEnded {
             winner,
+            winning_units,
+            runner_up,
             points: mut new_points,
             largest_trick_unit_size,
             failed_throw_size,
-        } = self.trick.complete()?;
+        } = self
+            .trick
+            .complete_with_points(&self.propagated.game_scoring_parameters.point_schedule)?;
 
-        let kitty_multipler = match self.propagated.kitty_penalty {
-            KittyPenalty::Times => 2 * largest_trick_unit_size,
-            KittyPenalty::Power => 2usize.pow(largest_trick_unit_size as u32),
+        let tractor = winning_units.iter().any(|u| u.is_tractor());
+        let bonus_rung = self
+            .propagated
+            .final_trick_bonus(winning_units.iter().map(|u| u.size()).max().unwrap_or(0));
+        let kitty_multipler = match bonus_rung.and_then(|rung| rung.kitty_multiplier) {
+            Some(multiplier) => multiplier,
+            None => self
+                .propagated
+                .kitty_penalty
+                .multiplier(largest_trick_unit_size, tractor),
         };
 
-        if failed_throw_size > 0 {
-            match self.propagated.throw_penalty {
-                ThrowPenalty::None => (),
-                ThrowPenalty::TenPointsPerAttempt => {
-                    if let Some(id) = self.trick.played_cards().first().map(|pc| pc.id) {
-                        *self.penalties.entry(id).or_insert(0) += 10;
-                    }
+        // When only the tricks count, the point cards, the bottom, the bonus
+        // and the throw penalties don't affect the score, so they aren't
+        // tracked or announced.
+        let points_per_trick = match self.propagated.scoring_policy {
+            ScoringPolicy::PointsPerTrick { points } => Some(points),
+            _ => None,
+        };
+
+        let mut msgs = vec![];
+        if failed_throw_size > 0 && points_per_trick.is_none() {
+            if let Some(id) = self.trick.played_cards().first().map(|pc| pc.id) {
+                let mut points = match self.propagated.throw_penalty {
+                    ThrowPenalty::None => 0,
+                    ThrowPenalty::TenPointsPerAttempt => 10,
+                };
+                if self.propagated.throw_penalty_policy == ThrowPenaltyPolicy::DeductPoints {
+                    points += 10 * failed_throw_size;
+                }
+                if points > 0 {
+                    *self.penalties.entry(id).or_insert(0) += points;
+                    msgs.push(MessageVariant::ThrowPenaltyDeducted { player: id, points });
                 }
             }
         }
 
-        let mut msgs = vec![];
         if let GameMode::FindingFriends {
             ref mut friends, ..
         } = self.game_mode
@@ -296,46 +675,113 @@ impl PlayPhase {
                 }
             }
         }
+        let point_schedule = &self.propagated.game_scoring_parameters.point_schedule;
+        let mut point_cards = self
+            .trick
+            .played_cards()
+            .iter()
+            .filter_map(|played| {
+                let cards = played
+                    .cards
+                    .iter()
+                    .filter(|c| point_schedule.is_point_card(**c))
+                    .copied()
+                    .collect::<Vec<_>>();
+                if cards.is_empty() {
+                    None
+                } else {
+                    Some(CapturedPointCards {
+                        player: Some(played.id),
+                        points: point_schedule.total(cards.iter().copied()),
+                        cards,
+                    })
+                }
+            })
+            .collect::<Vec<_>>();
         let points = bail_unwrap!(self.points.get_mut(&winner));
-        let kitty_points = self
+        let mut kitty_points = self
             .kitty
             .iter()
-            .filter(|c| c.points().is_some())
+            .filter(|c| point_schedule.is_point_card(**c))
             .copied()
             .collect::<Vec<_>>();
+        if points_per_trick.is_some() {
+            new_points.clear();
+            point_cards.clear();
+            kitty_points.clear();
+        }
 
         if self.hands.is_empty() {
             if self.propagated.should_reveal_kitty_at_end_of_game {
                 msgs.push(MessageVariant::EndOfGameKittyReveal {
                     cards: self.kitty.clone(),
+                    points: point_schedule.total(kitty_points.iter().copied()),
                 });
             }
             for _ in 0..kitty_multipler {
                 new_points.extend(kitty_points.iter().copied());
             }
             if !kitty_points.is_empty() && kitty_multipler > 0 {
+                point_cards.push(CapturedPointCards {
+                    player: None,
+                    cards: kitty_points.clone(),
+                    points: point_schedule.total(kitty_points.iter().copied()) * kitty_multipler,
+                });
+                let explanation = KittyMultiplier {
+                    penalty: self.propagated.kitty_penalty,
+                    largest_unit_size: largest_trick_unit_size,
+                    tractor,
+                    multiplier: kitty_multipler,
+                    final_trick_bonus: bonus_rung.filter(|rung| rung.kitty_multiplier.is_some()),
+                };
+                self.kitty_multiplier = Some(explanation);
                 msgs.push(MessageVariant::PointsInKitty {
-                    points: kitty_points.iter().flat_map(|c| c.points()).sum::<usize>(),
+                    points: point_schedule.total(kitty_points.iter().copied()),
                     multiplier: kitty_multipler,
+                    explanation,
+                });
+            }
+            if let Some(rung) =
+                bonus_rung.filter(|rung| rung.bonus_points > 0 && points_per_trick.is_none())
+            {
+                self.final_trick_bonus = Some(FinalTrickBonus {
+                    player: winner,
+                    points: rung.bonus_points,
+                });
+                msgs.push(MessageVariant::FinalTrickBonusEarned {
+                    player: winner,
+                    points: rung.bonus_points,
                 });
             }
         }
         let winner_idx = bail_unwrap!(self.propagated.players.iter().position(|p| p.id == winner));
-        if !new_points.is_empty() {
+        let num_points = if let Some(points) = points_per_trick {
+            points
+        } else if !new_points.is_empty() {
             let trump = self.trump;
-            let num_points = new_points.iter().flat_map(|c| c.points()).sum::<usize>();
+            let num_points = point_schedule.total(new_points.iter().copied());
             points.extend(new_points);
             points.sort_by(|a, b| trump.compare(*a, *b));
-            msgs.push(MessageVariant::TrickWon {
-                winner: self.propagated.players[winner_idx].id,
-                points: num_points,
-            });
+            num_points
         } else {
-            msgs.push(MessageVariant::TrickWon {
-                winner: self.propagated.players[winner_idx].id,
-                points: 0,
+            0
+        };
+        *self.tricks_won.entry(winner).or_insert(0) += 1;
+        let landlords_team = self.landlords_team.contains(&winner);
+        if num_points > 0 && !landlords_team {
+            msgs.push(MessageVariant::PointsCaptured {
+                player: winner,
+                points: num_points,
             });
         }
+        msgs.push(MessageVariant::TrickWon {
+            winner: self.propagated.players[winner_idx].id,
+            points: num_points,
+            winning_units,
+            runner_up,
+            point_cards,
+            landlords_team,
+        });
         let new_trick = Trick::new(
             self.trump,
             (0..self.propagated.players.len()).map(|offset| {
@@ -343,11 +789,15 @@ impl PlayPhase {
                 self.propagated.players[idx].id
             }),
         );
-        self.last_trick = Some(std::mem::replace(&mut self.trick, new_trick));
+        let finished_trick = std::mem::replace(&mut self.trick, new_trick);
+        self.trick_history.push(finished_trick.compact());
+        self.last_trick = Some(finished_trick);
 
         Ok(msgs)
     }
 
+    /// Advances each player by the levels for their team, as at the end of a
+    /// game, and returns the messages announcing the results.
     #[allow(clippy::too_many_arguments)]
     pub fn compute_player_level_deltas<'a, 'b: 'a>(
         players: impl Iterator<Item = &'b mut Player>,
@@ -358,110 +808,123 @@ impl PlayPhase {
         landlord: (PlayerID, Rank),
         advancement_policy: AdvancementPolicy,
         max_rank: Rank,
+        defended_ranks: &[Number],
     ) -> Vec<MessageVariant> {
-        let mut msgs = vec![];
-
-        let result = players
-            .map(|player| {
-                let is_defending = landlords_team.contains(&player.id);
-                let bump = if is_defending {
+        let mut players = players.collect::<Vec<_>>();
+        let results = score_players(
+            &players.iter().map(|p| (**p).clone()).collect::<Vec<_>>(),
+            landlords_team,
+            |is_defending| {
+                if is_defending {
                     landlord_level_bump
                 } else {
                     non_landlord_level_bump
-                };
-                let mut num_advances = 0;
-                let mut was_blocked = false;
-                let initial_rank = player.rank();
-
-                for bump_idx in 0..bump {
-                    let must_defend = match (advancement_policy, player.rank()) {
-                        (AdvancementPolicy::Unrestricted, r)
-                        | (AdvancementPolicy::Unrestricted, r)
-                        | (AdvancementPolicy::DefendPoints, r)
-                        | (AdvancementPolicy::DefendPoints, r)
-                            if r == max_rank
-                                || (r.successor() == Some(max_rank)
-                                    && max_rank == Rank::NoTrump) =>
-                        {
-                            true
-                        }
-                        (AdvancementPolicy::DefendPoints, Rank::Number(n))
-                            if n.points().is_some() =>
-                        {
-                            true
-                        }
-                        (AdvancementPolicy::FullyUnrestricted, _)
-                        | (AdvancementPolicy::Unrestricted, _)
-                        | (AdvancementPolicy::DefendPoints, _) => false,
-                    };
-                    // In order to advance past NoTrump, the landlord must also be defending
-                    // NoTrump.
-                    let landlord_must_defend = must_defend && player.rank() == Rank::NoTrump;
-
-                    if must_defend
-                        && (!is_defending
-                            || bump_idx > 0
-                            || (landlord_must_defend && landlord.1 != Rank::NoTrump))
-                    {
-                        was_blocked = true;
-                        break;
-                    }
-
-                    player.advance(max_rank);
-                    num_advances += 1;
-                }
-                if num_advances > 0 {
-                    msgs.push(MessageVariant::RankAdvanced {
-                        player: player.id,
-                        new_rank: player.rank(),
-                    });
-                }
-                if was_blocked {
-                    msgs.push(MessageVariant::AdvancementBlocked {
-                        player: player.id,
-                        rank: player.rank(),
-                    });
                 }
+            },
+            false,
+            landlord.1,
+            advancement_policy,
+            max_rank,
+            defended_ranks,
+        );
+        Self::apply_player_results(
+            players.iter_mut().map(|p| &mut **p),
+            &results,
+            landlord.0,
+            landlord_won,
+            max_rank,
+        )
+    }
 
-                (
-                    player.name.to_string(),
-                    PlayerGameFinishedResult {
-                        won_game: landlord_won == is_defending,
-                        is_defending,
-                        is_landlord: landlord.0 == player.id,
-                        ranks_up: num_advances,
-                        confetti: num_advances > 0
-                            && landlord_won
-                            && is_defending
-                            && initial_rank == max_rank,
-                        rank: initial_rank,
-                    },
-                )
+    /// Updates the players' ranks to their results, and returns the messages
+    /// announcing them, ending with `GameFinished`.
+    fn apply_player_results<'a>(
+        players: impl Iterator<Item = &'a mut Player>,
+        results: &[PlayerScoreResult],
+        landlord: PlayerID,
+        landlord_won: bool,
+        max_rank: Rank,
+    ) -> Vec<MessageVariant> {
+        let mut msgs = results
+            .iter()
+            .filter(|r| r.demoted)
+            .map(|r| MessageVariant::RankDemoted {
+                player: r.player,
+                new_rank: r.rank,
             })
-            .collect();
+            .collect::<Vec<_>>();
 
+        let mut result = HashMap::new();
+        for (player, r) in players.zip(results.iter()) {
+            if r.ranks_up > 0 {
+                msgs.push(MessageVariant::RankAdvanced {
+                    player: r.player,
+                    new_rank: r.rank,
+                });
+            }
+            if r.blocked {
+                msgs.push(MessageVariant::AdvancementBlocked {
+                    player: r.player,
+                    rank: r.rank,
+                });
+            }
+            result.insert(
+                player.name.to_string(),
+                PlayerGameFinishedResult {
+                    won_game: landlord_won == r.is_defending,
+                    is_defending: r.is_defending,
+                    is_landlord: landlord == r.player,
+                    ranks_up: r.ranks_up,
+                    confetti: r.ranks_up > 0
+                        && landlord_won
+                        && r.is_defending
+                        && r.previous_rank == max_rank,
+                    rank: r.previous_rank,
+                    won_match: r.metalevel > player.metalevel,
+                },
+            );
+            player.set_rank(r.rank);
+            player.set_meta_rank(r.metalevel);
+        }
         msgs.push(MessageVariant::GameFinished { result });
         msgs
     }
 
     pub fn calculate_points(&self) -> (isize, isize) {
-        let mut non_landlords_points: isize = self
-            .points
-            .iter()
-            .filter(|(id, _)| !self.landlords_team.contains(id))
-            .flat_map(|(_, cards)| cards)
-            .flat_map(|c| c.points())
-            .sum::<usize>() as isize;
+        if let ScoringPolicy::PointsPerTrick { points } = self.propagated.scoring_policy {
+            let non_landlords_points = (self
+                .tricks_won
+                .iter()
+                .filter(|(id, _)| !self.landlords_team.contains(id))
+                .map(|(_, n)| *n)
+                .sum::<usize>()
+                * points) as isize;
+            return (non_landlords_points, non_landlords_points);
+        }
+        let point_schedule = &self.propagated.game_scoring_parameters.point_schedule;
+        let mut non_landlords_points = point_schedule.total(
+            self.points
+                .iter()
+                .filter(|(id, _)| !self.landlords_team.contains(id))
+                .flat_map(|(_, cards)| cards.iter().copied()),
+        ) as isize;
 
-        let observed_points = self
-            .points
-            .iter()
-            .filter(|(id, _)| {
-                !self.propagated.hide_landlord_points || !self.landlords_team.contains(id)
-            })
-            .flat_map(|(_, cards)| cards)
-            .flat_map(|c| c.points())
-            .sum::<usize>() as isize;
+        let observed_points = point_schedule.total(
+            self.points
+                .iter()
+                .filter(|(id, _)| {
+                    !self.propagated.hide_landlord_points || !self.landlords_team.contains(id)
+                })
+                .flat_map(|(_, cards)| cards.iter().copied()),
+        ) as isize;
+
+        if let Some(bonus) = self.final_trick_bonus {
+            if self.landlords_team.contains(&bonus.player) {
+                non_landlords_points -= bonus.points as isize;
+            } else {
+                non_landlords_points += bonus.points as isize;
+            }
+        }
 
         for (id, penalty) in &self.penalties {
             if *penalty > 0 {
@@ -475,6 +938,86 @@ impl PlayPhase {
         (non_landlords_points, observed_points)
     }
 
+    /// Every result the game could still end in, from the points the
+    /// attacking team has so far. This assumes the last trick earns the
+    /// largest multiplier it could for the points in the bottom, and leaves
+    /// out throw penalties.
+    pub fn remaining_outcomes(&self) -> Result<Vec<ScoreBand>, Error> {
+        if self.propagated.scoring_policy != ScoringPolicy::PointCards {
+            bail!("Only games scored by point cards have score bands")
+        }
+        let gsp = &self.propagated.game_scoring_parameters;
+        let (non_landlords_points, observed_points) = self.calculate_points();
+        let remaining =
+            (gsp.point_schedule.deck_points(&self.decks) as isize - observed_points).max(0);
+
+        // The last trick can't be won with more cards than anyone has left.
+        let largest_unit_size = self
+            .propagated
+            .players
+            .iter()
+            .map(|p| {
+                self.hands.get(p.id).map_or(0, |h| h.len())
+                    + self
+                        .trick
+                        .played_cards()
+                        .iter()
+                        .find(|pc| pc.id == p.id)
+                        .map_or(0, |pc| pc.cards.len())
+            })
+            .max()
+            .unwrap_or(0);
+        let ladder = &self.propagated.final_trick_bonus_ladder;
+        let penalty = self.propagated.kitty_penalty;
+        let kitty_multiplier = ladder
+            .iter()
+            .flat_map(|rung| rung.kitty_multiplier)
+            .chain(vec![
+                penalty.multiplier(1, false),
+                penalty.multiplier(largest_unit_size, false),
+                penalty.multiplier(largest_unit_size, true),
+            ])
+            .max()
+            .unwrap_or(1);
+        let kitty_points = gsp.point_schedule.total(self.kitty.iter().copied());
+        let bonus_points = if self.final_trick_bonus.is_some() {
+            0
+        } else {
+            ladder
+                .iter()
+                .filter(|rung| rung.min_cards <= largest_unit_size)
+                .map(|rung| rung.bonus_points)
+                .max()
+                .unwrap_or(0)
+        };
+        let max_gain = if self.hands.is_empty() && self.trick.played_cards().is_empty() {
+            0
+        } else {
+            let kitty_gain = kitty_points
+                .saturating_mul(kitty_multiplier.max(1))
+                .min(isize::MAX as usize) as isize;
+            (remaining - kitty_points as isize)
+                .max(0)
+                .saturating_add(kitty_gain)
+                .saturating_add(bonus_points as isize)
+        };
+
+        let smaller_landlord_team = match self.game_mode {
+            GameMode::FindingFriends { num_friends, .. } => {
+                self.landlords_team.len() < num_friends + 1
+            }
+            GameMode::Tractor => false,
+        };
+        remaining_score_bands(
+            gsp,
+            &self.propagated.advancement_schedule,
+            &self.decks,
+            non_landlords_points,
+            max_gain,
+            smaller_landlord_team,
+        )
+    }
+
     pub fn game_finished(&self) -> bool {
         self.game_ended_early || self.hands.is_empty() && self.trick.played_cards().is_empty()
     }
@@ -483,9 +1026,13 @@ impl PlayPhase {
         if self.game_finished() {
             bail!("Game has already ended");
         }
+        if self.propagated.scoring_policy != ScoringPolicy::PointCards {
+            bail!("Only games scored by point cards can be ended early")
+        }
         let (non_landlords_points, observed_points) = self.calculate_points();
         let can_end_early = !next_threshold_reachable(
             &self.propagated.game_scoring_parameters,
+            &self.propagated.advancement_schedule,
             &self.decks,
             non_landlords_points,
             observed_points,
@@ -522,43 +1069,91 @@ impl PlayPhase {
 
         let mut propagated = self.propagated.clone();
 
-        let GameScoreResult {
-            non_landlord_delta: non_landlord_level_bump,
-            landlord_delta: landlord_level_bump,
-            landlord_won,
-            landlord_bonus: bonus_level_earned,
-        } = compute_level_deltas(
-            &propagated.game_scoring_parameters,
-            &self.decks,
+        let score = compute_score(
             non_landlords_points,
-            smaller_landlord_team,
+            &propagated,
+            &ScoreState {
+                decks: &self.decks,
+                landlord: self.landlord,
+                landlords_team: &self.landlords_team,
+                smaller_landlord_team,
+                blind_bid_levels: self.blind_bid_levels,
+                tricks_played: self.tricks_won.values().sum(),
+            },
         )?;
+        let landlord_won = score.landlord_won;
+        let shutout_bonus_levels = score
+            .bonuses
+            .iter()
+            .map(|b| match b {
+                ScoreBonus::Shutout { levels } => *levels,
+                _ => 0,
+            })
+            .sum();
 
         msgs.push(MessageVariant::EndOfGameSummary {
             landlord_won,
             non_landlords_points,
+            shutout_bonus_levels,
+            bid_history: self.bid_history.records().to_vec(),
+            kitty: self.kitty_reveal(),
+            buried_points: self.buried_points(),
+            kitty_multiplier: self.kitty_multiplier,
+            score: Some(Box::new(score.clone())),
         });
 
-        if bonus_level_earned {
-            msgs.push(MessageVariant::BonusLevelEarned);
-        };
+        for bonus in &score.bonuses {
+            msgs.push(match *bonus {
+                ScoreBonus::SmallerLandlordTeam => MessageVariant::BonusLevelEarned,
+                ScoreBonus::Shutout { levels } => MessageVariant::ShutoutBonusEarned { levels },
+                ScoreBonus::BlindBid { levels } => MessageVariant::BlindBidResolved {
+                    landlord_won,
+                    levels,
+                },
+            });
+        }
 
         let landlord_idx = bail_unwrap!(propagated
             .players
             .iter()
             .position(|p| p.id == self.landlord));
 
-        msgs.extend(Self::compute_player_level_deltas(
+        let max_rank = *propagated.max_rank;
+        msgs.extend(Self::apply_player_results(
             propagated.players.iter_mut(),
-            non_landlord_level_bump,
-            landlord_level_bump,
-            &self.landlords_team[..],
+            &score.players,
+            self.landlord,
             landlord_won,
-            (self.landlord, self.propagated.players[landlord_idx].level),
-            propagated.advancement_policy,
-            *propagated.max_rank,
+            max_rank,
         ));
 
+        let entry = LedgerEntry {
+            game: propagated.num_games_finished,
+            landlord: self.landlord,
+            landlords_team: self.landlords_team.clone(),
+            landlord_won,
+            non_landlords_points,
+            kitty_points: self.kitty_multiplier.map_or(0, |m| {
+                propagated
+                    .game_scoring_parameters
+                    .point_schedule
+                    .total(self.kitty.iter().copied())
+                    * m.multiplier
+            }),
+            players: score
+                .players
+                .iter()
+                .map(|r| LedgerPlayerResult {
+                    player: r.player,
+                    previous_rank: r.previous_rank,
+                    rank: r.rank,
+                    ranks_up: r.ranks_up,
+                    demoted: r.demoted,
+                })
+                .collect(),
+        };
+        propagated.score_ledger.record(entry);
+
         let mut idx = (landlord_idx + 1) % propagated.players.len();
         let (next_landlord, next_landlord_idx) = loop {
             if landlord_won == self.landlords_team.contains(&propagated.players[idx].id) {
@@ -572,6 +1167,8 @@ impl PlayPhase {
         });
         propagated.set_landlord(Some(next_landlord))?;
         propagated.num_games_finished += 1;
+        msgs.extend(propagated.form_next_teams(landlord_won));
+        msgs.extend(propagated.finish_match_game(now_millis()));
         msgs.extend(propagated.make_all_observers_into_players()?);
 
         Ok((
@@ -591,6 +1188,13 @@ impl PlayPhase {
     }
 
     pub fn destructively_redact_for_player(&mut self, player: PlayerID) {
+        // The history is only sent when asked for, to keep the state small.
+        self.trick_history.clear();
+        // The exchange has everyone's cards in it.
+        self.pre_lead_exchange = None;
+        if self.propagated.last_trick_policy == LastTrickPolicy::NoLastTrick {
+            self.last_trick = None;
+        }
         if self.propagated.hide_landlord_points {
             for (k, v) in self.points.iter_mut() {
                 if self.landlords_team.contains(k) {
@@ -602,12 +1206,17 @@ impl PlayPhase {
         let game_ongoing = !self.game_ended_early
             && (!self.hands.is_empty() || !self.trick.played_cards().is_empty());
         if game_ongoing {
-            self.hands.destructively_redact_except_for_player(player);
+            let mut visible = self.revealed_players.clone();
+            visible.push(player);
+            self.hands.destructively_redact_except_for_players(&visible);
         }
         if game_ongoing && player != self.exchanger {
-            for card in &mut self.kitty {
-                *card = Card::Unknown;
-            }
+            redact_kitty(
+                &mut self.kitty,
+                &self.kitty_buried_by,
+                self.propagated.face_up_kitty_burial,
+                player,
+            );
         }
     }
 }
@@ -0,0 +1,154 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use shengji_mechanics::types::{PlayerID, Rank};
+
+/// How one player's rank changed over a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LedgerPlayerResult {
+    pub player: PlayerID,
+    pub previous_rank: Rank,
+    pub rank: Rank,
+    pub ranks_up: usize,
+    pub demoted: bool,
+}
+
+/// The outcome of a single finished game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LedgerEntry {
+    /// The number of games finished in the room before this one.
+    pub game: usize,
+    pub landlord: PlayerID,
+    pub landlords_team: Vec<PlayerID>,
+    pub landlord_won: bool,
+    pub non_landlords_points: isize,
+    /// The points taken from the bottom cards by whichever team won the last
+    /// trick, after the multiplier.
+    pub kitty_points: usize,
+    pub players: Vec<LedgerPlayerResult>,
+}
+
+/// One player's tally over every game in the ledger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SeasonStanding {
+    pub player: PlayerID,
+    pub games_played: usize,
+    /// Games won on the landlord's team.
+    pub landlord_team_wins: usize,
+    /// Games won against the landlord's team.
+    pub opposing_team_wins: usize,
+    pub ranks_gained: usize,
+}
+
+impl SeasonStanding {
+    pub fn wins(&self) -> usize {
+        self.landlord_team_wins + self.opposing_team_wins
+    }
+}
+
+/// Every game finished in the room, oldest first, so that results don't have
+/// to be pieced back together from the chat.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct ScoreLedger(Vec<LedgerEntry>);
+
+impl ScoreLedger {
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.0
+    }
+
+    pub fn record(&mut self, entry: LedgerEntry) {
+        self.0.push(entry);
+    }
+
+    /// Tallies up the ledger for everyone who has played, most wins first and
+    /// then by ranks gained.
+    pub fn season_standings(&self) -> Vec<SeasonStanding> {
+        let mut standings: Vec<SeasonStanding> = vec![];
+        for entry in &self.0 {
+            for result in &entry.players {
+                let idx = match standings.iter().position(|s| s.player == result.player) {
+                    Some(idx) => idx,
+                    None => {
+                        standings.push(SeasonStanding {
+                            player: result.player,
+                            games_played: 0,
+                            landlord_team_wins: 0,
+                            opposing_team_wins: 0,
+                            ranks_gained: 0,
+                        });
+                        standings.len() - 1
+                    }
+                };
+                let standing = &mut standings[idx];
+                let on_landlords_team = entry.landlords_team.contains(&result.player);
+                standing.games_played += 1;
+                standing.ranks_gained += result.ranks_up;
+                match (on_landlords_team, entry.landlord_won) {
+                    (true, true) => standing.landlord_team_wins += 1,
+                    (false, false) => standing.opposing_team_wins += 1,
+                    _ => (),
+                }
+            }
+        }
+        standings.sort_by_key(|s| std::cmp::Reverse((s.wins(), s.ranks_gained)));
+        standings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shengji_mechanics::types::{Number, PlayerID, Rank};
+
+    use super::{LedgerEntry, LedgerPlayerResult, ScoreLedger};
+
+    #[test]
+    fn test_season_standings() {
+        let r2 = Rank::Number(Number::Two);
+        let entry = |landlords_team: Vec<usize>, landlord_won, ranks_up: usize| LedgerEntry {
+            game: 0,
+            landlord: PlayerID(landlords_team[0]),
+            landlords_team: landlords_team.iter().map(|id| PlayerID(*id)).collect(),
+            landlord_won,
+            non_landlords_points: 0,
+            kitty_points: 0,
+            players: (0..4)
+                .map(|id| LedgerPlayerResult {
+                    player: PlayerID(id),
+                    previous_rank: r2,
+                    rank: r2,
+                    ranks_up: if landlords_team.contains(&id) == landlord_won {
+                        ranks_up
+                    } else {
+                        0
+                    },
+                    demoted: false,
+                })
+                .collect(),
+        };
+        let mut ledger = ScoreLedger::default();
+        ledger.record(entry(vec![0, 2], true, 2));
+        ledger.record(entry(vec![2, 0], false, 1));
+        ledger.record(entry(vec![1, 3], true, 3));
+
+        let standings = ledger.season_standings();
+        assert_eq!(
+            standings
+                .iter()
+                .map(|s| (
+                    s.player.0,
+                    s.games_played,
+                    s.landlord_team_wins,
+                    s.opposing_team_wins,
+                    s.ranks_gained
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, 3, 1, 1, 4),
+                (3, 3, 1, 1, 4),
+                (0, 3, 1, 0, 2),
+                (2, 3, 1, 0, 2)
+            ]
+        );
+    }
+}
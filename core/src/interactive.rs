@@ -4,22 +4,37 @@ use serde::{Deserialize, Serialize};
 use slog::{debug, info, o, Logger};
 
 use shengji_mechanics::bidding::{
-    BidPolicy, BidReinforcementPolicy, BidTakebackPolicy, JokerBidPolicy,
+    Bid, BidPolicy, BidPriorityPolicy, BidReinforcementPolicy, BidRules, BidTakebackPolicy,
+    JokerBidPolicy,
 };
 use shengji_mechanics::deck::Deck;
-use shengji_mechanics::scoring::GameScoringParameters;
+use shengji_mechanics::naming::CardNames;
+use shengji_mechanics::ordered_card::DecompositionStrategy;
+use shengji_mechanics::scoring::{AdvancementSchedule, GameScoringParameters, ScoreBand};
+use shengji_mechanics::sorting::SortOrder;
 use shengji_mechanics::trick::{
-    ThrowEvaluationPolicy, TractorRequirements, TrickDrawPolicy, TrickUnit,
+    BeatPolicy, CompactTrick, JokerPairPolicy, MultiSuitLeadPolicy, PlayError, RuffPolicy,
+    ThrowEvaluationPolicy, ThrowEvaluationScope, TieBreakPolicy, TractorRequirements,
+    TractorRuffPolicy, TrickDrawPolicy, TrickError, TrickUnit, TrumpedThrowPolicy,
 };
-use shengji_mechanics::types::{Card, PlayerID, Rank};
+use shengji_mechanics::types::{Card, Number, PermanentTrump, PlayerID, Rank, Suit};
 
-use crate::game_state::{initialize_phase::InitializePhase, GameState};
+use crate::game_state::{
+    exchange_phase::KittyBurialError,
+    initialize_phase::InitializePhase,
+    play_phase::LastTrick,
+    score_ledger::{LedgerEntry, SeasonStanding},
+    GameState,
+};
 use crate::message::MessageVariant;
 use crate::settings::{
-    AdvancementPolicy, FirstLandlordSelectionPolicy, FriendSelection, FriendSelectionPolicy,
-    GameModeSettings, GameShadowingPolicy, GameStartPolicy, GameVisibility, KittyBidPolicy,
-    KittyPenalty, KittyTheftPolicy, MultipleJoinPolicy, PlayTakebackPolicy, PropagatedState,
-    ThrowPenalty,
+    AdvancementPolicy, BlindBidPolicy, ExchangeDelegationPolicy, FinalTrickBonusRung,
+    FirstLandlordSelectionPolicy, FriendSelection, FriendSelectionPolicy, GameModeSettings,
+    GameShadowingPolicy, GameStartPolicy, GameVisibility, HandRevealPolicy, KittyBidPolicy,
+    KittyBurialRules, KittyDiggingPolicy, KittyPenalty, KittyRevisionPolicy, KittyTheftPolicy,
+    LastTrickPolicy, MatchLength, MultipleJoinPolicy, NoBidPolicy, PlayTakebackPolicy,
+    PropagatedState, ScoringPolicy, TeamFormation, ThrowChallengePenalties, ThrowChallengePolicy,
+    ThrowPenalty, ThrowPenaltyPolicy, TrumpSelectionPolicy, TrumpSuitPolicy,
 };
 pub struct InteractiveGame {
     state: GameState,
@@ -72,6 +87,51 @@ impl InteractiveGame {
         self.state.next_player()
     }
 
+    /// The tricks finished so far this hand, for one of the seated players.
+    /// These aren't included in the state sent by `dump_state_for_player`.
+    pub fn trick_history(&self, id: PlayerID) -> Result<Vec<CompactTrick>, Error> {
+        match &self.state {
+            GameState::Play(p) => Ok(p.trick_history(id)?.to_vec()),
+            _ => bail!("Not valid in this phase!"),
+        }
+    }
+
+    /// The last finished trick, for one of the seated players.
+    pub fn last_trick(&self, id: PlayerID) -> Result<Option<LastTrick>, Error> {
+        match &self.state {
+            GameState::Play(p) => p.last_trick(id),
+            _ => bail!("Not valid in this phase!"),
+        }
+    }
+
+    /// Every bid the player could make right now.
+    pub fn valid_bids(&self, id: PlayerID) -> Result<Vec<Bid>, Error> {
+        match &self.state {
+            GameState::Draw(p) => p.valid_bids(id),
+            GameState::Exchange(p) => p.valid_bids(id),
+            _ => bail!("Not valid in this phase!"),
+        }
+    }
+
+    /// The results the current game could still end in.
+    pub fn remaining_outcomes(&self) -> Result<Vec<ScoreBand>, Error> {
+        match &self.state {
+            GameState::Play(p) => p.remaining_outcomes(),
+            _ => bail!("Not valid in this phase!"),
+        }
+    }
+
+    /// Every game played in the room so far, oldest first. This isn't
+    /// included in the state sent by `dump_state_for_player`.
+    pub fn score_ledger(&self) -> Vec<LedgerEntry> {
+        self.state.propagated().score_ledger().entries().to_vec()
+    }
+
+    /// The tally of every game played in the room so far.
+    pub fn season_standings(&self) -> Vec<SeasonStanding> {
+        self.state.propagated().score_ledger().season_standings()
+    }
+
     pub fn player_name(&self, player_id: PlayerID) -> Result<&'_ str, Error> {
         self.state.player_name(player_id)
     }
@@ -99,6 +159,10 @@ impl InteractiveGame {
                 self.state.set_chat_link(link.clone())?;
                 vec![]
             }
+            (Action::SetSortOrder(ref sort_order), _) => {
+                self.state.set_sort_order(id, sort_order.clone())?;
+                vec![]
+            }
             (Action::StartGame, GameState::Initialize(ref mut state)) => {
                 let s: &'_ PropagatedState = state;
                 info!(logger, "Starting game"; s);
@@ -122,6 +186,10 @@ impl InteractiveGame {
                 info!(logger, "Setting number of decks"; "num_decks" => num_decks);
                 state.set_num_decks(num_decks)?
             }
+            (Action::SetMinDeckNumber(number), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting minimum deck number"; "number" => number.map(|n| n.as_str()));
+                state.set_min_deck_number(number)?
+            }
             (Action::SetSpecialDecks(decks), GameState::Initialize(ref mut state)) => {
                 info!(logger, "Setting special decks"; "decks" => format!("{decks:?}"));
                 state.set_special_decks(decks)?
@@ -168,6 +236,14 @@ impl InteractiveGame {
                 info!(logger, "Setting bid reinforcement policy"; "policy" => policy);
                 state.set_bid_reinforcement_policy(policy)?
             }
+            (Action::SetBidPriorityPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting bid priority policy"; "policy" => policy);
+                state.set_bid_priority_policy(policy)?
+            }
+            (Action::SetBidRules(bid_rules), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting bid rules"; "bid_rules" => bid_rules);
+                state.set_bid_rules(bid_rules)?
+            }
             (Action::SetJokerBidPolicy(policy), GameState::Initialize(ref mut state)) => {
                 info!(logger, "Setting joker bid selection policy"; "policy" => policy);
                 state.set_joker_bid_policy(policy)?
@@ -179,6 +255,14 @@ impl InteractiveGame {
                 info!(logger, "Setting should reveal kitty at end of game"; "should_reveal" => should_reveal);
                 state.set_should_reveal_kitty_at_end_of_game(should_reveal)?
             }
+            (Action::SetMatchLength(length), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting match length"; "length" => length);
+                state.set_match_length(length)?
+            }
+            (Action::SetFaceUpKittyBurial(face_up), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting face-up kitty burial"; "face_up" => face_up);
+                state.set_face_up_kitty_burial(face_up)?
+            }
             (Action::SetLandlord(landlord), GameState::Initialize(ref mut state)) => {
                 info!(logger, "Setting landlord"; "landlord" => landlord.map(|l| l.0));
                 state.set_landlord(landlord)?;
@@ -232,6 +316,26 @@ impl InteractiveGame {
                 info!(logger, "Setting kitty bid policy"; "bid_policy" => kitty_bid_policy);
                 state.set_kitty_bid_policy(kitty_bid_policy)?
             }
+            (Action::SetNoBidPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting no bid policy"; "policy" => policy);
+                state.set_no_bid_policy(policy)?
+            }
+            (Action::SetBlindBidPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting blind bid policy"; "policy" => policy);
+                state.set_blind_bid_policy(policy)?
+            }
+            (Action::SetTrumpSelectionPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting trump selection policy"; "policy" => policy);
+                state.set_trump_selection_policy(policy)?
+            }
+            (Action::SetTrumpSuitPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting trump suit policy"; "policy" => policy);
+                state.set_trump_suit_policy(policy)?
+            }
+            (Action::SetPermanentTrump(permanent), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting permanent trump"; "permanent" => permanent);
+                state.set_permanent_trump(permanent)?
+            }
             (Action::SetTrickDrawPolicy(policy), GameState::Initialize(ref mut state)) => {
                 info!(logger, "Setting trick draw policy"; "draw_policy" => policy);
                 state.set_trick_draw_policy(policy)?
@@ -247,18 +351,88 @@ impl InteractiveGame {
                 info!(logger, "Setting game scoring parameters"; "parameters" => parameters);
                 state.set_game_scoring_parameters(parameters.clone())?
             }
+            (
+                Action::SetAdvancementSchedule(ref schedule),
+                GameState::Initialize(ref mut state),
+            ) => {
+                info!(logger, "Setting advancement schedule"; "schedule" => schedule);
+                state.set_advancement_schedule(schedule.clone())?
+            }
+            (Action::SetScoringPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting scoring policy"; "policy" => policy);
+                state.set_scoring_policy(policy)?
+            }
             (Action::SetThrowPenalty(throw_penalty), GameState::Initialize(ref mut state)) => {
                 info!(logger, "Setting throw penalty"; "penalty" => throw_penalty);
                 state.set_throw_penalty(throw_penalty)?
             }
+            (Action::SetThrowPenaltyPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting throw penalty policy"; "policy" => policy);
+                state.set_throw_penalty_policy(policy)?
+            }
+            (Action::SetThrowChallengePolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting throw challenge policy"; "policy" => policy);
+                state.set_throw_challenge_policy(policy)?
+            }
+            (
+                Action::SetThrowChallengePenalties(penalties),
+                GameState::Initialize(ref mut state),
+            ) => {
+                info!(logger, "Setting throw challenge penalties"; "penalties" => penalties);
+                state.set_throw_challenge_penalties(penalties)?
+            }
+            (Action::SetBeatPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting beat policy"; "policy" => policy);
+                state.set_beat_policy(policy)?
+            }
+            (Action::SetTieBreakPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting tie break policy"; "policy" => policy);
+                state.set_tie_break_policy(policy)?
+            }
+            (Action::SetMultiSuitLeadPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting multi-suit lead policy"; "policy" => policy);
+                state.set_multi_suit_lead_policy(policy)?
+            }
+            (Action::SetTrumpedThrowPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting trumped throw policy"; "policy" => policy);
+                state.set_trumped_throw_policy(policy)?
+            }
+            (Action::SetTractorRuffPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting tractor ruff policy"; "policy" => policy);
+                state.set_tractor_ruff_policy(policy)?
+            }
+            (Action::SetJokerPairPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting joker pair policy"; "policy" => policy);
+                state.set_joker_pair_policy(policy)?
+            }
+            (Action::SetDecompositionStrategy(strategy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting decomposition strategy"; "strategy" => strategy);
+                state.set_decomposition_strategy(strategy)?
+            }
+            (Action::SetRuffPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting ruff policy"; "policy" => policy);
+                state.set_ruff_policy(policy)?
+            }
             (Action::SetThrowEvaluationPolicy(policy), GameState::Initialize(ref mut state)) => {
                 info!(logger, "Setting throw evaluation policy"; "policy" => policy);
                 state.set_throw_evaluation_policy(policy)?
             }
+            (Action::SetThrowEvaluationScope(scope), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting throw evaluation scope"; "scope" => scope);
+                state.set_throw_evaluation_scope(scope)?
+            }
             (Action::SetPlayTakebackPolicy(policy), GameState::Initialize(ref mut state)) => {
                 info!(logger, "Setting play takeback policy"; "policy" => policy);
                 state.set_play_takeback_policy(policy)?
             }
+            (Action::SetHandRevealPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting hand reveal policy"; "policy" => policy);
+                state.set_hand_reveal_policy(policy)?
+            }
+            (Action::SetLastTrickPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting last trick policy"; "policy" => policy);
+                state.set_last_trick_policy(policy)?
+            }
             (Action::SetBidTakebackPolicy(policy), GameState::Initialize(ref mut state)) => {
                 info!(logger, "Setting bid takeback policy"; "policy" => policy);
                 state.set_bid_takeback_policy(policy)?
@@ -267,6 +441,37 @@ impl InteractiveGame {
                 info!(logger, "Setting kitty theft policy"; "policy" => policy);
                 state.set_kitty_theft_policy(policy)?
             }
+            (Action::SetKittyBurialRules(rules), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting kitty burial rules"; "rules" => rules);
+                state.set_kitty_burial_rules(rules)?
+            }
+            (Action::SetKittyDiggingPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting kitty digging policy"; "policy" => policy);
+                state.set_kitty_digging_policy(policy)?
+            }
+            (Action::SetExchangeDelegationPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting exchange delegation policy"; "policy" => policy);
+                state.set_exchange_delegation_policy(policy)?
+            }
+            (Action::SetTeamFormation(formation), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting team formation"; "formation" => formation);
+                state.set_team_formation(formation)?
+            }
+            (
+                Action::SetFinalTrickBonusLadder(ref ladder),
+                GameState::Initialize(ref mut state),
+            ) => {
+                info!(logger, "Setting final trick bonus ladder"; "ladder" => format!("{:?}", ladder));
+                state.set_final_trick_bonus_ladder(ladder.clone())?
+            }
+            (Action::SetDefendedRanks(ref ranks), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting defended ranks"; "ranks" => format!("{:?}", ranks));
+                state.set_defended_ranks(ranks.clone())?
+            }
+            (Action::SetKittyRevisionPolicy(policy), GameState::Initialize(ref mut state)) => {
+                info!(logger, "Setting kitty revision policy"; "policy" => policy);
+                state.set_kitty_revision_policy(policy)?
+            }
             (Action::SetGameShadowingPolicy(policy), GameState::Initialize(ref mut state)) => {
                 info!(logger, "Setting user multiple game session policy"; "policy" => policy);
                 state.set_user_multiple_game_session_policy(policy)?
@@ -284,21 +489,39 @@ impl InteractiveGame {
             }
             (Action::DrawCard, GameState::Draw(ref mut state)) => {
                 debug!(logger, "Drawing card");
-                state.draw_card(id)?;
-                vec![]
+                state.draw_card(id)?.into_iter().collect()
             }
             (Action::RevealCard, GameState::Draw(ref mut state)) => {
                 info!(logger, "Revealing card");
                 vec![state.reveal_card()?]
             }
-            (Action::Bid(card, count), GameState::Draw(ref mut state)) => {
-                info!(logger, "Making bid");
-                if state.bid(id, card, count) {
-                    vec![MessageVariant::MadeBid { card, count }]
+            (Action::ResolveNoBid(suit), GameState::Draw(ref mut state)) => {
+                info!(logger, "Resolving lack of bids"; "policy" => state.propagated().no_bid_policy);
+                if state.propagated().no_bid_policy == NoBidPolicy::Redeal {
+                    self.state = GameState::Draw(state.redeal(id)?);
+                    vec![MessageVariant::Redealt]
                 } else {
-                    bail!("bid was invalid")
+                    vec![state.pick_trump_without_bid(id, suit)?]
                 }
             }
+            (Action::Bid(card, count), GameState::Draw(ref mut state)) => {
+                info!(logger, "Making bid");
+                let displaced = state.try_bid(id, card, count)?;
+                let mut msgs = vec![MessageVariant::MadeBid { card, count }];
+                msgs.extend(displaced.map(|over| MessageVariant::BidTookPriority { over }));
+                msgs
+            }
+            (Action::BlindBid(card, count), GameState::Draw(ref mut state)) => {
+                info!(logger, "Making blind bid");
+                let displaced = state.try_blind_bid(id, card, count)?;
+                let mut msgs = vec![MessageVariant::MadeBlindBid { card, count }];
+                msgs.extend(displaced.map(|over| MessageVariant::BidTookPriority { over }));
+                msgs
+            }
+            (Action::SubmitSealedBid(bid), GameState::Draw(ref mut state)) => {
+                info!(logger, "Submitting sealed bid");
+                state.submit_sealed_bid(id, bid)?
+            }
             (Action::TakeBackBid, GameState::Draw(ref mut state)) => {
                 info!(logger, "Taking back bid");
                 state.take_back_bid(id)?;
@@ -311,11 +534,8 @@ impl InteractiveGame {
             }
             (Action::Bid(card, count), GameState::Exchange(ref mut state)) => {
                 info!(logger, "Making exchange bid");
-                if state.bid(id, card, count) {
-                    vec![MessageVariant::MadeBid { card, count }]
-                } else {
-                    bail!("bid was invalid")
-                }
+                state.try_bid(id, card, count)?;
+                vec![MessageVariant::MadeBid { card, count }]
             }
             (Action::TakeBackBid, GameState::Exchange(ref mut state)) => {
                 info!(logger, "Taking back bid");
@@ -327,6 +547,21 @@ impl InteractiveGame {
                 state.pick_up_cards(id)?;
                 vec![MessageVariant::PickedUpCards]
             }
+            (Action::DigKitty(card), GameState::Exchange(ref mut state)) => {
+                info!(logger, "Digging the kitty");
+                state.dig_kitty(id, card)?;
+                vec![MessageVariant::DugKitty { card }]
+            }
+            (Action::WaiveKittyBurialRules, GameState::Exchange(ref mut state)) => {
+                info!(logger, "Waiving the kitty burial rules");
+                state.waive_kitty_burial_rules(id)?;
+                vec![MessageVariant::KittyBurialRulesWaived]
+            }
+            (Action::DelegateExchange(player), GameState::Exchange(ref mut state)) => {
+                info!(logger, "Delegating the exchange"; "player" => player.0);
+                state.delegate_exchange(id, player)?;
+                vec![MessageVariant::DelegatedExchange { player }]
+            }
             (Action::PutDownKitty, GameState::Exchange(ref mut state)) => {
                 info!(logger, "Putting down cards after over-bid");
                 state.finalize(id)?;
@@ -352,6 +587,11 @@ impl InteractiveGame {
                 self.state = GameState::Play(state.advance(id)?);
                 vec![]
             }
+            (Action::ReviseKitty, GameState::Play(ref mut state)) => {
+                info!(logger, "Reopening the exchange to revise the kitty");
+                self.state = GameState::Exchange(state.revise_kitty(id)?);
+                vec![MessageVariant::RevisingKitty]
+            }
             (Action::PlayCards(ref cards), GameState::Play(ref mut state)) => {
                 info!(logger, "Playing cards");
                 state.play_cards(id, cards)?
@@ -367,11 +607,19 @@ impl InteractiveGame {
                 info!(logger, "Finishing trick");
                 state.finish_trick()?
             }
+            (Action::ChallengeThrow, GameState::Play(ref mut state)) => {
+                info!(logger, "Challenging throw");
+                state.challenge_throw(id)?
+            }
             (Action::TakeBackCards, GameState::Play(ref mut state)) => {
                 info!(logger, "Taking back cards");
                 state.take_back_cards(id)?;
                 vec![MessageVariant::TookBackPlay]
             }
+            (Action::RevealHand, GameState::Play(ref mut state)) => {
+                info!(logger, "Revealing hand");
+                vec![state.reveal_hand(id)?]
+            }
             (Action::EndGameEarly, GameState::Play(ref mut state)) => {
                 info!(logger, "Ending game early");
                 vec![state.finish_game_early()?]
@@ -399,6 +647,7 @@ impl InteractiveGame {
             let b = BroadcastMessage {
                 actor,
                 actor_name: self.state.player_name(actor)?.to_owned(),
+                card_names: CardNames::of(msg.cards()),
                 variant: msg,
             };
             out.extend(
@@ -417,14 +666,18 @@ pub enum Action {
     MakeObserver(PlayerID),
     MakePlayer(PlayerID),
     SetChatLink(Option<String>),
+    SetSortOrder(SortOrder),
     SetNumDecks(Option<usize>),
     SetSpecialDecks(Vec<Deck>),
+    SetMinDeckNumber(Option<Number>),
     SetKittySize(Option<usize>),
     SetFriendSelectionPolicy(FriendSelectionPolicy),
     SetMultipleJoinPolicy(MultipleJoinPolicy),
     SetFirstLandlordSelectionPolicy(FirstLandlordSelectionPolicy),
     SetBidPolicy(BidPolicy),
     SetBidReinforcementPolicy(BidReinforcementPolicy),
+    SetBidRules(BidRules),
+    SetBidPriorityPolicy(BidPriorityPolicy),
     SetJokerBidPolicy(JokerBidPolicy),
     SetHideLandlordsPoints(bool),
     SetHidePlayedCards(bool),
@@ -437,34 +690,81 @@ pub enum Action {
     SetGameMode(GameModeSettings),
     SetAdvancementPolicy(AdvancementPolicy),
     SetGameScoringParameters(GameScoringParameters),
+    SetAdvancementSchedule(AdvancementSchedule),
+    SetScoringPolicy(ScoringPolicy),
     SetKittyPenalty(KittyPenalty),
     SetKittyBidPolicy(KittyBidPolicy),
+    SetNoBidPolicy(NoBidPolicy),
+    SetBlindBidPolicy(BlindBidPolicy),
+    SetTrumpSelectionPolicy(TrumpSelectionPolicy),
+    SetTrumpSuitPolicy(TrumpSuitPolicy),
+    SetPermanentTrump(PermanentTrump),
     SetTrickDrawPolicy(TrickDrawPolicy),
     SetThrowPenalty(ThrowPenalty),
+    SetThrowPenaltyPolicy(ThrowPenaltyPolicy),
+    SetThrowChallengePolicy(ThrowChallengePolicy),
+    SetThrowChallengePenalties(ThrowChallengePenalties),
+    SetRuffPolicy(RuffPolicy),
+    SetBeatPolicy(BeatPolicy),
+    SetTieBreakPolicy(TieBreakPolicy),
+    SetMultiSuitLeadPolicy(MultiSuitLeadPolicy),
+    SetTrumpedThrowPolicy(TrumpedThrowPolicy),
+    SetTractorRuffPolicy(TractorRuffPolicy),
+    SetJokerPairPolicy(JokerPairPolicy),
+    SetDecompositionStrategy(DecompositionStrategy),
     SetThrowEvaluationPolicy(ThrowEvaluationPolicy),
+    SetThrowEvaluationScope(ThrowEvaluationScope),
     SetPlayTakebackPolicy(PlayTakebackPolicy),
+    SetHandRevealPolicy(HandRevealPolicy),
+    SetLastTrickPolicy(LastTrickPolicy),
     SetBidTakebackPolicy(BidTakebackPolicy),
     SetKittyTheftPolicy(KittyTheftPolicy),
+    SetKittyBurialRules(KittyBurialRules),
+    SetKittyDiggingPolicy(KittyDiggingPolicy),
+    SetExchangeDelegationPolicy(ExchangeDelegationPolicy),
+    SetTeamFormation(TeamFormation),
+    SetKittyRevisionPolicy(KittyRevisionPolicy),
+    SetDefendedRanks(Vec<Number>),
+    SetFinalTrickBonusLadder(Vec<FinalTrickBonusRung>),
     SetGameShadowingPolicy(GameShadowingPolicy),
     SetGameStartPolicy(GameStartPolicy),
     SetShouldRevealKittyAtEndOfGame(bool),
+    SetFaceUpKittyBurial(bool),
+    SetMatchLength(MatchLength),
     SetHideThrowHaltingPlayer(bool),
     SetTractorRequirements(TractorRequirements),
     SetGameVisibility(GameVisibility),
     StartGame,
     DrawCard,
     RevealCard,
+    /// Resolves a draw which nobody bid in, per the `NoBidPolicy`. The suit
+    /// is only used when the landlord picks trump.
+    ResolveNoBid(Option<Suit>),
     Bid(Card, usize),
+    /// Bids before drawing many cards, per the `BlindBidPolicy`.
+    BlindBid(Card, usize),
+    /// Submits a sealed bid, or passes, per `TrumpSelectionPolicy::SealedBids`.
+    SubmitSealedBid(Option<(Card, usize)>),
     PickUpKitty,
     PutDownKitty,
+    /// Takes over the bottom cards and declares trump with the card, per the
+    /// `KittyDiggingPolicy`.
+    DigKitty(Card),
+    DelegateExchange(PlayerID),
+    /// Lets the exchanger bury any cards for the rest of the exchange; only
+    /// the room owner can do this.
+    WaiveKittyBurialRules,
     MoveCardToKitty(Card),
     MoveCardToHand(Card),
     SetFriends(Vec<FriendSelection>),
     BeginPlay,
+    ReviseKitty,
     PlayCards(Vec<Card>),
     PlayCardsWithHint(Vec<Card>, Vec<TrickUnit>),
     EndTrick,
     TakeBackCards,
+    ChallengeThrow,
+    RevealHand,
     TakeBackBid,
     EndGameEarly,
     StartNewGame,
@@ -476,6 +776,29 @@ pub struct BroadcastMessage {
     actor: PlayerID,
     actor_name: String,
     variant: MessageVariant,
+    /// The names of the cards mentioned in the message, so that clients can
+    /// render them in their own locale.
+    #[serde(default)]
+    card_names: Vec<CardNames>,
+}
+
+/// The details of a failed action which clients can act on, sent to the
+/// player who tried it along with the error message.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum ActionError {
+    KittyBurial(KittyBurialError),
+    Play(PlayError),
+}
+
+impl ActionError {
+    pub fn from_error(e: &Error) -> Option<Self> {
+        if let Some(e) = e.downcast_ref::<KittyBurialError>() {
+            return Some(ActionError::KittyBurial(*e));
+        }
+        e.downcast_ref::<PlayError>()
+            .or_else(|| e.downcast_ref::<TrickError>().and_then(|e| e.play_error()))
+            .map(|e| ActionError::Play(e.clone()))
+    }
 }
 
 impl BroadcastMessage {
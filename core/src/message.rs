@@ -5,18 +5,33 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use shengji_mechanics::bidding::{
-    BidPolicy, BidReinforcementPolicy, BidTakebackPolicy, JokerBidPolicy,
+    BidPolicy, BidPriorityPolicy, BidReinforcementPolicy, BidRules, BidTakebackPolicy,
+    JokerBidPolicy,
 };
 use shengji_mechanics::deck::Deck;
-use shengji_mechanics::scoring::GameScoringParameters;
-use shengji_mechanics::trick::{ThrowEvaluationPolicy, TractorRequirements, TrickDrawPolicy};
-use shengji_mechanics::types::{Card, PlayerID, Rank};
+use shengji_mechanics::ordered_card::DecompositionStrategy;
+use shengji_mechanics::scoring::{AdvancementSchedule, GameScoringParameters};
+use shengji_mechanics::trick::{
+    BeatPolicy, JokerPairPolicy, MultiSuitLeadPolicy, PlayedUnits, RuffPolicy,
+    ThrowEvaluationPolicy, ThrowEvaluationScope, TieBreakPolicy, TractorRequirements,
+    TractorRuffPolicy, TrickDrawPolicy, TrickUnit, TrumpedThrowPolicy,
+};
+use shengji_mechanics::types::{Card, Number, PermanentTrump, PlayerID, Rank};
 
-use crate::game_state::play_phase::PlayerGameFinishedResult;
+use crate::game_state::bid_history::BidRecord;
+use crate::game_state::draw_phase::SealedBid;
+use crate::game_state::play_phase::{
+    BuriedPointCard, KittyMultiplier, KittyReveal, PlayerGameFinishedResult,
+};
+use crate::scoring::ScoreResult;
 use crate::settings::{
-    AdvancementPolicy, FirstLandlordSelectionPolicy, FriendSelectionPolicy, GameModeSettings,
-    GameShadowingPolicy, GameStartPolicy, GameVisibility, KittyBidPolicy, KittyPenalty,
-    KittyTheftPolicy, MultipleJoinPolicy, PlayTakebackPolicy, ThrowPenalty,
+    AdvancementPolicy, BlindBidPolicy, ExchangeDelegationPolicy, FinalTrickBonusRung,
+    FirstLandlordSelectionPolicy, FriendSelectionPolicy, GameModeSettings, GameShadowingPolicy,
+    GameStartPolicy, GameVisibility, HandRevealPolicy, KittyBidPolicy, KittyBurialRules,
+    KittyDiggingPolicy, KittyPenalty, KittyRevisionPolicy, KittyTheftPolicy, LastTrickPolicy,
+    MatchEndReason, MatchLength, MatchStanding, MultipleJoinPolicy, NoBidPolicy,
+    PlayTakebackPolicy, ScoringPolicy, TeamFormation, ThrowChallengePenalties,
+    ThrowChallengePolicy, ThrowPenalty, ThrowPenaltyPolicy, TrumpSelectionPolicy, TrumpSuitPolicy,
 };
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
@@ -26,6 +41,18 @@ pub enum MessageVariant {
     TrickWon {
         winner: PlayerID,
         points: usize,
+        /// The units with which `winner` won the trick.
+        #[serde(default)]
+        winning_units: Vec<TrickUnit>,
+        /// The play that `winner` beat, if any.
+        #[serde(default)]
+        runner_up: Option<PlayedUnits>,
+        /// The point cards making up `points`, by who played them.
+        #[serde(default)]
+        point_cards: Vec<CapturedPointCards>,
+        /// Whether `winner` is on the landlord's team, as far as it's known.
+        #[serde(default)]
+        landlords_team: bool,
     },
     RankAdvanced {
         player: PlayerID,
@@ -35,15 +62,23 @@ pub enum MessageVariant {
         player: PlayerID,
         rank: Rank,
     },
+    /// The player lost so badly as part of the landlord's team that they
+    /// were moved down a rank.
+    RankDemoted {
+        player: PlayerID,
+        new_rank: Rank,
+    },
     NewLandlordForNextGame {
         landlord: PlayerID,
     },
     PointsInKitty {
         points: usize,
         multiplier: usize,
+        explanation: KittyMultiplier,
     },
     EndOfGameKittyReveal {
         cards: Vec<Card>,
+        points: usize,
     },
     JoinedGame {
         player: PlayerID,
@@ -62,6 +97,9 @@ pub enum MessageVariant {
     AdvancementPolicySet {
         policy: AdvancementPolicy,
     },
+    AdvancementScheduleSet {
+        schedule: AdvancementSchedule,
+    },
     KittySizeSet {
         size: Option<usize>,
     },
@@ -80,6 +118,17 @@ pub enum MessageVariant {
     BidReinforcementPolicySet {
         policy: BidReinforcementPolicy,
     },
+    BidRulesSet {
+        bid_rules: BidRules,
+    },
+    BidPriorityPolicySet {
+        policy: BidPriorityPolicy,
+    },
+    /// The actor's bid was as strong as `over`'s, and replaced it by taking
+    /// priority.
+    BidTookPriority {
+        over: PlayerID,
+    },
     JokerBidPolicySet {
         policy: JokerBidPolicy,
     },
@@ -92,6 +141,9 @@ pub enum MessageVariant {
     NumDecksSet {
         num_decks: Option<usize>,
     },
+    MinDeckNumberSet {
+        number: Option<Number>,
+    },
     NumFriendsSet {
         num_friends: Option<usize>,
     },
@@ -101,10 +153,65 @@ pub enum MessageVariant {
     KittyTheftPolicySet {
         policy: KittyTheftPolicy,
     },
+    KittyBurialRulesSet {
+        rules: KittyBurialRules,
+    },
+    KittyDiggingPolicySet {
+        policy: KittyDiggingPolicy,
+    },
+    /// The player took over the bottom cards, and declared trump with the
+    /// card.
+    DugKitty {
+        card: Card,
+    },
+    /// The room owner lifted the `KittyBurialRules` for this exchange.
+    KittyBurialRulesWaived,
+    ExchangeDelegationPolicySet {
+        policy: ExchangeDelegationPolicy,
+    },
+    TeamFormationSet {
+        formation: TeamFormation,
+    },
+    /// Everyone but the first player moved over a seat, to change partners.
+    PartnersRotated,
+    /// The exchange of the bottom cards was handed over to the player.
+    DelegatedExchange {
+        player: PlayerID,
+    },
+    DefendedRanksSet {
+        ranks: Vec<Number>,
+    },
+    KittyRevisionPolicySet {
+        policy: KittyRevisionPolicy,
+    },
+    FinalTrickBonusLadderSet {
+        ladder: Vec<FinalTrickBonusRung>,
+    },
+    /// The last trick was won with a big enough unit to earn bonus points.
+    FinalTrickBonusEarned {
+        player: PlayerID,
+        points: usize,
+    },
+    FaceUpKittyBurialSet {
+        face_up: bool,
+    },
+    MatchLengthSet {
+        length: MatchLength,
+    },
+    MatchComplete {
+        reason: MatchEndReason,
+        standings: Vec<MatchStanding>,
+    },
+    /// The leader reopened the exchange to change the bottom cards before the
+    /// first card was led.
+    RevisingKitty,
     GameVisibilitySet {
         visibility: GameVisibility,
     },
     TookBackPlay,
+    RevealedHand {
+        cards: Vec<Card>,
+    },
     TookBackBid,
     PlayedCards {
         cards: Vec<Card>,
@@ -113,6 +220,34 @@ pub enum MessageVariant {
         original_cards: Vec<Card>,
         better_player: Option<PlayerID>,
     },
+    /// The points the thrower's team lost for a failed throw, under both the
+    /// `ThrowPenalty` and the `ThrowPenaltyPolicy`.
+    ThrowPenaltyDeducted {
+        player: PlayerID,
+        points: usize,
+    },
+    /// `player` took the lead by trumping a trick which was led in another
+    /// suit.
+    RuffOccurred {
+        player: PlayerID,
+        /// The player who was winning the trick.
+        over: PlayerID,
+        /// Whether `over` had also ruffed.
+        overruff: bool,
+    },
+    /// Points won by a trick for the team opposing the landlord.
+    PointsCaptured {
+        player: PlayerID,
+        points: usize,
+    },
+    ThrowChallenged {
+        thrower: PlayerID,
+        /// Whether the throw could be beaten.
+        upheld: bool,
+        /// The player whose team lost points for the challenge.
+        penalized: PlayerID,
+        points: usize,
+    },
     SetDefendingPointVisibility {
         visible: bool,
     },
@@ -144,18 +279,101 @@ pub enum MessageVariant {
     ThrowPenaltySet {
         throw_penalty: ThrowPenalty,
     },
+    ThrowPenaltyPolicySet {
+        policy: ThrowPenaltyPolicy,
+    },
+    ThrowChallengePolicySet {
+        policy: ThrowChallengePolicy,
+    },
+    ThrowChallengePenaltiesSet {
+        penalties: ThrowChallengePenalties,
+    },
     KittyBidPolicySet {
         policy: KittyBidPolicy,
     },
+    NoBidPolicySet {
+        policy: NoBidPolicy,
+    },
+    BlindBidPolicySet {
+        policy: BlindBidPolicy,
+    },
+    TrumpSelectionPolicySet {
+        policy: TrumpSelectionPolicy,
+    },
+    /// A card of the trump rank was drawn, which set trump.
+    DrewTrumpRankCard {
+        card: Card,
+    },
+    SubmittedSealedBid,
+    /// Everyone has submitted a sealed bid, and the strongest one won.
+    SealedBidsRevealed {
+        bids: Vec<SealedBid>,
+        winner: Option<PlayerID>,
+    },
+    MadeBlindBid {
+        card: Card,
+        count: usize,
+    },
+    /// The game was won on a blind bid, which changed the levels gained.
+    BlindBidResolved {
+        landlord_won: bool,
+        levels: usize,
+    },
+    /// Nobody bid, so the cards were dealt again.
+    Redealt,
+    /// Nobody bid, so trump was picked without a bid.
+    TrumpPickedWithoutBid {
+        card: Card,
+        random: bool,
+    },
+    TrumpSuitPolicySet {
+        policy: TrumpSuitPolicy,
+    },
+    PermanentTrumpSet {
+        permanent: PermanentTrump,
+    },
     TrickDrawPolicySet {
         policy: TrickDrawPolicy,
     },
     ThrowEvaluationPolicySet {
         policy: ThrowEvaluationPolicy,
     },
+    RuffPolicySet {
+        policy: RuffPolicy,
+    },
+    BeatPolicySet {
+        policy: BeatPolicy,
+    },
+    TieBreakPolicySet {
+        policy: TieBreakPolicy,
+    },
+    MultiSuitLeadPolicySet {
+        policy: MultiSuitLeadPolicy,
+    },
+    TrumpedThrowPolicySet {
+        policy: TrumpedThrowPolicy,
+    },
+    TractorRuffPolicySet {
+        policy: TractorRuffPolicy,
+    },
+    JokerPairPolicySet {
+        policy: JokerPairPolicy,
+    },
+    DecompositionStrategySet {
+        strategy: DecompositionStrategy,
+    },
+    ThrowEvaluationScopeSet {
+        scope: ThrowEvaluationScope,
+    },
     PlayTakebackPolicySet {
         policy: PlayTakebackPolicy,
     },
+    HandRevealPolicySet {
+        policy: HandRevealPolicy,
+    },
+    LastTrickPolicySet {
+        policy: LastTrickPolicy,
+    },
     BidTakebackPolicySet {
         policy: BidTakebackPolicy,
     },
@@ -166,8 +384,11 @@ pub enum MessageVariant {
         policy: GameStartPolicy,
     },
     GameScoringParametersChanged {
-        parameters: GameScoringParameters,
-        old_parameters: GameScoringParameters,
+        parameters: Box<GameScoringParameters>,
+        old_parameters: Box<GameScoringParameters>,
+    },
+    ScoringPolicySet {
+        policy: ScoringPolicy,
     },
     PickedUpCards,
     PutDownCards,
@@ -177,9 +398,27 @@ pub enum MessageVariant {
         result: HashMap<String, PlayerGameFinishedResult>,
     },
     BonusLevelEarned,
+    ShutoutBonusEarned {
+        levels: usize,
+    },
     EndOfGameSummary {
         landlord_won: bool,
         non_landlords_points: isize,
+        /// The extra levels the landlord's team earned for a shutout.
+        #[serde(default)]
+        shutout_bonus_levels: usize,
+        bid_history: Vec<BidRecord>,
+        /// The bottom cards, if they're revealed at the end of the game.
+        kitty: Option<KittyReveal>,
+        /// The point cards buried in the bottom, if they're public.
+        #[serde(default)]
+        buried_points: Vec<BuriedPointCard>,
+        #[serde(default)]
+        kitty_multiplier: Option<KittyMultiplier>,
+        /// How the result was worked out, so that it doesn't have to be
+        /// pieced back together.
+        #[serde(default)]
+        score: Option<Box<ScoreResult>>,
     },
     HideThrowHaltingPlayer {
         set: bool,
@@ -189,7 +428,38 @@ pub enum MessageVariant {
     },
 }
 
+/// Point cards captured in a trick.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CapturedPointCards {
+    /// The player who played the cards, or `None` for the bottom cards.
+    pub player: Option<PlayerID>,
+    pub cards: Vec<Card>,
+    /// What the cards are worth, including any multiplier for the bottom
+    /// cards.
+    pub points: usize,
+}
+
 impl MessageVariant {
+    /// The cards mentioned in this message.
+    pub fn cards(&self) -> Vec<Card> {
+        use MessageVariant::*;
+        match self {
+            EndOfGameKittyReveal { cards, .. } | PlayedCards { cards } | RevealedHand { cards } => {
+                cards.clone()
+            }
+            ThrowFailed { original_cards, .. } => original_cards.clone(),
+            MadeBid { card, .. }
+            | MadeBlindBid { card, .. }
+            | DrewTrumpRankCard { card }
+            | DugKitty { card }
+            | TrumpPickedWithoutBid { card, .. }
+            | PermanentTrumpSet {
+                permanent: PermanentTrump::Card(card),
+            } => vec![*card],
+            _ => vec![],
+        }
+    }
+
     pub fn to_string<'a>(
         &'a self,
         actor: PlayerID,
@@ -201,17 +471,19 @@ impl MessageVariant {
         Ok(match self {
             ResettingGame => format!("{} reset the game", n?),
             StartingGame => format!("{} started the game", n?),
-            TrickWon { winner, points: 0 } =>
+            TrickWon { winner, points: 0, .. } =>
                 format!("{} wins the trick, but gets no points :(", player_name(*winner)?),
-            TrickWon { winner, points } =>
+            TrickWon { winner, points, .. } =>
                 format!("{} wins the trick and gets {} points", player_name(*winner)?, points),
             RankAdvanced { player, new_rank } =>
                 format!("{} has advanced to rank {}", player_name(*player)?, new_rank.as_str()),
             AdvancementBlocked { player, rank } =>
                 format!("{} must defend on rank {}", player_name(*player)?, rank.as_str()),
+            RankDemoted { player, new_rank } =>
+                format!("{} was demoted to rank {} after losing badly", player_name(*player)?, new_rank.as_str()),
             NewLandlordForNextGame { landlord } =>
                 format!("{} will start the next game", player_name(*landlord)?),
-            PointsInKitty { points, multiplier } =>
+            PointsInKitty { points, multiplier, .. } =>
                 format!("{points} points were buried and are attached to the last trick, with a multiplier of {multiplier}"),
             JoinedGame { player } =>
                 format!("{} has joined the game", player_name(*player)?),
@@ -230,7 +502,16 @@ impl MessageVariant {
                 format!("{} required players to defend on A", n?),
             AdvancementPolicySet { policy: AdvancementPolicy::DefendPoints } =>
                 format!("{} required players to defend on points and A", n?),
+            AdvancementPolicySet { policy: AdvancementPolicy::MustWinExactly } =>
+                format!("{} required players to defend on A and win by exactly one level", n?),
+            AdvancementScheduleSet { schedule } if *schedule == AdvancementSchedule::default() =>
+                format!("{} set each scoring window to be worth one more level than the last", n?),
+            AdvancementScheduleSet { .. } => format!("{} changed the levels each scoring window is worth", n?),
             GameScoringParametersChanged { .. } => format!("{} changed the game's scoring parameters", n?),
+            ScoringPolicySet { policy: ScoringPolicy::PointCards } =>
+                format!("{} set the game to be scored by point cards", n?),
+            ScoringPolicySet { policy: ScoringPolicy::PointsPerTrick { points } } =>
+                format!("{} set every trick to be worth {} points, with no change in rank", n?, points),
             KittySizeSet { size: Some(size) } => format!("{} set the number of cards in the bottom to {}", n?, size),
             KittySizeSet { size: None } => format!("{} set the number of cards in the bottom to default", n?),
             FriendSelectionPolicySet { policy: FriendSelectionPolicy::Unrestricted } =>
@@ -261,12 +542,29 @@ impl MessageVariant {
                 format!("{} allowed reinforcing bids after they have been overturned", n?),
             BidReinforcementPolicySet { policy: BidReinforcementPolicy::OverturnOrReinforceWhileWinning } =>
                 format!("{} allowed overturning your own bids", n?),
+            BidRulesSet { bid_rules: BidRules { self_outbid, partner_reinforcement, equal_count_outbid, joker_bids_while_drawing, joker_bid_overturn, keep_bid_cards } } => {
+                let allowed = |allowed: bool| if allowed { "allowed" } else { "disallowed" };
+                format!(
+                    "{} {} outbidding your own bid, {} reinforcing your partner's bid, {} outbidding with as many cards, {} no-trump bids while drawing, {} overturning no-trump bids, and {} burying the cards of a bid which can still be overturned",
+                    n?, allowed(*self_outbid), allowed(*partner_reinforcement), allowed(*equal_count_outbid), allowed(*joker_bids_while_drawing), allowed(*joker_bid_overturn), allowed(!*keep_bid_cards)
+                )
+            }
+            BidPriorityPolicySet { policy: BidPriorityPolicy::FirstClick } =>
+                format!("{} gave equally strong bids to whoever bids first", n?),
+            BidPriorityPolicySet { policy: BidPriorityPolicy::DrawOrder } =>
+                format!("{} gave equally strong bids to whoever comes first in the draw order", n?),
+            BidPriorityPolicySet { policy: BidPriorityPolicy::DealersTeam } =>
+                format!("{} gave equally strong bids to the dealer's team", n?),
+            BidTookPriority { over } =>
+                format!("{}'s bid takes priority over {}'s", n?, player_name(*over)?),
             JokerBidPolicySet { policy: JokerBidPolicy::BothNumDecks } =>
                 format!("{} required no-trump bids to have every low or high joker", n?),
             JokerBidPolicySet { policy: JokerBidPolicy::LJNumDecksHJNumDecksLessOne } =>
                 format!("{} required low no-trump bids to have every low joker (one less required for high joker)", n?),
             JokerBidPolicySet { policy: JokerBidPolicy::BothTwoOrMore } =>
                 format!("{} required no-trump bids to have at least two low or high jokers", n?),
+            JokerBidPolicySet { policy: JokerBidPolicy::BothAtLeast { count } } =>
+                format!("{} required no-trump bids to have at least {} low or high jokers", n?, count),
             JokerBidPolicySet { policy: JokerBidPolicy::Disabled } =>
                 format!("{} disabled no-trump bids", n?),
             ShouldRevealKittyAtEndOfGameSet { should_reveal: true } =>
@@ -276,6 +574,9 @@ impl MessageVariant {
             NumDecksSet { num_decks: Some(num_decks) } =>
                 format!("{} set the number of decks to {}", n?, num_decks),
             NumDecksSet { num_decks: None } => format!("{} set the number of decks to default", n?),
+            MinDeckNumberSet { number: Some(number) } =>
+                format!("{} removed the cards below {} from the decks", n?, number.as_str()),
+            MinDeckNumberSet { number: None } => format!("{} set the decks to use all ranks", n?),
             SpecialDecksSet { ref special_decks } if special_decks.is_empty() =>
                 format!("{} set the decks to standard 54-card decks", n?),
             SpecialDecksSet { .. } => format!("{} changed the special deck settings", n?),
@@ -293,14 +594,29 @@ impl MessageVariant {
                 format!("{} set the game mode to Finding Friends with {} friends", n?, friends),
             TookBackBid => format!("{} took back their last bid", n?),
             TookBackPlay => format!("{} took back their last play", n?),
+            RevealedHand { .. } => format!("{} revealed their hand", n?),
             PlayedCards { ref cards } =>
                 format!("{} played {}", n?, cards.iter().map(|c| c.as_char()).collect::<String>()),
-            EndOfGameKittyReveal { ref cards } =>
+            EndOfGameKittyReveal { ref cards, points: 0 } =>
                 format!("{} in kitty", cards.iter().map(|c| c.as_char()).collect::<String>()),
+            EndOfGameKittyReveal { ref cards, points } =>
+                format!("{} in kitty, with {} points buried", cards.iter().map(|c| c.as_char()).collect::<String>(), points),
             ThrowFailed { ref original_cards, better_player: Some(better_player) } =>
                 format!("{} tried to throw {}, but {} can beat it", n?, original_cards.iter().map(|c| c.as_char()).collect::<String>(), player_name(*better_player)?),
             ThrowFailed { ref original_cards, better_player: None } =>
                 format!("{} tried to throw {}, but someone can beat it", n?, original_cards.iter().map(|c| c.as_char()).collect::<String>()),
+            ThrowPenaltyDeducted { player, points } =>
+                format!("{}'s team loses {} points for their failed throw", player_name(*player)?, points),
+            RuffOccurred { player, over, overruff: false } =>
+                format!("{} ruffed {}'s play", player_name(*player)?, player_name(*over)?),
+            RuffOccurred { player, over, overruff: true } =>
+                format!("{} overruffed {}", player_name(*player)?, player_name(*over)?),
+            PointsCaptured { player, points } =>
+                format!("{}'s team captured {} points", player_name(*player)?, points),
+            ThrowChallenged { thrower, upheld: true, penalized, points } =>
+                format!("{} challenged {}'s throw, which could be beaten; {}'s team loses {} points", n?, player_name(*thrower)?, player_name(*penalized)?, points),
+            ThrowChallenged { thrower, upheld: false, penalized, points } =>
+                format!("{} challenged {}'s throw, which couldn't be beaten; {}'s team loses {} points", n?, player_name(*thrower)?, player_name(*penalized)?, points),
             SetDefendingPointVisibility { visible: true } => format!("{} made the defending team's points visible", n?),
             SetDefendingPointVisibility { visible: false } => format!("{} hid the defending team's points", n?),
             SetCardVisibility { visible: true } => format!("{} made the played cards visible in the chat", n?),
@@ -316,14 +632,77 @@ impl MessageVariant {
                 format!("{} set the penalty for points in the bottom to twice the size of the last trick", n?),
             KittyPenaltySet { kitty_penalty: KittyPenalty::Power } =>
                 format!("{} set the penalty for points in the bottom to two to the power of the size of the last trick", n?),
+            KittyPenaltySet { kitty_penalty: KittyPenalty::Double } =>
+                format!("{} set the penalty for points in the bottom to twice the points", n?),
+            KittyPenaltySet { kitty_penalty: KittyPenalty::QuadrupleForTractors } =>
+                format!("{} set the penalty for points in the bottom to four times the points for a tractor, and twice otherwise", n?),
+            KittyPenaltySet { kitty_penalty: KittyPenalty::Custom { single, pair, tractor } } =>
+                format!("{} set the penalty for points in the bottom to {}x for singles, {}x for pairs and {}x for tractors", n?, single, pair, tractor),
             ThrowPenaltySet { throw_penalty: ThrowPenalty::None } =>
                 format!("{} removed the throw penalty", n?),
             ThrowPenaltySet { throw_penalty: ThrowPenalty::TenPointsPerAttempt } =>
                 format!("{} set the throw penalty to 10 points per throw", n?),
+            ThrowPenaltyPolicySet { policy: ThrowPenaltyPolicy::ForceSmallestUnit } =>
+                format!("{} set failed throws to force the smallest offending unit to be played", n?),
+            ThrowPenaltyPolicySet { policy: ThrowPenaltyPolicy::AllowTakeBack } =>
+                format!("{} allowed failed throws to be taken back before the next player plays", n?),
+            ThrowPenaltyPolicySet { policy: ThrowPenaltyPolicy::DeductPoints } =>
+                format!("{} set failed throws to cost 10 points per withdrawn card", n?),
+            ThrowChallengePolicySet { policy: ThrowChallengePolicy::CheckedWhenLed } =>
+                format!("{} set throws to be checked when they're led", n?),
+            ThrowChallengePolicySet { policy: ThrowChallengePolicy::CheckedWhenChallenged } =>
+                format!("{} set throws to be checked only when an opponent challenges them", n?),
+            ThrowChallengePenaltiesSet { penalties: ThrowChallengePenalties { thrower, challenger } } =>
+                format!("{} set failed throw challenges to cost the thrower {} points and the challenger {} points", n?, thrower, challenger),
             KittyBidPolicySet { policy: KittyBidPolicy::FirstCard } =>
                 format!("{} set the bid-from-bottom policy to be the first card revealed", n?),
             KittyBidPolicySet { policy: KittyBidPolicy::FirstCardOfLevelOrHighest } =>
                 format!("{} set the bid-from-bottom policy to be the first card of the appropriate level, or the highest if none are found", n?),
+            NoBidPolicySet { policy: NoBidPolicy::RevealKitty } =>
+                format!("{} set cards to be revealed from the bottom when nobody bids", n?),
+            NoBidPolicySet { policy: NoBidPolicy::Redeal } =>
+                format!("{} set the cards to be dealt again when nobody bids", n?),
+            NoBidPolicySet { policy: NoBidPolicy::RandomTrump } =>
+                format!("{} set trump to be picked at random when nobody bids", n?),
+            NoBidPolicySet { policy: NoBidPolicy::LandlordChooses } =>
+                format!("{} set the landlord to pick trump when nobody bids", n?),
+            BlindBidPolicySet { policy: BlindBidPolicy::NoBlindBids } =>
+                format!("{} disallowed blind bids", n?),
+            BlindBidPolicySet { policy: BlindBidPolicy::AllowBlindBids { max_cards_drawn, levels } } =>
+                format!("{} allowed blind bids before drawing more than {} cards, worth {} extra levels", n?, max_cards_drawn, levels),
+            TrumpSelectionPolicySet { policy: TrumpSelectionPolicy::Bidding } =>
+                format!("{} set trump to be picked by bidding", n?),
+            TrumpSelectionPolicySet { policy: TrumpSelectionPolicy::SealedBids } =>
+                format!("{} set trump to be picked by sealed bids once all the cards are drawn", n?),
+            TrumpSelectionPolicySet { policy: TrumpSelectionPolicy::FirstRankCardDrawn } =>
+                format!("{} set trump to be picked by the first card of the trump rank to be drawn", n?),
+            SubmittedSealedBid => format!("{} submitted a sealed bid", n?),
+            SealedBidsRevealed { winner: None, .. } => "Everyone passed on the sealed bids".to_string(),
+            SealedBidsRevealed { winner: Some(winner), .. } =>
+                format!("The sealed bids were revealed, and {} won", player_name(*winner)?),
+            DrewTrumpRankCard { card } => format!("{} drew {:?}, which sets trump", n?, card),
+            MadeBlindBid { card, count } => format!("{} bid {} {:?} blind", n?, count, card),
+            BlindBidResolved { landlord_won: true, levels } =>
+                format!("The landlord's team won their blind bid, and goes up {} extra levels", levels),
+            BlindBidResolved { landlord_won: false, levels } =>
+                format!("The landlord's team lost their blind bid, so the other team goes up {} extra levels", levels),
+            Redealt => format!("Nobody bid, so {} dealt the cards again", n?),
+            TrumpPickedWithoutBid { card, random: true } =>
+                format!("Nobody bid, so {} was picked at random as trump", card.as_char()),
+            TrumpPickedWithoutBid { card, random: false } =>
+                format!("Nobody bid, so {} picked {} as trump", n?, card.as_char()),
+            TrumpSuitPolicySet { policy: TrumpSuitPolicy::DeclaredSuit } =>
+                format!("{} set the winning bid to determine the trump suit", n?),
+            TrumpSuitPolicySet { policy: TrumpSuitPolicy::JokersAndRank } =>
+                format!("{} set only the jokers and the landlord's rank to be trump", n?),
+            TrumpSuitPolicySet { policy: TrumpSuitPolicy::JokersOnly } =>
+                format!("{} set only the jokers to be trump", n?),
+            PermanentTrumpSet { permanent: PermanentTrump::None } =>
+                format!("{} removed the permanent trump cards", n?),
+            PermanentTrumpSet { permanent: PermanentTrump::Number(number) } =>
+                format!("{} made every {} permanently trump", n?, number.as_str()),
+            PermanentTrumpSet { permanent: PermanentTrump::Card(card) } =>
+                format!("{} made {} the permanently-trump general", n?, card),
             TrickDrawPolicySet { policy: TrickDrawPolicy::NoProtections } =>
                 format!("{} removed all protections (pair can draw triple)", n?),
             TrickDrawPolicySet { policy: TrickDrawPolicy::NoFormatBasedDraw } =>
@@ -332,24 +711,165 @@ impl MessageVariant {
                 format!("{} protected longer tuples from being drawn out by shorter ones (pair does not draw triple)", n?),
             TrickDrawPolicySet { policy: TrickDrawPolicy::OnlyDrawTractorOnTractor } =>
                 format!("{} protected tractors from being drawn out by non-tractors", n?),
+            RuffPolicySet { policy: RuffPolicy::MayDiscard } =>
+                format!("{} allowed players out of the led suit to play any cards", n?),
+            RuffPolicySet { policy: RuffPolicy::MustRuff } =>
+                format!("{} required players out of the led suit to play trump if they have it", n?),
+            BeatPolicySet { policy: BeatPolicy::NoObligation } =>
+                format!("{} removed the requirement to beat the winning play", n?),
+            BeatPolicySet { policy: BeatPolicy::MustBeatIfAble } =>
+                format!("{} required players to beat the winning play if they can", n?),
+            TieBreakPolicySet { policy: TieBreakPolicy::FirstPlayed } =>
+                format!("{} set the first of several identical winning plays to win the trick", n?),
+            TieBreakPolicySet { policy: TieBreakPolicy::LastPlayed } =>
+                format!("{} set the last of several identical winning plays to win the trick", n?),
+            MultiSuitLeadPolicySet { policy: MultiSuitLeadPolicy::SingleSuitOnly } =>
+                format!("{} required leads to be in a single suit", n?),
+            MultiSuitLeadPolicySet { policy: MultiSuitLeadPolicy::AllowWhenVoidElsewhere } =>
+                format!("{} allowed throwing across suits when void in every other suit", n?),
+            TrumpedThrowPolicySet { policy: TrumpedThrowPolicy::Stands } =>
+                format!("{} set throws to stand if they can only be beaten by trumping", n?),
+            TrumpedThrowPolicySet { policy: TrumpedThrowPolicy::Fails } =>
+                format!("{} set throws to fail if they can be beaten by trumping", n?),
+            TractorRuffPolicySet { policy: TractorRuffPolicy::SameShape } =>
+                format!("{} required tractors to be ruffed with trump tractors", n?),
+            TractorRuffPolicySet { policy: TractorRuffPolicy::AnyTrump } =>
+                format!("{} allowed tractors to be ruffed with any trump cards", n?),
+            JokerPairPolicySet { policy: JokerPairPolicy::Throw } =>
+                format!("{} set leading both jokers to count as a throw", n?),
+            JokerPairPolicySet { policy: JokerPairPolicy::Pair } =>
+                format!("{} set leading both jokers to count as a pair", n?),
+            JokerPairPolicySet { policy: JokerPairPolicy::Unbeatable } =>
+                format!("{} set leading both jokers to count as an unbeatable pair", n?),
+            DecompositionStrategySet { strategy: DecompositionStrategy::MostOptionsFirst } =>
+                format!("{} set players who can't match the lead to give up whichever part of it leaves the most options first", n?),
+            DecompositionStrategySet { strategy: DecompositionStrategy::PreserveTractors } =>
+                format!("{} set players who can't match the lead to keep tractors together for as long as possible", n?),
+            DecompositionStrategySet { strategy: DecompositionStrategy::PreservePairs } =>
+                format!("{} set players who can't match the lead to break up tractors before other parts of it", n?),
+            DecompositionStrategySet { strategy: DecompositionStrategy::FewestForcedUnits } =>
+                format!("{} set players who can't match the lead to be held to as few units as possible", n?),
             ThrowEvaluationPolicySet { policy: ThrowEvaluationPolicy::All } =>
                 format!("{} set throws to be evaluated based on all of the cards", n?),
             ThrowEvaluationPolicySet { policy: ThrowEvaluationPolicy::Highest } =>
                 format!("{} set throws to be evaluated based on the highest card", n?),
             ThrowEvaluationPolicySet { policy: ThrowEvaluationPolicy::TrickUnitLength } =>
                 format!("{} set throws to be evaluated based on the longest component", n?),
+            ThrowEvaluationScopeSet { scope: ThrowEvaluationScope::AllHands } =>
+                format!("{} set throws to be checked against each player's hand", n?),
+            ThrowEvaluationScopeSet { scope: ThrowEvaluationScope::UnseenCards } =>
+                format!("{} set throws to be checked against all of the unseen cards together", n?),
+            ThrowEvaluationScopeSet { scope: ThrowEvaluationScope::OpponentsOnly } =>
+                format!("{} set throws to be checked against opponents' hands only", n?),
             PlayTakebackPolicySet { policy: PlayTakebackPolicy::AllowPlayTakeback } =>
                 format!("{} allowed taking back plays", n?),
             PlayTakebackPolicySet { policy: PlayTakebackPolicy::NoPlayTakeback } =>
                 format!("{} disallowed taking back plays", n?),
+            HandRevealPolicySet { policy: HandRevealPolicy::AllowHandReveal } =>
+                format!("{} allowed players to reveal their hands", n?),
+            HandRevealPolicySet { policy: HandRevealPolicy::NoHandReveal } =>
+                format!("{} disallowed players from revealing their hands", n?),
+            LastTrickPolicySet { policy: LastTrickPolicy::AllowLastTrick } =>
+                format!("{} allowed players to look at the last trick", n?),
+            LastTrickPolicySet { policy: LastTrickPolicy::NoLastTrick } =>
+                format!("{} disallowed players from looking at the last trick", n?),
             BidTakebackPolicySet { policy: BidTakebackPolicy::AllowBidTakeback } =>
                 format!("{} allowed taking back bids", n?),
             BidTakebackPolicySet { policy: BidTakebackPolicy::NoBidTakeback } =>
                 format!("{} disallowed taking back bids", n?),
+            BidTakebackPolicySet { policy: BidTakebackPolicy::AllowBidTakebackWhileDrawing } =>
+                format!("{} allowed taking back bids while cards are being drawn", n?),
             KittyTheftPolicySet { policy: KittyTheftPolicy::AllowKittyTheft } =>
                 format!("{} allowed stealing the bottom cards after the leader", n?),
             KittyTheftPolicySet { policy: KittyTheftPolicy::NoKittyTheft } =>
                 format!("{} disabled stealing the bottom cards after the leader", n?),
+            KittyBurialRulesSet { rules: KittyBurialRules { allow_point_cards, allow_trump } } => {
+                let allowed = |allowed: bool| if allowed { "allowed" } else { "disallowed" };
+                format!(
+                    "{} {} burying point cards and {} burying trump in the bottom",
+                    n?, allowed(*allow_point_cards), allowed(*allow_trump)
+                )
+            }
+            KittyDiggingPolicySet { policy: KittyDiggingPolicy::NoKittyDigging } =>
+                format!("{} disabled digging the bottom cards", n?),
+            KittyDiggingPolicySet { policy: KittyDiggingPolicy::DigWithJokers { jokers } } =>
+                format!("{} allowed digging the bottom cards with at least {} jokers", n?, jokers),
+            DugKitty { card } =>
+                format!("{} dug the bottom cards, declared {:?} and is now the leader", n?, card),
+            KittyBurialRulesWaived =>
+                format!("{} lifted the rules for burying cards for this hand", n?),
+            ExchangeDelegationPolicySet { policy: ExchangeDelegationPolicy::LandlordOnly } =>
+                format!("{} only allowed the leader to exchange the bottom cards", n?),
+            ExchangeDelegationPolicySet { policy: ExchangeDelegationPolicy::AllowTeammate } =>
+                format!("{} allowed the leader to hand the bottom cards to a teammate", n?),
+            TeamFormationSet { formation: TeamFormation::Fixed } =>
+                format!("{} fixed the teams for every game", n?),
+            TeamFormationSet { formation: TeamFormation::RotatePartners } =>
+                format!("{} set partners to change after every game", n?),
+            TeamFormationSet { formation: TeamFormation::WinnerStays } =>
+                format!("{} set partners to change only when the leader's team loses", n?),
+            PartnersRotated => "Everyone but the first player moved over a seat to change partners".to_string(),
+            DelegatedExchange { player } =>
+                format!("{} handed the bottom cards to {}", n?, player_name(*player)?),
+            DefendedRanksSet { ranks } if ranks.is_empty() =>
+                format!("{} removed the extra ranks which have to be defended", n?),
+            DefendedRanksSet { ranks } =>
+                format!(
+                    "{} required ranks {} to be defended",
+                    n?,
+                    ranks.iter().map(|r| r.as_str()).collect::<Vec<_>>().join(", ")
+                ),
+            FinalTrickBonusLadderSet { ladder } if ladder.is_empty() =>
+                format!("{} removed the bonus for winning the last trick with pairs or tractors", n?),
+            FinalTrickBonusLadderSet { ladder } =>
+                format!(
+                    "{} set the bonus for winning the last trick to {}",
+                    n?,
+                    ladder
+                        .iter()
+                        .map(|rung| {
+                            let mut bonus = vec![];
+                            if let Some(m) = rung.kitty_multiplier {
+                                bonus.push(format!("{m}x the bottom"));
+                            }
+                            if rung.bonus_points > 0 {
+                                bonus.push(format!("{} points", rung.bonus_points));
+                            }
+                            format!("{} cards: {}", rung.min_cards, bonus.join(" and "))
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            FinalTrickBonusEarned { player, points } =>
+                format!("{} won the last trick for a bonus of {} points", player_name(*player)?, points),
+            KittyRevisionPolicySet { policy: KittyRevisionPolicy::NoRevision } =>
+                format!("{} disallowed changing the bottom cards once play starts", n?),
+            KittyRevisionPolicySet { policy: KittyRevisionPolicy::AllowRevisionBeforeFirstLead } =>
+                format!("{} allowed the leader to change the bottom cards until the first card is led", n?),
+            MatchLengthSet { length: MatchLength { max_cycles: None, max_games: None, max_minutes: None } } =>
+                format!("{} removed the limits on the length of the match", n?),
+            MatchLengthSet { length } => {
+                let mut limits = vec![];
+                if let Some(cycles) = length.max_cycles {
+                    limits.push(format!("{cycles} times past the final rank"));
+                }
+                if let Some(games) = length.max_games {
+                    limits.push(format!("{games} games"));
+                }
+                if let Some(minutes) = length.max_minutes {
+                    limits.push(format!("{minutes} minutes"));
+                }
+                format!("{} limited the match to {}", n?, limits.join(" or "))
+            }
+            MatchComplete { standings, .. } => match standings.first() {
+                Some(winner) => format!("The match is over, and {} came first", player_name(winner.player)?),
+                None => "The match is over".to_string(),
+            },
+            FaceUpKittyBurialSet { face_up: true } =>
+                format!("{} made the cards buried in the bottom visible to everyone", n?),
+            FaceUpKittyBurialSet { face_up: false } =>
+                format!("{} hid the cards buried in the bottom", n?),
+            RevisingKitty => format!("{} took back the bottom cards to change them", n?),
             GameShadowingPolicySet { policy: GameShadowingPolicy::AllowMultipleSessions } =>
                 format!("{} allowed players to be shadowed by joining with the same name", n?),
             GameShadowingPolicySet { policy: GameShadowingPolicy::SingleSessionOnly } =>
@@ -361,15 +881,28 @@ impl MessageVariant {
             RevealedCardFromKitty => format!("{} revealed a card from the bottom of the deck", n?),
             PickedUpCards => format!("{} picked up the bottom cards", n?),
             PutDownCards => format!("{} put down the bottom cards", n?),
+            GameFinished { result } if result.values().any(|r| r.won_match) => {
+                let mut winners = result
+                    .iter()
+                    .filter(|(_, r)| r.won_match)
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>();
+                winners.sort_unstable();
+                format!("The game has finished, and {} won the match", winners.join(" and "))
+            }
             GameFinished { result: _ } => "The game has finished".to_string(),
             GameEndedEarly => format!("{} ended the game early", n?),
             BonusLevelEarned => "Landlord team earned a bonus level for defending with a smaller team".to_string(),
-            EndOfGameSummary { landlord_won : true, non_landlords_points } =>
+            ShutoutBonusEarned { levels } =>
+                format!("Landlord team earned {levels} bonus levels for keeping the opposing team to no points"),
+            EndOfGameSummary { landlord_won : true, non_landlords_points, .. } =>
                 format!("Landlord team won, opposing team only collected {non_landlords_points} points"),
-            EndOfGameSummary { landlord_won: false, non_landlords_points } =>
+            EndOfGameSummary { landlord_won: false, non_landlords_points, .. } =>
                 format!("Landlord team lost, opposing team collected {non_landlords_points} points"),
             HideThrowHaltingPlayer { set: true } => format!("{} hid the player who prevents throws", n?),
             HideThrowHaltingPlayer { set: false } => format!("{} un-hid the player who prevents throws", n?),
+            TractorRequirementsChanged { tractor_requirements: TractorRequirements { min_count, min_length, wild_card: Some(wild_card), .. } } =>
+                format!("{} required tractors to be at least {} cards wide by {} tuples long, with {} as a wild card", n?, min_count, min_length, wild_card),
             TractorRequirementsChanged { tractor_requirements } =>
                 format!("{} required tractors to be at least {} cards wide by {} tuples long", n?, tractor_requirements.min_count, tractor_requirements.min_length),
             GameVisibilitySet { visibility: GameVisibility::Public} => format!("{} listed the game publicly", n?),
@@ -0,0 +1,295 @@
+//! Working out the result of a game from the points the attacking team
+//! scored, without touching the game itself, so that the result can be shown
+//! (and explained) exactly as it will be applied.
+
+use anyhow::{anyhow, Error};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use shengji_mechanics::deck::Deck;
+use shengji_mechanics::player::Player;
+use shengji_mechanics::scoring::{compute_level_deltas, threshold_reached, GameScoreResult};
+use shengji_mechanics::types::{Number, PlayerID, Rank};
+
+use crate::settings::{AdvancementPolicy, PropagatedState, ScoringPolicy};
+
+/// What the score depends on in the game being scored, other than the points.
+#[derive(Debug, Clone, Copy)]
+pub struct ScoreState<'a> {
+    pub decks: &'a [Deck],
+    pub landlord: PlayerID,
+    pub landlords_team: &'a [PlayerID],
+    /// Whether fewer friends joined the landlord than were called.
+    pub smaller_landlord_team: bool,
+    pub blind_bid_levels: usize,
+    pub tricks_played: usize,
+}
+
+/// Extra levels on top of those from the points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ScoreBonus {
+    /// The landlord won with fewer friends than were called.
+    SmallerLandlordTeam,
+    /// The landlord's team kept the other team to no points at all.
+    Shutout { levels: usize },
+    /// The landlord won the bidding with a blind bid, which is at stake for
+    /// both teams.
+    BlindBid { levels: usize },
+}
+
+/// How one player's rank changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PlayerScoreResult {
+    pub player: PlayerID,
+    pub is_defending: bool,
+    pub previous_rank: Rank,
+    pub rank: Rank,
+    pub metalevel: usize,
+    pub ranks_up: usize,
+    /// Whether the player had levels left over, but had to stop at a rank
+    /// which needs to be defended.
+    pub blocked: bool,
+    pub demoted: bool,
+}
+
+/// The result of a game, and how it was arrived at.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ScoreResult {
+    pub non_landlords_points: isize,
+    pub landlord_won: bool,
+    /// The highest threshold above zero which the attacking team's points
+    /// reached, if any.
+    pub threshold: Option<isize>,
+    /// The levels for the winning team from the points alone.
+    pub base_levels: usize,
+    pub bonuses: Vec<ScoreBonus>,
+    /// The levels for the winning team, including bonuses.
+    pub levels: usize,
+    /// Whether the landlord's team lost by enough to be demoted.
+    pub landlord_demoted: bool,
+    pub players: Vec<PlayerScoreResult>,
+}
+
+impl ScoreResult {
+    pub fn bonus_levels(&self) -> usize {
+        self.levels - self.base_levels
+    }
+}
+
+/// Scores a game in which the attacking team ended up with
+/// `non_landlords_points`, without changing anything.
+pub fn compute_score(
+    non_landlords_points: isize,
+    settings: &PropagatedState,
+    state: &ScoreState<'_>,
+) -> Result<ScoreResult, Error> {
+    if let ScoringPolicy::PointsPerTrick { points } = settings.scoring_policy {
+        return Ok(ScoreResult {
+            non_landlords_points,
+            landlord_won: 2 * non_landlords_points <= (state.tricks_played * points) as isize,
+            threshold: None,
+            base_levels: 0,
+            bonuses: vec![],
+            levels: 0,
+            landlord_demoted: false,
+            players: settings
+                .players
+                .iter()
+                .map(|p| PlayerScoreResult {
+                    player: p.id,
+                    is_defending: state.landlords_team.contains(&p.id),
+                    previous_rank: p.rank(),
+                    rank: p.rank(),
+                    metalevel: p.metalevel,
+                    ranks_up: 0,
+                    blocked: false,
+                    demoted: false,
+                })
+                .collect(),
+        });
+    }
+
+    let gsp = &settings.game_scoring_parameters;
+    let GameScoreResult {
+        non_landlord_delta,
+        landlord_delta,
+        landlord_won,
+        landlord_bonus,
+    } = compute_level_deltas(
+        gsp,
+        &settings.advancement_schedule,
+        state.decks,
+        non_landlords_points,
+        state.smaller_landlord_team,
+    )?;
+
+    let mut bonuses = vec![];
+    let mut levels = if landlord_won {
+        landlord_delta
+    } else {
+        non_landlord_delta
+    };
+    let base_levels = if landlord_bonus {
+        bonuses.push(ScoreBonus::SmallerLandlordTeam);
+        levels - 1
+    } else {
+        levels
+    };
+    let shutout_levels = if landlord_won {
+        gsp.shutout_bonus(non_landlords_points)
+    } else {
+        0
+    };
+    if shutout_levels > 0 {
+        bonuses.push(ScoreBonus::Shutout {
+            levels: shutout_levels,
+        });
+        levels += shutout_levels;
+    }
+    if state.blind_bid_levels > 0 {
+        bonuses.push(ScoreBonus::BlindBid {
+            levels: state.blind_bid_levels,
+        });
+        levels += state.blind_bid_levels;
+    }
+
+    let landlord_demoted =
+        !landlord_won && gsp.demotes_landlord(state.decks, non_landlords_points)?;
+    let landlord_rank = settings
+        .players
+        .iter()
+        .find(|p| p.id == state.landlord)
+        .ok_or_else(|| anyhow!("landlord not found"))?
+        .rank();
+
+    let players = score_players(
+        &settings.players,
+        state.landlords_team,
+        |is_defending| {
+            if landlord_won == is_defending {
+                levels
+            } else {
+                0
+            }
+        },
+        landlord_demoted,
+        landlord_rank,
+        settings.advancement_policy,
+        *settings.max_rank,
+        &settings.defended_ranks,
+    );
+
+    Ok(ScoreResult {
+        non_landlords_points,
+        landlord_won,
+        threshold: threshold_reached(
+            gsp,
+            &settings.advancement_schedule,
+            state.decks,
+            non_landlords_points,
+        )?,
+        base_levels,
+        bonuses,
+        levels,
+        landlord_demoted,
+        players,
+    })
+}
+
+/// Works out how each player's rank changes when their team goes up by
+/// `levels(is_defending)`, without changing them. If `demote_landlords_team`,
+/// the landlord's team is demoted first.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn score_players(
+    players: &[Player],
+    landlords_team: &[PlayerID],
+    levels: impl Fn(bool) -> usize,
+    demote_landlords_team: bool,
+    landlord_rank: Rank,
+    advancement_policy: AdvancementPolicy,
+    max_rank: Rank,
+    defended_ranks: &[Number],
+) -> Vec<PlayerScoreResult> {
+    players
+        .iter()
+        .map(|p| {
+            let mut player = p.clone();
+            let is_defending = landlords_team.contains(&player.id);
+            let demoted = demote_landlords_team && is_defending && player.demote();
+            let (ranks_up, blocked) = advance_player(
+                &mut player,
+                levels(is_defending),
+                is_defending,
+                landlord_rank,
+                advancement_policy,
+                max_rank,
+                defended_ranks,
+            );
+            PlayerScoreResult {
+                player: player.id,
+                is_defending,
+                previous_rank: p.rank(),
+                rank: player.rank(),
+                metalevel: player.metalevel,
+                ranks_up,
+                blocked,
+                demoted,
+            }
+        })
+        .collect()
+}
+
+/// Advances the player by up to `bump` levels, stopping at any rank which has
+/// to be defended. Returns the number of levels advanced, and whether the
+/// player was stopped short.
+pub(crate) fn advance_player(
+    player: &mut Player,
+    bump: usize,
+    is_defending: bool,
+    landlord_rank: Rank,
+    advancement_policy: AdvancementPolicy,
+    max_rank: Rank,
+    defended_ranks: &[Number],
+) -> (usize, bool) {
+    let mut num_advances = 0;
+    for bump_idx in 0..bump {
+        let must_defend = match (advancement_policy, player.rank()) {
+            (AdvancementPolicy::Unrestricted, r)
+            | (AdvancementPolicy::DefendPoints, r)
+            | (AdvancementPolicy::MustWinExactly, r)
+                if r == max_rank
+                    || (r.successor() == Some(max_rank) && max_rank == Rank::NoTrump) =>
+            {
+                true
+            }
+            (AdvancementPolicy::DefendPoints, Rank::Number(n)) if n.points().is_some() => true,
+            (AdvancementPolicy::FullyUnrestricted, _)
+            | (AdvancementPolicy::Unrestricted, _)
+            | (AdvancementPolicy::DefendPoints, _)
+            | (AdvancementPolicy::MustWinExactly, _) => false,
+        } || matches!(player.rank(), Rank::Number(n) if defended_ranks.contains(&n));
+        // In order to advance past NoTrump, the landlord must also be defending
+        // NoTrump.
+        let landlord_must_defend = must_defend && player.rank() == Rank::NoTrump;
+
+        // Winning by more than one level doesn't count on the final
+        // rank if the win has to be exact.
+        let overshot = advancement_policy == AdvancementPolicy::MustWinExactly
+            && bump > 1
+            && (player.rank() == max_rank
+                || (player.rank().successor() == Some(max_rank) && max_rank == Rank::NoTrump));
+
+        if must_defend
+            && (!is_defending
+                || bump_idx > 0
+                || overshot
+                || (landlord_must_defend && landlord_rank != Rank::NoTrump))
+        {
+            return (num_advances, true);
+        }
+
+        player.advance(max_rank);
+        num_advances += 1;
+    }
+    (num_advances, false)
+}
@@ -8,14 +8,22 @@ use slog_derive::KV;
 use url::Url;
 
 use shengji_mechanics::bidding::{
-    BidPolicy, BidReinforcementPolicy, BidTakebackPolicy, JokerBidPolicy,
+    BidPolicy, BidPriorityPolicy, BidReinforcementPolicy, BidRules, BidTakebackPolicy,
+    JokerBidPolicy,
 };
 use shengji_mechanics::deck::Deck;
+use shengji_mechanics::ordered_card::DecompositionStrategy;
 use shengji_mechanics::player::Player;
-use shengji_mechanics::scoring::GameScoringParameters;
-use shengji_mechanics::trick::{ThrowEvaluationPolicy, TractorRequirements, TrickDrawPolicy};
-use shengji_mechanics::types::{Card, Number, PlayerID, Rank};
+use shengji_mechanics::scoring::{AdvancementSchedule, GameScoringParameters};
+use shengji_mechanics::sorting::SortOrder;
+use shengji_mechanics::trick::{
+    BeatPolicy, JokerPairPolicy, MultiSuitLeadPolicy, RuffPolicy, ThrowEvaluationPolicy,
+    ThrowEvaluationScope, TieBreakPolicy, TractorRequirements, TractorRuffPolicy, TrickDrawPolicy,
+    TrickRules, TrumpedThrowPolicy,
+};
+use shengji_mechanics::types::{Card, Number, PermanentTrump, PlayerID, Rank, Trump};
 
+use crate::game_state::score_ledger::ScoreLedger;
 use crate::message::MessageVariant;
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema, Hash, PartialEq, Eq)]
@@ -86,6 +94,8 @@ impl slog::KV for GameModeSettings {
 
 shengji_mechanics::impl_slog_value!(GameModeSettings);
 
+/// A flat penalty for each failed throw. This is on top of any points deducted
+/// under `ThrowPenaltyPolicy::DeductPoints`.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
 pub enum ThrowPenalty {
     #[default]
@@ -95,21 +105,132 @@ pub enum ThrowPenalty {
 
 shengji_mechanics::impl_slog_value!(ThrowPenalty);
 
+/// What happens to a player whose throw fails. In every case, the smallest
+/// unit which can be beaten is played in place of the throw.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum ThrowPenaltyPolicy {
+    /// The smallest offending unit must be played.
+    #[default]
+    ForceSmallestUnit,
+    /// The thrower may take back the forced play, as long as the next player
+    /// hasn't played yet.
+    AllowTakeBack,
+    /// The thrower's team loses 10 points for every card withdrawn from the
+    /// throw, as well as any `ThrowPenalty`.
+    DeductPoints,
+}
+
+shengji_mechanics::impl_slog_value!(ThrowPenaltyPolicy);
+
+/// Whether throws are checked as soon as they're led, or only when one of the
+/// thrower's opponents challenges them before the next player plays.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum ThrowChallengePolicy {
+    #[default]
+    CheckedWhenLed,
+    CheckedWhenChallenged,
+}
+
+shengji_mechanics::impl_slog_value!(ThrowChallengePolicy);
+
+/// The points lost by the loser of a throw challenge: the thrower's team if
+/// the throw could be beaten, and the challenger's team otherwise.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ThrowChallengePenalties {
+    pub thrower: usize,
+    pub challenger: usize,
+}
+
+impl Default for ThrowChallengePenalties {
+    fn default() -> Self {
+        ThrowChallengePenalties {
+            thrower: 10,
+            challenger: 10,
+        }
+    }
+}
+
+shengji_mechanics::impl_slog_value!(ThrowChallengePenalties);
+
+/// How much the points in the bottom are multiplied by when the other team
+/// wins the last trick.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
 pub enum KittyPenalty {
+    /// Twice the size of the largest unit in the last trick.
     #[default]
     Times,
+    /// Two to the power of the size of the largest unit in the last trick.
     Power,
+    /// Always twice.
+    Double,
+    /// Four times if the last trick was won with a tractor, and twice
+    /// otherwise.
+    QuadrupleForTractors,
+    /// A fixed multiplier for the last trick being won with single cards,
+    /// with pairs (or larger tuples), or with a tractor.
+    Custom {
+        single: usize,
+        pair: usize,
+        tractor: usize,
+    },
+}
+
+impl KittyPenalty {
+    pub fn multiplier(self, largest_unit_size: usize, tractor: bool) -> usize {
+        match self {
+            KittyPenalty::Times => 2 * largest_unit_size,
+            KittyPenalty::Power => 2usize.saturating_pow(largest_unit_size as u32),
+            KittyPenalty::Double => 2,
+            KittyPenalty::QuadrupleForTractors if tractor => 4,
+            KittyPenalty::QuadrupleForTractors => 2,
+            KittyPenalty::Custom { tractor: m, .. } if tractor => m,
+            KittyPenalty::Custom { pair, .. } if largest_unit_size > 1 => pair,
+            KittyPenalty::Custom { single, .. } => single,
+        }
+    }
 }
 
 shengji_mechanics::impl_slog_value!(KittyPenalty);
 
+/// A rung of the final trick bonus ladder, which applies when the last trick
+/// is won with a pair, tuple or tractor of at least `min_cards` cards. Only
+/// the highest rung reached applies.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FinalTrickBonusRung {
+    pub min_cards: usize,
+    /// Replaces the multiplier from the `KittyPenalty`, if set.
+    pub kitty_multiplier: Option<usize>,
+    /// Points for the team which won the last trick.
+    pub bonus_points: usize,
+}
+
+/// How a game is scored.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum ScoringPolicy {
+    /// By the point cards captured, with ranks advancing according to the
+    /// `GameScoringParameters`.
+    #[default]
+    PointCards,
+    /// Every trick is worth the same number of points, and nobody's rank
+    /// changes, for learning how tricks are played without the rest of the
+    /// scoring. The landlord's team wins if the other team takes no more than
+    /// half the tricks.
+    PointsPerTrick { points: usize },
+}
+
+shengji_mechanics::impl_slog_value!(ScoringPolicy);
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
 pub enum AdvancementPolicy {
+    /// The final rank has to be defended to win the match.
     #[default]
     Unrestricted,
+    /// The final rank is passed like any other.
     FullyUnrestricted,
     DefendPoints,
+    /// The final rank has to be defended, and the landlord's team only wins
+    /// the match if they win by exactly one level.
+    MustWinExactly,
 }
 
 shengji_mechanics::impl_slog_value!(AdvancementPolicy);
@@ -152,6 +273,93 @@ pub enum KittyBidPolicy {
 
 shengji_mechanics::impl_slog_value!(KittyBidPolicy);
 
+/// What happens when all the cards have been drawn and nobody has bid.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum NoBidPolicy {
+    /// Reveal cards from the bottom until one picks trump, per the
+    /// `KittyBidPolicy`.
+    #[default]
+    RevealKitty,
+    /// Shuffle and deal the cards again.
+    Redeal,
+    /// Pick a trump suit at random.
+    RandomTrump,
+    /// The landlord from the last game picks the trump suit.
+    LandlordChooses,
+}
+
+shengji_mechanics::impl_slog_value!(NoBidPolicy);
+
+/// Whether players can bid "blind", i.e. before they've drawn many cards, to
+/// raise the stakes of the game.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum BlindBidPolicy {
+    #[default]
+    NoBlindBids,
+    /// A bid made before drawing more than `max_cards_drawn` cards is blind.
+    /// If it wins the bidding for the landlord, the landlord's team goes up
+    /// `levels` more levels on a win, and the other team does on a loss.
+    AllowBlindBids {
+        max_cards_drawn: usize,
+        levels: usize,
+    },
+}
+
+shengji_mechanics::impl_slog_value!(BlindBidPolicy);
+
+/// How trump is picked while the cards are drawn.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum TrumpSelectionPolicy {
+    /// Players bid for trump.
+    #[default]
+    Bidding,
+    /// There's no bidding: the first card of the landlord's rank to be drawn
+    /// sets the trump suit, and whoever drew it is the landlord if there
+    /// isn't one yet. Before the first game, each player's own rank counts.
+    FirstRankCardDrawn,
+    /// Once all the cards are drawn, each player submits one sealed bid (or
+    /// passes), and the strongest bid wins. Equally strong bids are settled
+    /// by the `BidPriorityPolicy`, where the first click means the first
+    /// bid submitted.
+    SealedBids,
+}
+
+shengji_mechanics::impl_slog_value!(TrumpSelectionPolicy);
+
+/// Which cards are trump once the bidding is over.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum TrumpSuitPolicy {
+    /// The suit of the winning bid is trump, along with the jokers and the
+    /// landlord's rank; a joker bid means there's no trump suit.
+    #[default]
+    DeclaredSuit,
+    /// There's never a trump suit: only the jokers and the landlord's rank are
+    /// trump, whichever card won the bid.
+    JokersAndRank,
+    /// Only the jokers are trump; the landlord's rank is played as a regular
+    /// card in its suit.
+    JokersOnly,
+}
+
+shengji_mechanics::impl_slog_value!(TrumpSuitPolicy);
+
+impl TrumpSuitPolicy {
+    /// Adjusts the trump determined by the bids according to this policy.
+    pub fn apply(self, trump: Trump) -> Trump {
+        match self {
+            TrumpSuitPolicy::DeclaredSuit => trump,
+            TrumpSuitPolicy::JokersAndRank => Trump::NoTrump {
+                number: trump.number(),
+                permanent: trump.permanent(),
+            },
+            TrumpSuitPolicy::JokersOnly => Trump::NoTrump {
+                number: None,
+                permanent: trump.permanent(),
+            },
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
 pub enum PlayTakebackPolicy {
     #[default]
@@ -161,6 +369,24 @@ pub enum PlayTakebackPolicy {
 
 shengji_mechanics::impl_slog_value!(PlayTakebackPolicy);
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum HandRevealPolicy {
+    #[default]
+    AllowHandReveal,
+    NoHandReveal,
+}
+
+shengji_mechanics::impl_slog_value!(HandRevealPolicy);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum LastTrickPolicy {
+    #[default]
+    AllowLastTrick,
+    NoLastTrick,
+}
+
+shengji_mechanics::impl_slog_value!(LastTrickPolicy);
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
 pub enum KittyTheftPolicy {
     AllowKittyTheft,
@@ -170,6 +396,110 @@ pub enum KittyTheftPolicy {
 
 shengji_mechanics::impl_slog_value!(KittyTheftPolicy);
 
+/// Whether a player outside the landlord's team can take over the bottom
+/// cards ("dig the kitty") once the landlord is done exchanging.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum KittyDiggingPolicy {
+    #[default]
+    NoKittyDigging,
+    /// A player holding at least `jokers` jokers can dig the kitty once per
+    /// game. They declare trump again and become the landlord.
+    DigWithJokers { jokers: usize },
+}
+
+shengji_mechanics::impl_slog_value!(KittyDiggingPolicy);
+
+/// Whether the landlord can hand the kitty exchange over to a teammate.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum ExchangeDelegationPolicy {
+    #[default]
+    LandlordOnly,
+    /// The landlord can let a teammate exchange the bottom cards, and take
+    /// them back again to split the exchange between them.
+    AllowTeammate,
+}
+
+shengji_mechanics::impl_slog_value!(ExchangeDelegationPolicy);
+
+/// Whether the landlord can go back and change the bottom cards after the
+/// exchange is over.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum KittyRevisionPolicy {
+    #[default]
+    NoRevision,
+    /// The landlord can reopen the exchange until the first card is led.
+    AllowRevisionBeforeFirstLead,
+}
+
+shengji_mechanics::impl_slog_value!(KittyRevisionPolicy);
+
+/// How partners are chosen in Tractor, where everyone is on the same team as
+/// the players sitting every other seat from them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum TeamFormation {
+    /// Everyone stays in their seat, and so keeps the same partners.
+    #[default]
+    Fixed,
+    /// Everyone other than the first player moves over a seat after each
+    /// game, so that partners change every game.
+    RotatePartners,
+    /// Partners only change, as for `RotatePartners`, when the landlord's
+    /// team loses.
+    WinnerStays,
+}
+
+shengji_mechanics::impl_slog_value!(TeamFormation);
+
+/// When a match ends, rather than carrying on indefinitely. The match ends as
+/// soon as any of the limits is reached.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(default)]
+pub struct MatchLength {
+    /// The number of times any player has to go all the way past the final
+    /// rank.
+    pub max_cycles: Option<usize>,
+    pub max_games: Option<usize>,
+    pub max_minutes: Option<u64>,
+}
+
+shengji_mechanics::impl_slog_value!(MatchLength);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum MatchEndReason {
+    Cycles,
+    Games,
+    TimeLimit,
+}
+
+/// A player's final position when a match ends, best first.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MatchStanding {
+    pub player: PlayerID,
+    pub rank: Rank,
+    /// The number of times the player went past the final rank.
+    pub cycles: usize,
+}
+
+/// Which cards may be buried in the bottom during the exchange. Restricted
+/// cards can still be buried once the exchanger has nothing else left.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct KittyBurialRules {
+    pub allow_point_cards: bool,
+    pub allow_trump: bool,
+}
+
+impl Default for KittyBurialRules {
+    fn default() -> Self {
+        KittyBurialRules {
+            allow_point_cards: true,
+            allow_trump: true,
+        }
+    }
+}
+
+shengji_mechanics::impl_slog_value!(KittyBurialRules);
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
 pub enum GameShadowingPolicy {
     #[default]
@@ -221,11 +551,26 @@ pub struct PropagatedState {
     pub(crate) observers: Vec<Player>,
     #[slog(skip)]
     pub(crate) landlord: Option<PlayerID>,
+    /// The player who created the room, or whoever it was handed over to
+    /// when they left.
+    #[slog(skip)]
+    #[serde(default)]
+    room_owner: Option<PlayerID>,
     #[slog(skip)]
     max_player_id: usize,
     #[slog(skip)]
     #[serde(default)]
     pub(crate) num_games_finished: usize,
+    /// When the current match started, in milliseconds since the Unix epoch.
+    #[slog(skip)]
+    #[serde(default)]
+    pub(crate) match_started_at: Option<u64>,
+    #[slog(skip)]
+    #[serde(default)]
+    pub(crate) match_games_finished: usize,
+    #[slog(skip)]
+    #[serde(default)]
+    pub(crate) score_ledger: ScoreLedger,
 
     pub(crate) game_mode: GameModeSettings,
     #[serde(default)]
@@ -240,38 +585,111 @@ pub struct PropagatedState {
     #[slog(skip)]
     #[serde(default)]
     pub(crate) special_decks: Vec<Deck>,
+    /// The lowest number in every deck, for playing with a short deck (e.g.
+    /// with the 2s through 4s removed). Special decks keep any higher minimum
+    /// of their own.
+    #[serde(default)]
+    pub(crate) min_deck_number: Option<Number>,
     #[serde(default)]
     pub(crate) landlord_emoji: Option<String>,
     pub(crate) chat_link: Option<String>,
     #[serde(default)]
     pub(crate) advancement_policy: AdvancementPolicy,
     #[serde(default)]
+    pub(crate) advancement_schedule: AdvancementSchedule,
+    /// Ranks which have to be defended to advance past them, on top of any
+    /// required by the `AdvancementPolicy`.
+    #[slog(skip)]
+    #[serde(default)]
+    pub(crate) defended_ranks: Vec<Number>,
+    #[serde(default)]
     pub(crate) kitty_penalty: KittyPenalty,
+    #[slog(skip)]
+    #[serde(default)]
+    pub(crate) final_trick_bonus_ladder: Vec<FinalTrickBonusRung>,
     #[serde(default)]
     pub(crate) throw_penalty: ThrowPenalty,
     #[serde(default)]
+    pub(crate) throw_penalty_policy: ThrowPenaltyPolicy,
+    #[serde(default)]
+    pub(crate) throw_challenge_policy: ThrowChallengePolicy,
+    #[serde(default)]
+    pub(crate) throw_challenge_penalties: ThrowChallengePenalties,
+    #[serde(default)]
     pub(crate) hide_played_cards: bool,
     #[serde(default)]
     pub(crate) kitty_bid_policy: KittyBidPolicy,
     #[serde(default)]
+    pub(crate) no_bid_policy: NoBidPolicy,
+    #[serde(default)]
+    pub(crate) blind_bid_policy: BlindBidPolicy,
+    #[serde(default)]
+    pub(crate) trump_selection_policy: TrumpSelectionPolicy,
+    #[serde(default)]
+    pub(crate) trump_suit_policy: TrumpSuitPolicy,
+    #[serde(default)]
+    pub(crate) permanent_trump: PermanentTrump,
+    #[serde(default)]
     pub(crate) kitty_theft_policy: KittyTheftPolicy,
     #[serde(default)]
+    pub(crate) kitty_burial_rules: KittyBurialRules,
+    #[serde(default)]
+    pub(crate) kitty_digging_policy: KittyDiggingPolicy,
+    #[serde(default)]
+    pub(crate) exchange_delegation_policy: ExchangeDelegationPolicy,
+    #[serde(default)]
+    pub(crate) team_formation: TeamFormation,
+    #[serde(default)]
+    pub(crate) kitty_revision_policy: KittyRevisionPolicy,
+    /// Whether cards buried in the bottom are shown to everyone as soon as
+    /// they're buried.
+    #[serde(default)]
+    pub(crate) face_up_kitty_burial: bool,
+    #[serde(default)]
+    pub(crate) match_length: MatchLength,
+    #[serde(default)]
     pub(crate) trick_draw_policy: TrickDrawPolicy,
     #[serde(default)]
+    pub(crate) ruff_policy: RuffPolicy,
+    #[serde(default)]
+    pub(crate) beat_policy: BeatPolicy,
+    #[serde(default)]
+    pub(crate) tie_break_policy: TieBreakPolicy,
+    #[serde(default)]
+    pub(crate) multi_suit_lead_policy: MultiSuitLeadPolicy,
+    #[serde(default)]
+    pub(crate) trumped_throw_policy: TrumpedThrowPolicy,
+    #[serde(default)]
+    pub(crate) tractor_ruff_policy: TractorRuffPolicy,
+    #[serde(default)]
+    pub(crate) joker_pair_policy: JokerPairPolicy,
+    #[serde(default)]
+    pub(crate) decomposition_strategy: DecompositionStrategy,
+    #[serde(default)]
     pub(crate) throw_evaluation_policy: ThrowEvaluationPolicy,
     #[serde(default)]
+    pub(crate) throw_evaluation_scope: ThrowEvaluationScope,
+    #[serde(default)]
     pub(crate) first_landlord_selection_policy: FirstLandlordSelectionPolicy,
     #[serde(default)]
     pub(crate) bid_policy: BidPolicy,
     #[serde(default)]
     pub(crate) bid_reinforcement_policy: BidReinforcementPolicy,
     #[serde(default)]
+    pub(crate) bid_rules: BidRules,
+    #[serde(default)]
+    pub(crate) bid_priority_policy: BidPriorityPolicy,
+    #[serde(default)]
     pub(crate) joker_bid_policy: JokerBidPolicy,
     #[serde(default)]
     pub(crate) should_reveal_kitty_at_end_of_game: bool,
     #[serde(default)]
     pub(crate) play_takeback_policy: PlayTakebackPolicy,
     #[serde(default)]
+    pub(crate) hand_reveal_policy: HandRevealPolicy,
+    #[serde(default)]
+    pub(crate) last_trick_policy: LastTrickPolicy,
+    #[serde(default)]
     pub(crate) bid_takeback_policy: BidTakebackPolicy,
     #[serde(default)]
     pub(crate) game_shadowing_policy: GameShadowingPolicy,
@@ -280,6 +698,8 @@ pub struct PropagatedState {
     #[serde(default)]
     pub(crate) game_scoring_parameters: GameScoringParameters,
     #[serde(default)]
+    pub(crate) scoring_policy: ScoringPolicy,
+    #[serde(default)]
     pub(crate) hide_throw_halting_player: bool,
     #[serde(default)]
     pub(crate) tractor_requirements: TractorRequirements,
@@ -294,6 +714,10 @@ impl PropagatedState {
         &self.players
     }
 
+    pub fn score_ledger(&self) -> &ScoreLedger {
+        &self.score_ledger
+    }
+
     pub fn observers(&self) -> &[Player] {
         &self.observers
     }
@@ -302,10 +726,49 @@ impl PropagatedState {
         self.landlord
     }
 
+    /// The player who has been in the room the longest, who can override
+    /// some of the rules during a game.
+    pub fn room_owner(&self) -> Option<PlayerID> {
+        self.room_owner
+    }
+
+    /// Hands the room over to the next player in seating order, or to an
+    /// observer if there are no players left, when the owner leaves.
+    fn hand_over_room(&mut self, leaving: PlayerID) {
+        if self.room_owner == Some(leaving) {
+            self.room_owner = self
+                .players
+                .iter()
+                .chain(self.observers.iter())
+                .map(|p| p.id)
+                .find(|id| *id != leaving);
+        }
+    }
+
     pub fn trick_draw_policy(&self) -> TrickDrawPolicy {
         self.trick_draw_policy
     }
 
+    pub fn trick_rules(&self) -> TrickRules {
+        TrickRules {
+            trick_draw_policy: self.trick_draw_policy,
+            throw_eval_policy: self.throw_evaluation_policy,
+            throw_eval_scope: self.throw_evaluation_scope,
+            ruff_policy: self.ruff_policy,
+            beat_policy: self.beat_policy,
+            tie_break_policy: self.tie_break_policy,
+            tractor_ruff_policy: self.tractor_ruff_policy,
+            joker_pair_policy: self.joker_pair_policy,
+            decomposition_strategy: self.decomposition_strategy,
+            multi_suit_lead_policy: self.multi_suit_lead_policy,
+            trumped_throw_policy: self.trumped_throw_policy,
+            defer_throw_check: self.throw_challenge_policy
+                == ThrowChallengePolicy::CheckedWhenChallenged,
+            hide_throw_halting_player: self.hide_throw_halting_player,
+            tractor_requirements: self.tractor_requirements,
+        }
+    }
+
     pub fn num_decks(&self) -> usize {
         self.num_decks.unwrap_or(self.players.len() / 2)
     }
@@ -324,6 +787,11 @@ impl PropagatedState {
         while decks.len() < num_decks {
             decks.push(Deck::default());
         }
+        if let Some(min) = self.min_deck_number {
+            for deck in &mut decks {
+                deck.min = deck.min.max(min);
+            }
+        }
         Ok(decks)
     }
 
@@ -366,6 +834,9 @@ impl PropagatedState {
 
         self.max_player_id += 1;
         self.players.push(Player::new(id, name));
+        if self.room_owner.is_none() {
+            self.room_owner = Some(id);
+        }
 
         msgs.extend(self.num_players_changed()?);
         Ok((id, msgs))
@@ -407,6 +878,7 @@ impl PropagatedState {
                 self.landlord = None;
             }
             self.players.retain(|p| p.id != id);
+            self.hand_over_room(id);
             msgs.extend(self.num_players_changed()?);
             Ok(msgs)
         } else {
@@ -416,6 +888,7 @@ impl PropagatedState {
 
     pub fn remove_observer(&mut self, id: PlayerID) -> Result<(), Error> {
         self.observers.retain(|p| p.id != id);
+        self.hand_over_room(id);
         Ok(())
     }
 
@@ -452,6 +925,29 @@ impl PropagatedState {
         Ok(messages)
     }
 
+    pub fn set_min_deck_number(
+        &mut self,
+        min_deck_number: Option<Number>,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        let mut msgs = vec![];
+        if self.min_deck_number != min_deck_number {
+            self.min_deck_number = min_deck_number;
+            msgs.push(MessageVariant::MinDeckNumberSet {
+                number: min_deck_number,
+            });
+
+            msgs.extend(self.set_kitty_size(None)?);
+            if self
+                .game_scoring_parameters
+                .materialize(&self.decks()?)
+                .is_err()
+            {
+                msgs.extend(self.set_game_scoring_parameters(GameScoringParameters::default())?);
+            };
+        }
+        Ok(msgs)
+    }
+
     pub fn set_num_decks(
         &mut self,
         num_decks: Option<usize>,
@@ -499,8 +995,11 @@ impl PropagatedState {
             }
             let decks = self.decks()?;
             let deck_len = decks.iter().map(|d| d.len()).sum::<usize>();
-            if size >= deck_len {
-                bail!("kitty size too large")
+            if size == 0 {
+                bail!("the kitty needs at least one card")
+            }
+            if size >= deck_len || (deck_len - size) / self.players.len() == 0 {
+                bail!("kitty size too large to deal everyone a card")
             }
             let min = decks.iter().map(|d| d.min).min().unwrap_or(Number::Two);
             let n_decks_with_min = decks.iter().filter(|d| d.includes_number(min)).count();
@@ -560,10 +1059,53 @@ impl PropagatedState {
         Ok(vec![MessageVariant::BidReinforcementPolicySet { policy }])
     }
 
+    pub fn set_bid_rules(&mut self, bid_rules: BidRules) -> Result<Vec<MessageVariant>, Error> {
+        if bid_rules != self.bid_rules {
+            self.bid_rules = bid_rules;
+            Ok(vec![MessageVariant::BidRulesSet { bid_rules }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_bid_priority_policy(
+        &mut self,
+        policy: BidPriorityPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.bid_priority_policy {
+            self.bid_priority_policy = policy;
+            Ok(vec![MessageVariant::BidPriorityPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// The players known to be on `id`'s team while bidding: the players in
+    /// alternating seats in Tractor games, and nobody when finding friends.
+    pub fn bid_partners(&self, id: PlayerID) -> Vec<PlayerID> {
+        let position = match self.players.iter().position(|p| p.id == id) {
+            Some(position) => position,
+            None => return vec![],
+        };
+        match self.game_mode {
+            GameModeSettings::Tractor => self
+                .players
+                .iter()
+                .enumerate()
+                .filter(|(idx, p)| idx % 2 == position % 2 && p.id != id)
+                .map(|(_, p)| p.id)
+                .collect(),
+            GameModeSettings::FindingFriends { .. } => vec![],
+        }
+    }
+
     pub fn set_joker_bid_policy(
         &mut self,
         policy: JokerBidPolicy,
     ) -> Result<Vec<MessageVariant>, Error> {
+        if policy == (JokerBidPolicy::BothAtLeast { count: 0 }) {
+            bail!("No-trump bids need at least one joker")
+        }
         self.joker_bid_policy = policy;
         Ok(vec![MessageVariant::JokerBidPolicySet { policy }])
     }
@@ -628,6 +1170,44 @@ impl PropagatedState {
         }
     }
 
+    pub fn set_throw_penalty_policy(
+        &mut self,
+        policy: ThrowPenaltyPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.throw_penalty_policy {
+            self.throw_penalty_policy = policy;
+            Ok(vec![MessageVariant::ThrowPenaltyPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_throw_challenge_policy(
+        &mut self,
+        policy: ThrowChallengePolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.throw_challenge_policy {
+            self.throw_challenge_policy = policy;
+            Ok(vec![MessageVariant::ThrowChallengePolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_throw_challenge_penalties(
+        &mut self,
+        penalties: ThrowChallengePenalties,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if penalties != self.throw_challenge_penalties {
+            self.throw_challenge_penalties = penalties;
+            Ok(vec![MessageVariant::ThrowChallengePenaltiesSet {
+                penalties,
+            }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
     pub fn set_kitty_penalty(
         &mut self,
         penalty: KittyPenalty,
@@ -654,6 +1234,69 @@ impl PropagatedState {
         }
     }
 
+    pub fn set_no_bid_policy(&mut self, policy: NoBidPolicy) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.no_bid_policy {
+            self.no_bid_policy = policy;
+            Ok(vec![MessageVariant::NoBidPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_blind_bid_policy(
+        &mut self,
+        policy: BlindBidPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if let BlindBidPolicy::AllowBlindBids { levels: 0, .. } = policy {
+            bail!("Blind bids must be worth at least one level")
+        }
+        if policy != self.blind_bid_policy {
+            self.blind_bid_policy = policy;
+            Ok(vec![MessageVariant::BlindBidPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_trump_selection_policy(
+        &mut self,
+        policy: TrumpSelectionPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.trump_selection_policy {
+            self.trump_selection_policy = policy;
+            Ok(vec![MessageVariant::TrumpSelectionPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_trump_suit_policy(
+        &mut self,
+        policy: TrumpSuitPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.trump_suit_policy {
+            self.trump_suit_policy = policy;
+            Ok(vec![MessageVariant::TrumpSuitPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_permanent_trump(
+        &mut self,
+        permanent: PermanentTrump,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if let PermanentTrump::Card(Card::Unknown | Card::SmallJoker | Card::BigJoker) = permanent {
+            bail!("Only a suited card can be made permanently trump")
+        }
+        if permanent != self.permanent_trump {
+            self.permanent_trump = permanent;
+            Ok(vec![MessageVariant::PermanentTrumpSet { permanent }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
     pub fn set_trick_draw_policy(
         &mut self,
         policy: TrickDrawPolicy,
@@ -666,6 +1309,96 @@ impl PropagatedState {
         }
     }
 
+    pub fn set_ruff_policy(&mut self, policy: RuffPolicy) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.ruff_policy {
+            self.ruff_policy = policy;
+            Ok(vec![MessageVariant::RuffPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_beat_policy(&mut self, policy: BeatPolicy) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.beat_policy {
+            self.beat_policy = policy;
+            Ok(vec![MessageVariant::BeatPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_tie_break_policy(
+        &mut self,
+        policy: TieBreakPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.tie_break_policy {
+            self.tie_break_policy = policy;
+            Ok(vec![MessageVariant::TieBreakPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_multi_suit_lead_policy(
+        &mut self,
+        policy: MultiSuitLeadPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.multi_suit_lead_policy {
+            self.multi_suit_lead_policy = policy;
+            Ok(vec![MessageVariant::MultiSuitLeadPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_trumped_throw_policy(
+        &mut self,
+        policy: TrumpedThrowPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.trumped_throw_policy {
+            self.trumped_throw_policy = policy;
+            Ok(vec![MessageVariant::TrumpedThrowPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_tractor_ruff_policy(
+        &mut self,
+        policy: TractorRuffPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.tractor_ruff_policy {
+            self.tractor_ruff_policy = policy;
+            Ok(vec![MessageVariant::TractorRuffPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_joker_pair_policy(
+        &mut self,
+        policy: JokerPairPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.joker_pair_policy {
+            self.joker_pair_policy = policy;
+            Ok(vec![MessageVariant::JokerPairPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_decomposition_strategy(
+        &mut self,
+        strategy: DecompositionStrategy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if strategy != self.decomposition_strategy {
+            self.decomposition_strategy = strategy;
+            Ok(vec![MessageVariant::DecompositionStrategySet { strategy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
     pub fn set_throw_evaluation_policy(
         &mut self,
         policy: ThrowEvaluationPolicy,
@@ -678,6 +1411,18 @@ impl PropagatedState {
         }
     }
 
+    pub fn set_throw_evaluation_scope(
+        &mut self,
+        scope: ThrowEvaluationScope,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if scope != self.throw_evaluation_scope {
+            self.throw_evaluation_scope = scope;
+            Ok(vec![MessageVariant::ThrowEvaluationScopeSet { scope }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
     pub fn set_play_takeback_policy(
         &mut self,
         policy: PlayTakebackPolicy,
@@ -690,6 +1435,30 @@ impl PropagatedState {
         }
     }
 
+    pub fn set_hand_reveal_policy(
+        &mut self,
+        policy: HandRevealPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.hand_reveal_policy {
+            self.hand_reveal_policy = policy;
+            Ok(vec![MessageVariant::HandRevealPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_last_trick_policy(
+        &mut self,
+        policy: LastTrickPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.last_trick_policy {
+            self.last_trick_policy = policy;
+            Ok(vec![MessageVariant::LastTrickPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
     pub fn set_bid_takeback_policy(
         &mut self,
         policy: BidTakebackPolicy,
@@ -714,6 +1483,22 @@ impl PropagatedState {
         }
     }
 
+    pub fn set_advancement_schedule(
+        &mut self,
+        schedule: AdvancementSchedule,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if schedule != self.advancement_schedule {
+            self.game_scoring_parameters
+                .materialize(&self.decks()?)?
+                .with_schedule(&schedule)?
+                .explain()?;
+            self.advancement_schedule = schedule.clone();
+            Ok(vec![MessageVariant::AdvancementScheduleSet { schedule }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
     pub fn set_game_scoring_parameters(
         &mut self,
         parameters: GameScoringParameters,
@@ -726,8 +1511,8 @@ impl PropagatedState {
             let old_parameters =
                 std::mem::replace(&mut self.game_scoring_parameters, parameters.clone());
             let msgs = vec![MessageVariant::GameScoringParametersChanged {
-                parameters,
-                old_parameters,
+                parameters: Box::new(parameters),
+                old_parameters: Box::new(old_parameters),
             }];
             Ok(msgs)
         } else {
@@ -735,6 +1520,64 @@ impl PropagatedState {
         }
     }
 
+    pub fn set_scoring_policy(
+        &mut self,
+        policy: ScoringPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy == (ScoringPolicy::PointsPerTrick { points: 0 }) {
+            bail!("Each trick has to be worth some points")
+        }
+        if policy != self.scoring_policy {
+            self.scoring_policy = policy;
+            Ok(vec![MessageVariant::ScoringPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_defended_ranks(
+        &mut self,
+        mut ranks: Vec<Number>,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        ranks.sort();
+        ranks.dedup();
+        if ranks != self.defended_ranks {
+            self.defended_ranks = ranks.clone();
+            Ok(vec![MessageVariant::DefendedRanksSet { ranks }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_final_trick_bonus_ladder(
+        &mut self,
+        mut ladder: Vec<FinalTrickBonusRung>,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        ladder.sort_by_key(|rung| rung.min_cards);
+        if ladder.iter().any(|rung| rung.min_cards < 2) {
+            bail!("The final trick bonus needs at least a pair")
+        }
+        if ladder.windows(2).any(|w| w[0].min_cards == w[1].min_cards) {
+            bail!("Each rung of the final trick bonus needs a different size")
+        }
+        if ladder != self.final_trick_bonus_ladder {
+            self.final_trick_bonus_ladder = ladder.clone();
+            Ok(vec![MessageVariant::FinalTrickBonusLadderSet { ladder }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// The highest rung of the final trick bonus ladder reached by winning
+    /// the last trick with a unit of `unit_size` cards.
+    pub fn final_trick_bonus(&self, unit_size: usize) -> Option<FinalTrickBonusRung> {
+        self.final_trick_bonus_ladder
+            .iter()
+            .rev()
+            .find(|rung| rung.min_cards <= unit_size)
+            .copied()
+    }
+
     pub fn set_kitty_theft_policy(
         &mut self,
         policy: KittyTheftPolicy,
@@ -747,6 +1590,113 @@ impl PropagatedState {
         }
     }
 
+    pub fn set_kitty_burial_rules(
+        &mut self,
+        rules: KittyBurialRules,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if rules != self.kitty_burial_rules {
+            self.kitty_burial_rules = rules;
+            Ok(vec![MessageVariant::KittyBurialRulesSet { rules }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_kitty_digging_policy(
+        &mut self,
+        policy: KittyDiggingPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy == (KittyDiggingPolicy::DigWithJokers { jokers: 0 }) {
+            bail!("Digging the kitty needs at least one joker")
+        }
+        if policy != self.kitty_digging_policy {
+            self.kitty_digging_policy = policy;
+            Ok(vec![MessageVariant::KittyDiggingPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_exchange_delegation_policy(
+        &mut self,
+        policy: ExchangeDelegationPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.exchange_delegation_policy {
+            self.exchange_delegation_policy = policy;
+            Ok(vec![MessageVariant::ExchangeDelegationPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_team_formation(
+        &mut self,
+        formation: TeamFormation,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if formation != self.team_formation {
+            self.team_formation = formation;
+            Ok(vec![MessageVariant::TeamFormationSet { formation }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// Moves players to their seats for the next game, which decides who
+    /// their partners are. Only the seating changes, so the landlord's team is
+    /// still worked out from the seats when the next game starts.
+    pub(crate) fn form_next_teams(&mut self, landlord_won: bool) -> Option<MessageVariant> {
+        let rotate = match self.team_formation {
+            TeamFormation::Fixed => false,
+            TeamFormation::RotatePartners => true,
+            TeamFormation::WinnerStays => !landlord_won,
+        };
+        // In FindingFriends, the teams come from the friends instead.
+        if !rotate || self.game_mode != GameModeSettings::Tractor || self.players.len() < 4 {
+            return None;
+        }
+        self.players[1..].rotate_right(1);
+        Some(MessageVariant::PartnersRotated)
+    }
+
+    pub fn set_kitty_revision_policy(
+        &mut self,
+        policy: KittyRevisionPolicy,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if policy != self.kitty_revision_policy {
+            self.kitty_revision_policy = policy;
+            Ok(vec![MessageVariant::KittyRevisionPolicySet { policy }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_face_up_kitty_burial(
+        &mut self,
+        face_up: bool,
+    ) -> Result<Vec<MessageVariant>, Error> {
+        if face_up != self.face_up_kitty_burial {
+            self.face_up_kitty_burial = face_up;
+            Ok(vec![MessageVariant::FaceUpKittyBurialSet { face_up }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub fn set_match_length(&mut self, length: MatchLength) -> Result<Vec<MessageVariant>, Error> {
+        if length.max_cycles == Some(0)
+            || length.max_games == Some(0)
+            || length.max_minutes == Some(0)
+        {
+            bail!("Match limits must be at least one")
+        }
+        if length != self.match_length {
+            self.match_length = length;
+            Ok(vec![MessageVariant::MatchLengthSet { length }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
     pub fn set_game_visibility(
         &mut self,
         game_visibility: GameVisibility,
@@ -835,6 +1785,55 @@ impl PropagatedState {
         Ok(msgs)
     }
 
+    /// Counts a finished game towards the match, and ends the match if it
+    /// has reached one of its limits. The players start again from the
+    /// lowest rank for the next match.
+    pub(crate) fn finish_match_game(&mut self, now: u64) -> Option<MessageVariant> {
+        self.match_games_finished += 1;
+        let cycles = |p: &Player| p.metalevel.saturating_sub(1);
+        let reason = if self
+            .match_length
+            .max_cycles
+            .is_some_and(|max| self.players.iter().any(|p| cycles(p) >= max))
+        {
+            MatchEndReason::Cycles
+        } else if self
+            .match_length
+            .max_games
+            .is_some_and(|max| self.match_games_finished >= max)
+        {
+            MatchEndReason::Games
+        } else if self
+            .match_length
+            .max_minutes
+            .zip(self.match_started_at)
+            .is_some_and(|(max, started_at)| now.saturating_sub(started_at) >= max * 60 * 1000)
+        {
+            MatchEndReason::TimeLimit
+        } else {
+            return None;
+        };
+
+        let mut standings = self
+            .players
+            .iter()
+            .map(|p| MatchStanding {
+                player: p.id,
+                rank: p.rank(),
+                cycles: cycles(p),
+            })
+            .collect::<Vec<_>>();
+        standings.sort_by_key(|s| std::cmp::Reverse((s.cycles, s.rank)));
+
+        for player in &mut self.players {
+            player.set_rank(Rank::Number(Number::Two));
+            player.set_meta_rank(1);
+        }
+        self.match_started_at = None;
+        self.match_games_finished = 0;
+        Some(MessageVariant::MatchComplete { reason, standings })
+    }
+
     pub fn set_rank(&mut self, player_id: PlayerID, level: Rank) -> Result<(), Error> {
         match self.players.iter_mut().find(|p| p.id == player_id) {
             Some(ref mut player) => {
@@ -845,6 +1844,24 @@ impl PropagatedState {
         Ok(())
     }
 
+    /// Sets a player's (or observer's) preferred order for their hand.
+    pub fn set_sort_order(
+        &mut self,
+        player_id: PlayerID,
+        sort_order: SortOrder,
+    ) -> Result<(), Error> {
+        match self
+            .players
+            .iter_mut()
+            .chain(self.observers.iter_mut())
+            .find(|p| p.id == player_id)
+        {
+            Some(player) => player.sort_order = sort_order,
+            None => bail!("player not found"),
+        }
+        Ok(())
+    }
+
     pub fn set_meta_rank(&mut self, player_id: PlayerID, metalevel: usize) -> Result<(), Error> {
         match self.players.iter_mut().find(|p| p.id == player_id) {
             Some(ref mut player) => {
@@ -864,6 +1881,9 @@ impl PropagatedState {
         &mut self,
         tractor_requirements: TractorRequirements,
     ) -> Result<Vec<MessageVariant>, Error> {
+        if tractor_requirements.wild_card == Some(Card::Unknown) {
+            bail!("The wild card must be a known card")
+        }
         if self.tractor_requirements != tractor_requirements {
             self.tractor_requirements = tractor_requirements;
             Ok(vec![MessageVariant::TractorRequirementsChanged {
@@ -2,14 +2,17 @@ use std::env;
 
 use schemars::{schema_for, JsonSchema};
 use shengji_core::interactive::Action;
+use shengji_mechanics::hand_summary::HandSummary;
 use shengji_types::GameMessage;
 use shengji_wasm::{
     CanPlayCardsRequest, CanPlayCardsResponse, CardInfo, CardInfoRequest, ComputeScoreRequest,
     ComputeScoreResponse, DecomposeTrickFormatRequest, DecomposeTrickFormatResponse,
-    DecomposedTrickFormat, ExplainScoringRequest, ExplainScoringResponse, FindValidBidsRequest,
+    DecomposedTrickFormat, DiffHandsRequest, DiffHandsResponse, ExplainScoringRequest,
+    ExplainScoringResponse, FindLegalPlaysRequest, FindLegalPlaysResponse, FindValidBidsRequest,
     FindValidBidsResult, FindViablePlaysRequest, FindViablePlaysResult, FoundViablePlay,
-    NextThresholdReachableRequest, ScoreSegment, SortAndGroupCardsRequest,
-    SortAndGroupCardsResponse, SuitGroup,
+    NextThresholdReachableRequest, PreviewPlayRequest, PreviewPlayResponse, ReplayTrickRequest,
+    ReplayTrickResponse, ScoreSegment, SortAndGroupCardsRequest, SortAndGroupCardsResponse,
+    SuitGroup, SummarizeHandRequest,
 };
 use tempdir::TempDir;
 
@@ -25,6 +28,10 @@ pub struct _Combined {
     pub decomposed_trick_format: DecomposedTrickFormat,
     pub can_play_cards_request: CanPlayCardsRequest,
     pub can_play_cards_response: CanPlayCardsResponse,
+    pub preview_play_request: PreviewPlayRequest,
+    pub preview_play_response: PreviewPlayResponse,
+    pub replay_trick_request: ReplayTrickRequest,
+    pub replay_trick_response: ReplayTrickResponse,
     pub find_valid_bids_request: FindValidBidsRequest,
     pub find_valid_bids_response: FindValidBidsResult,
     pub sort_and_group_cards_request: SortAndGroupCardsRequest,
@@ -38,6 +45,12 @@ pub struct _Combined {
     pub compute_score_response: ComputeScoreResponse,
     pub card_info_request: CardInfoRequest,
     pub card_info: CardInfo,
+    pub summarize_hand_request: SummarizeHandRequest,
+    pub hand_summary: HandSummary,
+    pub diff_hands_request: DiffHandsRequest,
+    pub diff_hands_response: DiffHandsResponse,
+    pub find_legal_plays_request: FindLegalPlaysRequest,
+    pub find_legal_plays_response: FindLegalPlaysResponse,
 }
 
 fn main() {
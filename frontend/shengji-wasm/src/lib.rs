@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::io::{Cursor, Read};
 
 use gloo_utils::format::JsValueSerdeExt;
@@ -9,16 +10,24 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use shengji_mechanics::types::Suit;
 use shengji_mechanics::{
-    bidding::{Bid, BidPolicy, BidReinforcementPolicy, JokerBidPolicy},
-    deck::Deck,
-    hands::Hands,
-    ordered_card::OrderedCard,
+    bidding::{Bid, BidPolicy, BidReinforcementPolicy, BidRules, JokerBidPolicy},
+    card_set::CardSet,
+    deck::{Deck, JokerCounts},
+    hand_summary::HandSummary,
+    hands::{HandDiff, Hands, MissingCard},
+    ordered_card::{DecompositionLimits, OrderedCard},
     player::Player,
     scoring::{
-        self, compute_level_deltas, explain_level_deltas, GameScoreResult, GameScoringParameters,
+        self, compute_level_deltas, explain_level_deltas, AdvancementSchedule, GameScoreResult,
+        GameScoringParameters, PointSchedule,
     },
-    trick::{TractorRequirements, Trick, TrickDrawPolicy, TrickFormat, TrickUnit, UnitLike},
-    types::{Card, EffectiveSuit, PlayerID, Trump},
+    sorting::{sort_cards, SortOrder},
+    trick::{
+        BeatPolicy, LegalPlays, MultiSuitLeadPolicy, PlayCards, PlayError, PlayPreview,
+        ThrowEvaluationPolicy, TractorRequirements, Trick, TrickDrawPolicy, TrickFormat,
+        TrickReplayStep, TrickRules, TrickUnit, UnitLike,
+    },
+    types::{AdjacencyPolicy, Card, CardColor, EffectiveSuit, PermanentTrump, PlayerID, Trump},
 };
 use shengji_types::ZSTD_ZSTD_DICT;
 use wasm_bindgen::prelude::*;
@@ -77,6 +86,39 @@ pub fn find_viable_plays(req: JsValue) -> Result<JsValue, JsValue> {
     Ok(JsValue::from_serde(&FindViablePlaysResult { results }).map_err(|e| e.to_string())?)
 }
 
+#[derive(Deserialize, JsonSchema)]
+pub struct FindLegalPlaysRequest {
+    trick_format: TrickFormat,
+    hands: Hands,
+    player_id: PlayerID,
+    trick_draw_policy: TrickDrawPolicy,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct FindLegalPlaysResponse {
+    results: Vec<LegalPlays>,
+}
+
+#[wasm_bindgen]
+pub fn find_legal_plays(req: JsValue) -> Result<JsValue, JsValue> {
+    let FindLegalPlaysRequest {
+        trick_format,
+        hands,
+        player_id,
+        trick_draw_policy,
+    } = req.into_serde().map_err(|e| e.to_string())?;
+    let hand = hands.get(player_id).map_err(|e| e.to_string())?;
+    let results = trick_format
+        .legal_plays(
+            hand,
+            trick_draw_policy,
+            trick_format.decomposition_strategy(),
+            DecompositionLimits::default(),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(JsValue::from_serde(&FindLegalPlaysResponse { results }).map_err(|e| e.to_string())?)
+}
+
 #[derive(Deserialize, JsonSchema)]
 pub struct DecomposeTrickFormatRequest {
     trick_format: TrickFormat,
@@ -133,6 +175,7 @@ pub fn decompose_trick_format(req: JsValue) -> Result<JsValue, JsValue> {
             OrderedCard::make_map(available_cards.iter().copied(), trick_format.trump()),
             res.format.iter().cloned(),
             trick_draw_policy,
+            trick_format.adjacency_policy(),
         );
 
         let playable = if let Some(units) = iter.next() {
@@ -168,11 +211,23 @@ pub struct CanPlayCardsRequest {
     hands: Hands,
     cards: Vec<Card>,
     trick_draw_policy: TrickDrawPolicy,
+    #[serde(default)]
+    wild_card: Option<Card>,
+    #[serde(default)]
+    beat_policy: BeatPolicy,
+    #[serde(default)]
+    throw_evaluation_policy: ThrowEvaluationPolicy,
+    #[serde(default)]
+    multi_suit_lead_policy: MultiSuitLeadPolicy,
 }
 
 #[derive(Serialize, JsonSchema)]
 pub struct CanPlayCardsResponse {
     playable: bool,
+    /// The selected cards which aren't in the player's hand, if any.
+    missing_cards: Vec<MissingCard>,
+    /// Why the cards can't be played, if they can't.
+    error: Option<PlayError>,
 }
 
 #[wasm_bindgen]
@@ -183,11 +238,131 @@ pub fn can_play_cards(req: JsValue) -> Result<JsValue, JsValue> {
         hands,
         cards,
         trick_draw_policy,
+        wild_card,
+        beat_policy,
+        throw_evaluation_policy,
+        multi_suit_lead_policy,
     } = req.into_serde().map_err(|e| e.to_string())?;
+    let result = trick
+        .can_play_cards_with_lead_policy(
+            id,
+            &hands,
+            &cards,
+            trick_draw_policy,
+            wild_card,
+            multi_suit_lead_policy,
+        )
+        .and_then(|()| match beat_policy {
+            BeatPolicy::NoObligation => Ok(()),
+            BeatPolicy::MustBeatIfAble => trick.check_must_beat(
+                id,
+                &hands,
+                &cards,
+                trick_draw_policy,
+                throw_evaluation_policy,
+            ),
+        });
+    let error = result.as_ref().err().and_then(|e| e.play_error()).cloned();
     Ok(JsValue::from_serde(&CanPlayCardsResponse {
-        playable: trick
-            .can_play_cards(id, &hands, &cards, trick_draw_policy)
-            .is_ok(),
+        playable: result.is_ok(),
+        missing_cards: match error {
+            Some(PlayError::CardsNotInHand { ref missing }) => missing.clone(),
+            _ => vec![],
+        },
+        error,
+    })
+    .map_err(|e| e.to_string())?)
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PreviewPlayRequest {
+    trick: Trick,
+    id: PlayerID,
+    hands: Hands,
+    cards: Vec<Card>,
+    trick_draw_policy: TrickDrawPolicy,
+    #[serde(default)]
+    throw_evaluation_policy: ThrowEvaluationPolicy,
+    #[serde(default)]
+    tractor_requirements: TractorRequirements,
+    #[serde(default)]
+    point_schedule: PointSchedule,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct PreviewPlayResponse {
+    /// What would happen if the cards were played, if they can be.
+    preview: Option<PlayPreview>,
+    /// Why the cards can't be played, if they can't.
+    error: Option<PlayError>,
+}
+
+#[wasm_bindgen]
+pub fn preview_play(req: JsValue) -> Result<JsValue, JsValue> {
+    let PreviewPlayRequest {
+        trick,
+        id,
+        mut hands,
+        cards,
+        trick_draw_policy,
+        throw_evaluation_policy,
+        tractor_requirements,
+        point_schedule,
+    } = req.into_serde().map_err(|e| e.to_string())?;
+    let result = trick.preview_play(
+        PlayCards {
+            id,
+            hands: &mut hands,
+            cards: &cards,
+            rules: &TrickRules {
+                trick_draw_policy,
+                throw_eval_policy: throw_evaluation_policy,
+                tractor_requirements,
+                ..Default::default()
+            },
+            teammates: &[],
+            format_hint: None,
+        },
+        &point_schedule,
+    );
+    let response = match result {
+        Ok(preview) => PreviewPlayResponse {
+            preview: Some(preview),
+            error: None,
+        },
+        Err(e) => PreviewPlayResponse {
+            preview: None,
+            error: Some(e.play_error().cloned().ok_or_else(|| e.to_string())?),
+        },
+    };
+    Ok(JsValue::from_serde(&response).map_err(|e| e.to_string())?)
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ReplayTrickRequest {
+    trick: Trick,
+    #[serde(default)]
+    throw_evaluation_policy: ThrowEvaluationPolicy,
+    #[serde(default)]
+    point_schedule: PointSchedule,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ReplayTrickResponse {
+    format: Option<TrickFormat>,
+    steps: Vec<TrickReplayStep>,
+}
+
+#[wasm_bindgen]
+pub fn replay_trick(req: JsValue) -> Result<JsValue, JsValue> {
+    let ReplayTrickRequest {
+        trick,
+        throw_evaluation_policy,
+        point_schedule,
+    } = req.into_serde().map_err(|e| e.to_string())?;
+    Ok(JsValue::from_serde(&ReplayTrickResponse {
+        format: trick.trick_format().cloned(),
+        steps: trick.replay(throw_evaluation_policy, &point_schedule),
     })
     .map_err(|e| e.to_string())?)
 }
@@ -204,6 +379,18 @@ pub struct FindValidBidsRequest {
     bid_reinforcement_policy: BidReinforcementPolicy,
     joker_bid_policy: JokerBidPolicy,
     num_decks: usize,
+    #[serde(default)]
+    joker_counts: Option<JokerCounts>,
+    #[serde(default)]
+    permanent_trump: PermanentTrump,
+    #[serde(default)]
+    bid_rules: BidRules,
+    /// The bidder's known teammates, for `BidRules::partner_reinforcement`.
+    #[serde(default)]
+    partners: Vec<PlayerID>,
+    /// Whether cards are still being drawn.
+    #[serde(default)]
+    drawing: bool,
 }
 
 #[derive(Serialize, JsonSchema)]
@@ -227,7 +414,12 @@ pub fn find_valid_bids(req: JsValue) -> Result<JsValue, JsValue> {
             req.bid_policy,
             req.bid_reinforcement_policy,
             req.joker_bid_policy,
-            req.num_decks,
+            req.joker_counts
+                .unwrap_or_else(|| JokerCounts::standard(req.num_decks)),
+            req.permanent_trump,
+            req.bid_rules,
+            &req.partners,
+            !req.drawing,
         )
         .unwrap_or_default(),
     })
@@ -238,6 +430,8 @@ pub fn find_valid_bids(req: JsValue) -> Result<JsValue, JsValue> {
 pub struct SortAndGroupCardsRequest {
     trump: Trump,
     cards: Vec<Card>,
+    #[serde(default)]
+    sort_order: SortOrder,
 }
 
 #[derive(Serialize, JsonSchema)]
@@ -253,10 +447,13 @@ pub struct SuitGroup {
 
 #[wasm_bindgen]
 pub fn sort_and_group_cards(req: JsValue) -> Result<JsValue, JsValue> {
-    let SortAndGroupCardsRequest { trump, mut cards } =
-        req.into_serde().map_err(|e| e.to_string())?;
+    let SortAndGroupCardsRequest {
+        trump,
+        mut cards,
+        sort_order,
+    } = req.into_serde().map_err(|e| e.to_string())?;
 
-    cards.sort_by(|a, b| trump.compare(*a, *b));
+    sort_cards(&mut cards, trump, &sort_order);
 
     let mut results: Vec<SuitGroup> = vec![];
     for card in cards {
@@ -276,10 +473,59 @@ pub fn sort_and_group_cards(req: JsValue) -> Result<JsValue, JsValue> {
     Ok(JsValue::from_serde(&SortAndGroupCardsResponse { results }).map_err(|e| e.to_string())?)
 }
 
+#[derive(Deserialize, JsonSchema)]
+pub struct SummarizeHandRequest {
+    trump: Trump,
+    cards: Vec<Card>,
+    #[serde(default)]
+    point_schedule: PointSchedule,
+    #[serde(default)]
+    adjacency_policy: AdjacencyPolicy,
+}
+
+#[wasm_bindgen]
+pub fn summarize_hand(req: JsValue) -> Result<JsValue, JsValue> {
+    let SummarizeHandRequest {
+        trump,
+        cards,
+        point_schedule,
+        adjacency_policy,
+    } = req.into_serde().map_err(|e| e.to_string())?;
+    let summary = HandSummary::new(
+        &CardSet::from_cards(cards),
+        trump,
+        &point_schedule,
+        adjacency_policy,
+    );
+    Ok(JsValue::from_serde(&summary).map_err(|e| e.to_string())?)
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DiffHandsRequest {
+    before: Hands,
+    after: Hands,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct DiffHandsResponse {
+    diffs: HashMap<PlayerID, HandDiff>,
+}
+
+#[wasm_bindgen]
+pub fn diff_hands(req: JsValue) -> Result<JsValue, JsValue> {
+    let DiffHandsRequest { before, after } = req.into_serde().map_err(|e| e.to_string())?;
+    Ok(JsValue::from_serde(&DiffHandsResponse {
+        diffs: Hands::diff(&before, &after),
+    })
+    .map_err(|e| e.to_string())?)
+}
+
 #[derive(Deserialize, JsonSchema)]
 pub struct NextThresholdReachableRequest {
     decks: Vec<Deck>,
     params: GameScoringParameters,
+    #[serde(default)]
+    schedule: AdvancementSchedule,
     non_landlord_points: isize,
     observed_points: isize,
 }
@@ -289,19 +535,26 @@ pub fn next_threshold_reachable(req: JsValue) -> Result<bool, JsValue> {
     let NextThresholdReachableRequest {
         decks,
         params,
+        schedule,
         non_landlord_points,
         observed_points,
     } = req.into_serde().map_err(|e| e.to_string())?;
-    Ok(
-        scoring::next_threshold_reachable(&params, &decks, non_landlord_points, observed_points)
-            .map_err(|_| "Failed to determine if next threshold is reachable")?,
+    Ok(scoring::next_threshold_reachable(
+        &params,
+        &schedule,
+        &decks,
+        non_landlord_points,
+        observed_points,
     )
+    .map_err(|_| "Failed to determine if next threshold is reachable")?)
 }
 
 #[derive(Deserialize, JsonSchema)]
 pub struct ExplainScoringRequest {
     decks: Vec<Deck>,
     params: GameScoringParameters,
+    #[serde(default)]
+    schedule: AdvancementSchedule,
     smaller_landlord_team_size: bool,
 }
 
@@ -323,9 +576,10 @@ pub fn explain_scoring(req: JsValue) -> Result<JsValue, JsValue> {
     let ExplainScoringRequest {
         decks,
         params,
+        schedule,
         smaller_landlord_team_size,
     } = req.into_serde().map_err(|e| e.to_string())?;
-    let deltas = explain_level_deltas(&params, &decks, smaller_landlord_team_size)
+    let deltas = explain_level_deltas(&params, &schedule, &decks, smaller_landlord_team_size)
         .map_err(|e| format!("Failed to explain scores: {:?}", e))?;
 
     Ok(JsValue::from_serde(&ExplainScoringResponse {
@@ -339,7 +593,7 @@ pub fn explain_scoring(req: JsValue) -> Result<JsValue, JsValue> {
         step_size: params
             .step_size(&decks)
             .map_err(|e| format!("Failed to compute step size: {:?}", e))?,
-        total_points: decks.iter().map(|d| d.points() as isize).sum::<isize>(),
+        total_points: params.point_schedule.deck_points(&decks) as isize,
     })
     .map_err(|e| e.to_string())?)
 }
@@ -355,6 +609,8 @@ pub fn compute_deck_len(req: JsValue) -> Result<usize, JsValue> {
 pub struct ComputeScoreRequest {
     decks: Vec<Deck>,
     params: GameScoringParameters,
+    #[serde(default)]
+    schedule: AdvancementSchedule,
     smaller_landlord_team_size: bool,
     non_landlord_points: isize,
 }
@@ -370,11 +626,13 @@ pub fn compute_score(req: JsValue) -> Result<JsValue, JsValue> {
     let ComputeScoreRequest {
         decks,
         params,
+        schedule,
         smaller_landlord_team_size,
         non_landlord_points,
     } = req.into_serde().map_err(|e| e.to_string())?;
     let score = compute_level_deltas(
         &params,
+        &schedule,
         &decks,
         non_landlord_points,
         smaller_landlord_team_size,
@@ -382,6 +640,7 @@ pub fn compute_score(req: JsValue) -> Result<JsValue, JsValue> {
     .map_err(|_| "Failed to compute score")?;
     let next_threshold = params
         .materialize(&decks)
+        .and_then(|m| m.with_schedule(&schedule))
         .and_then(|n| n.next_relevant_score(non_landlord_points))
         .map_err(|_| "Couldn't find next valid score")?
         .0;
@@ -402,6 +661,8 @@ pub struct CardInfo {
     typ: char,
     number: Option<&'static str>,
     points: usize,
+    color: CardColor,
+    four_color: CardColor,
 }
 
 #[derive(Deserialize, JsonSchema)]
@@ -424,6 +685,8 @@ pub fn get_card_info(req: JsValue) -> Result<JsValue, JsValue> {
         typ: info.typ,
         number: info.number,
         points: info.points,
+        color: info.color,
+        four_color: info.four_color,
         effective_suit,
     })
     .map_err(|e| e.to_string())?)
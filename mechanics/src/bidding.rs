@@ -2,9 +2,10 @@ use anyhow::{anyhow, bail, Error};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::deck::JokerCounts;
 use crate::hands::Hands;
 use crate::player::Player;
-use crate::types::{Card, PlayerID, Rank};
+use crate::types::{Card, PermanentTrump, PlayerID, Rank};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
 pub enum BidPolicy {
@@ -22,6 +23,10 @@ pub enum JokerBidPolicy {
     BothTwoOrMore,
     BothNumDecks,
     LJNumDecksHJNumDecksLessOne,
+    /// Either joker can be bid with at least `count` cards.
+    BothAtLeast {
+        count: usize,
+    },
     Disabled,
 }
 
@@ -45,10 +50,136 @@ pub enum BidTakebackPolicy {
     #[default]
     AllowBidTakeback,
     NoBidTakeback,
+    /// Bids can be taken back until someone else bids or the drawing ends.
+    AllowBidTakebackWhileDrawing,
 }
 
 crate::impl_slog_value!(BidTakebackPolicy);
 
+/// Who wins when two players make equally strong bids before any more cards
+/// have been drawn.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum BidPriorityPolicy {
+    /// Whichever bid arrives first.
+    #[default]
+    FirstClick,
+    /// The player who comes first in the draw order.
+    DrawOrder,
+    /// A player on the dealer's team, and otherwise the player who comes
+    /// first in the draw order.
+    DealersTeam,
+}
+
+crate::impl_slog_value!(BidPriorityPolicy);
+
+impl BidPriorityPolicy {
+    /// Whether `challenger` takes priority over `holder`, where `draw_order`
+    /// lists the players in the order they draw cards.
+    pub fn has_priority(
+        self,
+        challenger: PlayerID,
+        holder: PlayerID,
+        draw_order: &[PlayerID],
+        dealers_team: &[PlayerID],
+    ) -> bool {
+        let position = |id| draw_order.iter().position(|p| *p == id);
+        let first_to_draw = matches!(
+            (position(challenger), position(holder)),
+            (Some(c), Some(h)) if c < h
+        );
+        match self {
+            BidPriorityPolicy::FirstClick => false,
+            BidPriorityPolicy::DrawOrder => first_to_draw,
+            BidPriorityPolicy::DealersTeam => {
+                match (
+                    dealers_team.contains(&challenger),
+                    dealers_team.contains(&holder),
+                ) {
+                    (true, false) => true,
+                    (false, true) => false,
+                    _ => first_to_draw,
+                }
+            }
+        }
+    }
+}
+
+/// Restrictions on bidding, checked on top of the `BidPolicy`,
+/// `BidReinforcementPolicy` and `JokerBidPolicy`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct BidRules {
+    /// Whether a player may outbid their own winning bid with more cards.
+    pub self_outbid: bool,
+    /// Whether a player may reinforce their partner's winning bid, i.e. bid
+    /// more of the same card.
+    pub partner_reinforcement: bool,
+    /// Whether a bid with as many cards as the winning bid may outbid it,
+    /// e.g. with jokers or a higher suit.
+    pub equal_count_outbid: bool,
+    /// Whether no-trump (joker) bids may be made before all the cards have
+    /// been drawn.
+    pub joker_bids_while_drawing: bool,
+    /// Whether a winning no-trump bid may be overturned by anyone else.
+    pub joker_bid_overturn: bool,
+    /// Whether bidders have to keep the cards they bid with in their hand
+    /// until the bid is overturned or the bidding closes, so that nobody can
+    /// bid just to use up the bid and then bury the cards.
+    pub keep_bid_cards: bool,
+}
+
+impl Default for BidRules {
+    fn default() -> Self {
+        BidRules {
+            self_outbid: true,
+            partner_reinforcement: true,
+            equal_count_outbid: true,
+            joker_bids_while_drawing: true,
+            joker_bid_overturn: true,
+            keep_bid_cards: false,
+        }
+    }
+}
+
+crate::impl_slog_value!(BidRules);
+
+impl BidRules {
+    /// Checks `new_bid` against the winning bid, where `partners` are the
+    /// bidder's known teammates.
+    pub fn check(
+        &self,
+        new_bid: &Bid,
+        winning_bid: Option<&Bid>,
+        partners: &[PlayerID],
+        done_drawing: bool,
+    ) -> Result<(), Error> {
+        if !self.joker_bids_while_drawing && !done_drawing && new_bid.card.is_joker() {
+            bail!("No-trump bids can't be made until all the cards have been drawn")
+        }
+        let winning_bid = match winning_bid {
+            Some(winning_bid) => winning_bid,
+            None => return Ok(()),
+        };
+        if !self.joker_bid_overturn && winning_bid.card.is_joker() && winning_bid.id != new_bid.id {
+            bail!("No-trump bids can't be overturned")
+        }
+        if !self.self_outbid && winning_bid.id == new_bid.id {
+            bail!("You can't outbid your own bid")
+        }
+        if !self.partner_reinforcement
+            && winning_bid.id != new_bid.id
+            && partners.contains(&winning_bid.id)
+            && winning_bid.card == new_bid.card
+        {
+            bail!("You can't reinforce your partner's bid")
+        }
+        if !self.equal_count_outbid && new_bid.count <= winning_bid.count {
+            bail!("A bid must have more cards than the winning bid")
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub struct Bid {
     pub id: PlayerID,
@@ -71,7 +202,11 @@ impl Bid {
         bid_policy: BidPolicy,
         bid_reinforcement_policy: BidReinforcementPolicy,
         joker_bid_policy: JokerBidPolicy,
-        num_decks: usize,
+        joker_counts: JokerCounts,
+        permanent_trump: PermanentTrump,
+        bid_rules: BidRules,
+        partners: &'_ [PlayerID],
+        done_drawing: bool,
     ) -> Result<Vec<Bid>, Error> {
         // Compute all valid bids.
         let most_recent_bid = bids.iter().rev().find(|b| b.id == id);
@@ -90,6 +225,8 @@ impl Bid {
             let mut valid_bid_cards = vec![];
             for (card, count) in counts {
                 let consider = match bid_level {
+                    // The general is always trump, so it can't declare a suit.
+                    _ if permanent_trump == PermanentTrump::Card(*card) => false,
                     _ if card.is_joker() => true,
                     Some(Rank::Number(bid_level)) if card.number() == Some(bid_level) => true,
                     _ => false,
@@ -123,20 +260,23 @@ impl Bid {
                             }
                             (Card::SmallJoker, JokerBidPolicy::LJNumDecksHJNumDecksLessOne)
                             | (Card::SmallJoker, JokerBidPolicy::BothNumDecks)
-                                if inner_count < num_decks =>
+                                if inner_count < joker_counts.small =>
                             {
                                 continue
                             }
                             (Card::BigJoker, JokerBidPolicy::LJNumDecksHJNumDecksLessOne)
-                                if inner_count < num_decks - 1 =>
+                                if inner_count < joker_counts.big.saturating_sub(1) =>
                             {
                                 continue
                             }
                             (Card::BigJoker, JokerBidPolicy::BothNumDecks)
-                                if inner_count < num_decks =>
+                                if inner_count < joker_counts.big =>
                             {
                                 continue
                             }
+                            (_, JokerBidPolicy::BothAtLeast { count }) if inner_count < count => {
+                                continue
+                            }
                             (_, _) => (),
                         }
                     }
@@ -181,7 +321,7 @@ impl Bid {
             valid_bids
         });
 
-        match (most_recent_bid, bid_reinforcement_policy, valid_bids) {
+        let valid_bids = match (most_recent_bid, bid_reinforcement_policy, valid_bids) {
             (Some(most_recent_bid), BidReinforcementPolicy::ReinforceWhileWinning, _)
             | (Some(most_recent_bid), BidReinforcementPolicy::ReinforceWhileEquivalent, _)
                 if Some(most_recent_bid) == bids.last() =>
@@ -192,14 +332,14 @@ impl Bid {
                     .counts(id)
                     .and_then(|c| c.get(&most_recent_bid.card).cloned())
                     .unwrap_or(0);
-                Ok((most_recent_bid.count + 1..=available)
+                (most_recent_bid.count + 1..=available)
                     .map(|count| Bid {
                         card: most_recent_bid.card,
                         count,
                         id,
                         epoch,
                     })
-                    .collect())
+                    .collect()
             }
             (
                 Some(most_recent_bid),
@@ -227,7 +367,7 @@ impl Bid {
                     }
                 }
 
-                Ok(valid_bids)
+                valid_bids
             }
             (
                 Some(_),
@@ -235,9 +375,17 @@ impl Bid {
                 Some(valid_bids),
             )
             | (Some(_), BidReinforcementPolicy::ReinforceWhileWinning, Some(valid_bids))
-            | (None, _, Some(valid_bids)) => Ok(valid_bids),
-            (_, _, None) => Ok(vec![]),
-        }
+            | (None, _, Some(valid_bids)) => valid_bids,
+            (_, _, None) => vec![],
+        };
+        Ok(valid_bids
+            .into_iter()
+            .filter(|b| {
+                bid_rules
+                    .check(b, bids.last(), partners, done_drawing)
+                    .is_ok()
+            })
+            .collect())
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -253,11 +401,15 @@ impl Bid {
         bid_policy: BidPolicy,
         bid_reinforcement_policy: BidReinforcementPolicy,
         joker_bid_policy: JokerBidPolicy,
-        num_decks: usize,
+        joker_counts: JokerCounts,
+        permanent_trump: PermanentTrump,
+        bid_rules: BidRules,
+        partners: &'_ [PlayerID],
+        done_drawing: bool,
         epoch: usize,
-    ) -> bool {
+    ) -> Result<(), Error> {
         if autobid.is_some() {
-            return false;
+            bail!("The bid was already made from the bottom cards")
         }
 
         let new_bid = Bid {
@@ -266,6 +418,7 @@ impl Bid {
             count,
             epoch,
         };
+        bid_rules.check(&new_bid, bids.last(), partners, done_drawing)?;
         if Self::valid_bids(
             id,
             bids,
@@ -276,32 +429,62 @@ impl Bid {
             bid_policy,
             bid_reinforcement_policy,
             joker_bid_policy,
-            num_decks,
-        )
-        .map(|b| b.contains(&new_bid))
-        .unwrap_or(false)
+            joker_counts,
+            permanent_trump,
+            bid_rules,
+            partners,
+            done_drawing,
+        )?
+        .contains(&new_bid)
         {
             bids.push(new_bid);
-            true
+            Ok(())
         } else {
-            false
+            bail!("bid was invalid")
         }
     }
 
+    /// The cards `id` has to keep in hand under `BidRules::keep_bid_cards`,
+    /// i.e. those they bid with if their bid is still winning.
+    pub fn cards_to_keep(bids: &'_ [Bid], id: PlayerID) -> Option<(Card, usize)> {
+        bids.last()
+            .filter(|b| b.id == id)
+            .map(|b| (b.card, b.count))
+    }
+
+    /// Whether neither bid would outbid the other under `bid_policy`.
+    pub fn same_strength(&self, other: &Bid, bid_policy: BidPolicy) -> bool {
+        self.count == other.count
+            && match (self.card, other.card) {
+                (a, b) if a.is_joker() || b.is_joker() => a == b,
+                (a, b) => {
+                    bid_policy != BidPolicy::JokerOrHigherSuit
+                        || self.count == 1
+                        || a.suit() == b.suit()
+                }
+            }
+    }
+
+    /// Takes back the player's bid if it's the most recent one, and returns
+    /// it.
     pub fn take_back_bid(
         id: PlayerID,
         bid_takeback_policy: BidTakebackPolicy,
         bids: &'_ mut Vec<Bid>,
+        done_drawing: bool,
         epoch: usize,
-    ) -> Result<(), Error> {
-        if bid_takeback_policy == BidTakebackPolicy::NoBidTakeback {
-            bail!("Taking back bids is not allowed!")
+    ) -> Result<Bid, Error> {
+        match bid_takeback_policy {
+            BidTakebackPolicy::NoBidTakeback => bail!("Taking back bids is not allowed!"),
+            BidTakebackPolicy::AllowBidTakebackWhileDrawing if done_drawing => {
+                bail!("Bids can't be taken back once all the cards have been drawn")
+            }
+            BidTakebackPolicy::AllowBidTakeback
+            | BidTakebackPolicy::AllowBidTakebackWhileDrawing => (),
         }
-        if bids.last().map(|b| (b.id, b.epoch)) == Some((id, epoch)) {
-            bids.pop();
-            Ok(())
-        } else {
-            bail!("Can't do that right now")
+        match bids.last() {
+            Some(b) if (b.id, b.epoch) == (id, epoch) => Ok(bids.pop().unwrap()),
+            _ => bail!("Can't do that right now"),
         }
     }
 
@@ -327,14 +510,17 @@ impl Bid {
 mod tests {
     use std::collections::HashSet;
 
+    use crate::deck::JokerCounts;
     use crate::hands::Hands;
     use crate::player::Player;
     use crate::types::{
         cards::{C_2, D_2, H_2, S_2},
-        Card, PlayerID,
+        Card, PermanentTrump, PlayerID,
     };
 
-    use super::{Bid, BidPolicy, BidReinforcementPolicy, JokerBidPolicy};
+    use super::{
+        Bid, BidPolicy, BidPriorityPolicy, BidReinforcementPolicy, BidRules, JokerBidPolicy,
+    };
 
     macro_rules! b {
         ($p:expr, $card:expr, $count:expr) => {
@@ -457,7 +643,11 @@ mod tests {
                     BidPolicy::JokerOrGreaterLength,
                     rpol,
                     JokerBidPolicy::BothTwoOrMore,
-                    3,
+                    JokerCounts::standard(3),
+                    PermanentTrump::None,
+                    BidRules::default(),
+                    &[],
+                    true,
                 )
                 .unwrap()
                 .into_iter()
@@ -538,7 +728,11 @@ mod tests {
                     BidPolicy::JokerOrHigherSuit,
                     rpol,
                     JokerBidPolicy::BothTwoOrMore,
-                    3,
+                    JokerCounts::standard(3),
+                    PermanentTrump::None,
+                    BidRules::default(),
+                    &[],
+                    true,
                 )
                 .unwrap()
                 .into_iter()
@@ -547,4 +741,325 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_valid_bids_joker_counts() {
+        let p = PlayerID(0);
+        let mut h = Hands::new(vec![p]);
+        h.add(
+            p,
+            vec![
+                Card::SmallJoker,
+                Card::SmallJoker,
+                Card::BigJoker,
+                Card::BigJoker,
+            ],
+        )
+        .unwrap();
+        let players = vec![Player::new(p, "p0".into())];
+
+        let valid = |joker_bid_policy, joker_counts| {
+            Bid::valid_bids(
+                p,
+                &[],
+                &h,
+                &players,
+                None,
+                0,
+                BidPolicy::JokerOrGreaterLength,
+                BidReinforcementPolicy::ReinforceWhileWinning,
+                joker_bid_policy,
+                joker_counts,
+                PermanentTrump::None,
+                BidRules::default(),
+                &[],
+                true,
+            )
+            .unwrap()
+            .into_iter()
+            .collect::<HashSet<_>>()
+        };
+
+        // With four-joker decks, there are more big jokers than decks, so a
+        // pair isn't enough to bid.
+        assert_eq!(
+            valid(
+                JokerBidPolicy::BothNumDecks,
+                JokerCounts { small: 2, big: 4 }
+            ),
+            vec![b!(p, Card::SmallJoker, 2)].into_iter().collect()
+        );
+        assert_eq!(
+            valid(
+                JokerBidPolicy::LJNumDecksHJNumDecksLessOne,
+                JokerCounts { small: 4, big: 3 }
+            ),
+            vec![b!(p, Card::BigJoker, 2)].into_iter().collect()
+        );
+        assert_eq!(
+            valid(JokerBidPolicy::BothNumDecks, JokerCounts::standard(2)),
+            vec![b!(p, Card::SmallJoker, 2), b!(p, Card::BigJoker, 2)]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[test]
+    fn test_valid_bids_permanent_trump() {
+        let p = PlayerID(0);
+        let mut h = Hands::new(vec![p]);
+        h.add(p, vec![S_2, S_2, H_2]).unwrap();
+        let players = vec![Player::new(p, "p0".into())];
+
+        let valid = |permanent_trump| {
+            Bid::valid_bids(
+                p,
+                &[],
+                &h,
+                &players,
+                None,
+                0,
+                BidPolicy::JokerOrGreaterLength,
+                BidReinforcementPolicy::ReinforceWhileWinning,
+                JokerBidPolicy::BothTwoOrMore,
+                JokerCounts::standard(2),
+                permanent_trump,
+                BidRules::default(),
+                &[],
+                true,
+            )
+            .unwrap()
+            .into_iter()
+            .collect::<HashSet<_>>()
+        };
+
+        assert_eq!(
+            valid(PermanentTrump::None),
+            vec![b!(p, S_2, 1), b!(p, S_2, 2), b!(p, H_2, 1)]
+                .into_iter()
+                .collect()
+        );
+        // The general can't be used to declare its suit.
+        assert_eq!(
+            valid(PermanentTrump::Card(S_2)),
+            vec![b!(p, H_2, 1)].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn test_bid_rules() {
+        let p = PlayerID(0);
+        let partner = PlayerID(2);
+        let mut h = Hands::new(vec![p, partner]);
+        h.add(p, vec![S_2, S_2, S_2, H_2, Card::BigJoker, Card::BigJoker])
+            .unwrap();
+        let players = vec![
+            Player::new(p, "p0".into()),
+            Player::new(partner, "p2".into()),
+        ];
+
+        let valid = |bids: &[Bid], bid_rules| {
+            Bid::valid_bids(
+                p,
+                bids,
+                &h,
+                &players,
+                None,
+                0,
+                BidPolicy::JokerOrGreaterLength,
+                BidReinforcementPolicy::OverturnOrReinforceWhileWinning,
+                JokerBidPolicy::BothTwoOrMore,
+                JokerCounts::standard(3),
+                PermanentTrump::None,
+                bid_rules,
+                &[partner],
+                true,
+            )
+            .unwrap()
+            .into_iter()
+            .collect::<HashSet<_>>()
+        };
+        let all = BidRules::default();
+
+        // Outbidding our own bid.
+        assert_eq!(
+            valid(&[b!(p, S_2, 1)], all),
+            vec![b!(p, S_2, 2), b!(p, S_2, 3), b!(p, Card::BigJoker, 2)]
+                .into_iter()
+                .collect()
+        );
+        let no_self_outbid = BidRules {
+            self_outbid: false,
+            ..all
+        };
+        assert!(valid(&[b!(p, S_2, 1)], no_self_outbid).is_empty());
+        assert_eq!(
+            no_self_outbid
+                .check(&b!(p, S_2, 2), Some(&b!(p, S_2, 1)), &[partner], true)
+                .unwrap_err()
+                .to_string(),
+            "You can't outbid your own bid"
+        );
+
+        // Reinforcing our partner's bid.
+        let no_partner_reinforcement = BidRules {
+            partner_reinforcement: false,
+            ..all
+        };
+        assert_eq!(
+            valid(&[b!(partner, S_2, 1)], no_partner_reinforcement),
+            vec![b!(p, Card::BigJoker, 2)].into_iter().collect()
+        );
+        assert_eq!(
+            no_partner_reinforcement
+                .check(&b!(p, S_2, 2), Some(&b!(partner, S_2, 1)), &[partner], true)
+                .unwrap_err()
+                .to_string(),
+            "You can't reinforce your partner's bid"
+        );
+        // The restriction only applies to partners.
+        assert!(no_partner_reinforcement
+            .check(
+                &b!(p, S_2, 2),
+                Some(&b!(PlayerID(1), S_2, 1)),
+                &[partner],
+                true
+            )
+            .is_ok());
+
+        // Outbidding with the same number of cards.
+        assert_eq!(
+            valid(&[b!(PlayerID(1), D_2, 2)], all),
+            vec![b!(p, S_2, 3), b!(p, Card::BigJoker, 2)]
+                .into_iter()
+                .collect()
+        );
+        let no_equal_count_outbid = BidRules {
+            equal_count_outbid: false,
+            ..all
+        };
+        assert_eq!(
+            valid(&[b!(PlayerID(1), D_2, 2)], no_equal_count_outbid),
+            vec![b!(p, S_2, 3)].into_iter().collect()
+        );
+        assert_eq!(
+            no_equal_count_outbid
+                .check(
+                    &b!(p, Card::BigJoker, 2),
+                    Some(&b!(PlayerID(1), D_2, 2)),
+                    &[],
+                    true
+                )
+                .unwrap_err()
+                .to_string(),
+            "A bid must have more cards than the winning bid"
+        );
+    }
+
+    #[test]
+    fn test_joker_bid_rules() {
+        let p = PlayerID(0);
+        let mut h = Hands::new(vec![p]);
+        h.add(
+            p,
+            vec![
+                S_2,
+                S_2,
+                S_2,
+                Card::SmallJoker,
+                Card::SmallJoker,
+                Card::SmallJoker,
+                Card::BigJoker,
+                Card::BigJoker,
+            ],
+        )
+        .unwrap();
+        let players = vec![Player::new(p, "p0".into())];
+
+        let valid = |bids: &[Bid], joker_bid_policy, bid_rules, done_drawing| {
+            Bid::valid_bids(
+                p,
+                bids,
+                &h,
+                &players,
+                None,
+                0,
+                BidPolicy::JokerOrGreaterLength,
+                BidReinforcementPolicy::ReinforceWhileWinning,
+                joker_bid_policy,
+                JokerCounts::standard(3),
+                PermanentTrump::None,
+                bid_rules,
+                &[],
+                done_drawing,
+            )
+            .unwrap()
+            .into_iter()
+            .filter(|b| b.card.is_joker())
+            .collect::<HashSet<_>>()
+        };
+        let all = BidRules::default();
+
+        assert_eq!(
+            valid(&[], JokerBidPolicy::BothAtLeast { count: 3 }, all, true),
+            vec![b!(p, Card::SmallJoker, 3)].into_iter().collect()
+        );
+
+        let not_while_drawing = BidRules {
+            joker_bids_while_drawing: false,
+            ..all
+        };
+        assert!(valid(&[], JokerBidPolicy::BothTwoOrMore, not_while_drawing, false).is_empty());
+        assert_eq!(
+            valid(&[], JokerBidPolicy::BothTwoOrMore, not_while_drawing, true).len(),
+            3
+        );
+
+        let winning_bid = b!(PlayerID(1), Card::SmallJoker, 2);
+        assert_eq!(
+            valid(&[winning_bid], JokerBidPolicy::BothTwoOrMore, all, true),
+            vec![b!(p, Card::SmallJoker, 3), b!(p, Card::BigJoker, 2)]
+                .into_iter()
+                .collect()
+        );
+        let no_overturn = BidRules {
+            joker_bid_overturn: false,
+            ..all
+        };
+        assert!(valid(
+            &[winning_bid],
+            JokerBidPolicy::BothTwoOrMore,
+            no_overturn,
+            true
+        )
+        .is_empty());
+        assert_eq!(
+            no_overturn
+                .check(&b!(p, Card::BigJoker, 2), Some(&winning_bid), &[], true)
+                .unwrap_err()
+                .to_string(),
+            "No-trump bids can't be overturned"
+        );
+    }
+
+    #[test]
+    fn test_bid_priority() {
+        let [p0, p1, p2, p3] = [PlayerID(0), PlayerID(1), PlayerID(2), PlayerID(3)];
+        let draw_order = [p1, p2, p3, p0];
+        let dealers_team = [p0, p2];
+
+        assert!(!BidPriorityPolicy::FirstClick.has_priority(p1, p2, &draw_order, &dealers_team));
+        assert!(BidPriorityPolicy::DrawOrder.has_priority(p1, p2, &draw_order, &dealers_team));
+        assert!(!BidPriorityPolicy::DrawOrder.has_priority(p0, p1, &draw_order, &dealers_team));
+        assert!(BidPriorityPolicy::DealersTeam.has_priority(p0, p1, &draw_order, &dealers_team));
+        assert!(!BidPriorityPolicy::DealersTeam.has_priority(p3, p2, &draw_order, &dealers_team));
+        assert!(BidPriorityPolicy::DealersTeam.has_priority(p2, p0, &draw_order, &dealers_team));
+
+        assert!(b!(p0, H_2, 2).same_strength(&b!(p1, S_2, 2), BidPolicy::JokerOrGreaterLength));
+        assert!(!b!(p0, H_2, 2).same_strength(&b!(p1, S_2, 2), BidPolicy::JokerOrHigherSuit));
+        assert!(b!(p0, H_2, 1).same_strength(&b!(p1, S_2, 1), BidPolicy::JokerOrHigherSuit));
+        assert!(!b!(p0, H_2, 1).same_strength(&b!(p1, S_2, 2), BidPolicy::GreaterLength));
+        assert!(!b!(p0, Card::SmallJoker, 2)
+            .same_strength(&b!(p1, Card::BigJoker, 2), BidPolicy::GreaterLength));
+    }
 }
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A snapshot of the usage of a cache, for monitoring.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// A small least-recently-used cache, bounded by the number of entries.
+///
+/// The caches this is used for are keyed by card counts, so they only ever
+/// hold a handful of entries; eviction does a linear scan rather than
+/// maintaining a separate recency list.
+///
+/// Lookups only require a shared reference (recency and statistics are
+/// tracked with atomics), so the cache can sit behind a `RwLock` and serve
+/// concurrent readers without contention.
+pub(crate) struct LruCache<K, V> {
+    capacity: usize,
+    tick: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    entries: HashMap<K, (V, AtomicU64)>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            entries: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &K) -> Option<&V> {
+        let tick = self.tick.fetch_add(1, Ordering::Relaxed) + 1;
+        match self.entries.get(key) {
+            Some((v, last_used)) => {
+                last_used.store(tick, Ordering::Relaxed);
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(v)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        let tick = self.tick.fetch_add(1, Ordering::Relaxed) + 1;
+        self.entries.insert(key, (value, AtomicU64::new(tick)));
+        self.evict();
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.len(),
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| last_used.load(Ordering::Relaxed))
+                .map(|(k, _)| k.clone());
+            match oldest {
+                Some(k) => {
+                    self.entries.remove(&k);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheStats, LruCache};
+    use crate::ordered_card::{
+        decomposition_cache_stats, full_decomposition_ordering, set_decomposition_cache_limits,
+        warm_caches, DecompositionCacheLimits, CACHE_LIMITS_TEST_LOCK,
+    };
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut c = LruCache::new(2);
+        c.insert(1, "a");
+        c.insert(2, "b");
+        assert_eq!(c.get(&1), Some(&"a"));
+        // 2 is now the least-recently used entry.
+        c.insert(3, "c");
+        assert_eq!(c.stats().entries, 2);
+        assert_eq!(c.get(&2), None);
+        assert_eq!(c.get(&1), Some(&"a"));
+        assert_eq!(c.get(&3), Some(&"c"));
+
+        c.set_capacity(1);
+        assert_eq!(c.stats().entries, 1);
+        assert_eq!(c.get(&3), Some(&"c"));
+
+        c.set_capacity(0);
+        c.insert(4, "d");
+        assert_eq!(
+            c.stats(),
+            CacheStats {
+                hits: 4,
+                misses: 1,
+                entries: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_lru_eviction_order() {
+        let mut c = LruCache::new(3);
+        for k in 1..=3 {
+            c.insert(k, k * 10);
+        }
+        // Reading and re-inserting both count as uses, so 2 is now the
+        // least-recently used entry, followed by 3.
+        c.get(&1);
+        c.insert(3, 31);
+        c.insert(4, 40);
+        assert_eq!(c.get(&2), None);
+        c.insert(5, 50);
+        assert_eq!(c.get(&1), None);
+        assert_eq!(c.get(&3), Some(&31));
+        assert_eq!(c.get(&4), Some(&40));
+        assert_eq!(c.get(&5), Some(&50));
+
+        // Shrinking a full cache keeps the most recently used entries.
+        c.get(&3);
+        c.set_capacity(1);
+        assert_eq!(c.stats().entries, 1);
+        assert_eq!(c.get(&3), Some(&31));
+        c.set_capacity(3);
+        c.insert(6, 60);
+        assert_eq!(c.stats().entries, 2);
+    }
+
+    #[test]
+    fn test_lru_stats() {
+        let mut c = LruCache::new(2);
+        assert_eq!(c.stats(), CacheStats::default());
+        assert_eq!(c.get(&1), None);
+        c.insert(1, "a");
+        c.insert(2, "b");
+        // Inserting doesn't count as a hit or a miss.
+        c.insert(2, "c");
+        assert_eq!(c.get(&1), Some(&"a"));
+        assert_eq!(c.get(&2), Some(&"c"));
+        assert_eq!(c.get(&3), None);
+        assert_eq!(
+            c.stats(),
+            CacheStats {
+                hits: 2,
+                misses: 2,
+                entries: 2,
+            }
+        );
+        // Evicted entries are misses, but the counts aren't reset.
+        c.set_capacity(0);
+        assert_eq!(c.get(&1), None);
+        assert_eq!(
+            c.stats(),
+            CacheStats {
+                hits: 2,
+                misses: 3,
+                entries: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_shrink_decomposition_caches() {
+        let _lock = CACHE_LIMITS_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let expected = full_decomposition_ordering(8);
+        set_decomposition_cache_limits(DecompositionCacheLimits::default());
+        warm_caches(8);
+        let stats = decomposition_cache_stats();
+        assert!(stats.partitions.entries >= 8);
+        assert!(stats.sequential_assignments.entries >= 3);
+        assert!(stats.full_decompositions.entries >= 8);
+
+        set_decomposition_cache_limits(DecompositionCacheLimits {
+            max_partitions: 2,
+            max_sequential_assignments: 2,
+            max_full_decompositions: 2,
+        });
+        let stats = decomposition_cache_stats();
+        assert!(stats.partitions.entries <= 2);
+        assert!(stats.sequential_assignments.entries <= 2);
+        assert!(stats.full_decompositions.entries <= 2);
+
+        // Evicted decompositions are recomputed on demand.
+        set_decomposition_cache_limits(DecompositionCacheLimits::default());
+        assert_eq!(full_decomposition_ordering(8), expected);
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Index;
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Card, EffectiveSuit, Trump, CARDS_BY_CODE};
+
+const NUM_CODES: usize = CARDS_BY_CODE.len();
+
+/// A multiset of cards, stored as a count for each `Card::as_u8` code along
+/// with a bitset of the codes which are present.
+///
+/// Lookups are constant-time, and operations over the whole set only visit
+/// the distinct cards which are present. Serialized as a map from card to
+/// count, the same as a `HashMap<Card, usize>`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CardSet {
+    counts: [usize; NUM_CODES],
+    present: u64,
+    len: usize,
+}
+
+impl Default for CardSet {
+    fn default() -> Self {
+        CardSet {
+            counts: [0; NUM_CODES],
+            present: 0,
+            len: 0,
+        }
+    }
+}
+
+impl CardSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_cards(cards: impl IntoIterator<Item = Card>) -> Self {
+        let mut set = Self::new();
+        for card in cards {
+            set.insert(card);
+        }
+        set
+    }
+
+    /// The total number of cards, counting copies.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn count(&self, card: Card) -> usize {
+        self.counts[card.as_u8() as usize]
+    }
+
+    pub fn contains(&self, card: Card) -> bool {
+        self.present & (1 << card.as_u8()) != 0
+    }
+
+    /// Like `HashMap::get`, for compatibility with code which used to take
+    /// a map of counts.
+    pub fn get(&self, card: &Card) -> Option<&usize> {
+        let code = card.as_u8() as usize;
+        if self.present & (1 << code) != 0 {
+            Some(&self.counts[code])
+        } else {
+            None
+        }
+    }
+
+    pub fn insert(&mut self, card: Card) {
+        self.insert_n(card, 1);
+    }
+
+    pub fn insert_n(&mut self, card: Card, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let code = card.as_u8() as usize;
+        self.counts[code] += count;
+        self.present |= 1 << code;
+        self.len += count;
+    }
+
+    /// Removes a single copy of `card`, returning whether there was one to
+    /// remove.
+    pub fn remove(&mut self, card: Card) -> bool {
+        let code = card.as_u8() as usize;
+        if self.counts[code] == 0 {
+            return false;
+        }
+        self.counts[code] -= 1;
+        if self.counts[code] == 0 {
+            self.present &= !(1 << code);
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// Whether this set has at least as many copies of each card as `other`.
+    pub fn contains_all(&self, other: &CardSet) -> bool {
+        other.present & !self.present == 0
+            && other
+                .codes()
+                .all(|code| self.counts[code] >= other.counts[code])
+    }
+
+    /// The number of cards in the given effective suit.
+    pub fn count_in_suit(&self, trump: Trump, suit: EffectiveSuit) -> usize {
+        self.codes()
+            .filter(|code| trump.effective_suit(CARDS_BY_CODE[*code]) == suit)
+            .map(|code| self.counts[code])
+            .sum()
+    }
+
+    /// The larger of the two counts for each card.
+    pub fn union(&self, other: &CardSet) -> CardSet {
+        self.combine(other, self.present | other.present, usize::max)
+    }
+
+    /// The smaller of the two counts for each card.
+    pub fn intersection(&self, other: &CardSet) -> CardSet {
+        self.combine(other, self.present & other.present, usize::min)
+    }
+
+    /// The cards in this set which are left over after taking away `other`.
+    pub fn difference(&self, other: &CardSet) -> CardSet {
+        self.combine(other, self.present, usize::saturating_sub)
+    }
+
+    fn combine(&self, other: &CardSet, present: u64, f: impl Fn(usize, usize) -> usize) -> CardSet {
+        let mut set = CardSet::new();
+        for code in Codes(present) {
+            set.insert_n(
+                CARDS_BY_CODE[code],
+                f(self.counts[code], other.counts[code]),
+            );
+        }
+        set
+    }
+
+    fn codes(&self) -> Codes {
+        Codes(self.present)
+    }
+
+    /// Each distinct card along with its count, in `Card::as_u8` order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'_ Card, &'_ usize)> + Clone + '_ {
+        self.codes()
+            .map(move |code| (&CARDS_BY_CODE[code], &self.counts[code]))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &'_ usize> + '_ {
+        self.iter().map(|(_, count)| count)
+    }
+
+    /// Every card, repeated by its count.
+    pub fn cards(&self) -> impl Iterator<Item = Card> + '_ {
+        self.iter()
+            .flat_map(|(card, count)| (0..*count).map(move |_| *card))
+    }
+
+    pub fn to_counts(&self) -> HashMap<Card, usize> {
+        self.iter().map(|(card, count)| (*card, *count)).collect()
+    }
+}
+
+/// The codes whose bits are set, from lowest to highest.
+#[derive(Clone)]
+struct Codes(u64);
+
+impl Iterator for Codes {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            None
+        } else {
+            let code = self.0.trailing_zeros() as usize;
+            self.0 &= self.0 - 1;
+            Some(code)
+        }
+    }
+}
+
+impl Index<&Card> for CardSet {
+    type Output = usize;
+
+    fn index(&self, card: &Card) -> &usize {
+        &self.counts[card.as_u8() as usize]
+    }
+}
+
+impl<'a> IntoIterator for &'a CardSet {
+    type Item = (&'a Card, &'a usize);
+    type IntoIter = Box<dyn Iterator<Item = (&'a Card, &'a usize)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> Self {
+        Self::from_cards(iter)
+    }
+}
+
+impl From<&HashMap<Card, usize>> for CardSet {
+    fn from(counts: &HashMap<Card, usize>) -> Self {
+        let mut set = CardSet::new();
+        for (card, count) in counts {
+            set.insert_n(*card, *count);
+        }
+        set
+    }
+}
+
+impl fmt::Debug for CardSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl Serialize for CardSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl<'d> Deserialize<'d> for CardSet {
+    fn deserialize<D: serde::Deserializer<'d>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(CardSet::from(&HashMap::<Card, usize>::deserialize(
+            deserializer,
+        )?))
+    }
+}
+
+impl JsonSchema for CardSet {
+    fn schema_name() -> String {
+        HashMap::<Card, usize>::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        HashMap::<Card, usize>::json_schema(gen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CardSet;
+    use crate::types::{
+        cards::*, Card, EffectiveSuit, Number, PermanentTrump, Suit, Trump, FULL_DECK,
+    };
+
+    #[test]
+    fn test_card_set() {
+        let mut set = CardSet::from_cards(vec![S_2, S_2, H_3, Card::BigJoker]);
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.count(S_2), 2);
+        assert!(set.contains(Card::BigJoker));
+        assert!(!set.contains(Card::SmallJoker));
+        assert_eq!(set.get(&H_4), None);
+
+        assert!(set.remove(H_3));
+        assert!(!set.remove(H_3));
+        assert!(!set.contains(H_3));
+        assert_eq!(set.len(), 3);
+
+        let trump = Trump::Standard {
+            suit: Suit::Hearts,
+            number: Number::Two,
+            permanent: PermanentTrump::None,
+        };
+        assert_eq!(set.count_in_suit(trump, EffectiveSuit::Trump), 3);
+        assert_eq!(set.count_in_suit(trump, EffectiveSuit::Spades), 0);
+
+        let other = CardSet::from_cards(vec![S_2, D_5]);
+        assert!(set.contains_all(&CardSet::from_cards(vec![S_2, S_2])));
+        assert!(!set.contains_all(&other));
+        assert_eq!(
+            set.union(&other),
+            CardSet::from_cards(vec![S_2, S_2, D_5, Card::BigJoker])
+        );
+        assert_eq!(set.intersection(&other), CardSet::from_cards(vec![S_2]));
+        assert_eq!(
+            set.difference(&other),
+            CardSet::from_cards(vec![S_2, Card::BigJoker])
+        );
+
+        let deck = CardSet::from_cards(FULL_DECK.iter().copied());
+        assert_eq!(deck.len(), 54);
+        assert_eq!(deck.cards().count(), 54);
+        let json = serde_json::to_string(&deck).unwrap();
+        assert_eq!(deck.to_counts(), serde_json::from_str(&json).unwrap());
+        assert_eq!(serde_json::from_str::<CardSet>(&json).unwrap(), deck);
+    }
+}
@@ -2,13 +2,17 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slog_derive::KV;
 
-use crate::types::{Card, Number, FULL_DECK};
+use crate::types::{Card, Number, Suit, FULL_DECK};
 
 #[derive(Clone, Debug, Serialize, Deserialize, KV, JsonSchema)]
 pub struct Deck {
     pub exclude_small_joker: bool,
     pub exclude_big_joker: bool,
     pub min: Number,
+    /// Whether each (non-excluded) joker appears twice, e.g. for decks which
+    /// come with four jokers.
+    #[serde(default)]
+    pub extra_jokers: bool,
 }
 
 impl slog::Value for Deck {
@@ -28,99 +32,175 @@ impl Default for Deck {
             exclude_small_joker: false,
             exclude_big_joker: false,
             min: Number::Two,
+            extra_jokers: false,
         }
     }
 }
 
 impl Deck {
+    /// The cards that this deck is made up of.
+    pub fn spec(&self) -> DeckSpec {
+        let jokers = if self.extra_jokers { 2 } else { 1 };
+        DeckSpec {
+            suits: Suit::iter().collect(),
+            numbers: std::iter::successors(Some(self.min), |n| n.successor()).collect(),
+            small_jokers: if self.exclude_small_joker { 0 } else { jokers },
+            big_jokers: if self.exclude_big_joker { 0 } else { jokers },
+        }
+    }
+
     pub fn includes_number(&self, number: Number) -> bool {
         number >= self.min
     }
 
     pub fn includes_card(&self, card: Card) -> bool {
-        match card {
-            Card::BigJoker if self.exclude_big_joker => false,
-            Card::SmallJoker if self.exclude_small_joker => false,
-            Card::Suited { number, .. } if !self.includes_number(number) => false,
-            _ => true,
-        }
+        self.count(card) > 0
+    }
+
+    /// The number of copies of `card` in this deck.
+    pub fn count(&self, card: Card) -> usize {
+        self.spec().count(card)
     }
 
     pub fn points(&self) -> usize {
-        let mut pts = 0;
-        if self.includes_number(Number::Five) {
-            pts += 5 * 4;
-        }
-        if self.includes_number(Number::Ten) {
-            pts += 10 * 4;
-        }
-        if self.includes_number(Number::King) {
-            pts += 10 * 4;
-        }
-        pts
+        self.spec().cards().filter_map(|c| c.points()).sum()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.exclude_big_joker && self.exclude_small_joker && self.min == Number::Ace
+        self.spec().is_empty()
     }
 
     pub fn len(&self) -> usize {
-        let mut cards = 54;
-        if self.exclude_big_joker {
-            cards -= 1;
+        self.spec().len()
+    }
+
+    pub fn cards(&'_ self) -> impl Iterator<Item = Card> + '_ {
+        self.spec().into_cards()
+    }
+
+    /// Every card in the given decks, one deck after another.
+    pub fn all_cards(decks: &'_ [Deck]) -> impl Iterator<Item = Card> + '_ {
+        decks.iter().flat_map(|d| d.cards())
+    }
+}
+
+/// Which cards are in a deck, and how many copies of each, without assuming
+/// a standard 54-card deck.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DeckSpec {
+    /// The suits present in the deck.
+    pub suits: Vec<Suit>,
+    /// The ranks present in each of the suits.
+    pub numbers: Vec<Number>,
+    pub small_jokers: usize,
+    pub big_jokers: usize,
+}
+
+impl Default for DeckSpec {
+    fn default() -> Self {
+        Deck::default().spec()
+    }
+}
+
+impl DeckSpec {
+    pub fn includes_suit(&self, suit: Suit) -> bool {
+        self.suits.contains(&suit)
+    }
+
+    pub fn includes_number(&self, number: Number) -> bool {
+        self.numbers.contains(&number)
+    }
+
+    /// The number of copies of `card` in this deck.
+    pub fn count(&self, card: Card) -> usize {
+        match card {
+            Card::Unknown => 0,
+            Card::SmallJoker => self.small_jokers,
+            Card::BigJoker => self.big_jokers,
+            Card::Suited { suit, number }
+                if self.includes_suit(suit) && self.includes_number(number) =>
+            {
+                1
+            }
+            Card::Suited { .. } => 0,
         }
-        if self.exclude_small_joker {
-            cards -= 1;
+    }
+
+    /// The lowest rank in the deck, if there are any suited cards.
+    pub fn min_number(&self) -> Option<Number> {
+        if self.suits.is_empty() {
+            None
+        } else {
+            self.numbers.iter().min().copied()
         }
+    }
 
-        let mut n = Number::Two;
-        while n < self.min {
-            cards -= 4;
-            if let Some(nn) = n.successor() {
-                n = nn;
-            } else {
-                break;
-            }
+    pub fn joker_counts(&self) -> JokerCounts {
+        JokerCounts {
+            small: self.small_jokers,
+            big: self.big_jokers,
         }
+    }
 
-        cards
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.suits.len() * self.numbers.len() + self.small_jokers + self.big_jokers
     }
 
+    /// The cards in the deck, in `FULL_DECK` order.
     pub fn cards(&'_ self) -> impl Iterator<Item = Card> + '_ {
-        DeckIterator {
-            deck: self,
-            index: 0,
-        }
+        FULL_DECK
+            .iter()
+            .flat_map(move |card| (0..self.count(*card)).map(move |_| *card))
+    }
+
+    pub fn into_cards(self) -> impl Iterator<Item = Card> {
+        FULL_DECK
+            .iter()
+            .flat_map(move |card| (0..self.count(*card)).map(move |_| *card))
     }
 }
 
-pub struct DeckIterator<'d> {
-    deck: &'d Deck,
-    index: usize,
+/// The number of each kind of joker in play, e.g. to decide which joker bids
+/// are allowed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct JokerCounts {
+    pub small: usize,
+    pub big: usize,
 }
 
-impl<'d> Iterator for DeckIterator<'d> {
-    type Item = Card;
-    fn next(&mut self) -> Option<Card> {
-        loop {
-            if self.index >= FULL_DECK.len() {
-                break None;
-            }
-            let card = FULL_DECK[self.index];
-            self.index += 1;
+impl JokerCounts {
+    pub fn of(decks: &[Deck]) -> Self {
+        Self::of_specs(decks.iter().map(Deck::spec))
+    }
 
-            if self.deck.includes_card(card) {
-                break Some(card);
-            }
+    pub fn of_specs(specs: impl IntoIterator<Item = DeckSpec>) -> Self {
+        specs.into_iter().fold(
+            JokerCounts { small: 0, big: 0 },
+            |JokerCounts { small, big }, spec| JokerCounts {
+                small: small + spec.small_jokers,
+                big: big + spec.big_jokers,
+            },
+        )
+    }
+
+    /// The joker counts for `num_decks` standard decks.
+    pub fn standard(num_decks: usize) -> Self {
+        JokerCounts {
+            small: num_decks,
+            big: num_decks,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::types::Number;
+    use crate::types::{cards, Card, Number, Suit};
 
-    use super::Deck;
+    use super::{Deck, DeckSpec, JokerCounts};
 
     #[test]
     fn test_deck_points_calc() {
@@ -161,4 +241,100 @@ mod tests {
             assert_eq!(deck.cards().flat_map(|c| c.points()).sum::<usize>(), points);
         }
     }
+
+    #[test]
+    fn test_joker_counts() {
+        let four_jokers = Deck {
+            extra_jokers: true,
+            ..Default::default()
+        };
+        assert_eq!(four_jokers.len(), 56);
+        assert_eq!(four_jokers.cards().count(), 56);
+        assert_eq!(four_jokers.count(Card::BigJoker), 2);
+        assert_eq!(four_jokers.points(), 100);
+
+        let no_jokers = Deck {
+            exclude_small_joker: true,
+            exclude_big_joker: true,
+            ..Default::default()
+        };
+        let two_small_jokers = Deck {
+            exclude_big_joker: true,
+            extra_jokers: true,
+            ..Default::default()
+        };
+        assert_eq!(two_small_jokers.len(), 54);
+
+        let decks = vec![four_jokers, no_jokers, two_small_jokers];
+        assert_eq!(JokerCounts::of(&decks), JokerCounts { small: 4, big: 2 });
+        assert_eq!(
+            Deck::all_cards(&decks)
+                .filter(|c| *c == Card::SmallJoker)
+                .count(),
+            4
+        );
+        assert_eq!(
+            JokerCounts::of(&[Deck::default()]),
+            JokerCounts::standard(1)
+        );
+    }
+
+    #[test]
+    fn test_all_cards() {
+        assert_eq!(Card::all().count(), 54);
+        assert!(Card::all().all(|c| Deck::default().includes_card(c)));
+
+        let decks = vec![
+            Deck::default(),
+            Deck {
+                exclude_small_joker: true,
+                min: Number::Five,
+                ..Default::default()
+            },
+        ];
+        let cards = Deck::all_cards(&decks).collect::<Vec<_>>();
+        assert_eq!(cards.len(), 54 + 41);
+        assert_eq!(cards.iter().filter(|c| **c == Card::SmallJoker).count(), 1);
+        assert_eq!(cards.iter().filter(|c| **c == Card::BigJoker).count(), 2);
+    }
+
+    #[test]
+    fn test_deck_spec() {
+        let spec = Deck {
+            min: Number::Nine,
+            exclude_big_joker: true,
+            ..Default::default()
+        }
+        .spec();
+        assert_eq!(spec.numbers.len(), 6);
+        assert_eq!(spec.min_number(), Some(Number::Nine));
+        assert_eq!(spec.joker_counts(), JokerCounts { small: 1, big: 0 });
+        assert_eq!(spec.len(), 25);
+        assert_eq!(spec.cards().count(), 25);
+        assert_eq!(DeckSpec::default(), Deck::default().spec());
+
+        let short = DeckSpec {
+            suits: vec![Suit::Spades, Suit::Hearts],
+            numbers: vec![Number::Ten, Number::Ace],
+            small_jokers: 0,
+            big_jokers: 2,
+        };
+        assert_eq!(short.len(), 6);
+        assert_eq!(
+            short.clone().into_cards().collect::<Vec<_>>(),
+            vec![
+                cards::H_A,
+                cards::H_10,
+                cards::S_A,
+                cards::S_10,
+                Card::BigJoker,
+                Card::BigJoker
+            ]
+        );
+        assert_eq!(short.count(cards::D_10), 0);
+        assert_eq!(
+            JokerCounts::of_specs(vec![short, DeckSpec::default()]),
+            JokerCounts { small: 1, big: 3 }
+        );
+    }
 }
@@ -2,12 +2,52 @@ use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::iter::Peekable;
 
 use anyhow::Error;
+use itertools::Either;
+
+use crate::ordered_card::{AdjacentTupleSizes, MatchingCards, OrderedCard, PlayRequirements};
+use crate::types::AdjacencyPolicy;
+
+/// Enumerates the concrete assignments of `cards` which satisfy all of the
+/// `requirements`, highest cards first.
+///
+/// Each assignment has one entry per requirement, in the same order as the
+/// requirements, listing the cards (and how many copies of each) used for it.
+/// Requirements which can't be met by any cards, e.g. empty tuples, yield no
+/// assignments.
+pub fn find_card_assignments(
+    cards: &BTreeMap<OrderedCard, usize>,
+    requirements: &PlayRequirements,
+    adjacency_policy: AdjacencyPolicy,
+) -> impl Iterator<Item = Vec<MatchingCards>> {
+    if requirements.iter().any(|r| r.is_empty() || r.contains(&0)) {
+        return Either::Left(std::iter::empty());
+    }
+    let cards = cards
+        .iter()
+        .filter(|(_, ct)| **ct > 0)
+        .map(|(c, ct)| (*c, *ct))
+        .collect();
+    Either::Right(find_format_matches(
+        requirements.clone(),
+        cards,
+        adjacency_policy,
+    ))
+}
 
-use crate::ordered_card::{AdjacentTupleSizes, MatchingCards, OrderedCard};
+/// Finds the highest assignment of `cards` which satisfies all of the
+/// `requirements`, if any exists.
+pub fn find_card_assignment(
+    cards: &BTreeMap<OrderedCard, usize>,
+    requirements: &PlayRequirements,
+    adjacency_policy: AdjacencyPolicy,
+) -> Option<Vec<MatchingCards>> {
+    find_card_assignments(cards, requirements, adjacency_policy).next()
+}
 
 pub fn find_format_matches(
     format: Vec<AdjacentTupleSizes>,
     cards: BTreeMap<OrderedCard, usize>,
+    adjacency_policy: AdjacencyPolicy,
 ) -> impl Iterator<Item = Vec<MatchingCards>> {
     let mut queue = VecDeque::new();
 
@@ -19,6 +59,7 @@ pub fn find_format_matches(
         format_seq: format,
         queue,
         cards,
+        adjacency_policy,
         visited: HashSet::new(),
     }
 }
@@ -122,6 +163,7 @@ struct FormatMatchIterator {
     format: BTreeMap<AdjacentTupleSizes, usize>,
     format_seq: Vec<AdjacentTupleSizes>,
     cards: BTreeMap<OrderedCard, usize>,
+    adjacency_policy: AdjacencyPolicy,
     visited: HashSet<FormatMatchState>,
     queue: VecDeque<QueueItem>,
 }
@@ -184,7 +226,7 @@ impl FormatMatchIterator {
                     // outside the trump suit -- e.g. if the trump
                     // number is 2, there are three potential 2x2
                     // tractors starting at A.
-                    for s in next_card.successor() {
+                    for s in next_card.successor(self.adjacency_policy) {
                         stk.push((s, remaining_tuples.clone(), seq_so_far.clone()));
                     }
                 }
@@ -317,13 +359,14 @@ mod tests {
     use std::collections::BTreeMap;
 
     use crate::ordered_card::OrderedCard;
-    use crate::types::{cards::*, Card, Number, Suit, Trump};
+    use crate::types::{cards::*, AdjacencyPolicy, Card, Number, PermanentTrump, Suit, Trump};
 
-    use super::find_format_matches;
+    use super::{find_card_assignment, find_card_assignments, find_format_matches};
 
     const TRUMP: Trump = Trump::Standard {
         number: Number::Four,
         suit: Suit::Spades,
+        permanent: PermanentTrump::None,
     };
     macro_rules! oc {
         ($card:expr) => {
@@ -345,7 +388,8 @@ mod tests {
         .into_iter()
         .collect();
 
-        let v = find_format_matches(vec![vec![1]], counts.clone()).collect::<Vec<_>>();
+        let v = find_format_matches(vec![vec![1]], counts.clone(), AdjacencyPolicy::default())
+            .collect::<Vec<_>>();
 
         assert_eq!(
             v,
@@ -357,7 +401,8 @@ mod tests {
             ]
         );
 
-        let v = find_format_matches(vec![vec![2]], counts).collect::<Vec<_>>();
+        let v = find_format_matches(vec![vec![2]], counts, AdjacencyPolicy::default())
+            .collect::<Vec<_>>();
 
         assert_eq!(
             v,
@@ -380,7 +425,12 @@ mod tests {
         .into_iter()
         .collect();
 
-        let v = find_format_matches(vec![vec![1], vec![1]], counts.clone()).collect::<Vec<_>>();
+        let v = find_format_matches(
+            vec![vec![1], vec![1]],
+            counts.clone(),
+            AdjacencyPolicy::default(),
+        )
+        .collect::<Vec<_>>();
         assert_eq!(
             v[0],
             vec![vec![(oc!(Card::BigJoker), 1)], vec![(oc!(S_5), 1)]]
@@ -397,7 +447,8 @@ mod tests {
         // 2, 2
         assert_eq!(v.len(), 9);
 
-        let v = find_format_matches(vec![vec![2], vec![2]], counts).collect::<Vec<_>>();
+        let v = find_format_matches(vec![vec![2], vec![2]], counts, AdjacencyPolicy::default())
+            .collect::<Vec<_>>();
 
         // There are 3 unique choices of two pairsA
         // 55, 33
@@ -418,7 +469,8 @@ mod tests {
         .into_iter()
         .collect();
 
-        let v = find_format_matches(vec![vec![2, 2]], counts).collect::<Vec<_>>();
+        let v = find_format_matches(vec![vec![2, 2]], counts, AdjacencyPolicy::default())
+            .collect::<Vec<_>>();
 
         assert_eq!(
             v,
@@ -441,7 +493,12 @@ mod tests {
         .into_iter()
         .collect();
 
-        let v = find_format_matches(vec![vec![2, 2], vec![2, 2]], counts).collect::<Vec<_>>();
+        let v = find_format_matches(
+            vec![vec![2, 2], vec![2, 2]],
+            counts,
+            AdjacencyPolicy::default(),
+        )
+        .collect::<Vec<_>>();
 
         assert_eq!(
             v[0],
@@ -472,7 +529,7 @@ mod tests {
         .collect();
         let fmt = vec![vec![4, 4], vec![3, 3], vec![1], vec![3]];
 
-        let v = find_format_matches(fmt, counts).collect::<Vec<_>>();
+        let v = find_format_matches(fmt, counts, AdjacencyPolicy::default()).collect::<Vec<_>>();
 
         assert_eq!(
             v[0],
@@ -502,7 +559,7 @@ mod tests {
         .collect();
         let fmt = (0..10).map(|_| vec![1]).collect();
 
-        let v = find_format_matches(fmt, counts).collect::<Vec<_>>();
+        let v = find_format_matches(fmt, counts, AdjacencyPolicy::default()).collect::<Vec<_>>();
 
         assert_eq!(
             v[0],
@@ -521,4 +578,58 @@ mod tests {
         );
         assert_eq!(v.len(), 17865);
     }
+
+    #[test]
+    fn test_find_card_assignments() {
+        let counts = vec![
+            (oc!(S_2), 2),
+            (oc!(S_3), 3),
+            (oc!(S_5), 1),
+            (oc!(S_6), 0),
+            (oc!(Card::BigJoker), 1),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            find_card_assignment(
+                &counts,
+                &vec![vec![2, 2], vec![1]],
+                AdjacencyPolicy::default()
+            ),
+            Some(vec![
+                vec![(oc!(S_2), 2), (oc!(S_3), 2)],
+                vec![(oc!(Card::BigJoker), 1)]
+            ])
+        );
+        assert_eq!(
+            find_card_assignments(
+                &counts,
+                &vec![vec![1], vec![2, 2]],
+                AdjacencyPolicy::default()
+            )
+            .count(),
+            3
+        );
+        assert_eq!(
+            find_card_assignment(
+                &counts,
+                &vec![vec![2], vec![2], vec![2]],
+                AdjacencyPolicy::default()
+            ),
+            None
+        );
+        assert_eq!(
+            find_card_assignment(&counts, &vec![vec![1, 1, 1, 1]], AdjacencyPolicy::default()),
+            None
+        );
+        assert_eq!(
+            find_card_assignment(&counts, &vec![vec![]], AdjacencyPolicy::default()),
+            None
+        );
+        assert_eq!(
+            find_card_assignment(&counts, &vec![vec![0]], AdjacencyPolicy::default()),
+            None
+        );
+    }
 }
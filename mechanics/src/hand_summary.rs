@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::card_set::CardSet;
+use crate::ordered_card::OrderedCard;
+use crate::scoring::PointSchedule;
+use crate::types::{AdjacencyPolicy, EffectiveSuit, Suit, Trump};
+
+/// Statistics about the cards a player holds in one effective suit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SuitSummary {
+    pub suit: EffectiveSuit,
+    pub count: usize,
+    pub points: usize,
+    /// The most distinct cards which are adjacent to one another, e.g. a
+    /// hand with 5, 6, 7 and 9 has a run of three.
+    pub longest_run: usize,
+    /// The longest run in which every card is at least a pair, i.e. the
+    /// longest tractor of pairs that could be played.
+    pub longest_pair_run: usize,
+    pub is_void: bool,
+}
+
+/// Statistics about a player's hand, given the current trump.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct HandSummary {
+    /// One entry for trump, and one for each suit which isn't trump.
+    pub suits: Vec<SuitSummary>,
+    pub count: usize,
+    pub points: usize,
+}
+
+impl HandSummary {
+    pub fn new(
+        hand: &CardSet,
+        trump: Trump,
+        point_schedule: &PointSchedule,
+        adjacency_policy: AdjacencyPolicy,
+    ) -> Self {
+        let suits = std::iter::once(EffectiveSuit::Trump)
+            .chain(
+                Suit::iter()
+                    .filter(|s| trump.suit() != Some(*s))
+                    .map(EffectiveSuit::from),
+            )
+            .map(|suit| {
+                let in_suit = OrderedCard::make_map(
+                    hand.cards().filter(|c| trump.effective_suit(*c) == suit),
+                    trump,
+                );
+                let count = in_suit.values().sum::<usize>();
+                SuitSummary {
+                    suit,
+                    count,
+                    points: point_schedule.total(
+                        in_suit
+                            .iter()
+                            .flat_map(|(c, ct)| (0..*ct).map(move |_| c.card)),
+                    ),
+                    longest_run: longest_run(&in_suit, 1, adjacency_policy),
+                    longest_pair_run: longest_run(&in_suit, 2, adjacency_policy),
+                    is_void: count == 0,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        HandSummary {
+            count: suits.iter().map(|s| s.count).sum(),
+            points: suits.iter().map(|s| s.points).sum(),
+            suits,
+        }
+    }
+
+    pub fn suit(&self, suit: EffectiveSuit) -> Option<&'_ SuitSummary> {
+        self.suits.iter().find(|s| s.suit == suit)
+    }
+}
+
+/// The length of the longest chain of adjacent cards which each have at least
+/// `min_count` copies.
+fn longest_run(
+    in_suit: &BTreeMap<OrderedCard, usize>,
+    min_count: usize,
+    adjacency_policy: AdjacencyPolicy,
+) -> usize {
+    // Successors are always higher than the card they follow, so walking the
+    // cards in order sees every card's predecessors before the card itself.
+    let mut run_lengths = BTreeMap::new();
+    for (card, count) in in_suit {
+        if *count < min_count {
+            continue;
+        }
+        let length = *run_lengths.entry(*card).or_insert(1);
+        for next in card.successor(adjacency_policy) {
+            if in_suit.get(&next).is_some_and(|ct| *ct >= min_count) {
+                let next_length = run_lengths.entry(next).or_insert(1);
+                *next_length = (*next_length).max(length + 1);
+            }
+        }
+    }
+    run_lengths.into_values().max().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HandSummary;
+    use crate::card_set::CardSet;
+    use crate::scoring::PointSchedule;
+    use crate::types::{
+        cards::*, AdjacencyPolicy, Card, EffectiveSuit, Number, PermanentTrump, Suit, Trump,
+    };
+
+    #[test]
+    fn test_hand_summary() {
+        let trump = Trump::Standard {
+            suit: Suit::Spades,
+            number: Number::Four,
+            permanent: PermanentTrump::None,
+        };
+        let hand = CardSet::from_cards(vec![
+            H_5,
+            H_5,
+            H_6,
+            H_6,
+            H_7,
+            H_9,
+            D_K,
+            S_2,
+            H_4,
+            Card::BigJoker,
+        ]);
+        let summary = HandSummary::new(
+            &hand,
+            trump,
+            &PointSchedule::default(),
+            AdjacencyPolicy::default(),
+        );
+        assert_eq!(summary.suits.len(), 4);
+        assert_eq!(summary.count, 10);
+        assert_eq!(summary.points, 20);
+
+        let hearts = summary.suit(EffectiveSuit::Hearts).unwrap();
+        assert_eq!(hearts.count, 6);
+        assert_eq!(hearts.points, 10);
+        assert_eq!(hearts.longest_run, 3);
+        assert_eq!(hearts.longest_pair_run, 2);
+
+        let trump_summary = summary.suit(EffectiveSuit::Trump).unwrap();
+        assert_eq!(trump_summary.count, 3);
+        assert_eq!(trump_summary.longest_run, 1);
+
+        assert!(summary.suit(EffectiveSuit::Clubs).unwrap().is_void);
+        assert!(summary.suit(EffectiveSuit::Spades).is_none());
+    }
+}
@@ -1,18 +1,22 @@
-use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::types::{Card, EffectiveSuit, PlayerID, Trump};
+use crate::card_set::CardSet;
+use crate::deck::DeckSpec;
+use crate::sorting::{sort_cards, SortOrder};
+use crate::types::{Card, EffectiveSuit, PermanentTrump, PlayerID, Trump};
 
 #[derive(Error, Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub enum HandError {
     #[error("unknown player ID {:?}", _0)]
     UnknownPlayerID(PlayerID),
-    #[error("cards not found in hand")]
-    CardsNotFound,
+    #[error("cards not found in hand: {}", describe_missing(missing))]
+    CardsNotFound { missing: Vec<MissingCard> },
     #[error("cards cannot be played")]
     CardsNotPlayable,
     #[error("unknown cards can't be added to hand")]
@@ -21,35 +25,188 @@ pub enum HandError {
     TrumpNotSet,
 }
 
+impl HandError {
+    /// The cards which weren't in the hand, if that's what went wrong.
+    pub fn missing_cards(&self) -> &'_ [MissingCard] {
+        match self {
+            HandError::CardsNotFound { missing } => missing,
+            _ => &[],
+        }
+    }
+}
+
+/// A card which was asked for, but which the hand doesn't have enough copies
+/// of.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct MissingCard {
+    pub card: Card,
+    pub required: usize,
+    pub available: usize,
+}
+
+fn describe_missing(missing: &[MissingCard]) -> String {
+    missing
+        .iter()
+        .map(|m| format!("{} (needed {}, have {})", m.card, m.required, m.available))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A way in which a set of hands couldn't have been dealt from some decks.
+#[derive(Error, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum HandViolation {
+    #[error("{count} copies of {card} are in hands, but the decks only have {allowed}")]
+    TooManyCopies {
+        card: Card,
+        count: usize,
+        allowed: usize,
+    },
+    #[error("expected {expected} cards in hands, but found {actual}")]
+    WrongTotal { expected: usize, actual: usize },
+}
+
+/// The cards which entered and left a player's hand between two states.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct HandDiff {
+    pub added: CardSet,
+    pub removed: CardSet,
+}
+
+impl HandDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Hands {
-    hands: HashMap<PlayerID, HashMap<Card, usize>>,
+    hands: HashMap<PlayerID, CardSet>,
     trump: Option<Trump>,
+    /// Which physical deck each card in the hands was dealt from, where known.
+    /// This is only for display (e.g. deck-back colors), and never affects
+    /// the game logic: identical cards from different decks are equivalent.
+    #[serde(default)]
+    deck_indices: HashMap<PlayerID, HashMap<Card, Vec<usize>>>,
+    /// The cards in each hand which have been shown to the table, e.g. when
+    /// bidding. These stay visible when the rest of the hand is redacted.
+    #[serde(default)]
+    revealed: HashMap<PlayerID, CardSet>,
+    #[serde(skip)]
+    suit_counts: SuitCountCache,
+}
+
+const NUM_EFFECTIVE_SUITS: usize = EffectiveSuit::Trump as usize + 1;
+
+/// The number of cards each player holds in each effective suit, along with
+/// the trump they were counted under. Entries are filled in on demand and
+/// dropped whenever the player's hand changes.
+#[derive(Default)]
+struct SuitCountCache(RwLock<HashMap<PlayerID, (Trump, [usize; NUM_EFFECTIVE_SUITS])>>);
+
+impl SuitCountCache {
+    fn invalidate(&mut self, id: PlayerID) {
+        self.0.get_mut().unwrap().remove(&id);
+    }
+
+    fn clear(&mut self) {
+        self.0.get_mut().unwrap().clear();
+    }
+}
+
+impl Clone for SuitCountCache {
+    fn clone(&self) -> Self {
+        SuitCountCache(RwLock::new(self.0.read().unwrap().clone()))
+    }
+}
+
+impl fmt::Debug for SuitCountCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.read().unwrap().iter())
+            .finish()
+    }
 }
 
 impl Hands {
     pub fn new(players: impl IntoIterator<Item = PlayerID>) -> Self {
         Hands {
-            hands: players.into_iter().map(|id| (id, HashMap::new())).collect(),
+            hands: players.into_iter().map(|id| (id, CardSet::new())).collect(),
             trump: None,
+            deck_indices: HashMap::new(),
+            revealed: HashMap::new(),
+            suit_counts: SuitCountCache::default(),
         }
     }
 
     pub fn destructively_redact_except_for_player(&mut self, id: PlayerID) {
+        self.destructively_redact_except_for_players(&[id]);
+    }
+
+    /// Redacts every hand other than those of `ids`, e.g. the viewer and any
+    /// players who have chosen to show their cards. The revealed portion of
+    /// each redacted hand is kept.
+    pub fn destructively_redact_except_for_players(&mut self, ids: &[PlayerID]) {
         for (pid, cards) in &mut self.hands {
-            if *pid != id {
-                let count = cards.values().sum();
-                cards.clear();
-                cards.insert(Card::Unknown, count);
+            if !ids.contains(pid) {
+                let count = cards.len();
+                *cards = self.revealed.get(pid).cloned().unwrap_or_default();
+                let concealed = count - cards.len();
+                cards.insert_n(Card::Unknown, concealed);
             }
         }
+        self.deck_indices.retain(|pid, _| ids.contains(pid));
+        self.suit_counts.clear();
+    }
+
+    /// The decks that the cards in the player's hand were dealt from, for the
+    /// cards where that's known.
+    pub fn deck_indices(&self, id: PlayerID) -> Option<&'_ HashMap<Card, Vec<usize>>> {
+        self.deck_indices.get(&id)
     }
 
-    pub fn get(&self, id: PlayerID) -> Result<&'_ HashMap<Card, usize>, HandError> {
+    pub fn get(&self, id: PlayerID) -> Result<&'_ CardSet, HandError> {
         self.exists(id)?;
         Ok(&self.hands[&id])
     }
 
+    /// Marks the given cards in the player's hand as shown to the table.
+    /// Cards which were already revealed aren't revealed a second time, so
+    /// e.g. reinforcing a single into a pair only reveals one more card.
+    pub fn reveal(
+        &mut self,
+        id: PlayerID,
+        cards: impl IntoIterator<Item = Card> + Clone,
+    ) -> Result<(), HandError> {
+        self.contains(id, cards.clone())?;
+        let revealed = self.revealed.entry(id).or_default();
+        *revealed = revealed.union(&CardSet::from_cards(cards));
+        Ok(())
+    }
+
+    /// Hides the revealed copies of `card` in the player's hand beyond the
+    /// first `keep`, e.g. when the bid which showed them is taken back.
+    pub fn unreveal(&mut self, id: PlayerID, card: Card, keep: usize) {
+        if let Some(revealed) = self.revealed.get_mut(&id) {
+            while revealed.count(card) > keep {
+                revealed.remove(card);
+            }
+        }
+    }
+
+    /// The cards in the player's hand which have been shown to the table.
+    pub fn revealed(&self, id: PlayerID) -> Option<&'_ CardSet> {
+        self.revealed.get(&id)
+    }
+
+    /// The cards in the player's hand which haven't been shown to the table.
+    pub fn concealed(&self, id: PlayerID) -> Result<CardSet, HandError> {
+        let hand = self.get(id)?;
+        Ok(match self.revealed.get(&id) {
+            Some(revealed) => hand.difference(revealed),
+            None => hand.clone(),
+        })
+    }
+
     pub fn set_trump(&mut self, trump: Trump) {
         self.trump = Some(trump);
     }
@@ -76,47 +233,164 @@ impl Hands {
     ) -> Result<(), HandError> {
         self.exists(id)?;
 
-        let required = Card::count(cards);
-
-        for (card, number) in required {
-            if self.hands[&id].get(&card).cloned().unwrap_or(0) < number {
-                return Err(HandError::CardsNotFound);
-            }
+        let hand = &self.hands[&id];
+        let required = CardSet::from_cards(cards);
+        if hand.contains_all(&required) {
+            Ok(())
+        } else {
+            Err(HandError::CardsNotFound {
+                missing: required
+                    .difference(hand)
+                    .iter()
+                    .map(|(card, _)| MissingCard {
+                        card: *card,
+                        required: required.count(*card),
+                        available: hand.count(*card),
+                    })
+                    .collect(),
+            })
         }
-        Ok(())
     }
 
-    pub fn is_void(&self, id: PlayerID, suit: EffectiveSuit) -> Result<bool, HandError> {
-        self.exists(id)?;
-        let trump = self.trump()?;
+    /// Whether the player has no cards left in `suit`.
+    pub fn is_void(
+        &self,
+        id: PlayerID,
+        suit: EffectiveSuit,
+        trump: Trump,
+    ) -> Result<bool, HandError> {
+        Ok(self.count_in_effective_suit(id, suit, trump)? == 0)
+    }
 
-        for (card, number) in &self.hands[&id] {
-            if *number > 0 && trump.effective_suit(*card) == suit {
-                return Ok(false);
+    /// The number of cards the player has in `suit`. The counts for every suit
+    /// are computed together and cached until the player's hand changes, so
+    /// repeated queries during a trick are cheap.
+    pub fn count_in_effective_suit(
+        &self,
+        id: PlayerID,
+        suit: EffectiveSuit,
+        trump: Trump,
+    ) -> Result<usize, HandError> {
+        self.exists(id)?;
+        if let Some((cached_trump, counts)) = self.suit_counts.0.read().unwrap().get(&id) {
+            if *cached_trump == trump {
+                return Ok(counts[suit as usize]);
             }
         }
 
-        Ok(true)
+        let mut counts = [0; NUM_EFFECTIVE_SUITS];
+        for (card, count) in &self.hands[&id] {
+            counts[trump.effective_suit(*card) as usize] += *count;
+        }
+        self.suit_counts
+            .0
+            .write()
+            .unwrap()
+            .insert(id, (trump, counts));
+        Ok(counts[suit as usize])
     }
 
-    pub fn counts(&self, id: PlayerID) -> Option<&'_ HashMap<Card, usize>> {
+    pub fn counts(&self, id: PlayerID) -> Option<&'_ CardSet> {
         self.hands.get(&id)
     }
 
     pub fn is_empty(&self) -> bool {
-        !self.hands.values().any(|h| h.values().any(|c| *c > 0))
+        self.hands.values().all(|h| h.is_empty())
     }
 
-    pub fn _get_cards(&self, id: PlayerID) -> Result<Vec<Card>, HandError> {
-        self.exists(id)?;
-        let mut cards = Card::cards(self.hands[&id].iter())
-            .copied()
-            .collect::<Vec<Card>>();
-        if let Some(trump) = self.trump {
-            cards.sort_by(|a, b| trump.compare(*a, *b));
+    /// Checks that these hands could have been dealt from `specs`, with
+    /// `kitty_size` cards set aside: no card may be held more times than the
+    /// decks contain it, and every other card must be in someone's hand.
+    pub fn validate_against(
+        &self,
+        specs: &[DeckSpec],
+        kitty_size: usize,
+    ) -> Result<(), Vec<HandViolation>> {
+        let mut held = CardSet::new();
+        for hand in self.hands.values() {
+            for (card, count) in hand {
+                held.insert_n(*card, *count);
+            }
+        }
+
+        let mut violations = held
+            .iter()
+            .filter_map(|(card, count)| {
+                let allowed = specs.iter().map(|s| s.count(*card)).sum();
+                if *count > allowed {
+                    Some(HandViolation::TooManyCopies {
+                        card: *card,
+                        count: *count,
+                        allowed,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let expected = specs
+            .iter()
+            .map(|s| s.len())
+            .sum::<usize>()
+            .saturating_sub(kitty_size);
+        if held.len() != expected {
+            violations.push(HandViolation::WrongTotal {
+                expected,
+                actual: held.len(),
+            });
+        }
+
+        if violations.is_empty() {
+            Ok(())
         } else {
-            cards.sort_by(|a, b| Trump::NoTrump { number: None }.compare(*a, *b));
+            Err(violations)
         }
+    }
+
+    /// The cards added to and removed from each player's hand going from
+    /// `before` to `after`. Players whose hands didn't change are omitted, and
+    /// a player missing from either side is treated as having no cards there.
+    pub fn diff(before: &Hands, after: &Hands) -> HashMap<PlayerID, HandDiff> {
+        let empty = CardSet::new();
+        before
+            .hands
+            .keys()
+            .chain(
+                after
+                    .hands
+                    .keys()
+                    .filter(|id| !before.hands.contains_key(id)),
+            )
+            .filter_map(|id| {
+                let old = before.hands.get(id).unwrap_or(&empty);
+                let new = after.hands.get(id).unwrap_or(&empty);
+                let diff = HandDiff {
+                    added: new.difference(old),
+                    removed: old.difference(new),
+                };
+                if diff.is_empty() {
+                    None
+                } else {
+                    Some((*id, diff))
+                }
+            })
+            .collect()
+    }
+
+    pub fn _get_cards(&self, id: PlayerID) -> Result<Vec<Card>, HandError> {
+        self.sorted_cards(id, &SortOrder::default())
+    }
+
+    /// The player's cards, sorted for display according to `order`.
+    pub fn sorted_cards(&self, id: PlayerID, order: &SortOrder) -> Result<Vec<Card>, HandError> {
+        self.exists(id)?;
+        let mut cards = self.hands[&id].cards().collect::<Vec<Card>>();
+        let trump = self.trump.unwrap_or(Trump::NoTrump {
+            number: None,
+            permanent: PermanentTrump::None,
+        });
+        sort_cards(&mut cards, trump, order);
         Ok(cards)
     }
 
@@ -133,7 +407,31 @@ impl Hands {
             }
         }
         for card in cards {
-            *hand.entry(card).or_insert(0) += 1;
+            hand.insert(card);
+        }
+        self.suit_counts.invalidate(id);
+        Ok(())
+    }
+
+    /// Like `add`, but also records which deck each card came from.
+    pub fn add_from_decks(
+        &mut self,
+        id: PlayerID,
+        cards: impl IntoIterator<Item = (Card, Option<usize>)> + Clone,
+    ) -> Result<(), HandError> {
+        self.add(
+            id,
+            cards
+                .clone()
+                .into_iter()
+                .map(|(card, _)| card)
+                .collect::<Vec<_>>(),
+        )?;
+        let deck_indices = self.deck_indices.entry(id).or_default();
+        for (card, deck_index) in cards {
+            if let Some(deck_index) = deck_index {
+                deck_indices.entry(card).or_default().push(deck_index);
+            }
         }
         Ok(())
     }
@@ -143,27 +441,53 @@ impl Hands {
         id: PlayerID,
         cards: impl IntoIterator<Item = Card> + Clone,
     ) -> Result<(), HandError> {
+        self.remove_from_decks(id, cards)?;
+        Ok(())
+    }
+
+    /// Like `remove`, but also returns the decks that the removed cards came
+    /// from, in the same order as `cards`.
+    pub fn remove_from_decks(
+        &mut self,
+        id: PlayerID,
+        cards: impl IntoIterator<Item = Card> + Clone,
+    ) -> Result<Vec<Option<usize>>, HandError> {
         self.contains(id, cards.clone())?;
 
         let hand = self.hands.get_mut(&id).unwrap();
+        let mut deck_indices = self.deck_indices.get_mut(&id);
+        let mut revealed = self.revealed.get_mut(&id);
+        let mut removed = vec![];
         for card in cards {
-            if let Entry::Occupied(mut o) = hand.entry(card) {
-                *o.get_mut() -= 1;
-                if *o.get() == 0 {
-                    o.remove();
-                }
+            hand.remove(card);
+            // Take revealed copies first, so that the table doesn't learn
+            // anything new about the cards left behind.
+            if let Some(revealed) = revealed.as_mut() {
+                revealed.remove(card);
             }
+            removed.push(
+                deck_indices
+                    .as_mut()
+                    .and_then(|d| d.get_mut(&card))
+                    .and_then(|d| d.pop()),
+            );
         }
-        Ok(())
+        if let Some(deck_indices) = deck_indices {
+            deck_indices.retain(|_, d| !d.is_empty());
+        }
+        self.suit_counts.invalidate(id);
+        Ok(removed)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Hands;
+    use super::{HandDiff, HandViolation, Hands, MissingCard};
+    use crate::card_set::CardSet;
+    use crate::deck::Deck;
     use crate::types::{
-        cards::{S_2, S_3, S_4, S_5},
-        PlayerID,
+        cards::{H_4, H_5, S_2, S_3, S_4, S_5},
+        Card, EffectiveSuit, Number, PermanentTrump, PlayerID, Suit, Trump,
     };
 
     const P1: PlayerID = PlayerID(1);
@@ -191,4 +515,195 @@ mod tests {
         hands.remove(P3, vec![S_2, S_3, S_4, S_5]).unwrap_err();
         assert_eq!(hands._get_cards(P3).unwrap(), hands._get_cards(P4).unwrap());
     }
+
+    #[test]
+    fn test_missing_cards() {
+        let mut hands = Hands::new(vec![P1]);
+        hands.add(P1, vec![S_2, S_3]).unwrap();
+        let err = hands.remove(P1, vec![S_2, S_2, S_3, S_5]).unwrap_err();
+        assert_eq!(
+            err.missing_cards(),
+            &[
+                MissingCard {
+                    card: S_2,
+                    required: 2,
+                    available: 1,
+                },
+                MissingCard {
+                    card: S_5,
+                    required: 1,
+                    available: 0,
+                },
+            ]
+        );
+        assert_eq!(hands._get_cards(P1).unwrap(), vec![S_2, S_3]);
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut before = Hands::new(vec![P1, P2, P3]);
+        before.add(P1, vec![S_2, S_2, S_3]).unwrap();
+        before.add(P2, vec![S_4]).unwrap();
+        let mut after = before.clone();
+        after.remove(P1, vec![S_2, S_3]).unwrap();
+        after.add(P1, vec![S_5]).unwrap();
+        after.add(P3, vec![S_4]).unwrap();
+
+        let diff = Hands::diff(&before, &after);
+        assert_eq!(diff.len(), 2);
+        assert_eq!(
+            diff[&P1],
+            HandDiff {
+                added: CardSet::from_cards(vec![S_5]),
+                removed: CardSet::from_cards(vec![S_2, S_3]),
+            }
+        );
+        assert_eq!(diff[&P3].added, CardSet::from_cards(vec![S_4]));
+        assert!(diff[&P3].removed.is_empty());
+        assert!(!diff.contains_key(&P2));
+
+        assert!(Hands::diff(&after, &after).is_empty());
+        assert_eq!(Hands::diff(&after, &before)[&P1].added.len(), 2);
+    }
+
+    #[test]
+    fn test_revealed() {
+        let mut hands = Hands::new(vec![P1, P2]);
+        hands.add(P1, vec![S_2, S_2, S_3, S_5]).unwrap();
+        hands.add(P2, vec![S_4]).unwrap();
+        hands.reveal(P1, vec![S_2]).unwrap();
+        hands.reveal(P1, vec![S_2, S_2]).unwrap();
+        hands.reveal(P1, vec![S_4]).unwrap_err();
+        assert_eq!(
+            hands.revealed(P1),
+            Some(&CardSet::from_cards(vec![S_2, S_2]))
+        );
+        assert_eq!(
+            hands.concealed(P1).unwrap(),
+            CardSet::from_cards(vec![S_3, S_5])
+        );
+        assert_eq!(hands.revealed(P2), None);
+
+        let mut redacted = hands.clone();
+        redacted.destructively_redact_except_for_player(P2);
+        assert_eq!(
+            redacted.counts(P1),
+            Some(&CardSet::from_cards(vec![
+                S_2,
+                S_2,
+                Card::Unknown,
+                Card::Unknown
+            ]))
+        );
+
+        hands.remove(P1, vec![S_2, S_3]).unwrap();
+        assert_eq!(hands.revealed(P1), Some(&CardSet::from_cards(vec![S_2])));
+        assert_eq!(hands.concealed(P1).unwrap(), CardSet::from_cards(vec![S_5]));
+    }
+
+    #[test]
+    fn test_validate_against() {
+        let specs = [Deck::default().spec()];
+        let mut hands = Hands::new(vec![P1, P2]);
+        let cards = specs[0].cards().collect::<Vec<_>>();
+        hands.add(P1, cards[..27].to_vec()).unwrap();
+        hands.add(P2, cards[27..52].to_vec()).unwrap();
+        assert_eq!(hands.validate_against(&specs, 2), Ok(()));
+        assert_eq!(
+            hands.validate_against(&specs, 0),
+            Err(vec![HandViolation::WrongTotal {
+                expected: 54,
+                actual: 52,
+            }])
+        );
+
+        hands.add(P2, vec![cards[0]]).unwrap();
+        assert_eq!(
+            hands.validate_against(&specs, 1),
+            Err(vec![HandViolation::TooManyCopies {
+                card: cards[0],
+                count: 2,
+                allowed: 1,
+            }])
+        );
+        assert_eq!(
+            hands.validate_against(&[specs[0].clone(), specs[0].clone()], 55),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_count_in_effective_suit() {
+        let spades = Trump::Standard {
+            suit: Suit::Spades,
+            number: Number::Four,
+            permanent: PermanentTrump::None,
+        };
+        let hearts = Trump::Standard {
+            suit: Suit::Hearts,
+            number: Number::Four,
+            permanent: PermanentTrump::None,
+        };
+        let mut hands = Hands::new(vec![P1, P2]);
+        hands.add(P1, vec![S_2, S_3, H_4, H_5]).unwrap();
+        assert_eq!(
+            hands
+                .count_in_effective_suit(P1, EffectiveSuit::Trump, spades)
+                .unwrap(),
+            3
+        );
+        assert!(!hands.is_void(P1, EffectiveSuit::Hearts, spades).unwrap());
+        assert!(hands.is_void(P1, EffectiveSuit::Clubs, hearts).unwrap());
+        assert_eq!(
+            hands
+                .count_in_effective_suit(P1, EffectiveSuit::Trump, hearts)
+                .unwrap(),
+            2
+        );
+        assert!(hands.is_void(P1, EffectiveSuit::Spades, spades).unwrap());
+        assert!(hands.is_void(P2, EffectiveSuit::Trump, spades).unwrap());
+        hands
+            .count_in_effective_suit(P3, EffectiveSuit::Trump, spades)
+            .unwrap_err();
+
+        // Changes to the hand are reflected in later queries.
+        hands.remove(P1, vec![H_5]).unwrap();
+        assert!(hands.is_void(P1, EffectiveSuit::Hearts, spades).unwrap());
+        hands.add(P2, vec![S_5]).unwrap();
+        assert!(!hands.is_void(P2, EffectiveSuit::Trump, spades).unwrap());
+        hands.destructively_redact_except_for_player(P1);
+        assert!(hands.is_void(P2, EffectiveSuit::Trump, spades).unwrap());
+        assert_eq!(
+            hands
+                .count_in_effective_suit(P2, EffectiveSuit::Unknown, spades)
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_deck_indices() {
+        let mut hands = Hands::new(vec![P1, P2]);
+        hands
+            .add_from_decks(P1, vec![(S_2, Some(0)), (S_2, Some(1)), (S_3, None)])
+            .unwrap();
+        hands.add(P2, vec![S_5]).unwrap();
+        assert_eq!(hands.deck_indices(P1).unwrap()[&S_2], vec![0, 1]);
+        assert_eq!(hands.deck_indices(P2), None);
+
+        assert_eq!(
+            hands.remove_from_decks(P1, vec![S_3, S_2]).unwrap(),
+            vec![None, Some(1)]
+        );
+        hands.remove_from_decks(P1, vec![S_2, S_2]).unwrap_err();
+        assert_eq!(
+            hands.remove_from_decks(P1, vec![S_2]).unwrap(),
+            vec![Some(0)]
+        );
+        assert!(hands.deck_indices(P1).unwrap().is_empty());
+
+        hands.add_from_decks(P2, vec![(S_4, Some(1))]).unwrap();
+        hands.destructively_redact_except_for_player(P1);
+        assert_eq!(hands.deck_indices(P2), None);
+    }
 }
@@ -0,0 +1,139 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::card_set::CardSet;
+use crate::ordered_card::OrderedCard;
+use crate::scoring::PointSchedule;
+use crate::types::{AdjacencyPolicy, Card, EffectiveSuit, Trump};
+
+/// A card which could be buried in the kitty, along with what it would cost
+/// to give it up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BurialCandidate {
+    pub card: Card,
+    pub is_trump: bool,
+    /// Whether the card is part of a tractor of pairs in the hand.
+    pub in_tractor: bool,
+    /// Whether the hand holds at least two copies of the card.
+    pub in_pair: bool,
+    pub points: usize,
+    /// The number of cards in the hand in the same effective suit, since
+    /// burying short suits helps to void them.
+    pub suit_length: usize,
+}
+
+/// Ranks every card in the hand by how good it would be to bury, best first.
+/// Low off-suit singles in short suits come first; points, pairs, tractors
+/// and trump are kept back, in increasing order of importance.
+///
+/// Each copy of a card gets its own entry, so the first `kitty_size` entries
+/// are a reasonable burial.
+pub fn rank_burial_candidates(
+    hand: &CardSet,
+    trump: Trump,
+    point_schedule: &PointSchedule,
+    adjacency_policy: AdjacencyPolicy,
+) -> Vec<BurialCandidate> {
+    let ordered = OrderedCard::make_map(hand.cards(), trump);
+    let has_pair = |card: &OrderedCard| ordered.get(card).is_some_and(|ct| *ct >= 2);
+    let suit_length = |suit: EffectiveSuit| {
+        ordered
+            .iter()
+            .filter(|(c, _)| trump.effective_suit(c.card) == suit)
+            .map(|(_, ct)| *ct)
+            .sum::<usize>()
+    };
+
+    let mut candidates = ordered
+        .iter()
+        .flat_map(|(card, count)| {
+            let in_pair = *count >= 2;
+            let in_tractor = in_pair
+                && card
+                    .successor(adjacency_policy)
+                    .iter()
+                    .chain(card.predecessor(adjacency_policy).iter())
+                    .any(has_pair);
+            let suit = trump.effective_suit(card.card);
+            let candidate = BurialCandidate {
+                card: card.card,
+                is_trump: suit == EffectiveSuit::Trump,
+                in_tractor,
+                in_pair,
+                points: point_schedule.points(card.card).unwrap_or(0),
+                suit_length: suit_length(suit),
+            };
+            (0..*count).map(move |_| candidate)
+        })
+        .collect::<Vec<_>>();
+
+    candidates.sort_by(|a, b| {
+        (a.is_trump, a.in_tractor, a.in_pair, a.points, a.suit_length)
+            .cmp(&(b.is_trump, b.in_tractor, b.in_pair, b.points, b.suit_length))
+            .then_with(|| trump.compare(a.card, b.card))
+    });
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rank_burial_candidates;
+    use crate::card_set::CardSet;
+    use crate::scoring::PointSchedule;
+    use crate::types::{cards::*, AdjacencyPolicy, Card, Number, PermanentTrump, Suit, Trump};
+
+    #[test]
+    fn test_rank_burial_candidates() {
+        let trump = Trump::Standard {
+            suit: Suit::Spades,
+            number: Number::Two,
+            permanent: PermanentTrump::None,
+        };
+        let hand = CardSet::from_cards(vec![
+            H_3,
+            H_3,
+            H_4,
+            H_4,
+            H_6,
+            H_6,
+            H_8,
+            H_K,
+            D_9,
+            D_5,
+            C_3,
+            C_J,
+            C_Q,
+            S_3,
+            Card::BigJoker,
+        ]);
+        let ranked = rank_burial_candidates(
+            &hand,
+            trump,
+            &PointSchedule::default(),
+            AdjacencyPolicy::default(),
+        )
+        .into_iter()
+        .map(|c| c.card)
+        .collect::<Vec<_>>();
+        assert_eq!(
+            ranked,
+            vec![
+                D_9,
+                C_3,
+                C_J,
+                C_Q,
+                H_8,
+                D_5,
+                H_K,
+                H_6,
+                H_6,
+                H_3,
+                H_3,
+                H_4,
+                H_4,
+                S_3,
+                Card::BigJoker,
+            ]
+        );
+    }
+}
@@ -5,13 +5,21 @@
 #![allow(clippy::upper_case_acronyms)]
 
 pub mod bidding;
+mod cache;
+pub mod card_set;
 pub mod deck;
 pub mod format_match;
+pub mod hand_summary;
 pub mod hands;
+pub mod kitty_advisor;
 pub mod multiset_iter;
+pub mod naming;
+pub mod notation;
 pub mod ordered_card;
 pub mod player;
+pub mod requirements;
 pub mod scoring;
+pub mod sorting;
 pub mod trick;
 pub mod types;
 
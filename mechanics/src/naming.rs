@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Card, Number, Suit};
+
+/// The languages that cards, suits, and numbers can be named in. Serialized as
+/// the locale key, e.g. `"en"`.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "zh")]
+    Chinese,
+}
+
+crate::impl_slog_value!(Locale);
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::Chinese];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::Chinese => "zh",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Locale::ALL.iter().copied().find(|l| l.key() == key)
+    }
+}
+
+pub fn suit_name(suit: Suit, locale: Locale) -> &'static str {
+    match (locale, suit) {
+        (Locale::English, Suit::Spades) => "spades",
+        (Locale::English, Suit::Hearts) => "hearts",
+        (Locale::English, Suit::Diamonds) => "diamonds",
+        (Locale::English, Suit::Clubs) => "clubs",
+        (Locale::Chinese, Suit::Spades) => "黑桃",
+        (Locale::Chinese, Suit::Hearts) => "红桃",
+        (Locale::Chinese, Suit::Diamonds) => "方块",
+        (Locale::Chinese, Suit::Clubs) => "梅花",
+    }
+}
+
+/// The filled symbol for the suit, e.g. `♠`. `Suit::as_char` is the outlined
+/// one.
+pub fn suit_symbol(suit: Suit) -> char {
+    match suit {
+        Suit::Spades => '♠',
+        Suit::Hearts => '♥',
+        Suit::Diamonds => '♦',
+        Suit::Clubs => '♣',
+    }
+}
+
+pub fn number_name(number: Number, locale: Locale) -> &'static str {
+    match (locale, number) {
+        (Locale::English, Number::Two) => "two",
+        (Locale::English, Number::Three) => "three",
+        (Locale::English, Number::Four) => "four",
+        (Locale::English, Number::Five) => "five",
+        (Locale::English, Number::Six) => "six",
+        (Locale::English, Number::Seven) => "seven",
+        (Locale::English, Number::Eight) => "eight",
+        (Locale::English, Number::Nine) => "nine",
+        (Locale::English, Number::Ten) => "ten",
+        (Locale::English, Number::Jack) => "jack",
+        (Locale::English, Number::Queen) => "queen",
+        (Locale::English, Number::King) => "king",
+        (Locale::English, Number::Ace) => "ace",
+        // Chinese players name the ranks by their symbols.
+        (Locale::Chinese, number) => number.as_str(),
+    }
+}
+
+pub fn card_name(card: Card, locale: Locale) -> String {
+    match (locale, card) {
+        (Locale::English, Card::Suited { suit, number }) => format!(
+            "{} of {}",
+            number_name(number, locale),
+            suit_name(suit, locale)
+        ),
+        (Locale::Chinese, Card::Suited { suit, number }) => {
+            format!("{}{}", suit_name(suit, locale), number_name(number, locale))
+        }
+        (Locale::English, Card::SmallJoker) => "small joker".to_string(),
+        (Locale::English, Card::BigJoker) => "big joker".to_string(),
+        (Locale::English, Card::Unknown) => "unknown card".to_string(),
+        (Locale::Chinese, Card::SmallJoker) => "小王".to_string(),
+        (Locale::Chinese, Card::BigJoker) => "大王".to_string(),
+        (Locale::Chinese, Card::Unknown) => "未知牌".to_string(),
+    }
+}
+
+/// The short symbolic form of the card, e.g. `A♠` or `LJ`.
+pub fn card_symbol(card: Card) -> String {
+    match card {
+        Card::Suited { suit, number } => format!("{}{}", number.as_str(), suit_symbol(suit)),
+        _ => card.to_string(),
+    }
+}
+
+/// Every name for a card, so that clients don't need their own tables.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct CardNames {
+    pub card: Card,
+    /// The short symbolic form, e.g. `A♠`.
+    pub symbol: String,
+    /// The unicode playing-card glyph, e.g. `🂡`.
+    pub glyph: char,
+    /// The full name of the card, keyed by locale.
+    pub names: BTreeMap<Locale, String>,
+}
+
+impl CardNames {
+    pub fn new(card: Card) -> Self {
+        CardNames {
+            card,
+            symbol: card_symbol(card),
+            glyph: card.as_char(),
+            names: Locale::ALL
+                .iter()
+                .map(|l| (*l, card_name(card, *l)))
+                .collect(),
+        }
+    }
+
+    /// The names of each distinct card in `cards`, in order of first
+    /// appearance.
+    pub fn of(cards: impl IntoIterator<Item = Card>) -> Vec<Self> {
+        let mut seen = vec![];
+        for card in cards {
+            if !seen.contains(&card) {
+                seen.push(card);
+            }
+        }
+        seen.into_iter().map(CardNames::new).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{card_name, card_symbol, CardNames, Locale};
+    use crate::types::{cards, Card};
+
+    #[test]
+    fn test_card_names() {
+        assert_eq!(card_name(cards::S_A, Locale::English), "ace of spades");
+        assert_eq!(card_name(cards::H_10, Locale::Chinese), "红桃10");
+        assert_eq!(card_name(Card::BigJoker, Locale::Chinese), "大王");
+        assert_eq!(card_symbol(cards::D_Q), "Q♦");
+        assert_eq!(card_symbol(Card::SmallJoker), "LJ");
+
+        let names = CardNames::of(vec![cards::C_2, Card::SmallJoker, cards::C_2]);
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[0].names[&Locale::English], "two of clubs");
+        assert_eq!(names[1].names[&Locale::Chinese], "小王");
+
+        for locale in Locale::ALL {
+            assert_eq!(Locale::from_key(locale.key()), Some(locale));
+        }
+        assert_eq!(
+            serde_json::to_string(&names[1].names).unwrap(),
+            r#"{"en":"small joker","zh":"小王"}"#
+        );
+    }
+}
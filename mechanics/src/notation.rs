@@ -0,0 +1,228 @@
+//! A compact, canonical way of writing down a hand, e.g. for bug reports,
+//! puzzles, and tests:
+//!
+//! ```text
+//! T: HJ LJ 2♠2♠ | ♥: A K 7 | ♦: QQ 3
+//! ```
+//!
+//! Each group is a label followed by the cards in that group, and groups are
+//! separated by `|`. The trump group `T` lists whole cards, while the groups
+//! for the other suits only list the numbers. Copies of the same card are
+//! written next to one another without a space. Unknown cards go in a `?`
+//! group, written as `[]`.
+
+use std::iter::once;
+
+use crate::card_set::CardSet;
+use crate::naming::{card_symbol, suit_symbol};
+use crate::types::{Card, EffectiveSuit, Number, Suit, Trump};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum NotationError {
+    #[error("missing label in group {0:?}")]
+    MissingLabel(String),
+    #[error("unknown group label {0:?}")]
+    UnknownLabel(String),
+    #[error("invalid cards {0:?}")]
+    InvalidCards(String),
+}
+
+/// Writes the hand in canonical form: trump first, then the other suits in
+/// `ALL_SUITS` order, each from highest to lowest. Empty groups are left out.
+pub fn format_hand(hand: &CardSet, trump: Trump) -> String {
+    once(EffectiveSuit::Trump)
+        .chain(Suit::iter().map(EffectiveSuit::from))
+        .chain(once(EffectiveSuit::Unknown))
+        .filter_map(|suit| {
+            let mut cards = hand
+                .cards()
+                .filter(|c| trump.effective_suit(*c) == suit)
+                .collect::<Vec<_>>();
+            if cards.is_empty() {
+                return None;
+            }
+            cards.sort_by(|a, b| trump.compare(*b, *a));
+
+            let (label, write_card): (String, fn(Card) -> String) = match suit.suit() {
+                Some(s) => (suit_symbol(s).to_string(), |c| match c.number() {
+                    Some(n) => n.as_str().to_string(),
+                    None => card_symbol(c),
+                }),
+                None if suit == EffectiveSuit::Trump => ("T".to_string(), card_symbol),
+                None => ("?".to_string(), card_symbol),
+            };
+
+            let mut tokens: Vec<String> = vec![];
+            for (idx, card) in cards.iter().enumerate() {
+                match tokens.last_mut() {
+                    Some(token) if cards[idx - 1] == *card => token.push_str(&write_card(*card)),
+                    _ => tokens.push(write_card(*card)),
+                }
+            }
+            Some(format!("{}: {}", label, tokens.join(" ")))
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Reads a hand written by `format_hand`. Suits may be written as letters or
+/// as either the filled or outlined symbols, and the groups can be in any
+/// order. The cards are returned in the order they were written.
+pub fn parse_hand(s: &str) -> Result<Vec<Card>, NotationError> {
+    let mut cards = vec![];
+    for group in s.split('|') {
+        let group = group.trim();
+        if group.is_empty() {
+            continue;
+        }
+        let (label, rest) = match group.split_once(':') {
+            Some((label, rest)) => (label.trim(), rest),
+            None => return Err(NotationError::MissingLabel(group.to_string())),
+        };
+        let suit = match label.to_uppercase().as_str() {
+            "T" | "?" => None,
+            l => {
+                let mut chars = l.chars();
+                match (chars.next().and_then(Suit::from_any_char), chars.next()) {
+                    (Some(suit), None) => Some(suit),
+                    _ => return Err(NotationError::UnknownLabel(label.to_string())),
+                }
+            }
+        };
+        for token in rest.split_whitespace() {
+            let parsed = match suit {
+                Some(suit) => parse_numbers(token).map(|numbers| {
+                    numbers
+                        .into_iter()
+                        .map(|number| Card::Suited { suit, number })
+                        .collect()
+                }),
+                None => parse_cards(token),
+            };
+            match parsed {
+                Some(parsed) => cards.extend(parsed),
+                None => return Err(NotationError::InvalidCards(token.to_string())),
+            }
+        }
+    }
+    Ok(cards)
+}
+
+/// Splits a run of numbers like `AA` or `1010`.
+fn parse_numbers(token: &str) -> Option<Vec<Number>> {
+    let upper = token.to_uppercase();
+    let mut numbers = vec![];
+    let mut rest = upper.as_str();
+    while !rest.is_empty() {
+        let len = if rest.starts_with("10") { 2 } else { 1 };
+        numbers.push(Number::from_str(rest.get(..len)?)?);
+        rest = &rest[len..];
+    }
+    Some(numbers)
+}
+
+/// Splits a run of whole cards like `HJHJ`, `2♠2♠` or `♠2♠2`.
+fn parse_cards(token: &str) -> Option<Vec<Card>> {
+    let upper = token.to_uppercase();
+    let mut cards = vec![];
+    let mut rest = upper.as_str();
+    while !rest.is_empty() {
+        let first = rest.chars().next()?;
+        let len = if ["HJ", "LJ", "[]"].iter().any(|j| rest.starts_with(j)) {
+            2
+        } else {
+            // A number is either `10` or a single character, and comes
+            // either before or after the suit.
+            let number_start = if Suit::from_any_char(first).is_some() {
+                first.len_utf8()
+            } else {
+                0
+            };
+            let number_len = match rest[number_start..].chars().next() {
+                _ if rest[number_start..].starts_with("10") => 2,
+                Some(c) => c.len_utf8(),
+                None => return None,
+            };
+            let suit_len = match rest[number_start + number_len..].chars().next() {
+                Some(c) if number_start == 0 && Suit::from_any_char(c).is_some() => c.len_utf8(),
+                _ if number_start > 0 => 0,
+                _ => return None,
+            };
+            number_start + number_len + suit_len
+        };
+        cards.push(rest.get(..len)?.parse().ok()?);
+        rest = &rest[len..];
+    }
+    Some(cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_hand, parse_hand, NotationError};
+    use crate::card_set::CardSet;
+    use crate::types::{cards::*, Card, Number, PermanentTrump, Suit, Trump};
+
+    #[test]
+    fn test_format_and_parse_hand() {
+        let trump = Trump::Standard {
+            suit: Suit::Spades,
+            number: Number::Two,
+            permanent: PermanentTrump::None,
+        };
+        let hand = CardSet::from_cards(vec![
+            H_7,
+            S_2,
+            Card::BigJoker,
+            H_A,
+            S_2,
+            H_10,
+            H_10,
+            H_2,
+            D_Q,
+            Card::SmallJoker,
+            S_5,
+        ]);
+        let written = format_hand(&hand, trump);
+        assert_eq!(written, "T: HJ LJ 2♠2♠ 2♥ 5♠ | ♥: A 1010 7 | ♦: Q");
+        assert_eq!(CardSet::from_cards(parse_hand(&written).unwrap()), hand);
+
+        assert_eq!(
+            parse_hand("h: a 1010 | t: hj s2S2 ♤3 4♧ | ?: [][]").unwrap(),
+            vec![
+                H_A,
+                H_10,
+                H_10,
+                Card::BigJoker,
+                S_2,
+                S_2,
+                S_3,
+                C_4,
+                Card::Unknown,
+                Card::Unknown,
+            ]
+        );
+        assert_eq!(parse_hand("").unwrap(), vec![]);
+        assert_eq!(format_hand(&CardSet::new(), trump), "");
+
+        assert_eq!(
+            parse_hand("♥ A K"),
+            Err(NotationError::MissingLabel("♥ A K".to_string()))
+        );
+        assert_eq!(
+            parse_hand("X: A"),
+            Err(NotationError::UnknownLabel("X".to_string()))
+        );
+        assert_eq!(
+            parse_hand("♥: A 1"),
+            Err(NotationError::InvalidCards("1".to_string()))
+        );
+        assert_eq!(
+            parse_hand("T: 22♠"),
+            Err(NotationError::InvalidCards("22♠".to_string()))
+        );
+        assert_eq!(
+            parse_hand("T: ♠♥"),
+            Err(NotationError::InvalidCards("♠♥".to_string()))
+        );
+    }
+}
@@ -1,12 +1,15 @@
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, HashMap, HashSet};
-use std::sync::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
 
-use itertools::Itertools;
+use itertools::{Either, Itertools};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use crate::types::{Card, Trump};
+pub use crate::cache::CacheStats;
+use crate::cache::LruCache;
+use crate::types::{AdjacencyPolicy, Card, Trump};
 
 pub type MatchingCards = Vec<(OrderedCard, usize)>;
 pub type MatchingCardsRef = [(OrderedCard, usize)];
@@ -27,9 +30,9 @@ impl std::fmt::Debug for OrderedCard {
 }
 
 impl OrderedCard {
-    pub fn successor(self) -> Vec<OrderedCard> {
+    pub fn successor(self, adjacency_policy: AdjacencyPolicy) -> Vec<OrderedCard> {
         self.trump
-            .successor(self.card)
+            .successor(self.card, adjacency_policy)
             .into_iter()
             .map(|card| Self {
                 card,
@@ -38,6 +41,43 @@ impl OrderedCard {
             .collect()
     }
 
+    pub fn predecessor(self, adjacency_policy: AdjacencyPolicy) -> Vec<OrderedCard> {
+        self.trump
+            .predecessor(self.card, adjacency_policy)
+            .into_iter()
+            .map(|card| Self {
+                card,
+                trump: self.trump,
+            })
+            .collect()
+    }
+
+    /// Whether this card is only adjacent to the next card in its suit by
+    /// skipping over the trump number; see `Trump::skips_trump_number`.
+    pub fn skips_trump_number(self) -> bool {
+        self.trump.skips_trump_number(self.card)
+    }
+
+    /// The number of `successor` steps needed to get from this card to
+    /// `other`, or `None` if `other` can't be reached. A card is zero steps
+    /// from itself.
+    pub fn distance(self, other: OrderedCard, adjacency_policy: AdjacencyPolicy) -> Option<usize> {
+        let mut frontier = vec![self];
+        let mut steps = 0;
+        while !frontier.is_empty() {
+            if frontier.contains(&other) {
+                return Some(steps);
+            }
+            frontier = frontier
+                .into_iter()
+                .flat_map(|c| c.successor(adjacency_policy))
+                .unique()
+                .collect();
+            steps += 1;
+        }
+        None
+    }
+
     pub fn make_map(
         cards: impl Iterator<Item = Card>,
         trump: Trump,
@@ -80,88 +120,335 @@ impl PartialOrd for OrderedCard {
 
 type Usizes = Vec<usize>;
 
+/// Bounds on the number of entries kept in each of the decomposition caches.
+///
+/// Each cache is keyed by the number of cards (or tuples) being decomposed,
+/// and the least-recently-used entry is evicted once a cache is full. Entries
+/// for large card counts can be very large, so servers which see the
+/// occasional huge throw may want to keep these small.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DecompositionCacheLimits {
+    /// Maximum number of entries in the tuple-partition cache.
+    pub max_partitions: usize,
+    /// Maximum number of entries in the sequential-assignment cache.
+    pub max_sequential_assignments: usize,
+    /// Maximum number of entries in the full-decomposition cache.
+    pub max_full_decompositions: usize,
+}
+
+impl Default for DecompositionCacheLimits {
+    fn default() -> Self {
+        Self {
+            max_partitions: 32,
+            max_sequential_assignments: 16,
+            max_full_decompositions: 32,
+        }
+    }
+}
+
+/// Updates the bounds on the decomposition caches, evicting entries if they
+/// are now over their limit.
+pub fn set_decomposition_cache_limits(limits: DecompositionCacheLimits) {
+    GROUP_CACHE
+        .write()
+        .unwrap()
+        .set_capacity(limits.max_partitions);
+    SEQUENTIAL_ASSIGNMENT_CACHE
+        .write()
+        .unwrap()
+        .set_capacity(limits.max_sequential_assignments);
+    FULL_DECOMPOSITION_CACHE
+        .write()
+        .unwrap()
+        .set_capacity(limits.max_full_decompositions);
+}
+
+/// Usage statistics for each of the decomposition caches.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DecompositionCacheStats {
+    pub partitions: CacheStats,
+    pub sequential_assignments: CacheStats,
+    pub full_decompositions: CacheStats,
+}
+
+/// Returns a snapshot of the hits, misses, and sizes of the decomposition
+/// caches, for monitoring.
+pub fn decomposition_cache_stats() -> DecompositionCacheStats {
+    DecompositionCacheStats {
+        partitions: GROUP_CACHE.read().unwrap().stats(),
+        sequential_assignments: SEQUENTIAL_ASSIGNMENT_CACHE.read().unwrap().stats(),
+        full_decompositions: FULL_DECOMPOSITION_CACHE.read().unwrap().stats(),
+    }
+}
+
+/// Bounds on the amount of work done when decomposing a play, to avoid
+/// stalling on very large throws.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DecompositionLimits {
+    /// Maximum number of tuple-partitions of the cards which may be considered.
+    pub max_partitions: usize,
+    /// Maximum number of decompositions which may be produced.
+    pub max_decompositions: usize,
+}
+
+impl DecompositionLimits {
+    pub fn unlimited() -> Self {
+        Self {
+            max_partitions: usize::MAX,
+            max_decompositions: usize::MAX,
+        }
+    }
+}
+
+impl Default for DecompositionLimits {
+    fn default() -> Self {
+        Self {
+            max_partitions: 512,
+            max_decompositions: 50_000,
+        }
+    }
+}
+
+#[derive(Error, Debug, Copy, Clone, Eq, PartialEq)]
+#[error("decomposing {num_cards} cards exceeds the configured limits")]
+pub struct DecompositionTooLarge {
+    pub num_cards: usize,
+}
+
+/// Precomputes the partition and decomposition tables for plays of up to
+/// `max_cards` cards, so that e.g. a server can pay that cost at startup
+/// rather than during the first large throw of a game.
+///
+/// Only as many tables as the `DecompositionCacheLimits` allow are kept; since
+/// the larger tables are computed last, those are the ones which are retained.
+pub fn warm_caches(max_cards: usize) {
+    for num_cards in 1..=max_cards {
+        shared_full_decomposition_ordering(num_cards);
+    }
+}
+
 lazy_static::lazy_static! {
-    static ref GROUP_CACHE: Mutex<HashMap<usize, Vec<AdjacentTupleSizes>>> = Mutex::new(HashMap::new());
-    static ref SEQUENTIAL_ASSIGNMENT_CACHE: Mutex<HashMap<usize, Vec<Vec<Usizes>>>> = Mutex::new(HashMap::new());
-    static ref FULL_DECOMPOSITION_CACHE: Mutex<HashMap<usize, Vec<PlayRequirements>>> = Mutex::new(HashMap::new());
+    static ref GROUP_CACHE: RwLock<LruCache<usize, Arc<[AdjacentTupleSizes]>>> =
+        RwLock::new(LruCache::new(DecompositionCacheLimits::default().max_partitions));
+    static ref SEQUENTIAL_ASSIGNMENT_CACHE: RwLock<LruCache<usize, Arc<[Vec<Usizes>]>>> =
+        RwLock::new(LruCache::new(DecompositionCacheLimits::default().max_sequential_assignments));
+    static ref FULL_DECOMPOSITION_CACHE: RwLock<LruCache<usize, Arc<[PlayRequirements]>>> =
+        RwLock::new(LruCache::new(DecompositionCacheLimits::default().max_full_decompositions));
 }
 
+/// Held by the tests which change the cache limits, so that they don't
+/// observe each other's limits.
+#[cfg(test)]
+pub(crate) static CACHE_LIMITS_TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
 pub fn subsequent_decomposition_ordering(
-    mut adj_reqs: PlayRequirements,
+    adj_reqs: PlayRequirements,
     include_new_adjacency: bool,
 ) -> Vec<PlayRequirements> {
+    subsequent_decomposition_iter(adj_reqs, include_new_adjacency).collect()
+}
+
+///
+/// Lazily computes the same sequence of decompositions as
+/// `subsequent_decomposition_ordering`, so that callers which only need the
+/// first few decompositions don't have to pay for the rest.
+///
+pub fn subsequent_decomposition_iter(
+    adj_reqs: PlayRequirements,
+    include_new_adjacency: bool,
+) -> SubsequentDecompositionIter {
+    try_subsequent_decomposition_iter(
+        adj_reqs,
+        include_new_adjacency,
+        DecompositionLimits::unlimited(),
+    )
+    .expect("unlimited decompositions are never too large")
+}
+
+///
+/// Like `subsequent_decomposition_iter`, but fails rather than computing the
+/// decomposition of any requirement which exceeds the `limits`.
+///
+pub fn try_subsequent_decomposition_iter(
+    mut adj_reqs: PlayRequirements,
+    include_new_adjacency: bool,
+    limits: DecompositionLimits,
+) -> Result<SubsequentDecompositionIter, DecompositionTooLarge> {
     if !adj_reqs.iter().all(|adj_req| !adj_req.is_empty()) {
-        return vec![];
+        return Ok(SubsequentDecompositionIter {
+            decompositions: vec![],
+            current_decomps: vec![],
+            can_include_new_adjacency: vec![],
+            h: vec![],
+            strategy: DecompositionStrategy::default(),
+        });
     }
 
     for adj_req in &mut adj_reqs {
         adj_req.sort_by(|a, b| b.cmp(a));
     }
 
+    let mut lengths = adj_reqs
+        .iter()
+        .map(|adj_req| adj_req.iter().sum::<usize>())
+        .collect::<Vec<_>>();
+    lengths.sort_unstable();
+    lengths.dedup();
+    let full_decomps = full_decompositions_by_length(&lengths, limits)?;
+
     let mut decompositions = Vec::with_capacity(adj_reqs.len());
     for adj_req in &adj_reqs {
         let len = adj_req.iter().sum::<usize>();
-        let mut decomp = full_decomposition_ordering(len);
-        decomp.reverse();
-        while let Some(v) = decomp.pop() {
-            if v.len() == 1 && v.get(0) == Some(adj_req) {
-                break;
-            }
-        }
+        let decomp = full_decomps[&len].clone();
+        // Skip everything up to and including the requirement itself.
+        let start = decomp
+            .iter()
+            .position(|v| v.len() == 1 && v.first() == Some(adj_req))
+            .map(|idx| idx + 1)
+            .unwrap_or(decomp.len());
 
-        decompositions.push(decomp);
-    }
-    let mut subsequent_decomps = vec![];
-    let mut current_decomps: HashMap<usize, PlayRequirements> = HashMap::new();
-    for (i, adj_req) in adj_reqs.iter().enumerate() {
-        current_decomps.insert(i, vec![adj_req.clone()]);
+        decompositions.push((decomp, start));
     }
     let can_include_new_adjacency = adj_reqs
         .iter()
         .map(|a| include_new_adjacency || a.len() > 1)
         .collect::<Vec<_>>();
 
-    // Keep the indices of decompositions as a range to assist in the later loop.
-    let mut h = (0..adj_reqs.len()).collect::<Vec<usize>>();
+    Ok(SubsequentDecompositionIter {
+        decompositions,
+        // Keep the indices of decompositions as a range to assist in the later loop.
+        h: (0..adj_reqs.len()).collect(),
+        current_decomps: adj_reqs.into_iter().map(|adj_req| vec![adj_req]).collect(),
+        can_include_new_adjacency,
+        strategy: DecompositionStrategy::default(),
+    })
+}
+
+/// Computes the full decomposition ordering for each of the distinct
+/// `lengths`. With the `parallel` feature, the lengths are decomposed
+/// concurrently; the results are keyed by length, so the ordering of the
+/// subsequent decompositions is unaffected.
+#[cfg(feature = "parallel")]
+fn full_decompositions_by_length(
+    lengths: &[usize],
+    limits: DecompositionLimits,
+) -> Result<HashMap<usize, Arc<[PlayRequirements]>>, DecompositionTooLarge> {
+    use rayon::prelude::*;
 
-    loop {
-        // Decompose the value with the most remaining decompositions.
-        h.sort_by(|idx_a, idx_b| {
-            decompositions
-                .get(*idx_b)
-                .map(|d| d.len())
-                .unwrap_or(0)
-                .cmp(&decompositions.get(*idx_a).map(|d| d.len()).unwrap_or(0))
-        });
-        let to_decompose = h.first();
-
-        if let Some((idx, v)) = to_decompose.and_then(|i| {
-            decompositions
-                .get_mut(*i)
-                .and_then(|v: &mut Vec<PlayRequirements>| v.pop())
-                .map(|v: PlayRequirements| (i, v))
-        }) {
-            current_decomps.insert(*idx, v);
-        } else {
-            break;
-        }
-        // If we decomposed something which didn't include an adjacency requirement into
-        // something which does, ensure that that's allowed by the caller.
-        let include = h.iter().all(|i| {
-            current_decomps[i]
-                .iter()
-                .all(|a| a.len() == 1 || can_include_new_adjacency[*i])
-        });
-        if include {
-            let mut full_decomp = h
-                .iter()
-                .flat_map(|i| current_decomps[i].iter().cloned())
-                .collect::<PlayRequirements>();
-            full_decomp.sort_by(|a, b| b.cmp(a));
-            subsequent_decomps.push(full_decomp);
+    lengths
+        .par_iter()
+        .map(|len| Ok((*len, try_shared_full_decomposition_ordering(*len, limits)?)))
+        .collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn full_decompositions_by_length(
+    lengths: &[usize],
+    limits: DecompositionLimits,
+) -> Result<HashMap<usize, Arc<[PlayRequirements]>>, DecompositionTooLarge> {
+    lengths
+        .iter()
+        .map(|len| Ok((*len, try_shared_full_decomposition_ordering(*len, limits)?)))
+        .collect()
+}
+
+/// Which of the original requirements to decompose next when computing
+/// subsequent decompositions, i.e. which structure a player is forced to give
+/// up first when they can't follow the trick-format exactly.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum DecompositionStrategy {
+    /// Decompose the requirement with the most remaining decompositions.
+    #[default]
+    MostOptionsFirst,
+    /// Decompose requirements without tractors before breaking up tractors.
+    PreserveTractors,
+    /// Break up tractors before decomposing requirements without tractors.
+    PreservePairs,
+    /// Decompose whichever requirement would next produce the fewest units.
+    FewestForcedUnits,
+}
+
+crate::impl_slog_value!(DecompositionStrategy);
+
+/// Iterator over subsequent decompositions, produced by `subsequent_decomposition_iter`.
+pub struct SubsequentDecompositionIter {
+    /// The full decomposition for each of the original requirements, along
+    /// with the index of the next decomposition to consider.
+    decompositions: Vec<(Arc<[PlayRequirements]>, usize)>,
+    /// The current decomposition for each of the original requirements.
+    current_decomps: Vec<PlayRequirements>,
+    can_include_new_adjacency: Vec<bool>,
+    h: Vec<usize>,
+    strategy: DecompositionStrategy,
+}
+
+impl SubsequentDecompositionIter {
+    /// Uses `strategy` to pick which requirement to decompose next, rather
+    /// than the default.
+    pub fn with_strategy(mut self, strategy: DecompositionStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+}
+
+impl Iterator for SubsequentDecompositionIter {
+    type Item = PlayRequirements;
+
+    fn next(&mut self) -> Option<PlayRequirements> {
+        loop {
+            let decompositions = &mut self.decompositions;
+            let current_decomps = &self.current_decomps;
+            let remaining = |idx: usize| {
+                decompositions
+                    .get(idx)
+                    .map(|(d, next)| d.len() - next)
+                    .unwrap_or(0)
+            };
+            let has_tractor = |idx: usize| current_decomps[idx].iter().any(|a| a.len() > 1);
+            let strategy = self.strategy;
+            let priority = |idx: usize| match strategy {
+                DecompositionStrategy::MostOptionsFirst => 0,
+                DecompositionStrategy::PreserveTractors => has_tractor(idx) as usize,
+                DecompositionStrategy::PreservePairs => !has_tractor(idx) as usize,
+                DecompositionStrategy::FewestForcedUnits => decompositions
+                    .get(idx)
+                    .and_then(|(d, next)| d.get(*next))
+                    .map(|v| v.len())
+                    .unwrap_or(usize::MAX),
+            };
+            // Decompose the highest-priority value, breaking ties by the most
+            // remaining decompositions.
+            self.h.sort_by_key(|idx| {
+                (
+                    remaining(*idx) == 0,
+                    priority(*idx),
+                    std::cmp::Reverse(remaining(*idx)),
+                )
+            });
+            let idx = *self.h.first()?;
+            let (decomp, next) = decompositions.get_mut(idx)?;
+            let v = decomp.get(*next)?.clone();
+            *next += 1;
+            self.current_decomps[idx] = v;
+
+            // If we decomposed something which didn't include an adjacency requirement into
+            // something which does, ensure that that's allowed by the caller.
+            let include = self.h.iter().all(|i| {
+                self.current_decomps[*i]
+                    .iter()
+                    .all(|a| a.len() == 1 || self.can_include_new_adjacency[*i])
+            });
+            if include {
+                let mut full_decomp = self
+                    .h
+                    .iter()
+                    .flat_map(|i| self.current_decomps[*i].iter().cloned())
+                    .collect::<PlayRequirements>();
+                full_decomp.sort_by(|a, b| b.cmp(a));
+                return Some(full_decomp);
+            }
         }
     }
-    subsequent_decomps
 }
 
 ///
@@ -170,42 +457,196 @@ pub fn subsequent_decomposition_ordering(
 /// The result is a list of sequences of adjacent card-lengths. Note: single cards are never
 /// required to be adjacent.
 ///
+/// This is the `DecompositionOrder::LargestTuplesFirst` order, which is also
+/// the order used by the shared, lazy, and fallible variants below.
+///
 pub fn full_decomposition_ordering(num_cards: usize) -> Vec<PlayRequirements> {
+    shared_full_decomposition_ordering(num_cards).to_vec()
+}
+
+/// The order in which `full_decomposition_ordering_by` returns
+/// decompositions. Every order is stable: decompositions which it doesn't
+/// distinguish stay in the `LargestTuplesFirst` order.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum DecompositionOrder {
+    /// Grouped by tuple-partition, in the order of `find_tuple_partitions`
+    /// (i.e. larger tuples first), and then within each partition by the
+    /// order of `compute_adjacent_assignments` (i.e. longer tractors first).
+    #[default]
+    LargestTuplesFirst,
+    /// Decompositions with longer tractors first, e.g. `[[2, 2]]` before `[[4]]`.
+    LongestTractorsFirst,
+    /// Decompositions with more non-single tuples first, e.g. `[[2], [2]]`
+    /// before `[[4]]`.
+    MostPairsFirst,
+}
+
+crate::impl_slog_value!(DecompositionOrder);
+
+///
+/// Like `full_decomposition_ordering`, but in the specified `order`.
+///
+pub fn full_decomposition_ordering_by(
+    num_cards: usize,
+    order: DecompositionOrder,
+) -> Vec<PlayRequirements> {
+    let mut decomps = full_decomposition_ordering(num_cards);
+    match order {
+        DecompositionOrder::LargestTuplesFirst => (),
+        DecompositionOrder::LongestTractorsFirst => decomps.sort_by_key(|d| {
+            std::cmp::Reverse(d.iter().map(|a| a.len()).max().unwrap_or_default())
+        }),
+        DecompositionOrder::MostPairsFirst => decomps
+            .sort_by_key(|d| std::cmp::Reverse(d.iter().flatten().filter(|t| **t > 1).count())),
+    }
+    decomps
+}
+
+///
+/// Like `full_decomposition_ordering`, but returns the cached decompositions
+/// directly rather than a copy.
+///
+pub fn shared_full_decomposition_ordering(num_cards: usize) -> Arc<[PlayRequirements]> {
+    try_shared_full_decomposition_ordering(num_cards, DecompositionLimits::unlimited())
+        .expect("unlimited decompositions are never too large")
+}
+
+///
+/// Like `full_decomposition_ordering`, but gives up with a
+/// `DecompositionTooLarge` error once the number of partitions or
+/// decompositions would exceed the `limits`.
+///
+/// Decompositions which have already been computed are returned regardless of
+/// the limits, since they're cheap to look up.
+///
+pub fn try_full_decomposition_ordering(
+    num_cards: usize,
+    limits: DecompositionLimits,
+) -> Result<Vec<PlayRequirements>, DecompositionTooLarge> {
+    Ok(try_shared_full_decomposition_ordering(num_cards, limits)?.to_vec())
+}
+
+fn try_shared_full_decomposition_ordering(
+    num_cards: usize,
+    limits: DecompositionLimits,
+) -> Result<Arc<[PlayRequirements]>, DecompositionTooLarge> {
     assert!(num_cards >= 1);
 
     {
-        let m = FULL_DECOMPOSITION_CACHE.lock().unwrap();
+        let m = FULL_DECOMPOSITION_CACHE.read().unwrap();
         if let Some(v) = m.get(&num_cards) {
-            return v.clone();
+            return Ok(v.clone());
         }
     }
 
+    let too_large = DecompositionTooLarge { num_cards };
+    // Count the partitions up-front, since enumerating them is itself expensive.
+    if count_tuple_partitions(num_cards) > limits.max_partitions {
+        return Err(too_large);
+    }
     let groupings = find_tuple_partitions(num_cards);
+    let full_decomp: Arc<[PlayRequirements]> =
+        decompose_groupings(&groupings, limits.max_decompositions)
+            .ok_or(too_large)?
+            .into();
 
-    let mut full_decomp = vec![];
+    let mut m = FULL_DECOMPOSITION_CACHE.write().unwrap();
+    m.insert(num_cards, full_decomp.clone());
 
-    for group in groupings {
-        // Find the non-single cards
-        let one_idx = group.iter().position(|v| *v == 1).unwrap_or(group.len());
-        let gt_1 = &group[..one_idx];
-        let eq_1 = &group[one_idx..];
+    Ok(full_decomp)
+}
 
-        if gt_1.is_empty() {
-            full_decomp.push(eq_1.iter().map(|v| vec![*v]).collect());
-        } else {
-            for mut decomposition in group_into_sequential_tuples(gt_1) {
-                decomposition.extend(eq_1.iter().map(|v| vec![*v]));
-                decomposition.sort_by(|a, b| b.cmp(a));
-                full_decomp.push(decomposition);
-            }
+///
+/// Lazily computes the same sequence of decompositions as
+/// `full_decomposition_ordering`. If the full ordering has already been
+/// computed, it is read from the cache; otherwise, decompositions are computed
+/// one tuple-partition at a time as the iterator is advanced.
+///
+pub fn full_decomposition_iter(num_cards: usize) -> impl Iterator<Item = PlayRequirements> {
+    assert!(num_cards >= 1);
+
+    let cached = FULL_DECOMPOSITION_CACHE
+        .read()
+        .unwrap()
+        .get(&num_cards)
+        .cloned();
+    match cached {
+        Some(v) => Either::Left((0..v.len()).map(move |idx| v[idx].clone())),
+        None => {
+            let groupings = find_tuple_partitions(num_cards);
+            Either::Right(
+                (0..groupings.len()).flat_map(move |idx| decompose_grouping(&groupings[idx])),
+            )
         }
     }
-    let full_decomp: Vec<_> = full_decomp.into_iter().unique().collect();
+}
 
-    let mut m = FULL_DECOMPOSITION_CACHE.lock().unwrap();
-    m.insert(num_cards, full_decomp.clone());
+/// Decomposes each of the `groupings` in turn, concatenating the results, or
+/// returns `None` once there are more than `max_decompositions` of them.
+///
+/// With the `parallel` feature, the groupings are decomposed a batch at a
+/// time, one grouping per thread; the batches are concatenated in order, so
+/// the result is the same as decomposing them serially.
+#[cfg(feature = "parallel")]
+fn decompose_groupings(
+    groupings: &[AdjacentTupleSizes],
+    max_decompositions: usize,
+) -> Option<Vec<PlayRequirements>> {
+    use rayon::prelude::*;
 
-    full_decomp
+    let mut full_decomp = vec![];
+    for batch in groupings.chunks(rayon::current_num_threads().max(1)) {
+        let decomps = batch
+            .par_iter()
+            .map(|g| decompose_grouping(g))
+            .collect::<Vec<_>>();
+        full_decomp.extend(decomps.into_iter().flatten());
+        if full_decomp.len() > max_decompositions {
+            return None;
+        }
+    }
+    Some(full_decomp)
+}
+
+#[cfg(not(feature = "parallel"))]
+fn decompose_groupings(
+    groupings: &[AdjacentTupleSizes],
+    max_decompositions: usize,
+) -> Option<Vec<PlayRequirements>> {
+    let mut full_decomp = vec![];
+    for g in groupings {
+        full_decomp.extend(decompose_grouping(g));
+        if full_decomp.len() > max_decompositions {
+            return None;
+        }
+    }
+    Some(full_decomp)
+}
+
+/// Computes all of the decompositions for a single tuple-partition, as
+/// returned by `find_tuple_partitions`.
+///
+/// Distinct partitions never produce the same decomposition, so it's
+/// sufficient to deduplicate within a single partition.
+fn decompose_grouping(group: &[usize]) -> Vec<PlayRequirements> {
+    // Find the non-single cards
+    let one_idx = group.iter().position(|v| *v == 1).unwrap_or(group.len());
+    let gt_1 = &group[..one_idx];
+    let eq_1 = &group[one_idx..];
+
+    if gt_1.is_empty() {
+        vec![eq_1.iter().map(|v| vec![*v]).collect()]
+    } else {
+        group_into_sequential_tuples(gt_1)
+            .into_iter()
+            .map(|mut decomposition| {
+                decomposition.extend(eq_1.iter().map(|v| vec![*v]));
+                decomposition.sort_by(|a, b| b.cmp(a));
+                decomposition
+            })
+            .unique()
+            .collect()
+    }
 }
 
 /// For a given number of cards `num`, compute all of the different ways we
@@ -215,55 +656,81 @@ pub fn full_decomposition_ordering(num_cards: usize) -> Vec<PlayRequirements> {
 /// e.g. find_tuple_partitions(4) gives
 /// [[4], [3, 1], [2, 2], [2, 1, 1], [1, 1, 1, 1]]
 ///
-fn find_tuple_partitions(num: usize) -> Vec<AdjacentTupleSizes> {
+/// Each partition is sorted in descending order, and the partitions
+/// themselves are in descending lexicographic order without duplicates, so
+/// the first partition is always `[num]` and the last is all ones.
+///
+pub fn find_tuple_partitions(num: usize) -> Arc<[AdjacentTupleSizes]> {
     assert!(num >= 1);
     {
-        let m = GROUP_CACHE.lock().unwrap();
+        let m = GROUP_CACHE.read().unwrap();
         if let Some(v) = m.get(&num) {
             return v.clone();
         }
     }
-    let mut groupings = Vec::new();
-    if num == 1 {
-        groupings.push(vec![1]);
-    } else {
-        let smaller_groupings = find_tuple_partitions(num - 1);
-        // try incrementing each smaller grouping
-        for mut g in smaller_groupings {
-            let mut incremented = HashSet::new();
-
-            for v in &g {
-                if !incremented.contains(v) {
-                    incremented.insert(*v);
-                    let mut found = false;
-                    let mut g_ = vec![];
-                    for vv in &g {
-                        if *vv == *v && !found {
-                            found = true;
-                            g_.push(*vv + 1);
-                        } else {
-                            g_.push(*vv);
-                        }
-                    }
-                    groupings.push(g_);
-                }
-            }
+    // Walk the partitions in descending lexicographic order: starting from
+    // `[num]`, repeatedly take the last part greater than one, decrement it,
+    // and re-spread the remainder (including any trailing ones) into parts
+    // no larger than the decremented one.
+    let mut groupings = vec![];
+    let mut current = vec![num];
+    loop {
+        groupings.push(current.clone());
 
-            groupings.push({
-                g.push(1);
-                g
-            });
+        let mut ones = 0;
+        while current.last() == Some(&1) {
+            current.pop();
+            ones += 1;
+        }
+        let part = match current.last_mut() {
+            Some(last) => {
+                *last -= 1;
+                *last
+            }
+            None => break,
+        };
+        let mut remainder = ones + 1;
+        while remainder > 0 {
+            let next = part.min(remainder);
+            current.push(next);
+            remainder -= next;
         }
     }
-    groupings.sort_by(|a, b| b.cmp(a));
-    groupings.dedup();
+    let groupings: Arc<[AdjacentTupleSizes]> = groupings.into();
 
-    let mut m = GROUP_CACHE.lock().unwrap();
+    let mut m = GROUP_CACHE.write().unwrap();
     m.insert(num, groupings.clone());
 
     groupings
 }
 
+/// Iterates over the same partitions as `find_tuple_partitions`, without
+/// copying them out of the cache.
+pub fn tuple_partitions_iter(num: usize) -> impl Iterator<Item = AdjacentTupleSizes> {
+    let partitions = find_tuple_partitions(num);
+    (0..partitions.len()).map(move |idx| partitions[idx].clone())
+}
+
+/// Iterates over the same assignments as `compute_adjacent_assignments`,
+/// without copying them out of the cache.
+pub fn adjacent_assignments_iter(length: usize) -> impl Iterator<Item = Vec<Vec<usize>>> {
+    let assignments = compute_adjacent_assignments(length);
+    (0..assignments.len()).map(move |idx| assignments[idx].clone())
+}
+
+/// Counts the number of partitions `find_tuple_partitions` would return,
+/// without enumerating them.
+fn count_tuple_partitions(num: usize) -> usize {
+    let mut ways = vec![0usize; num + 1];
+    ways[0] = 1;
+    for part in 1..=num {
+        for total in part..=num {
+            ways[total] = ways[total].saturating_add(ways[total - part]);
+        }
+    }
+    ways[num]
+}
+
 /// For a given slice of tuple-sizes, allocate them into all possible sequential
 /// orderings.
 ///
@@ -281,11 +748,11 @@ fn find_tuple_partitions(num: usize) -> Vec<AdjacentTupleSizes> {
 fn group_into_sequential_tuples(values: &[usize]) -> Vec<PlayRequirements> {
     let assignments = compute_adjacent_assignments(values.len());
     assignments
-        .into_iter()
+        .iter()
         .flat_map(|assignment| {
             let assignment: Vec<Vec<usize>> = assignment
-                .into_iter()
-                .map(|subassignment| subassignment.into_iter().map(|idx| values[idx]).collect())
+                .iter()
+                .map(|subassignment| subassignment.iter().map(|idx| values[*idx]).collect())
                 .collect();
 
             if assignment.iter().all(|p| p.iter().all(|pp| *pp == p[0])) {
@@ -308,16 +775,29 @@ fn group_into_sequential_tuples(values: &[usize]) -> Vec<PlayRequirements> {
         .collect()
 }
 
-fn compute_adjacent_assignments(length: usize) -> Vec<Vec<Usizes>> {
+///
+/// Computes all of the ways to split the indices `0..length` into groups (i.e.
+/// the set partitions of the indices), where each group becomes one sequence
+/// of adjacent tuples.
+///
+/// e.g. compute_adjacent_assignments(3) gives
+/// [[[0, 1, 2]], [[0, 1], [2]], [[0, 2], [1]], [[0], [1, 2]], [[0], [1], [2]]]
+///
+/// The indices within each group are ascending, and the groups are ordered by
+/// their first index. Assignments are ordered by descending size of their
+/// largest group, then by ascending number of groups; ties are broken in a
+/// fixed order, so the output is stable across calls.
+///
+pub fn compute_adjacent_assignments(length: usize) -> Arc<[Vec<Usizes>]> {
     assert!(length >= 1);
     if length == 1 {
-        return vec![vec![vec![0]]];
+        return Arc::from(vec![vec![vec![0]]]);
     }
 
     {
-        let m = SEQUENTIAL_ASSIGNMENT_CACHE.lock().unwrap();
-        if let Some(seq) = m.get(&length).as_ref() {
-            return seq.to_vec();
+        let m = SEQUENTIAL_ASSIGNMENT_CACHE.read().unwrap();
+        if let Some(seq) = m.get(&length) {
+            return seq.clone();
         }
     }
 
@@ -325,7 +805,8 @@ fn compute_adjacent_assignments(length: usize) -> Vec<Vec<Usizes>> {
     let shorter = compute_adjacent_assignments(length - 1);
     let mut assignments: Vec<Vec<Usizes>> = vec![];
 
-    for mut part in shorter {
+    for part in shorter.iter() {
+        let mut part = part.clone();
         for i in 0..part.len() {
             let list = part.get_mut(i).unwrap();
             list.push(elem);
@@ -345,19 +826,96 @@ fn compute_adjacent_assignments(length: usize) -> Vec<Vec<Usizes>> {
         b_max_len.cmp(&a_max_len).then(a.len().cmp(&b.len()))
     });
     assignments.dedup();
+    let assignments: Arc<[Vec<Usizes>]> = assignments.into();
 
-    let mut m = SEQUENTIAL_ASSIGNMENT_CACHE.lock().unwrap();
+    let mut m = SEQUENTIAL_ASSIGNMENT_CACHE.write().unwrap();
     m.insert(length, assignments.clone());
     assignments
 }
 
+///
+/// Breaks the cards of a single effective suit up into their "best" structure:
+/// tractors first (longest, then widest, then highest), then the remaining
+/// tuples (largest, then highest), and finally the remaining singles (highest
+/// first).
+///
+/// Each entry is a group of adjacent tuples, in the same format as produced by
+/// the format matcher; e.g. a tractor of pairs is `[(3, 2), (4, 2)]` and a
+/// single is `[(5, 1)]`.
+///
+pub fn best_structure_decomposition(
+    counts: &BTreeMap<OrderedCard, usize>,
+    adjacency_policy: AdjacencyPolicy,
+) -> Vec<MatchingCards> {
+    let mut counts = counts
+        .iter()
+        .filter(|(_, ct)| **ct > 0)
+        .map(|(c, ct)| (*c, *ct))
+        .collect::<BTreeMap<_, _>>();
+    let mut groups = vec![];
+
+    while let Some(tractor) = find_best_tractor(&counts, adjacency_policy) {
+        for (card, width) in &tractor {
+            let ct = counts.get_mut(card).unwrap();
+            *ct -= width;
+            if *ct == 0 {
+                counts.remove(card);
+            }
+        }
+        groups.push(tractor);
+    }
+
+    let mut tuples = counts.into_iter().collect::<Vec<_>>();
+    tuples.sort_by(|(a, a_ct), (b, b_ct)| b_ct.cmp(a_ct).then(b.cmp(a)));
+    groups.extend(tuples.into_iter().map(|t| vec![t]));
+    groups
+}
+
+/// Finds the longest, widest, and then highest tractor in `counts`, if any.
+fn find_best_tractor(
+    counts: &BTreeMap<OrderedCard, usize>,
+    adjacency_policy: AdjacencyPolicy,
+) -> Option<MatchingCards> {
+    let mut best: Option<(Vec<OrderedCard>, usize)> = None;
+
+    for (card, count) in counts.iter().filter(|(_, ct)| **ct >= 2) {
+        let mut stk = vec![(vec![*card], *count)];
+        while let Some((path, width)) = stk.pop() {
+            let is_better = best
+                .as_ref()
+                .map(|(p, w)| (path.len(), width, path[0]) > (p.len(), *w, p[0]))
+                .unwrap_or(true);
+            if path.len() >= 2 && is_better {
+                best = Some((path.clone(), width));
+            }
+            for next in path.last().unwrap().successor(adjacency_policy) {
+                let next_count = counts.get(&next).copied().unwrap_or_default();
+                if next_count >= 2 {
+                    let mut path = path.clone();
+                    path.push(next);
+                    stk.push((path, width.min(next_count)));
+                }
+            }
+        }
+    }
+
+    best.map(|(path, width)| path.into_iter().map(|c| (c, width)).collect())
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::{
-        compute_adjacent_assignments, find_tuple_partitions, full_decomposition_ordering,
-        subsequent_decomposition_ordering, PlayRequirements,
+        adjacent_assignments_iter, best_structure_decomposition, compute_adjacent_assignments,
+        count_tuple_partitions, decomposition_cache_stats, find_tuple_partitions,
+        full_decomposition_iter, full_decomposition_ordering, full_decomposition_ordering_by,
+        set_decomposition_cache_limits, subsequent_decomposition_iter,
+        subsequent_decomposition_ordering, try_full_decomposition_ordering,
+        try_subsequent_decomposition_iter, tuple_partitions_iter, warm_caches,
+        DecompositionCacheLimits, DecompositionLimits, DecompositionOrder, DecompositionStrategy,
+        DecompositionTooLarge, OrderedCard, PlayRequirements, CACHE_LIMITS_TEST_LOCK,
     };
+    use crate::types::{cards::*, AdjacencyPolicy, Card, Number, PermanentTrump, Suit, Trump};
 
     #[test]
     fn test_subsequent_decomposition_ordering() {
@@ -743,11 +1301,216 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_full_decomposition_ordering_by() {
+        assert_eq!(
+            full_decomposition_ordering_by(4, DecompositionOrder::LargestTuplesFirst),
+            full_decomposition_ordering(4)
+        );
+        assert_eq!(
+            full_decomposition_ordering_by(4, DecompositionOrder::LongestTractorsFirst),
+            vec![
+                vec![vec![2, 2]],
+                vec![vec![4]],
+                vec![vec![3], vec![1]],
+                vec![vec![2], vec![2]],
+                vec![vec![2], vec![1], vec![1]],
+                vec![vec![1], vec![1], vec![1], vec![1]]
+            ]
+        );
+        assert_eq!(
+            full_decomposition_ordering_by(4, DecompositionOrder::MostPairsFirst),
+            vec![
+                vec![vec![2, 2]],
+                vec![vec![2], vec![2]],
+                vec![vec![4]],
+                vec![vec![3], vec![1]],
+                vec![vec![2], vec![1], vec![1]],
+                vec![vec![1], vec![1], vec![1], vec![1]]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decomposition_iters_match_orderings() {
+        // Exercise the lazy path before the full ordering is cached.
+        assert_eq!(
+            full_decomposition_iter(9).take(3).collect::<Vec<_>>(),
+            vec![vec![vec![9]], vec![vec![8], vec![1]], vec![vec![7, 2]]]
+        );
+        for n in 1..=9 {
+            assert_eq!(
+                full_decomposition_iter(n).collect::<Vec<_>>(),
+                full_decomposition_ordering(n)
+            );
+        }
+
+        let reqs: Vec<PlayRequirements> = vec![
+            vec![vec![2]],
+            vec![vec![4, 4]],
+            vec![vec![2, 2], vec![3], vec![2]],
+            vec![vec![3], vec![3], vec![1]],
+        ];
+        for r in reqs {
+            for include_new_adjacency in [true, false] {
+                assert_eq!(
+                    subsequent_decomposition_iter(r.clone(), include_new_adjacency)
+                        .collect::<Vec<_>>(),
+                    subsequent_decomposition_ordering(r.clone(), include_new_adjacency)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bounded_decomposition_caches() {
+        let _lock = CACHE_LIMITS_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let expected = (1..=10)
+            .map(full_decomposition_ordering)
+            .collect::<Vec<_>>();
+
+        set_decomposition_cache_limits(DecompositionCacheLimits {
+            max_partitions: 2,
+            max_sequential_assignments: 2,
+            max_full_decompositions: 2,
+        });
+        for (n, e) in (1..=10).zip(expected.iter()) {
+            assert_eq!(&full_decomposition_ordering(n), e);
+        }
+        assert!(decomposition_cache_stats().full_decompositions.entries <= 2);
+
+        set_decomposition_cache_limits(DecompositionCacheLimits::default());
+        for (n, e) in (1..=10).zip(expected.iter()) {
+            assert_eq!(&full_decomposition_ordering(n), e);
+        }
+    }
+
+    #[test]
+    fn test_warm_caches() {
+        warm_caches(0);
+        warm_caches(6);
+        assert_eq!(full_decomposition_ordering(6).len(), 19);
+    }
+
+    #[test]
+    fn test_try_full_decomposition_ordering() {
+        for n in 1..8 {
+            assert_eq!(
+                try_full_decomposition_ordering(n, DecompositionLimits::default()).unwrap(),
+                full_decomposition_ordering(n)
+            );
+            assert_eq!(count_tuple_partitions(n), find_tuple_partitions(n).len());
+        }
+
+        assert_eq!(
+            try_full_decomposition_ordering(40, DecompositionLimits::default()),
+            Err(DecompositionTooLarge { num_cards: 40 })
+        );
+        let limits = DecompositionLimits {
+            max_partitions: usize::MAX,
+            max_decompositions: 100,
+        };
+        assert_eq!(
+            try_full_decomposition_ordering(23, limits),
+            Err(DecompositionTooLarge { num_cards: 23 })
+        );
+        assert!(try_subsequent_decomposition_iter(vec![vec![23]], true, limits).is_err());
+        assert!(try_subsequent_decomposition_iter(vec![vec![2, 2]], true, limits).is_ok());
+    }
+
+    #[test]
+    fn test_decomposition_limits() {
+        let _lock = CACHE_LIMITS_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        // Cached decompositions are returned regardless of the limits, so
+        // turn the cache off to check them.
+        set_decomposition_cache_limits(DecompositionCacheLimits {
+            max_full_decompositions: 0,
+            ..DecompositionCacheLimits::default()
+        });
+        let too_large = |num_cards| Err(DecompositionTooLarge { num_cards });
+
+        // Hitting the partition limit.
+        let partitions = count_tuple_partitions(14);
+        let limits = DecompositionLimits {
+            max_partitions: partitions - 1,
+            max_decompositions: usize::MAX,
+        };
+        assert_eq!(try_full_decomposition_ordering(14, limits), too_large(14));
+        assert!(try_subsequent_decomposition_iter(vec![vec![14]], true, limits).is_err());
+        // Each requirement is decomposed separately.
+        assert!(try_subsequent_decomposition_iter(vec![vec![7], vec![7]], true, limits).is_ok());
+        let limits = DecompositionLimits {
+            max_partitions: partitions,
+            ..limits
+        };
+        assert_eq!(
+            try_full_decomposition_ordering(14, limits),
+            Ok(full_decomposition_iter(14).collect::<Vec<_>>())
+        );
+
+        // Hitting the decomposition limit.
+        let decomps = full_decomposition_iter(13).collect::<Vec<_>>();
+        let limits = DecompositionLimits {
+            max_partitions: usize::MAX,
+            max_decompositions: decomps.len() - 1,
+        };
+        assert_eq!(try_full_decomposition_ordering(13, limits), too_large(13));
+        assert!(try_subsequent_decomposition_iter(vec![vec![13]], false, limits).is_err());
+        let limits = DecompositionLimits {
+            max_decompositions: decomps.len(),
+            ..limits
+        };
+        assert_eq!(try_full_decomposition_ordering(13, limits), Ok(decomps));
+
+        // Nothing is too large without limits.
+        let tiny = DecompositionLimits {
+            max_partitions: 1,
+            max_decompositions: 1,
+        };
+        assert_eq!(try_full_decomposition_ordering(2, tiny), too_large(2));
+        assert_eq!(
+            try_full_decomposition_ordering(12, DecompositionLimits::unlimited()),
+            Ok(full_decomposition_iter(12).collect::<Vec<_>>())
+        );
+        assert!(try_subsequent_decomposition_iter(
+            vec![vec![2, 2], vec![12]],
+            true,
+            DecompositionLimits::unlimited()
+        )
+        .is_ok());
+
+        set_decomposition_cache_limits(DecompositionCacheLimits::default());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_decomposition_matches_serial() {
+        use super::{decompose_grouping, decompose_groupings};
+
+        for n in 1..=12 {
+            let groupings = find_tuple_partitions(n);
+            let serial = groupings
+                .iter()
+                .flat_map(|g| decompose_grouping(g))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                decompose_groupings(&groupings, usize::MAX).as_ref(),
+                Some(&serial)
+            );
+            assert_eq!(decompose_groupings(&groupings, serial.len() - 1), None);
+            assert_eq!(full_decomposition_ordering(n), serial);
+        }
+    }
+
     #[test]
     fn test_compute_adjacent_assignments() {
         let f = |n| -> Vec<Vec<Vec<usize>>> {
             compute_adjacent_assignments(n)
-                .into_iter()
+                .iter()
                 .map(|x| x.iter().map(|y| y.to_vec()).collect::<Vec<_>>())
                 .collect::<Vec<_>>()
         };
@@ -785,11 +1548,116 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_partition_and_assignment_iters() {
+        for n in 1..7 {
+            assert_eq!(
+                tuple_partitions_iter(n).collect::<Vec<_>>(),
+                find_tuple_partitions(n).to_vec()
+            );
+            assert_eq!(
+                adjacent_assignments_iter(n).collect::<Vec<_>>(),
+                compute_adjacent_assignments(n).to_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn test_partition_and_assignment_iters_outlive_cache() {
+        let _lock = CACHE_LIMITS_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let expected = (1..=8)
+            .map(|n| {
+                (
+                    find_tuple_partitions(n).to_vec(),
+                    compute_adjacent_assignments(n).to_vec(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Iterators which were started before their entries are evicted
+        // still yield all of the items.
+        let mut iters = (1..=8)
+            .map(|n| (tuple_partitions_iter(n), adjacent_assignments_iter(n)))
+            .collect::<Vec<_>>();
+        for (partitions, assignments) in &mut iters {
+            partitions.next();
+            assignments.next();
+        }
+        set_decomposition_cache_limits(DecompositionCacheLimits {
+            max_partitions: 0,
+            max_sequential_assignments: 0,
+            max_full_decompositions: 0,
+        });
+        for ((partitions, assignments), (p, a)) in iters.into_iter().zip(&expected) {
+            assert_eq!(partitions.collect::<Vec<_>>(), p[1..]);
+            assert_eq!(assignments.collect::<Vec<_>>(), a[1..]);
+        }
+
+        // Without a cache, they're computed from scratch.
+        for (n, (p, a)) in (1..=8).zip(&expected) {
+            assert_eq!(&tuple_partitions_iter(n).collect::<Vec<_>>(), p);
+            assert_eq!(&adjacent_assignments_iter(n).collect::<Vec<_>>(), a);
+        }
+
+        set_decomposition_cache_limits(DecompositionCacheLimits::default());
+    }
+
+    /// The recursive construction `find_tuple_partitions` used to use: grow
+    /// one of each distinct tuple-size in the partitions of `num - 1` by a
+    /// card, or add a single.
+    fn recursive_tuple_partitions(num: usize) -> Vec<Vec<usize>> {
+        if num == 1 {
+            return vec![vec![1]];
+        }
+        let mut groupings = vec![];
+        for g in recursive_tuple_partitions(num - 1) {
+            for (idx, v) in g.iter().enumerate() {
+                if !g[..idx].contains(v) {
+                    let mut grown = g.clone();
+                    grown[idx] += 1;
+                    groupings.push(grown);
+                }
+            }
+            let mut g = g;
+            g.push(1);
+            groupings.push(g);
+        }
+        groupings.sort_by(|a, b| b.cmp(a));
+        groupings.dedup();
+        groupings
+    }
+
+    #[test]
+    fn test_tuple_partitions_match_recursive() {
+        for n in [1, 2, 3, 5, 8, 11, 16] {
+            let expected = recursive_tuple_partitions(n);
+            assert_eq!(find_tuple_partitions(n).to_vec(), expected);
+            assert_eq!(count_tuple_partitions(n), expected.len());
+        }
+    }
+
+    #[test]
+    fn test_find_tuple_partitions_large() {
+        for n in [12, 20, 30] {
+            let partitions = find_tuple_partitions(n);
+            assert_eq!(partitions.len(), count_tuple_partitions(n));
+            assert_eq!(partitions[0], vec![n]);
+            assert_eq!(partitions[partitions.len() - 1], vec![1; n]);
+            for p in partitions.iter() {
+                assert_eq!(p.iter().sum::<usize>(), n);
+                assert!(p.windows(2).all(|w| w[0] >= w[1]));
+            }
+            assert!(partitions.windows(2).all(|w| w[0] > w[1]));
+        }
+    }
+
     #[test]
     fn test_find_tuple_partitions() {
         let f = |n| -> Vec<Vec<usize>> {
             find_tuple_partitions(n)
-                .into_iter()
+                .iter()
                 .map(|x| x.to_vec())
                 .collect::<Vec<_>>()
         };
@@ -837,4 +1705,178 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_best_structure_decomposition() {
+        let trump = Trump::Standard {
+            number: Number::Four,
+            suit: Suit::Spades,
+            permanent: PermanentTrump::None,
+        };
+        let oc = |card| OrderedCard { card, trump };
+        let counts = OrderedCard::make_map(
+            vec![
+                S_2, S_2, S_3, S_3, S_5, S_5, S_7, S_7, S_8, S_9, S_9, S_9, S_J, S_J, S_J, S_Q,
+                S_Q, S_Q, S_K, S_K, S_A, S_A, S_A,
+            ]
+            .into_iter(),
+            trump,
+        );
+
+        assert_eq!(
+            best_structure_decomposition(&counts, AdjacencyPolicy::default()),
+            vec![
+                vec![(oc(S_J), 2), (oc(S_Q), 2), (oc(S_K), 2), (oc(S_A), 2)],
+                // The trump number is skipped, so 2-3-5 is also a tractor.
+                vec![(oc(S_2), 2), (oc(S_3), 2), (oc(S_5), 2)],
+                vec![(oc(S_9), 3)],
+                vec![(oc(S_7), 2)],
+                vec![(oc(S_A), 1)],
+                vec![(oc(S_Q), 1)],
+                vec![(oc(S_J), 1)],
+                vec![(oc(S_8), 1)],
+            ]
+        );
+
+        // Longer tractors are preferred over wider ones.
+        let counts = OrderedCard::make_map(
+            vec![S_6, S_6, S_7, S_7, S_7, S_8, S_8, S_8].into_iter(),
+            trump,
+        );
+        assert_eq!(
+            best_structure_decomposition(&counts, AdjacencyPolicy::default()),
+            vec![
+                vec![(oc(S_6), 2), (oc(S_7), 2), (oc(S_8), 2)],
+                vec![(oc(S_8), 1)],
+                vec![(oc(S_7), 1)],
+            ]
+        );
+        let counts = OrderedCard::make_map(
+            vec![S_6, S_6, S_7, S_7, S_7, S_8, S_8, S_8, S_10, S_10].into_iter(),
+            trump,
+        );
+        assert_eq!(
+            best_structure_decomposition(&counts, AdjacencyPolicy::default()),
+            vec![
+                vec![(oc(S_6), 2), (oc(S_7), 2), (oc(S_8), 2)],
+                vec![(oc(S_10), 2)],
+                vec![(oc(S_8), 1)],
+                vec![(oc(S_7), 1)],
+            ]
+        );
+        assert!(
+            best_structure_decomposition(&Default::default(), AdjacencyPolicy::default())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_distance() {
+        let trump = Trump::Standard {
+            number: Number::Four,
+            suit: Suit::Spades,
+            permanent: PermanentTrump::None,
+        };
+        let oc = |card| OrderedCard { card, trump };
+        let d = |a, b| oc(a).distance(oc(b), AdjacencyPolicy::default());
+        assert_eq!(d(S_3, S_3), Some(0));
+        assert_eq!(d(S_3, S_5), Some(1));
+        assert_eq!(d(S_2, S_6), Some(3));
+        assert_eq!(d(S_K, H_4), Some(2));
+        assert_eq!(d(S_A, Card::BigJoker), Some(4));
+        assert_eq!(d(S_5, S_3), None);
+        assert_eq!(d(H_3, H_5), Some(1));
+        assert_eq!(d(H_3, S_5), None);
+        assert!(oc(H_3).skips_trump_number());
+        assert!(!oc(H_5).skips_trump_number());
+        assert_eq!(
+            oc(H_3).distance(
+                oc(H_5),
+                AdjacencyPolicy {
+                    skip_trump_number: false,
+                    ..Default::default()
+                }
+            ),
+            None
+        );
+        assert_eq!(
+            oc(H_4).distance(
+                oc(Card::BigJoker),
+                AdjacencyPolicy {
+                    off_suit_trump_number_connects: false,
+                    skip_trump_number: true,
+                    jokers_connect: true,
+                }
+            ),
+            None
+        );
+        assert_eq!(
+            oc(S_5).predecessor(AdjacencyPolicy::default()),
+            vec![oc(S_3)]
+        );
+    }
+
+    #[test]
+    fn test_decomposition_strategies() {
+        let first_two = |adj_reqs: PlayRequirements, strategy| {
+            subsequent_decomposition_iter(adj_reqs, true)
+                .with_strategy(strategy)
+                .take(2)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            first_two(
+                vec![vec![2, 2], vec![3]],
+                DecompositionStrategy::MostOptionsFirst
+            ),
+            vec![
+                vec![vec![3], vec![2], vec![2]],
+                vec![vec![3], vec![2], vec![1], vec![1]]
+            ]
+        );
+        assert_eq!(
+            first_two(
+                vec![vec![2, 2], vec![3]],
+                DecompositionStrategy::PreserveTractors
+            ),
+            vec![
+                vec![vec![2, 2], vec![2], vec![1]],
+                vec![vec![2, 2], vec![1], vec![1], vec![1]]
+            ]
+        );
+        assert_eq!(
+            first_two(
+                vec![vec![2, 2], vec![3]],
+                DecompositionStrategy::PreservePairs
+            ),
+            vec![
+                vec![vec![3], vec![2], vec![2]],
+                vec![vec![3], vec![2], vec![1], vec![1]]
+            ]
+        );
+        assert_eq!(
+            first_two(
+                vec![vec![2, 2], vec![3]],
+                DecompositionStrategy::FewestForcedUnits
+            ),
+            vec![
+                vec![vec![3], vec![2], vec![2]],
+                vec![vec![2], vec![2], vec![2], vec![1]]
+            ]
+        );
+        // Every strategy eventually decomposes everything into singles.
+        for strategy in [
+            DecompositionStrategy::MostOptionsFirst,
+            DecompositionStrategy::PreserveTractors,
+            DecompositionStrategy::PreservePairs,
+            DecompositionStrategy::FewestForcedUnits,
+        ] {
+            assert_eq!(
+                subsequent_decomposition_iter(vec![vec![2, 2], vec![2]], true)
+                    .with_strategy(strategy)
+                    .last(),
+                Some(vec![vec![1]; 6])
+            );
+        }
+    }
 }
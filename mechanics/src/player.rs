@@ -1,6 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::sorting::SortOrder;
 use crate::types::{Number, PlayerID, Rank};
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -9,6 +10,10 @@ pub struct Player {
     pub name: String,
     pub level: Rank,
     pub metalevel: usize,
+    /// How the player would like their hand to be sorted, so that it's
+    /// displayed the same way on every device they connect from.
+    #[serde(default)]
+    pub sort_order: SortOrder,
 }
 
 impl Player {
@@ -18,6 +23,7 @@ impl Player {
             name,
             level: Rank::Number(Number::Two),
             metalevel: 1,
+            sort_order: SortOrder::default(),
         }
     }
 
@@ -33,6 +39,18 @@ impl Player {
         self.metalevel = metalevel;
     }
 
+    /// Moves the player down a rank, stopping at 2. Returns whether the rank
+    /// changed.
+    pub fn demote(&mut self) -> bool {
+        match self.level.predecessor() {
+            Some(previous_level) => {
+                self.level = previous_level;
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn advance(&mut self, max_rank: Rank) {
         match self.level.successor() {
             Some(next_level) if self.level != max_rank => {
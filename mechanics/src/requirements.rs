@@ -0,0 +1,120 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::ordered_card::PlayRequirements;
+
+/// A wrapper around `PlayRequirements` with a compact textual form.
+///
+/// Each group of adjacent tuple sizes is written with its sizes joined by
+/// `-`, and the groups are separated by `|`. For example, a tractor of two
+/// triples, a pair, and a single is written as `3-3|2|1`, i.e.
+/// `vec![vec![3, 3], vec![2], vec![1]]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Requirements(pub PlayRequirements);
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseRequirementsError {
+    #[error("empty group of tuples in {0:?}")]
+    EmptyGroup(String),
+    #[error("invalid tuple size {0:?}")]
+    InvalidTupleSize(String),
+}
+
+impl fmt::Display for Requirements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, group) in self.0.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "|")?;
+            }
+            for (jdx, size) in group.iter().enumerate() {
+                if jdx > 0 {
+                    write!(f, "-")?;
+                }
+                write!(f, "{}", size)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Requirements {
+    type Err = ParseRequirementsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(Requirements(vec![]));
+        }
+        s.split('|')
+            .map(|group| {
+                let group = group.trim();
+                if group.is_empty() {
+                    return Err(ParseRequirementsError::EmptyGroup(s.to_string()));
+                }
+                group
+                    .split('-')
+                    .map(|size| match size.trim().parse::<usize>() {
+                        Ok(n) if n > 0 => Ok(n),
+                        _ => Err(ParseRequirementsError::InvalidTupleSize(size.to_string())),
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()
+            .map(Requirements)
+    }
+}
+
+impl From<PlayRequirements> for Requirements {
+    fn from(requirements: PlayRequirements) -> Self {
+        Requirements(requirements)
+    }
+}
+
+impl From<Requirements> for PlayRequirements {
+    fn from(requirements: Requirements) -> Self {
+        requirements.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseRequirementsError, Requirements};
+    use crate::ordered_card::full_decomposition_ordering;
+
+    #[test]
+    fn test_requirements_round_trip() {
+        let r: Requirements = "3-3|2|1".parse().unwrap();
+        assert_eq!(r, Requirements(vec![vec![3, 3], vec![2], vec![1]]));
+        assert_eq!(r.to_string(), "3-3|2|1");
+        assert_eq!(
+            " 2 - 2 | 1 ".parse::<Requirements>().unwrap(),
+            Requirements(vec![vec![2, 2], vec![1]])
+        );
+        assert_eq!("".parse::<Requirements>().unwrap(), Requirements(vec![]));
+
+        for n in 1..7 {
+            for requirements in full_decomposition_ordering(n) {
+                let r = Requirements(requirements);
+                assert_eq!(r.to_string().parse::<Requirements>().unwrap(), r);
+            }
+        }
+    }
+
+    #[test]
+    fn test_requirements_parse_errors() {
+        assert_eq!(
+            "2||1".parse::<Requirements>(),
+            Err(ParseRequirementsError::EmptyGroup("2||1".to_string()))
+        );
+        assert_eq!(
+            "2-0".parse::<Requirements>(),
+            Err(ParseRequirementsError::InvalidTupleSize("0".to_string()))
+        );
+        assert_eq!(
+            "2-x".parse::<Requirements>(),
+            Err(ParseRequirementsError::InvalidTupleSize("x".to_string()))
+        );
+    }
+}
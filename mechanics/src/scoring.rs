@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use anyhow::{anyhow, bail, Error};
 use schemars::JsonSchema;
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use slog_derive::KV;
 
 use crate::deck::Deck;
+use crate::types::{Card, Number};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
 pub enum BonusLevelPolicy {
@@ -16,6 +17,59 @@ pub enum BonusLevelPolicy {
 
 crate::impl_slog_value!(BonusLevelPolicy);
 
+/// How many points each card is worth.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PointSchedule {
+    /// The points for each number, in every suit. Numbers which aren't listed
+    /// are worth nothing.
+    pub numbers: BTreeMap<Number, usize>,
+    /// The points for specific cards, e.g. the jokers. These take precedence
+    /// over `numbers`.
+    pub cards: HashMap<Card, usize>,
+}
+
+impl Default for PointSchedule {
+    /// 5s are worth 5 points, and 10s and Ks are worth 10.
+    fn default() -> Self {
+        Self {
+            numbers: [(Number::Five, 5), (Number::Ten, 10), (Number::King, 10)]
+                .into_iter()
+                .collect(),
+            cards: HashMap::new(),
+        }
+    }
+}
+
+impl PointSchedule {
+    /// The points the card is worth, or `None` if it isn't a point card.
+    pub fn points(&self, card: Card) -> Option<usize> {
+        self.cards
+            .get(&card)
+            .or_else(|| card.number().and_then(|n| self.numbers.get(&n)))
+            .copied()
+            .filter(|p| *p > 0)
+    }
+
+    pub fn is_point_card(&self, card: Card) -> bool {
+        self.points(card).is_some()
+    }
+
+    /// Whether every card of this number is worth points.
+    pub fn is_point_number(&self, number: Number) -> bool {
+        self.numbers.get(&number).is_some_and(|p| *p > 0)
+    }
+
+    pub fn total(&self, cards: impl IntoIterator<Item = Card>) -> usize {
+        cards.into_iter().flat_map(|c| self.points(c)).sum()
+    }
+
+    /// The total number of points in the decks.
+    pub fn deck_points(&self, decks: &[Deck]) -> usize {
+        self.total(Deck::all_cards(decks))
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct PartialGameScoreResult {
     landlord_won: bool,
@@ -78,6 +132,18 @@ pub struct GameScoringParameters {
     deadzone_size: usize,
     truncate_zero_crossing_window: bool,
     pub bonus_level_policy: BonusLevelPolicy,
+    #[slog(skip)]
+    #[serde(default)]
+    pub point_schedule: PointSchedule,
+    /// Extra levels for the landlord's team if the other team doesn't
+    /// capture any points, or zero for no bonus.
+    #[serde(default)]
+    pub shutout_bonus_levels: usize,
+    /// The number of steps the other team has to reach for the landlord's
+    /// team to be demoted a level, e.g. twice the steps needed to take
+    /// over; or zero to never demote.
+    #[serde(default)]
+    pub demotion_steps: usize,
 }
 crate::impl_slog_value!(GameScoringParameters);
 
@@ -90,6 +156,9 @@ impl Default for GameScoringParameters {
             truncate_zero_crossing_window: true,
             step_adjustments: HashMap::new(),
             bonus_level_policy: BonusLevelPolicy::default(),
+            point_schedule: PointSchedule::default(),
+            shutout_bonus_levels: 0,
+            demotion_steps: 0,
         }
     }
 }
@@ -97,7 +166,7 @@ impl Default for GameScoringParameters {
 impl GameScoringParameters {
     pub fn step_size(&self, decks: &[Deck]) -> Result<usize, Error> {
         let num_decks = decks.len();
-        let total_points = decks.iter().map(|d| d.points() as isize).sum::<isize>();
+        let total_points = self.point_schedule.deck_points(decks) as isize;
         let step_size = (num_decks * self.step_size_per_deck) as isize
             + self
                 .step_adjustments
@@ -117,10 +186,38 @@ impl GameScoringParameters {
         }
     }
 
+    /// The extra levels the landlord's team earns for keeping the other team
+    /// to no points at all.
+    pub fn shutout_bonus(&self, non_landlords_points: isize) -> usize {
+        if non_landlords_points <= 0 {
+            self.shutout_bonus_levels
+        } else {
+            0
+        }
+    }
+
+    /// Whether the landlord's team lost by enough to be demoted a level.
+    pub fn demotes_landlord(
+        &self,
+        decks: &[Deck],
+        non_landlords_points: isize,
+    ) -> Result<bool, Error> {
+        if self.demotion_steps == 0 {
+            return Ok(false);
+        }
+        let threshold = (self.demotion_steps * self.step_size(decks)?) as isize;
+        Ok(non_landlords_points >= threshold)
+    }
+
     pub fn materialize(&self, decks: &[Deck]) -> Result<MaterializedScoringParameters, Error> {
         if self.num_steps_to_non_landlord_turnover == 0 {
             bail!("Landlord team must be able to win")
         }
+        if self.demotion_steps != 0
+            && self.demotion_steps <= self.num_steps_to_non_landlord_turnover
+        {
+            bail!("The landlord team can only be demoted after the other team takes over")
+        }
 
         let s = self.step_size(decks)? as isize;
         let landlord_wins = if self.truncate_zero_crossing_window {
@@ -175,16 +272,77 @@ impl GameScoringParameters {
         MaterializedScoringParameters::new(
             landlord_wins.into_iter().rev(),
             landlord_loses,
-            decks.iter().map(|d| d.points()).sum::<usize>() as isize,
+            self.point_schedule.deck_points(decks) as isize,
         )
     }
 }
 
+/// The most levels a single game can be scheduled to be worth.
+const MAX_SCHEDULED_LEVELS: usize = 13;
+
+/// How many levels each scoring window is worth. The windows are counted out
+/// from where the attacking team takes over: the landlord's team's windows
+/// going down in points, and the attacking team's going up past the dead
+/// zone. Windows past the end of a list are each worth one more level than
+/// the one before, so the default, empty, schedule goes up by one level per
+/// window.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct AdvancementSchedule {
+    /// The levels for the landlord's team in each window they win, starting
+    /// with the one just below the turnover.
+    pub landlord_levels: Vec<usize>,
+    /// The levels for the attacking team in each window they go up in,
+    /// starting with the first one past the dead zone.
+    pub attacking_levels: Vec<usize>,
+}
+crate::impl_slog_value!(AdvancementSchedule);
+
+impl AdvancementSchedule {
+    pub fn validate(&self) -> Result<(), Error> {
+        for levels in [&self.landlord_levels, &self.attacking_levels] {
+            if levels.contains(&0) {
+                bail!("Each scoring window has to be worth at least one level")
+            }
+            if levels.iter().any(|l| *l > MAX_SCHEDULED_LEVELS) {
+                bail!(
+                    "A scoring window can be worth at most {} levels",
+                    MAX_SCHEDULED_LEVELS
+                )
+            }
+            if levels.windows(2).any(|w| w[0] > w[1]) {
+                bail!("Scoring windows further from the turnover can't be worth fewer levels")
+            }
+        }
+        Ok(())
+    }
+
+    /// The levels for the `window`th window, counting from 1; the 0th window
+    /// is the dead zone.
+    fn levels(levels: &[usize], window: usize) -> usize {
+        match (window, levels.last()) {
+            (0, _) | (_, None) => window,
+            (w, Some(_)) if w <= levels.len() => levels[w - 1],
+            (w, Some(last)) => last + (w - levels.len()),
+        }
+    }
+
+    pub fn landlord_levels(&self, window: usize) -> usize {
+        Self::levels(&self.landlord_levels, window)
+    }
+
+    pub fn attacking_levels(&self, window: usize) -> usize {
+        Self::levels(&self.attacking_levels, window)
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct MaterializedScoringParameters {
     landlord_wins: Vec<LandlordWinningScoreSegment>,
     landlord_loses: Vec<LandlordLosingScoreSegment>,
     total_points: isize,
+    #[serde(default)]
+    schedule: AdvancementSchedule,
 }
 
 impl MaterializedScoringParameters {
@@ -198,6 +356,7 @@ impl MaterializedScoringParameters {
             landlord_wins: landlord_wins.into_iter().collect(),
             landlord_loses: landlord_loses.into_iter().collect(),
             total_points,
+            schedule: AdvancementSchedule::default(),
         };
         gsp.landlord_wins.sort_by_key(|s| s.start);
         gsp.landlord_loses.sort_by_key(|s| s.start);
@@ -243,7 +402,23 @@ impl MaterializedScoringParameters {
         Ok(gsp)
     }
 
+    /// Scores the windows with `schedule` rather than one level per window.
+    pub fn with_schedule(mut self, schedule: &AdvancementSchedule) -> Result<Self, Error> {
+        schedule.validate()?;
+        self.schedule = schedule.clone();
+        Ok(self)
+    }
+
     pub fn score(&self, non_landlords_points: isize) -> Result<PartialGameScoreResult, Error> {
+        Ok(self.score_segment(non_landlords_points)?.0)
+    }
+
+    /// Scores the game, along with the end (exclusive) of the run of scores
+    /// which get the same result.
+    pub fn score_segment(
+        &self,
+        non_landlords_points: isize,
+    ) -> Result<(PartialGameScoreResult, isize), Error> {
         let landlord_won = non_landlords_points
             < self
                 .landlord_wins
@@ -254,21 +429,29 @@ impl MaterializedScoringParameters {
         if landlord_won {
             for s in PropagateMore::new(self.landlord_wins.iter().rev().copied()).take(50) {
                 if s.start <= non_landlords_points && non_landlords_points < s.end {
-                    return Ok(PartialGameScoreResult {
-                        non_landlord_delta: 0,
-                        landlord_delta: s.landlord_delta,
-                        landlord_won: true,
-                    });
+                    return Ok((
+                        PartialGameScoreResult {
+                            non_landlord_delta: 0,
+                            landlord_delta: self.schedule.landlord_levels(s.landlord_delta),
+                            landlord_won: true,
+                        },
+                        s.end,
+                    ));
                 }
             }
         } else {
             for s in PropagateMore::new(self.landlord_loses.iter().copied()).take(50) {
                 if s.start <= non_landlords_points && non_landlords_points < s.end {
-                    return Ok(PartialGameScoreResult {
-                        non_landlord_delta: s.non_landlord_delta,
-                        landlord_delta: 0,
-                        landlord_won: false,
-                    });
+                    return Ok((
+                        PartialGameScoreResult {
+                            non_landlord_delta: self
+                                .schedule
+                                .attacking_levels(s.non_landlord_delta),
+                            landlord_delta: 0,
+                            landlord_won: false,
+                        },
+                        s.end,
+                    ));
                 }
             }
         }
@@ -382,46 +565,122 @@ impl Propagatable for LandlordLosingScoreSegment {
 
 pub fn explain_level_deltas(
     gsp: &GameScoringParameters,
+    schedule: &AdvancementSchedule,
     decks: &[Deck],
     smaller_landlord_team_size: bool,
 ) -> Result<Vec<(isize, GameScoreResult)>, Error> {
-    gsp.materialize(decks)?.explain().map(|explanation| {
-        explanation
-            .into_iter()
-            .map(|(pts, gsr)| {
-                (
-                    pts,
-                    GameScoreResult::new(gsr, gsp.bonus_level_policy, smaller_landlord_team_size),
-                )
-            })
-            .collect()
-    })
+    gsp.materialize(decks)?
+        .with_schedule(schedule)?
+        .explain()
+        .map(|explanation| {
+            explanation
+                .into_iter()
+                .map(|(pts, gsr)| {
+                    (
+                        pts,
+                        GameScoreResult::new(
+                            gsr,
+                            gsp.bonus_level_policy,
+                            smaller_landlord_team_size,
+                        ),
+                    )
+                })
+                .collect()
+        })
 }
 
 pub fn compute_level_deltas(
     gsp: &GameScoringParameters,
+    schedule: &AdvancementSchedule,
     decks: &[Deck],
     non_landlords_points: isize,
     smaller_landlord_team_size: bool,
 ) -> Result<GameScoreResult, Error> {
     Ok(GameScoreResult::new(
-        gsp.materialize(decks)?.score(non_landlords_points)?,
+        gsp.materialize(decks)?
+            .with_schedule(schedule)?
+            .score(non_landlords_points)?,
         gsp.bonus_level_policy,
         smaller_landlord_team_size,
     ))
 }
 
+/// A range of final scores for the attacking team, all with the same result.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ScoreBand {
+    pub min_points: isize,
+    pub max_points: isize,
+    pub result: GameScoreResult,
+}
+
+/// Every result the game could still end in, given that the attacking team
+/// has `non_landlords_points` and could gain up to `max_gain` more, lowest
+/// scores first. Scores too far out to be scored at all are left out.
+pub fn remaining_score_bands(
+    gsp: &GameScoringParameters,
+    schedule: &AdvancementSchedule,
+    decks: &[Deck],
+    non_landlords_points: isize,
+    max_gain: isize,
+    smaller_landlord_team_size: bool,
+) -> Result<Vec<ScoreBand>, Error> {
+    let m = gsp.materialize(decks)?.with_schedule(schedule)?;
+    let max_points = non_landlords_points.saturating_add(max_gain.max(0));
+    let mut bands: Vec<ScoreBand> = vec![];
+    let mut points = non_landlords_points;
+    while points <= max_points {
+        let (result, end) = match m.score_segment(points) {
+            Ok(segment) => segment,
+            Err(_) if !bands.is_empty() => break,
+            Err(e) => return Err(e),
+        };
+        let result =
+            GameScoreResult::new(result, gsp.bonus_level_policy, smaller_landlord_team_size);
+        let band_end = (end - 1).min(max_points);
+        match bands.last_mut() {
+            Some(band) if band.result == result => band.max_points = band_end,
+            _ => bands.push(ScoreBand {
+                min_points: points,
+                max_points: band_end,
+                result,
+            }),
+        }
+        points = end;
+    }
+    Ok(bands)
+}
+
+/// The highest threshold above zero which the attacking team's points reached,
+/// i.e. the lowest score which still gets the same result, if any.
+pub fn threshold_reached(
+    gsp: &GameScoringParameters,
+    schedule: &AdvancementSchedule,
+    decks: &[Deck],
+    non_landlords_points: isize,
+) -> Result<Option<isize>, Error> {
+    Ok(gsp
+        .materialize(decks)?
+        .with_schedule(schedule)?
+        .explain()?
+        .into_iter()
+        .skip(1)
+        .map(|(pts, _)| pts)
+        .rev()
+        .find(|pts| *pts <= non_landlords_points))
+}
+
 /// Computes whether the game can be considered "finished" (i.e. there are insufficient remaining
 /// points for the attacking team to change the outcome of the game).
 ///
 /// Note: does not account for kitty bonuses.
 pub fn next_threshold_reachable(
     gsp: &GameScoringParameters,
+    schedule: &AdvancementSchedule,
     decks: &[Deck],
     non_landlords_points: isize,
     observed_points: isize,
 ) -> Result<bool, Error> {
-    let m = gsp.materialize(decks)?;
+    let m = gsp.materialize(decks)?.with_schedule(schedule)?;
     let threshold = m.next_relevant_score(non_landlords_points)?.0;
     let total_points = m.total_points();
     let remaining_points = total_points - observed_points;
@@ -430,9 +689,14 @@ pub fn next_threshold_reachable(
 
 #[cfg(test)]
 mod tests {
-    use super::{compute_level_deltas, BonusLevelPolicy, GameScoreResult, GameScoringParameters};
+    use super::{
+        compute_level_deltas, remaining_score_bands, threshold_reached, AdvancementSchedule,
+        BonusLevelPolicy, GameScoreResult, GameScoringParameters, PointSchedule,
+    };
 
     use crate::deck::Deck;
+    use crate::player::Player;
+    use crate::types::{cards, Card, Number, PlayerID, Rank};
 
     #[test]
     fn test_level_deltas() {
@@ -443,7 +707,14 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, -80, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                -80,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 0,
                 landlord_delta: 5,
@@ -452,7 +723,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, -40, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                -40,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 0,
                 landlord_delta: 4,
@@ -461,7 +739,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, -35, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                -35,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 0,
                 landlord_delta: 3,
@@ -470,7 +755,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 0, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                0,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 0,
                 landlord_delta: 3,
@@ -479,7 +771,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 5, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                5,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 0,
                 landlord_delta: 2,
@@ -488,7 +787,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 35, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                35,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 0,
                 landlord_delta: 2,
@@ -497,7 +803,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 40, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                40,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 0,
                 landlord_delta: 1,
@@ -506,7 +819,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 75, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                75,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 0,
                 landlord_delta: 1,
@@ -515,7 +835,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 80, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                80,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 0,
                 landlord_delta: 0,
@@ -524,7 +851,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 115, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                115,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 0,
                 landlord_delta: 0,
@@ -533,7 +867,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 120, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                120,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 1,
                 landlord_delta: 0,
@@ -542,7 +883,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 155, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                155,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 1,
                 landlord_delta: 0,
@@ -551,7 +899,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 160, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                160,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 2,
                 landlord_delta: 0,
@@ -560,7 +915,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 195, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                195,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 2,
                 landlord_delta: 0,
@@ -569,7 +931,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 200, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                200,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 3,
                 landlord_delta: 0,
@@ -578,7 +947,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 235, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                235,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 3,
                 landlord_delta: 0,
@@ -587,7 +963,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 240, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                240,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 4,
                 landlord_delta: 0,
@@ -596,7 +979,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&gsp_nobonus, &decks, 280, false,).unwrap(),
+            compute_level_deltas(
+                &gsp_nobonus,
+                &AdvancementSchedule::default(),
+                &decks,
+                280,
+                false,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 5,
                 landlord_delta: 0,
@@ -605,7 +995,14 @@ mod tests {
             })
         );
         assert_eq!(
-            compute_level_deltas(&GameScoringParameters::default(), &decks, 0, true,).unwrap(),
+            compute_level_deltas(
+                &GameScoringParameters::default(),
+                &AdvancementSchedule::default(),
+                &decks,
+                0,
+                true,
+            )
+            .unwrap(),
             (GameScoreResult {
                 non_landlord_delta: 0,
                 landlord_delta: 4,
@@ -616,6 +1013,7 @@ mod tests {
         assert_eq!(
             compute_level_deltas(
                 &GameScoringParameters::default(),
+                &AdvancementSchedule::default(),
                 &[Deck::default(), Deck::default(), Deck::default()],
                 0,
                 true,
@@ -631,6 +1029,7 @@ mod tests {
         assert_eq!(
             compute_level_deltas(
                 &GameScoringParameters::default(),
+                &AdvancementSchedule::default(),
                 &[Deck::default(), Deck::default(), Deck::default()],
                 50,
                 true,
@@ -644,4 +1043,159 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_advancement_schedule() {
+        let decks = [Deck::default(), Deck::default()];
+        let gsp = GameScoringParameters::default();
+        let schedule = AdvancementSchedule {
+            landlord_levels: vec![1, 1, 2],
+            attacking_levels: vec![2, 3],
+        };
+        let levels = |pts| {
+            let r = compute_level_deltas(&gsp, &schedule, &decks, pts, false).unwrap();
+            (r.landlord_delta, r.non_landlord_delta)
+        };
+        assert_eq!(levels(60), (1, 0));
+        assert_eq!(levels(20), (1, 0));
+        assert_eq!(levels(0), (2, 0));
+        // Past the end of the list, each window is worth one more level.
+        assert_eq!(levels(-40), (3, 0));
+        assert_eq!(levels(100), (0, 0));
+        assert_eq!(levels(130), (0, 2));
+        assert_eq!(levels(170), (0, 3));
+        assert_eq!(levels(210), (0, 4));
+
+        // Windows worth the same number of levels are one band.
+        let bands = remaining_score_bands(&gsp, &schedule, &decks, 5, 75, false).unwrap();
+        assert_eq!(
+            bands
+                .iter()
+                .map(|b| (b.min_points, b.max_points, b.result.landlord_delta))
+                .collect::<Vec<_>>(),
+            vec![(5, 79, 1), (80, 80, 0)]
+        );
+        assert_eq!(
+            threshold_reached(&gsp, &schedule, &decks, 60).unwrap(),
+            Some(5)
+        );
+
+        for (landlord_levels, attacking_levels) in [
+            (vec![2, 1], vec![]),
+            (vec![], vec![0, 1]),
+            (vec![14], vec![]),
+        ] {
+            let schedule = AdvancementSchedule {
+                landlord_levels,
+                attacking_levels,
+            };
+            assert!(schedule.validate().is_err());
+            assert!(compute_level_deltas(&gsp, &schedule, &decks, 0, false).is_err());
+        }
+    }
+
+    #[test]
+    fn test_point_schedule() {
+        let decks = [Deck::default(), Deck::default()];
+        let standard = PointSchedule::default();
+        assert_eq!(standard.deck_points(&decks), 200);
+        assert_eq!(
+            standard.deck_points(&decks),
+            decks.iter().map(|d| d.points()).sum::<usize>()
+        );
+        assert_eq!(standard.points(cards::H_K), Some(10));
+        assert_eq!(standard.points(cards::H_A), None);
+
+        let mut aces = PointSchedule::default();
+        aces.numbers.insert(Number::Ace, 10);
+        aces.cards.insert(Card::BigJoker, 20);
+        aces.cards.insert(cards::S_5, 0);
+        assert_eq!(aces.points(cards::H_A), Some(10));
+        assert_eq!(aces.points(Card::BigJoker), Some(20));
+        assert!(!aces.is_point_card(cards::S_5));
+        assert!(aces.is_point_number(Number::Ace));
+        assert_eq!(aces.deck_points(&decks), 2 * (100 + 40 + 20 - 5));
+
+        let gsp = GameScoringParameters {
+            point_schedule: aces,
+            ..Default::default()
+        };
+        assert_eq!(gsp.materialize(&decks).unwrap().total_points(), 310);
+    }
+
+    #[test]
+    fn test_demotion() {
+        let decks = [Deck::default(), Deck::default()];
+        let mut gsp = GameScoringParameters::default();
+        assert!(!gsp.demotes_landlord(&decks, 200).unwrap());
+
+        gsp.demotion_steps = 2;
+        assert!(gsp.materialize(&decks).is_err());
+
+        gsp.demotion_steps = 4;
+        gsp.materialize(&decks).unwrap();
+        assert!(!gsp.demotes_landlord(&decks, 155).unwrap());
+        assert!(gsp.demotes_landlord(&decks, 160).unwrap());
+
+        let mut player = Player::new(PlayerID(0), "p".into());
+        player.set_rank(Rank::Number(Number::Three));
+        assert!(player.demote());
+        assert_eq!(player.rank(), Rank::Number(Number::Two));
+        assert!(!player.demote());
+        assert_eq!(player.rank(), Rank::Number(Number::Two));
+    }
+
+    #[test]
+    fn test_remaining_score_bands() {
+        let decks = [Deck::default(), Deck::default()];
+        let gsp = GameScoringParameters::default();
+        let bands =
+            remaining_score_bands(&gsp, &AdvancementSchedule::default(), &decks, 60, 40, false)
+                .unwrap();
+        assert_eq!(
+            bands
+                .iter()
+                .map(|b| (
+                    b.min_points,
+                    b.max_points,
+                    b.result.landlord_won,
+                    b.result.landlord_delta
+                ))
+                .collect::<Vec<_>>(),
+            vec![(60, 79, true, 1), (80, 100, false, 0)]
+        );
+        assert_eq!(
+            remaining_score_bands(&gsp, &AdvancementSchedule::default(), &decks, 60, 0, false)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Scores past the last window which can be scored are left out,
+        // rather than stepped through.
+        let bands = remaining_score_bands(
+            &gsp,
+            &AdvancementSchedule::default(),
+            &decks,
+            60,
+            isize::MAX,
+            false,
+        )
+        .unwrap();
+        assert!(bands.len() < 100);
+        assert_eq!(bands[1].min_points, 80);
+    }
+
+    #[test]
+    fn test_threshold_reached() {
+        let decks = [Deck::default(), Deck::default()];
+        let gsp = GameScoringParameters::default();
+        let threshold =
+            |pts| threshold_reached(&gsp, &AdvancementSchedule::default(), &decks, pts).unwrap();
+        assert_eq!(threshold(-20), None);
+        assert_eq!(threshold(0), None);
+        assert_eq!(threshold(35), Some(5));
+        assert_eq!(threshold(80), Some(80));
+        assert_eq!(threshold(130), Some(120));
+    }
 }
@@ -0,0 +1,153 @@
+use std::cmp::Ordering;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Card, EffectiveSuit, Suit, Trump};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum TrumpPlacement {
+    TrumpFirst,
+    #[default]
+    TrumpLast,
+}
+
+crate::impl_slog_value!(TrumpPlacement);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum SortDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+crate::impl_slog_value!(SortDirection);
+
+/// How to order a hand for display. The default matches `Trump::compare`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(default)]
+pub struct SortOrder {
+    pub trump_placement: TrumpPlacement,
+    /// The direction of the cards within each suit.
+    pub direction: SortDirection,
+    /// The order of the non-trump suits. Any suits which aren't listed come
+    /// afterwards, in the usual order.
+    pub suit_order: Vec<Suit>,
+    /// Whether to put pairs (and larger tuples) ahead of single cards within
+    /// each suit.
+    pub group_tuples: bool,
+}
+
+impl SortOrder {
+    pub fn compare(&self, trump: Trump, card1: Card, card2: Card) -> Ordering {
+        self.compare_suits(trump, card1, card2)
+            .then_with(|| self.compare_within_suit(trump, card1, card2))
+    }
+
+    fn compare_suits(&self, trump: Trump, card1: Card, card2: Card) -> Ordering {
+        let group = |card| match trump.effective_suit(card) {
+            EffectiveSuit::Unknown => 0,
+            EffectiveSuit::Trump if self.trump_placement == TrumpPlacement::TrumpFirst => 1,
+            EffectiveSuit::Trump => 3,
+            _ => 2,
+        };
+        let position = |card| {
+            let effective_suit = trump.effective_suit(card);
+            self.suit_order
+                .iter()
+                .position(|s| EffectiveSuit::from(*s) == effective_suit)
+                .unwrap_or(self.suit_order.len())
+        };
+        group(card1)
+            .cmp(&group(card2))
+            .then_with(|| position(card1).cmp(&position(card2)))
+            .then_with(|| trump.suit_ordinal(card1).cmp(&trump.suit_ordinal(card2)))
+    }
+
+    fn compare_within_suit(&self, trump: Trump, card1: Card, card2: Card) -> Ordering {
+        match self.direction {
+            SortDirection::Ascending => trump.compare(card1, card2),
+            SortDirection::Descending => trump.compare(card2, card1),
+        }
+    }
+}
+
+/// Sorts the cards for display, so that the server and the clients agree.
+pub fn sort_cards(cards: &mut [Card], trump: Trump, order: &SortOrder) {
+    if order.group_tuples {
+        let counts = Card::count(cards.iter().copied());
+        let is_tuple = |card: &Card| counts[card] > 1;
+        cards.sort_by(|a, b| {
+            order
+                .compare_suits(trump, *a, *b)
+                .then_with(|| is_tuple(b).cmp(&is_tuple(a)))
+                .then_with(|| order.compare_within_suit(trump, *a, *b))
+        });
+    } else {
+        cards.sort_by(|a, b| order.compare(trump, *a, *b));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sort_cards, SortDirection, SortOrder, TrumpPlacement};
+    use crate::types::{cards::*, Card, Number, PermanentTrump, Suit, Trump, FULL_DECK};
+
+    const TRUMP: Trump = Trump::Standard {
+        number: Number::Four,
+        suit: Suit::Spades,
+        permanent: PermanentTrump::None,
+    };
+
+    #[test]
+    fn test_default_matches_compare() {
+        let mut expected = FULL_DECK.to_vec();
+        expected.sort_by(|a, b| TRUMP.compare(*a, *b));
+        let mut cards = FULL_DECK.to_vec();
+        sort_cards(&mut cards, TRUMP, &SortOrder::default());
+        assert_eq!(cards, expected);
+    }
+
+    #[test]
+    fn test_sort_order() {
+        let hand = || vec![H_3, S_5, Card::BigJoker, D_K, H_A, D_2, S_4];
+
+        let mut cards = hand();
+        sort_cards(
+            &mut cards,
+            TRUMP,
+            &SortOrder {
+                trump_placement: TrumpPlacement::TrumpFirst,
+                direction: SortDirection::Descending,
+                suit_order: vec![],
+                group_tuples: false,
+            },
+        );
+        assert_eq!(cards, vec![Card::BigJoker, S_4, S_5, H_A, H_3, D_K, D_2]);
+
+        let mut cards = hand();
+        sort_cards(
+            &mut cards,
+            TRUMP,
+            &SortOrder {
+                suit_order: vec![Suit::Hearts],
+                ..Default::default()
+            },
+        );
+        assert_eq!(cards, vec![H_3, H_A, D_2, D_K, S_5, S_4, Card::BigJoker]);
+    }
+
+    #[test]
+    fn test_group_tuples() {
+        let mut cards = vec![H_3, H_A, H_7, H_7, D_2, H_3, H_3];
+        sort_cards(
+            &mut cards,
+            TRUMP,
+            &SortOrder {
+                group_tuples: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(cards, vec![H_3, H_3, H_3, H_7, H_7, H_A, D_2]);
+    }
+}
@@ -1,16 +1,20 @@
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::hands::{HandError, Hands};
+use crate::card_set::CardSet;
+use crate::hands::{HandError, Hands, MissingCard};
+use crate::multiset_iter::multiset_k_combination_iter;
 use crate::ordered_card::{
-    subsequent_decomposition_ordering, AdjacentTupleSizes, MatchingCards, MatchingCardsRef,
-    OrderedCard,
+    subsequent_decomposition_iter, try_subsequent_decomposition_iter, AdjacentTupleSizes,
+    DecompositionLimits, DecompositionStrategy, MatchingCards, MatchingCardsRef, OrderedCard,
+    PlayRequirements,
 };
-use crate::types::{Card, EffectiveSuit, PlayerID, Trump};
+use crate::scoring::PointSchedule;
+use crate::types::{AdjacencyPolicy, Card, EffectiveSuit, PlayerID, Trump, FULL_DECK};
 
 pub enum PlayCardsMessage {
     ThrowFailed {
@@ -29,21 +33,129 @@ pub enum TrickError {
         #[from]
         source: HandError,
     },
-    #[error("wrong number of cards provided")]
-    WrongNumberOfCards,
-    #[error("the cards have the wrong number of suits")]
-    WrongNumberOfSuits,
     #[error("player is playing out of order")]
     OutOfOrder,
-    #[error("this play is illegal")]
-    IllegalPlay,
     #[error("this play doesn't match the format")]
     NonMatchingPlay,
     #[error("the proposed grouping is invalid")]
     NonMatchingProposal,
+    #[error("the format of {num_cards} cards is too large to check")]
+    DecompositionTooLarge { num_cards: usize },
+    #[error("the lead isn't a throw")]
+    NotAThrow,
+    #[error(transparent)]
+    InvalidPlay(#[from] PlayError),
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+impl TrickError {
+    /// The reason the play was rejected, if that's what went wrong.
+    pub fn play_error(&self) -> Option<&'_ PlayError> {
+        match self {
+            TrickError::InvalidPlay(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Why a play was rejected, with the cards involved, so that clients can show
+/// (and localize) the reason without parsing the error message.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum PlayError {
+    #[error("the game has already ended")]
+    GameEnded,
+    #[error("it's not your turn to play")]
+    OutOfTurn { next_player: Option<PlayerID> },
+    #[error("no cards were selected")]
+    NoCards,
+    #[error("the cards aren't in your hand: {missing:?}")]
+    CardsNotInHand { missing: Vec<MissingCard> },
+    #[error("{required} cards must be played, but {played} were selected")]
+    WrongNumberOfCards { required: usize, played: usize },
+    #[error("the cards must all be in the same suit")]
+    MultipleSuits { suits: Vec<EffectiveSuit> },
+    #[error("you must follow suit: you have {held_in_suit} cards in the suit, but only played {played_in_suit}")]
+    MustFollowSuit {
+        suit: EffectiveSuit,
+        played_in_suit: usize,
+        held_in_suit: usize,
+    },
+    #[error("you must play {} using {playable:?}", describe_requirements(required))]
+    MustMatchFormat {
+        required: PlayRequirements,
+        playable: Vec<Card>,
+    },
+    #[error(
+        "you must play trump: you have {held_trump} trump cards, but only played {played_trump}"
+    )]
+    MustRuff {
+        played_trump: usize,
+        held_trump: usize,
+    },
+    #[error("you must beat the winning play, e.g. with {playable:?}")]
+    MustBeat { playable: Vec<Card> },
+}
+
+impl PlayError {
+    fn from_follow_failure(suit: EffectiveSuit, reason: FollowFailureReason) -> Self {
+        match reason {
+            FollowFailureReason::WrongNumberOfCards { required, played } => {
+                PlayError::WrongNumberOfCards { required, played }
+            }
+            FollowFailureReason::DidNotFollowSuit {
+                played_in_suit,
+                held_in_suit,
+            } => PlayError::MustFollowSuit {
+                suit,
+                played_in_suit,
+                held_in_suit,
+            },
+            FollowFailureReason::DidNotMatchFormat { required, playable } => {
+                PlayError::MustMatchFormat { required, playable }
+            }
+            FollowFailureReason::DidNotRuff {
+                played_trump,
+                held_trump,
+            } => PlayError::MustRuff {
+                played_trump,
+                held_trump,
+            },
+        }
+    }
+}
+
+fn describe_requirements(required: &PlayRequirements) -> String {
+    UnitLike::multi_description(required.iter().map(|adjacent_tuples| UnitLike {
+        adjacent_tuples: adjacent_tuples.clone(),
+    }))
+}
+
+/// A machine-readable reason why a play doesn't follow the trick-format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum FollowFailureReason {
+    /// The play has a different number of cards than the trick-format.
+    WrongNumberOfCards { required: usize, played: usize },
+    /// The player held more cards in the led suit than they played.
+    DidNotFollowSuit {
+        played_in_suit: usize,
+        held_in_suit: usize,
+    },
+    /// The player could have played `required` (e.g. a pair) in the led suit,
+    /// using the `playable` cards, but the play doesn't include it.
+    DidNotMatchFormat {
+        required: PlayRequirements,
+        playable: Vec<Card>,
+    },
+    /// The player was short of the led suit and held trump, but didn't use
+    /// as much trump as they could to make up the rest of the play.
+    DidNotRuff {
+        played_trump: usize,
+        held_trump: usize,
+    },
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema, Default)]
 pub enum TrickDrawPolicy {
     #[default]
     NoProtections,
@@ -55,6 +167,83 @@ pub enum TrickDrawPolicy {
 
 crate::impl_slog_value!(TrickDrawPolicy);
 
+/// What a player who is short of the led suit must play alongside the rest of
+/// their cards in that suit.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+pub enum RuffPolicy {
+    /// Any other cards may be played.
+    #[default]
+    MayDiscard,
+    /// Trump must be played, as far as the player has any.
+    MustRuff,
+}
+
+crate::impl_slog_value!(RuffPolicy);
+
+/// Whether a player following a trick has to beat the winning play.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+pub enum BeatPolicy {
+    /// Players may play any legal cards.
+    #[default]
+    NoObligation,
+    /// Players must play cards which beat the currently-winning play, if any
+    /// of their legal plays would.
+    MustBeatIfAble,
+}
+
+crate::impl_slog_value!(BeatPolicy);
+
+/// Which of several identical winning plays takes the trick, e.g. when two
+/// players play the same card from different decks.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+pub enum TieBreakPolicy {
+    #[default]
+    FirstPlayed,
+    LastPlayed,
+}
+
+crate::impl_slog_value!(TieBreakPolicy);
+
+/// Which trump plays can ruff a led tractor.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+pub enum TractorRuffPolicy {
+    /// Only trump of the same shape, e.g. a trump tractor.
+    #[default]
+    SameShape,
+    /// Any trump cards of the same count, though a ruff of the same shape
+    /// still beats one which isn't.
+    AnyTrump,
+}
+
+crate::impl_slog_value!(TractorRuffPolicy);
+
+/// How a lead of the small and big joker together is treated.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+pub enum JokerPairPolicy {
+    /// As a throw of two single cards.
+    #[default]
+    Throw,
+    /// As a pair, ranked as a pair of small jokers.
+    Pair,
+    /// As a pair which can't be beaten.
+    Unbeatable,
+}
+
+crate::impl_slog_value!(JokerPairPolicy);
+
+/// Whether a lead may span several suits.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+pub enum MultiSuitLeadPolicy {
+    #[default]
+    SingleSuitOnly,
+    /// A player who holds no cards of the non-trump suits outside of their
+    /// throw may throw across suits. Each suit is followed separately, and
+    /// such a lead can't be beaten.
+    AllowWhenVoidElsewhere,
+}
+
+crate::impl_slog_value!(MultiSuitLeadPolicy);
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
 pub enum ThrowEvaluationPolicy {
     #[default]
@@ -65,12 +254,51 @@ pub enum ThrowEvaluationPolicy {
 
 crate::impl_slog_value!(ThrowEvaluationPolicy);
 
+/// Whether a throw fails when another player could beat it by trumping, even
+/// though nobody can beat it in its own suit.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+pub enum TrumpedThrowPolicy {
+    /// Only plays in the throw's suit are considered.
+    #[default]
+    Stands,
+    /// The throw also fails if a player who is void in its suit could trump
+    /// all of it, in which case its smallest unit is played instead.
+    Fails,
+}
+
+crate::impl_slog_value!(TrumpedThrowPolicy);
+
+/// Which cards a throw is checked against when deciding whether it can be
+/// beaten.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
+pub enum ThrowEvaluationScope {
+    /// Each of the other players' actual hands.
+    #[default]
+    AllHands,
+    /// All of the cards the thrower hasn't seen, as though they were held in
+    /// a single hand: a throw fails if it could be beaten by any combination
+    /// of the other players' cards.
+    UnseenCards,
+    /// Only the hands of the thrower's opponents; teammates can't stop a
+    /// throw.
+    OpponentsOnly,
+}
+
+crate::impl_slog_value!(ThrowEvaluationScope);
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct TractorRequirements {
     /// The minimum number of cards in each unit of the tractor
     pub min_count: usize,
     /// The minimum length of the tractor
     pub min_length: usize,
+    /// Which cards are considered adjacent when forming tractors
+    #[serde(default)]
+    pub adjacency_policy: AdjacencyPolicy,
+    /// A card which, when played alongside cards of a single suit, can stand
+    /// in for any card of that suit, e.g. to complete a tractor.
+    #[serde(default)]
+    pub wild_card: Option<Card>,
 }
 
 impl Default for TractorRequirements {
@@ -78,12 +306,87 @@ impl Default for TractorRequirements {
         Self {
             min_count: 2,
             min_length: 2,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
         }
     }
 }
 
 crate::impl_slog_value!(TractorRequirements);
 
+/// Memoizes matching requirements against a set of cards (typically a
+/// player's hand), keyed by the trump, the requirements, and the card counts.
+///
+/// Within a single trick, the same lead format is matched against the same
+/// hands repeatedly, e.g. for validation, hints, and bots; callers can hold a
+/// context for the duration of the trick to avoid recomputing the matches.
+#[derive(Debug, Default)]
+pub struct MatchingContext {
+    first_matches: HashMap<MatchingKey, Option<Vec<MatchingCards>>>,
+}
+
+type MatchingKey = (
+    Trump,
+    PlayRequirements,
+    MatchingCards,
+    TrickDrawPolicy,
+    AdjacencyPolicy,
+);
+
+impl MatchingContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Finds the first match of the `requirements` in `counts`, as
+    /// `UnitLike::check_play` would.
+    pub fn first_match(
+        &mut self,
+        counts: &BTreeMap<OrderedCard, usize>,
+        requirements: &[UnitLike],
+        trick_draw_policy: TrickDrawPolicy,
+        adjacency_policy: AdjacencyPolicy,
+    ) -> Option<Vec<MatchingCards>> {
+        let trump = match counts.keys().next() {
+            Some(c) => c.trump,
+            // There's nothing to match against.
+            None if !requirements.is_empty() => return None,
+            None => return Some(vec![]),
+        };
+        let key = (
+            trump,
+            requirements
+                .iter()
+                .map(|u| u.adjacent_tuples.clone())
+                .collect(),
+            counts.iter().map(|(c, ct)| (*c, *ct)).collect(),
+            trick_draw_policy,
+            adjacency_policy,
+        );
+        self.first_matches
+            .entry(key)
+            .or_insert_with(|| {
+                UnitLike::check_play(
+                    counts.clone(),
+                    requirements.iter().cloned(),
+                    trick_draw_policy,
+                    adjacency_policy,
+                )
+                .next()
+            })
+            .clone()
+    }
+
+    /// The number of memoized matches.
+    pub fn len(&self) -> usize {
+        self.first_matches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.first_matches.is_empty()
+    }
+}
+
 type Members = Vec<OrderedCard>;
 
 #[derive(Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
@@ -172,6 +475,20 @@ pub struct TrickFormat {
     suit: EffectiveSuit,
     trump: Trump,
     units: Units,
+    #[serde(default)]
+    adjacency_policy: AdjacencyPolicy,
+    #[serde(default)]
+    wild_card: Option<Card>,
+    #[serde(default)]
+    ruff_policy: RuffPolicy,
+    #[serde(default)]
+    tie_break_policy: TieBreakPolicy,
+    #[serde(default)]
+    tractor_ruff_policy: TractorRuffPolicy,
+    #[serde(default)]
+    joker_pair_policy: JokerPairPolicy,
+    #[serde(default)]
+    decomposition_strategy: DecompositionStrategy,
 }
 
 impl TrickFormat {
@@ -179,10 +496,107 @@ impl TrickFormat {
         self.trump
     }
 
+    pub fn adjacency_policy(&self) -> AdjacencyPolicy {
+        self.adjacency_policy
+    }
+
+    pub fn ruff_policy(&self) -> RuffPolicy {
+        self.ruff_policy
+    }
+
+    pub fn with_ruff_policy(mut self, ruff_policy: RuffPolicy) -> Self {
+        self.ruff_policy = ruff_policy;
+        self
+    }
+
+    pub fn tie_break_policy(&self) -> TieBreakPolicy {
+        self.tie_break_policy
+    }
+
+    pub fn with_tie_break_policy(mut self, tie_break_policy: TieBreakPolicy) -> Self {
+        self.tie_break_policy = tie_break_policy;
+        self
+    }
+
+    pub fn tractor_ruff_policy(&self) -> TractorRuffPolicy {
+        self.tractor_ruff_policy
+    }
+
+    pub fn with_tractor_ruff_policy(mut self, tractor_ruff_policy: TractorRuffPolicy) -> Self {
+        self.tractor_ruff_policy = tractor_ruff_policy;
+        self
+    }
+
+    pub fn joker_pair_policy(&self) -> JokerPairPolicy {
+        self.joker_pair_policy
+    }
+
+    /// Sets how a lead of both jokers is treated. Unless that's as a throw,
+    /// a format led with exactly the two jokers becomes a single pair.
+    pub fn with_joker_pair_policy(
+        mut self,
+        joker_pair_policy: JokerPairPolicy,
+        cards: &[Card],
+    ) -> Self {
+        self.joker_pair_policy = joker_pair_policy;
+        if joker_pair_policy != JokerPairPolicy::Throw && is_joker_pair(cards) {
+            self.units = vec![TrickUnit::Repeated {
+                count: 2,
+                card: OrderedCard {
+                    card: Card::SmallJoker,
+                    trump: self.trump,
+                },
+            }];
+        }
+        self
+    }
+
+    pub fn decomposition_strategy(&self) -> DecompositionStrategy {
+        self.decomposition_strategy
+    }
+
+    /// Sets which structure of the format players are forced to give up first
+    /// when they can't follow it exactly.
+    pub fn with_decomposition_strategy(
+        mut self,
+        decomposition_strategy: DecompositionStrategy,
+    ) -> Self {
+        self.decomposition_strategy = decomposition_strategy;
+        self
+    }
+
     pub fn size(&self) -> usize {
         self.units.iter().map(|u| u.size()).sum()
     }
 
+    /// Under `TractorRuffPolicy::AnyTrump`, reads `cards` as a ruff of a
+    /// non-trump lead containing a tractor which doesn't match the lead's
+    /// shape, as one unit per card.
+    fn loose_ruff(&self, cards: &[Card]) -> Option<Units> {
+        if self.tractor_ruff_policy != TractorRuffPolicy::AnyTrump
+            || self.suit == EffectiveSuit::Trump
+            || !self.units.iter().any(|u| u.is_tractor())
+            || cards.len() != self.size()
+            || cards
+                .iter()
+                .any(|c| self.trump.effective_suit(*c) != EffectiveSuit::Trump)
+        {
+            return None;
+        }
+        let mut units = cards
+            .iter()
+            .map(|card| TrickUnit::Repeated {
+                count: 1,
+                card: OrderedCard {
+                    card: *card,
+                    trump: self.trump,
+                },
+            })
+            .collect::<Units>();
+        units.sort_by_key(|u| std::cmp::Reverse(u.first_card()));
+        Some(units)
+    }
+
     pub fn suit(&self) -> EffectiveSuit {
         self.suit
     }
@@ -198,16 +612,17 @@ impl TrickFormat {
             .map(UnitLike::from)
             .map(|u| u.adjacent_tuples)
             .collect();
+        let strategy = self.decomposition_strategy;
 
         // Include the current trick-format, and then the subsequent decomposition if we get that
         // far. Compute the latter lazily, since we usually won't.
         std::iter::once(units).chain(
             std::iter::once_with(move || {
-                subsequent_decomposition_ordering(
+                subsequent_decomposition_iter(
                     adj_tuples,
                     trick_draw_policy != TrickDrawPolicy::OnlyDrawTractorOnTractor,
                 )
-                .into_iter()
+                .with_strategy(strategy)
                 .map(|requirements| {
                     requirements
                         .into_iter()
@@ -221,13 +636,89 @@ impl TrickFormat {
 
     pub fn is_legal_play(
         &self,
-        hand: &HashMap<Card, usize>,
+        hand: &CardSet,
         proposed: &'_ [Card],
         trick_draw_policy: TrickDrawPolicy,
     ) -> bool {
+        matches!(
+            self.try_is_legal_play(
+                hand,
+                proposed,
+                trick_draw_policy,
+                self.decomposition_strategy,
+                DecompositionLimits::unlimited()
+            ),
+            Ok(true)
+        )
+    }
+
+    ///
+    /// Like `is_legal_play`, but doesn't decompose the trick-format beyond the
+    /// `limits`. Past them, plays which don't match the trick-format outright
+    /// can't be checked, and `TrickError::DecompositionTooLarge` is returned
+    /// instead.
+    ///
+    pub fn try_is_legal_play(
+        &self,
+        hand: &CardSet,
+        proposed: &'_ [Card],
+        trick_draw_policy: TrickDrawPolicy,
+        decomposition_strategy: DecompositionStrategy,
+        limits: DecompositionLimits,
+    ) -> Result<bool, TrickError> {
+        Ok(self
+            .explain_illegal_play(
+                hand,
+                proposed,
+                trick_draw_policy,
+                decomposition_strategy,
+                limits,
+            )?
+            .is_none())
+    }
+
+    ///
+    /// Explains why the proposed play doesn't follow this trick-format, or
+    /// returns `None` if it's a legal play.
+    ///
+    pub fn explain_illegal_play(
+        &self,
+        hand: &CardSet,
+        proposed: &'_ [Card],
+        trick_draw_policy: TrickDrawPolicy,
+        decomposition_strategy: DecompositionStrategy,
+        limits: DecompositionLimits,
+    ) -> Result<Option<FollowFailureReason>, TrickError> {
+        self.explain_illegal_play_with_context(
+            &mut MatchingContext::new(),
+            hand,
+            proposed,
+            trick_draw_policy,
+            decomposition_strategy,
+            limits,
+        )
+    }
+
+    ///
+    /// Like `explain_illegal_play`, but reuses (and records) the matches of
+    /// the player's hand in `ctx`, so that checking several candidate plays
+    /// against the same hand doesn't repeat the work.
+    ///
+    pub fn explain_illegal_play_with_context(
+        &self,
+        ctx: &mut MatchingContext,
+        hand: &CardSet,
+        proposed: &'_ [Card],
+        trick_draw_policy: TrickDrawPolicy,
+        decomposition_strategy: DecompositionStrategy,
+        limits: DecompositionLimits,
+    ) -> Result<Option<FollowFailureReason>, TrickError> {
         let required = self.units.iter().map(|c| c.size()).sum::<usize>();
         if proposed.len() != required {
-            return false;
+            return Ok(Some(FollowFailureReason::WrongNumberOfCards {
+                required,
+                played: proposed.len(),
+            }));
         }
 
         let num_proposed_correct_suit = proposed
@@ -236,70 +727,291 @@ impl TrickFormat {
             .count();
 
         if num_proposed_correct_suit < required {
-            let num_correct_suit = hand
-                .iter()
-                .flat_map(|(c, ct)| {
-                    if self.trump.effective_suit(*c) == self.suit {
-                        Some(*ct)
-                    } else {
-                        None
-                    }
-                })
-                .sum::<usize>();
+            let num_correct_suit = hand.count_in_suit(self.trump, self.suit);
             // If this is all of the correct suit that is available, it's fine
             // Otherwise, this is an invalid play.
-            num_correct_suit == num_proposed_correct_suit
+            if num_correct_suit == num_proposed_correct_suit {
+                let required_trump = self.required_trump(hand, num_correct_suit);
+                let played_trump = proposed
+                    .iter()
+                    .filter(|c| self.trump.effective_suit(**c) == EffectiveSuit::Trump)
+                    .count();
+                if played_trump < required_trump {
+                    Ok(Some(FollowFailureReason::DidNotRuff {
+                        played_trump,
+                        held_trump: hand.count_in_suit(self.trump, EffectiveSuit::Trump),
+                    }))
+                } else {
+                    Ok(None)
+                }
+            } else {
+                Ok(Some(FollowFailureReason::DidNotFollowSuit {
+                    played_in_suit: num_proposed_correct_suit,
+                    held_in_suit: num_correct_suit,
+                }))
+            }
         } else {
             if let TrickDrawPolicy::NoFormatBasedDraw = trick_draw_policy {
-                return true;
+                return Ok(None);
             }
-            let available_cards = Card::cards(
-                hand.iter()
-                    .filter(|(c, _)| self.trump.effective_suit(**c) == self.suit),
-            )
-            .copied()
-            .collect::<Vec<_>>();
+            let available_cards = OrderedCard::make_map(
+                Card::cards(
+                    hand.iter()
+                        .filter(|(c, _)| self.trump.effective_suit(**c) == self.suit),
+                )
+                .copied(),
+                self.trump,
+            );
 
-            for requirement in self.decomposition(trick_draw_policy) {
+            let mut check_requirement = |requirement: Vec<UnitLike>| {
                 // If it's a match, we're good!
                 let play_matches = UnitLike::check_play(
                     OrderedCard::make_map(proposed.iter().copied(), self.trump),
                     requirement.iter().cloned(),
                     TrickDrawPolicy::NoProtections,
+                    self.adjacency_policy,
                 )
                 .next()
                 .is_some();
 
                 if play_matches {
-                    return true;
+                    return Some(None);
                 }
                 // Otherwise, if it could match in the player's hand, it's not OK.
-                let hand_can_play = UnitLike::check_play(
-                    OrderedCard::make_map(available_cards.iter().copied(), self.trump),
-                    requirement.iter().cloned(),
+                let hand_play = ctx.first_match(
+                    &available_cards,
+                    &requirement,
                     trick_draw_policy,
-                )
-                .next()
-                .is_some();
-                if hand_can_play {
-                    return false;
+                    self.adjacency_policy,
+                );
+                hand_play.map(|m| {
+                    Some(FollowFailureReason::DidNotMatchFormat {
+                        required: requirement.into_iter().map(|u| u.adjacent_tuples).collect(),
+                        playable: m
+                            .iter()
+                            .flatten()
+                            .flat_map(|(c, ct)| (0..*ct).map(move |_| c.card()))
+                            .collect(),
+                    })
+                })
+            };
+
+            // Only decompose the format if the play doesn't match it outright,
+            // since that's the expensive part.
+            if let Some(reason) = check_requirement(self.units.iter().map(UnitLike::from).collect())
+            {
+                return Ok(reason);
+            }
+            let decomposition = self.try_subsequent_decomposition(
+                trick_draw_policy,
+                decomposition_strategy,
+                limits,
+            )?;
+            for requirement in decomposition {
+                if let Some(reason) = check_requirement(requirement) {
+                    return Ok(reason);
                 }
             }
 
             // Couldn't meet requirements in either hand or proposed play, so the proposed play is
             // legal.
-            true
+            Ok(None)
         }
     }
 
-    pub fn matches(&self, cards: &[Card]) -> Result<impl Iterator<Item = Units> + '_, TrickError> {
-        let suit = self.trump.effective_suit(cards[0]);
-        for card in cards {
-            if self.trump.effective_suit(*card) != suit {
-                return Err(TrickError::NonMatchingPlay);
+    /// How many trump cards a player with `num_in_suit` cards in the led suit
+    /// must play to make up the rest of the trick.
+    fn required_trump(&self, hand: &CardSet, num_in_suit: usize) -> usize {
+        if self.ruff_policy == RuffPolicy::MayDiscard || self.suit == EffectiveSuit::Trump {
+            return 0;
+        }
+        hand.count_in_suit(self.trump, EffectiveSuit::Trump)
+            .min(self.size().saturating_sub(num_in_suit))
+    }
+
+    ///
+    /// Lists every legal way to follow this trick-format from `hand`, grouped
+    /// by the decomposition of the trick-format that each play matches. The
+    /// groups are in decomposition order, and each play is only listed under
+    /// the first decomposition it matches.
+    ///
+    pub fn legal_plays(
+        &self,
+        hand: &CardSet,
+        trick_draw_policy: TrickDrawPolicy,
+        decomposition_strategy: DecompositionStrategy,
+        limits: DecompositionLimits,
+    ) -> Result<Vec<LegalPlays>, TrickError> {
+        let required = self.size();
+        let available_cards = OrderedCard::make_map(
+            Card::cards(
+                hand.iter()
+                    .filter(|(c, _)| self.trump.effective_suit(**c) == self.suit),
+            )
+            .copied(),
+            self.trump,
+        );
+        let num_available = available_cards.values().sum::<usize>();
+
+        // Players who are short of the suit have to play all of it.
+        if num_available <= required {
+            let in_suit = to_cards(available_cards.iter().map(|(c, ct)| (*c, *ct)));
+            let num_other_cards = required - num_available;
+            let num_trump_cards = self.required_trump(hand, num_available);
+            let mut groups = vec![LegalPlays {
+                format: None,
+                plays: vec![in_suit.clone()],
+                num_other_cards,
+                num_trump_cards,
+            }];
+            // Wild cards can stand in for the rest of the suit, so long as
+            // they don't take the place of trump which has to be played.
+            if let Some(wild_card) = self
+                .wild_card
+                .filter(|c| self.trump.effective_suit(*c) != self.suit)
+            {
+                let wild_is_trump = self.trump.effective_suit(wild_card) == EffectiveSuit::Trump;
+                let num_wild = hand.count(wild_card).min(if wild_is_trump {
+                    num_other_cards
+                } else {
+                    num_other_cards - num_trump_cards
+                });
+                if num_wild > 0 {
+                    let mut play = in_suit;
+                    play.extend(vec![wild_card; num_wild]);
+                    groups.push(LegalPlays {
+                        format: None,
+                        plays: vec![play],
+                        num_other_cards: num_other_cards - num_wild,
+                        num_trump_cards: if wild_is_trump {
+                            num_trump_cards.saturating_sub(num_wild)
+                        } else {
+                            num_trump_cards
+                        },
+                    });
+                }
+            }
+            return Ok(groups);
+        }
+
+        let mut seen = HashSet::new();
+        let mut groups = vec![];
+        if trick_draw_policy != TrickDrawPolicy::NoFormatBasedDraw {
+            let mut ctx = MatchingContext::new();
+            let requirements = std::iter::once(self.units.iter().map(UnitLike::from).collect())
+                .chain(self.try_subsequent_decomposition(
+                    trick_draw_policy,
+                    decomposition_strategy,
+                    limits,
+                )?);
+            for requirement in requirements {
+                let matches = UnitLike::check_play(
+                    available_cards.clone(),
+                    requirement.iter().cloned(),
+                    TrickDrawPolicy::NoProtections,
+                    self.adjacency_policy,
+                )
+                .take(limits.max_decompositions.saturating_add(1))
+                .collect::<Vec<_>>();
+                if matches.len() > limits.max_decompositions {
+                    return Err(TrickError::DecompositionTooLarge {
+                        num_cards: required,
+                    });
+                }
+                let plays = matches
+                    .into_iter()
+                    .map(|m| {
+                        let mut cards = m.into_iter().flatten().collect::<Vec<_>>();
+                        cards.sort();
+                        cards
+                    })
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .map(to_cards)
+                    .filter(|cards| seen.insert(cards.clone()))
+                    .collect::<Vec<_>>();
+                let hand_matches = ctx
+                    .first_match(
+                        &available_cards,
+                        &requirement,
+                        trick_draw_policy,
+                        self.adjacency_policy,
+                    )
+                    .is_some();
+                if !plays.is_empty() {
+                    groups.push(LegalPlays {
+                        format: Some(requirement),
+                        plays,
+                        num_other_cards: 0,
+                        num_trump_cards: 0,
+                    });
+                }
+                // Once the hand can match a requirement, plays which don't
+                // match any requirement so far are no longer allowed.
+                if hand_matches {
+                    return Ok(groups);
+                }
             }
         }
 
+        // The hand couldn't be held to any of the requirements, so any cards
+        // in the suit will do.
+        let keys = available_cards.keys().copied().collect::<Vec<_>>();
+        let combinations = multiset_k_combination_iter(keys, |c| available_cards[c], required)
+            .take(limits.max_decompositions.saturating_add(1))
+            .collect::<Vec<_>>();
+        if combinations.len() > limits.max_decompositions {
+            return Err(TrickError::DecompositionTooLarge {
+                num_cards: required,
+            });
+        }
+        let plays = combinations
+            .into_iter()
+            .map(to_cards)
+            .filter(|cards| seen.insert(cards.clone()))
+            .collect::<Vec<_>>();
+        if !plays.is_empty() {
+            groups.push(LegalPlays {
+                format: None,
+                plays,
+                num_other_cards: 0,
+                num_trump_cards: 0,
+            });
+        }
+        Ok(groups)
+    }
+
+    fn try_subsequent_decomposition(
+        &self,
+        trick_draw_policy: TrickDrawPolicy,
+        decomposition_strategy: DecompositionStrategy,
+        limits: DecompositionLimits,
+    ) -> Result<impl Iterator<Item = Vec<UnitLike>>, TrickError> {
+        let adj_tuples = self
+            .units
+            .iter()
+            .map(UnitLike::from)
+            .map(|u| u.adjacent_tuples)
+            .collect();
+        let decomposition = try_subsequent_decomposition_iter(
+            adj_tuples,
+            trick_draw_policy != TrickDrawPolicy::OnlyDrawTractorOnTractor,
+            limits,
+        )
+        .map_err(|e| TrickError::DecompositionTooLarge {
+            num_cards: e.num_cards,
+        })?
+        .with_strategy(decomposition_strategy);
+        Ok(decomposition.map(|requirements| {
+            requirements
+                .into_iter()
+                .map(|adjacent_tuples| UnitLike { adjacent_tuples })
+                .collect()
+        }))
+    }
+
+    pub fn matches(&self, cards: &[Card]) -> Result<impl Iterator<Item = Units> + '_, TrickError> {
+        let suit = play_suit(self.trump, self.wild_card, cards);
         if suit != self.suit && suit != EffectiveSuit::Trump {
             return Err(TrickError::NonMatchingPlay);
         }
@@ -308,11 +1020,27 @@ impl TrickFormat {
             return Err(TrickError::NonMatchingPlay);
         }
 
-        let mut matches = UnitLike::check_play(
-            OrderedCard::make_map(cards.iter().copied(), self.trump),
-            self.units.iter().map(UnitLike::from),
-            TrickDrawPolicy::NoProtections,
+        let mut matches = wild_card_readings(
+            self.trump,
+            self.wild_card,
+            suit,
+            cards,
+            self.adjacency_policy,
         )
+        .into_iter()
+        .filter(move |reading| {
+            reading
+                .iter()
+                .all(|card| self.trump.effective_suit(*card) == suit)
+        })
+        .flat_map(move |reading| {
+            UnitLike::check_play(
+                OrderedCard::make_map(reading.into_iter(), self.trump),
+                self.units.iter().map(UnitLike::from),
+                TrickDrawPolicy::NoProtections,
+                self.adjacency_policy,
+            )
+        })
         .peekable();
 
         if matches.peek().is_none() {
@@ -344,18 +1072,32 @@ impl TrickFormat {
         proposed: Option<&'_ [TrickUnit]>,
     ) -> Result<TrickFormat, TrickError> {
         if cards.is_empty() {
-            return Err(TrickError::WrongNumberOfSuits);
+            return Err(PlayError::NoCards.into());
         }
-        let suit = trump.effective_suit(cards[0]);
-        for card in cards {
-            if trump.effective_suit(*card) != suit {
-                return Err(TrickError::WrongNumberOfSuits);
+        let suit = play_suit(trump, tractor_requirements.wild_card, cards);
+        let mut possibilities = vec![];
+        for reading in wild_card_readings(
+            trump,
+            tractor_requirements.wild_card,
+            suit,
+            cards,
+            tractor_requirements.adjacency_policy,
+        ) {
+            if reading
+                .iter()
+                .any(|card| trump.effective_suit(*card) != suit)
+            {
+                let mut suits = vec![];
+                for card in &reading {
+                    let card_suit = trump.effective_suit(*card);
+                    if !suits.contains(&card_suit) {
+                        suits.push(card_suit);
+                    }
+                }
+                return Err(PlayError::MultipleSuits { suits }.into());
             }
+            possibilities.extend(TrickUnit::find_plays(trump, tractor_requirements, reading));
         }
-        let mut possibilities =
-            TrickUnit::find_plays(trump, tractor_requirements, cards.iter().copied())
-                .into_iter()
-                .collect::<Vec<Units>>();
 
         let sort = |mut u: Units| {
             u.sort_by(|a, b| {
@@ -375,6 +1117,13 @@ impl TrickFormat {
                             suit,
                             units: proposed,
                             trump,
+                            adjacency_policy: tractor_requirements.adjacency_policy,
+                            wild_card: tractor_requirements.wild_card,
+                            ruff_policy: RuffPolicy::default(),
+                            tie_break_policy: TieBreakPolicy::default(),
+                            tractor_ruff_policy: TractorRuffPolicy::default(),
+                            joker_pair_policy: JokerPairPolicy::default(),
+                            decomposition_strategy: DecompositionStrategy::default(),
                         });
                     }
                 }
@@ -383,32 +1132,122 @@ impl TrickFormat {
             None => {
                 possibilities
                     .sort_by_key(|units| units.iter().map(|u| (u.size(), u.is_tractor())).max());
-                let units = possibilities.pop().ok_or(TrickError::IllegalPlay)?;
+                let units = possibilities.pop().ok_or(PlayError::NoCards)?;
                 Ok(TrickFormat {
                     suit,
                     units: sort(units),
                     trump,
+                    adjacency_policy: tractor_requirements.adjacency_policy,
+                    wild_card: tractor_requirements.wild_card,
+                    ruff_policy: RuffPolicy::default(),
+                    tie_break_policy: TieBreakPolicy::default(),
+                    tractor_ruff_policy: TractorRuffPolicy::default(),
+                    joker_pair_policy: JokerPairPolicy::default(),
+                    decomposition_strategy: DecompositionStrategy::default(),
                 })
             }
         }
     }
 }
 
+/// Plays which legally follow a trick-format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LegalPlays {
+    /// The decomposition of the trick-format which these plays match, or
+    /// `None` if the player isn't held to any of them.
+    pub format: Option<Vec<UnitLike>>,
+    /// The cards in the trick's suit for each play, from lowest to highest,
+    /// with the plays themselves in order. When the player is short of the
+    /// suit, copies of the wild card may follow, standing in for the rest of
+    /// it.
+    pub plays: Vec<Vec<Card>>,
+    /// How many cards from outside the suit complete each play. This is only
+    /// nonzero when the player is short of the suit, in which case any of
+    /// their other cards may be used.
+    pub num_other_cards: usize,
+    /// How many of the `num_other_cards` must be trump, under
+    /// `RuffPolicy::MustRuff`.
+    #[serde(default)]
+    pub num_trump_cards: usize,
+}
+
+fn to_cards(matching: impl IntoIterator<Item = (OrderedCard, usize)>) -> Vec<Card> {
+    matching
+        .into_iter()
+        .flat_map(|(c, ct)| (0..ct).map(move |_| c.card()))
+        .collect()
+}
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct PlayedCards {
     pub id: PlayerID,
     pub cards: Vec<Card>,
     pub bad_throw_cards: Vec<Card>,
     pub better_player: Option<PlayerID>,
+    /// The deck that each of `cards` was dealt from, where known; see
+    /// `Hands::deck_indices`.
+    #[serde(default)]
+    pub deck_indices: Vec<Option<usize>>,
+}
+
+/// The hypothetical result of a play, from `Trick::preview_play`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PlayPreview {
+    /// The cards which would be played. If a throw fails, this is only the
+    /// part of it that is forced.
+    pub cards: Vec<Card>,
+    /// The cards of a failed throw which would stay in the player's hand.
+    pub bad_throw_cards: Vec<Card>,
+    /// The player who would be winning the trick afterwards.
+    pub winner: Option<PlayerID>,
+    /// Whether the play would put the player in the lead.
+    pub takes_lead: bool,
+    /// The cards which would be winning the trick.
+    pub winning_cards: Vec<Card>,
+    /// The point cards which would be in the trick.
+    pub points: Vec<Card>,
 }
 
-pub struct PlayCards<'a, 'b, 'c> {
+/// A single play in a trick, from `Trick::replay`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TrickReplayStep {
+    pub id: PlayerID,
+    pub cards: Vec<Card>,
+    pub bad_throw_cards: Vec<Card>,
+    /// The player winning the trick once this play was made.
+    pub winner: Option<PlayerID>,
+    /// The point cards played so far, including this play.
+    pub points: Vec<Card>,
+}
+
+pub struct PlayCards<'a, 'b, 'c, 'd> {
     pub id: PlayerID,
     pub hands: &'a mut Hands,
     pub cards: &'b [Card],
+    pub rules: &'d TrickRules,
+    /// The thrower's known teammates, which are skipped under
+    /// `ThrowEvaluationScope::OpponentsOnly`.
+    pub teammates: &'d [PlayerID],
+    pub format_hint: Option<&'c [TrickUnit]>,
+}
+
+/// The game's rules for playing cards into a trick, for `Trick::play_cards`
+/// and `Trick::challenge_throw`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TrickRules {
     pub trick_draw_policy: TrickDrawPolicy,
     pub throw_eval_policy: ThrowEvaluationPolicy,
-    pub format_hint: Option<&'c [TrickUnit]>,
+    pub throw_eval_scope: ThrowEvaluationScope,
+    pub ruff_policy: RuffPolicy,
+    pub beat_policy: BeatPolicy,
+    pub tie_break_policy: TieBreakPolicy,
+    pub tractor_ruff_policy: TractorRuffPolicy,
+    pub joker_pair_policy: JokerPairPolicy,
+    pub decomposition_strategy: DecompositionStrategy,
+    pub multi_suit_lead_policy: MultiSuitLeadPolicy,
+    pub trumped_throw_policy: TrumpedThrowPolicy,
+    /// Leaves a throw unchecked, so that it can be challenged with
+    /// `Trick::challenge_throw` instead.
+    pub defer_throw_check: bool,
     pub hide_throw_halting_player: bool,
     pub tractor_requirements: TractorRequirements,
 }
@@ -424,7 +1263,14 @@ pub struct Trick {
     #[serde(default)]
     played_card_mappings: Vec<Option<Units>>,
     current_winner: Option<PlayerID>,
+    /// The index in `played_cards` and the matching units of each play which
+    /// took the lead in turn, ending with `current_winner`.
+    #[serde(default)]
+    leaders: Vec<(usize, Units)>,
     trick_format: Option<TrickFormat>,
+    /// The formats of the other suits in a lead which spans several suits.
+    #[serde(default)]
+    side_formats: Vec<TrickFormat>,
     trump: Trump,
 }
 
@@ -435,7 +1281,9 @@ impl Trick {
             played_cards: Vec::with_capacity(player_queue.len()),
             played_card_mappings: Vec::with_capacity(player_queue.len()),
             current_winner: None,
+            leaders: vec![],
             trick_format: None,
+            side_formats: vec![],
             player_queue,
             trump,
         }
@@ -457,10 +1305,57 @@ impl Trick {
         self.trump
     }
 
+    /// The player who is currently winning the trick, if any cards have been
+    /// played.
+    pub fn current_winner(&self) -> Option<PlayerID> {
+        self.current_winner
+    }
+
+    /// The plays and winner of the trick, without the formats and units
+    /// needed to continue it.
+    pub fn compact(&self) -> CompactTrick {
+        CompactTrick {
+            plays: self
+                .played_cards
+                .iter()
+                .map(|p| CompactPlay {
+                    id: p.id,
+                    cards: p.cards.iter().map(|c| c.as_u8()).collect(),
+                })
+                .collect(),
+            winner: self.current_winner,
+        }
+    }
+
     pub fn trick_format(&self) -> Option<&'_ TrickFormat> {
         self.trick_format.as_ref()
     }
 
+    /// The formats of the other suits when the lead spans several suits.
+    pub fn side_formats(&self) -> &'_ [TrickFormat] {
+        &self.side_formats
+    }
+
+    /// Whether `cards` may be led as a throw across several suits under
+    /// `MultiSuitLeadPolicy::AllowWhenVoidElsewhere`, i.e. the cards span
+    /// more than one suit and the player holds no non-trump cards of any
+    /// other suit.
+    pub fn can_lead_multiple_suits(&self, hand: &CardSet, cards: &[Card]) -> bool {
+        let mut suits = cards
+            .iter()
+            .map(|c| self.trump.effective_suit(*c))
+            .collect::<Vec<_>>();
+        suits.sort();
+        suits.dedup();
+        self.trick_format.is_none()
+            && suits.len() > 1
+            && !suits.contains(&EffectiveSuit::Unknown)
+            && hand.cards().all(|c| {
+                let suit = self.trump.effective_suit(c);
+                suit == EffectiveSuit::Trump || suits.contains(&suit)
+            })
+    }
+
     ///
     /// Determines whether the player can play the cards.
     ///
@@ -474,28 +1369,353 @@ impl Trick {
         cards: &[Card],
         trick_draw_policy: TrickDrawPolicy,
     ) -> Result<(), TrickError> {
-        hands.contains(id, cards.iter().cloned())?;
+        self.can_play_cards_with_wild_card(id, hands, cards, trick_draw_policy, None)
+    }
+
+    /// Like `can_play_cards`, but a lead may include copies of `wild_card`
+    /// alongside cards of another suit.
+    pub fn can_play_cards_with_wild_card(
+        &self,
+        id: PlayerID,
+        hands: &Hands,
+        cards: &[Card],
+        trick_draw_policy: TrickDrawPolicy,
+        wild_card: Option<Card>,
+    ) -> Result<(), TrickError> {
+        if let Err(e) = hands.contains(id, cards.iter().cloned()) {
+            return Err(match e {
+                HandError::CardsNotFound { missing } => {
+                    PlayError::CardsNotInHand { missing }.into()
+                }
+                e => e.into(),
+            });
+        }
         match self.trick_format.as_ref() {
-            Some(tf) => {
-                if tf.is_legal_play(hands.get(id)?, cards, trick_draw_policy) {
-                    Ok(())
-                } else {
-                    Err(TrickError::IllegalPlay)
+            Some(_) if !self.side_formats.is_empty() => {
+                match self.explain_illegal_multi_suit_play(
+                    hands.get(id)?,
+                    cards,
+                    trick_draw_policy,
+                    DecompositionLimits::default(),
+                )? {
+                    None => Ok(()),
+                    Some(e) => Err(e.into()),
                 }
             }
+            Some(tf) => match tf.explain_illegal_play(
+                hands.get(id)?,
+                cards,
+                trick_draw_policy,
+                tf.decomposition_strategy,
+                DecompositionLimits::default(),
+            )? {
+                None => Ok(()),
+                Some(reason) => Err(PlayError::from_follow_failure(tf.suit, reason).into()),
+            },
+            None if cards.is_empty() => Err(PlayError::NoCards.into()),
             None => {
-                let num_suits = cards
-                    .iter()
-                    .map(|c| self.trump.effective_suit(*c))
-                    .collect::<HashSet<EffectiveSuit>>()
-                    .len();
-                if num_suits == 1 {
+                let suit = play_suit(self.trump, wild_card, cards);
+                let mut suits = vec![];
+                for card in cards {
+                    let card_suit = match wild_card {
+                        Some(wild_card) if *card == wild_card => suit,
+                        _ => self.trump.effective_suit(*card),
+                    };
+                    if !suits.contains(&card_suit) {
+                        suits.push(card_suit);
+                    }
+                }
+                if suits.len() == 1 {
                     Ok(())
                 } else {
-                    Err(TrickError::WrongNumberOfSuits)
+                    Err(PlayError::MultipleSuits { suits }.into())
+                }
+            }
+        }
+    }
+
+    /// Like `can_play_cards_with_wild_card`, but a lead may also span
+    /// several suits if `multi_suit_lead_policy` allows it.
+    pub fn can_play_cards_with_lead_policy(
+        &self,
+        id: PlayerID,
+        hands: &Hands,
+        cards: &[Card],
+        trick_draw_policy: TrickDrawPolicy,
+        wild_card: Option<Card>,
+        multi_suit_lead_policy: MultiSuitLeadPolicy,
+    ) -> Result<(), TrickError> {
+        match self.can_play_cards_with_wild_card(id, hands, cards, trick_draw_policy, wild_card) {
+            Err(TrickError::InvalidPlay(PlayError::MultipleSuits { .. }))
+                if multi_suit_lead_policy == MultiSuitLeadPolicy::AllowWhenVoidElsewhere
+                    && self.can_lead_multiple_suits(hands.get(id)?, cards) =>
+            {
+                Ok(())
+            }
+            r => r,
+        }
+    }
+
+    /// Checks a play following a lead which spans several suits: the player
+    /// must follow each suit as far as they can, and the cards played in a
+    /// suit they can follow in full must match that suit's format.
+    fn explain_illegal_multi_suit_play(
+        &self,
+        hand: &CardSet,
+        cards: &[Card],
+        trick_draw_policy: TrickDrawPolicy,
+        limits: DecompositionLimits,
+    ) -> Result<Option<PlayError>, TrickError> {
+        let formats = self.trick_format.iter().chain(self.side_formats.iter());
+        let required = formats.clone().map(|tf| tf.size()).sum::<usize>();
+        if cards.len() != required {
+            return Ok(Some(PlayError::WrongNumberOfCards {
+                required,
+                played: cards.len(),
+            }));
+        }
+        for tf in formats {
+            let in_suit = cards
+                .iter()
+                .copied()
+                .filter(|c| self.trump.effective_suit(*c) == tf.suit)
+                .collect::<Vec<_>>();
+            let held_in_suit = hand.count_in_suit(self.trump, tf.suit);
+            if in_suit.len() < held_in_suit.min(tf.size()) {
+                return Ok(Some(PlayError::MustFollowSuit {
+                    suit: tf.suit,
+                    played_in_suit: in_suit.len(),
+                    held_in_suit,
+                }));
+            }
+            if in_suit.len() == tf.size() {
+                if let Some(reason) = tf.explain_illegal_play(
+                    hand,
+                    &in_suit,
+                    trick_draw_policy,
+                    tf.decomposition_strategy,
+                    limits,
+                )? {
+                    return Ok(Some(PlayError::from_follow_failure(tf.suit, reason)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// The format a play must match to take the lead. A lead which spans
+    /// several suits can only be beaten by ruffing every suit at once, i.e.
+    /// by trumps matching the units of all of its suits together.
+    fn winning_format(
+        trick_format: Option<&'_ TrickFormat>,
+        side_formats: &'_ [TrickFormat],
+    ) -> Option<TrickFormat> {
+        let tf = trick_format?;
+        if side_formats.is_empty() {
+            return Some(tf.clone());
+        }
+        let mut units = std::iter::once(tf)
+            .chain(side_formats.iter())
+            .flat_map(|f| f.units.iter().cloned())
+            .collect::<Units>();
+        units.sort_by_key(|u| std::cmp::Reverse(u.size()));
+        Some(TrickFormat {
+            suit: EffectiveSuit::Trump,
+            units,
+            ..tf.clone()
+        })
+    }
+
+    /// Whether playing `cards` would make the player the winner of the trick
+    /// so far. Leading never counts as winning.
+    pub fn would_win(
+        &self,
+        id: PlayerID,
+        cards: &[Card],
+        throw_eval_policy: ThrowEvaluationPolicy,
+    ) -> bool {
+        if self.trick_format.is_none() {
+            return false;
+        }
+        let mut played_cards = self.played_cards.clone();
+        played_cards.push(PlayedCards {
+            id,
+            cards: cards.to_vec(),
+            bad_throw_cards: vec![],
+            better_player: None,
+            deck_indices: vec![],
+        });
+        Self::winner(
+            Self::winning_format(self.trick_format.as_ref(), &self.side_formats).as_ref(),
+            &played_cards,
+            throw_eval_policy,
+        ) == Some(id)
+    }
+
+    /// The legal plays with which the player following the current trick
+    /// would beat the currently-winning play, either in the led suit or by
+    /// trumping it. A lead spanning several suits can only be beaten by
+    /// trumping all of it.
+    pub fn beating_plays(
+        &self,
+        id: PlayerID,
+        hands: &Hands,
+        trick_draw_policy: TrickDrawPolicy,
+        throw_eval_policy: ThrowEvaluationPolicy,
+    ) -> Result<Vec<Vec<Card>>, TrickError> {
+        let tf = match Self::winning_format(self.trick_format.as_ref(), &self.side_formats) {
+            Some(tf) => tf,
+            None => return Ok(vec![]),
+        };
+        let hand = hands.get(id)?;
+        let multi_suit = !self.side_formats.is_empty();
+        if multi_suit
+            && self
+                .trick_format
+                .iter()
+                .chain(self.side_formats.iter())
+                .any(|f| {
+                    f.suit != EffectiveSuit::Trump && hand.count_in_suit(self.trump, f.suit) > 0
+                })
+        {
+            // Players who have to follow one of the suits can't trump the rest.
+            return Ok(vec![]);
+        }
+        let candidates = if !multi_suit && hand.count_in_suit(self.trump, tf.suit) > 0 {
+            // Only a play entirely in the led suit can win, and players who
+            // are short of the suit don't have one.
+            tf.legal_plays(
+                hand,
+                trick_draw_policy,
+                tf.decomposition_strategy,
+                DecompositionLimits::default(),
+            )?
+            .into_iter()
+            .filter(|group| group.num_other_cards == 0)
+            .flat_map(|group| group.plays)
+            .collect::<Vec<_>>()
+        } else {
+            let trump_cards = OrderedCard::make_map(
+                Card::cards(
+                    hand.iter()
+                        .filter(|(c, _)| self.trump.effective_suit(**c) == EffectiveSuit::Trump),
+                )
+                .copied(),
+                self.trump,
+            );
+            let mut candidates = UnitLike::check_play(
+                trump_cards.clone(),
+                tf.units.iter().map(UnitLike::from),
+                TrickDrawPolicy::NoProtections,
+                tf.adjacency_policy,
+            )
+            .map(|m| {
+                let mut cards = m.into_iter().flatten().collect::<Vec<_>>();
+                cards.sort();
+                cards
+            })
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(to_cards)
+            .collect::<Vec<_>>();
+            // Wild cards can stand in for trump to complete the ruff, so try
+            // the plays which include them as well.
+            if let Some(wild_card) = tf.wild_card.filter(|c| hand.count(*c) > 0) {
+                let wild_card = OrderedCard {
+                    card: wild_card,
+                    trump: self.trump,
+                };
+                let mut pool = trump_cards;
+                pool.insert(wild_card, hand.count(wild_card.card));
+                let keys = pool.keys().copied().collect::<Vec<_>>();
+                let limits = DecompositionLimits::default();
+                let combinations = multiset_k_combination_iter(keys, |c| pool[c], tf.size())
+                    .take(limits.max_decompositions.saturating_add(1))
+                    .collect::<Vec<_>>();
+                if combinations.len() > limits.max_decompositions {
+                    return Err(TrickError::DecompositionTooLarge {
+                        num_cards: tf.size(),
+                    });
                 }
+                candidates.extend(
+                    combinations
+                        .into_iter()
+                        .filter(|m| m.iter().any(|(c, _)| *c == wild_card))
+                        .map(to_cards),
+                );
+            }
+            candidates
+        };
+        let mut plays = vec![];
+        for cards in candidates {
+            if !self.would_win(id, &cards, throw_eval_policy) {
+                continue;
             }
+            if multi_suit
+                && self
+                    .explain_illegal_multi_suit_play(
+                        hand,
+                        &cards,
+                        trick_draw_policy,
+                        DecompositionLimits::default(),
+                    )?
+                    .is_some()
+            {
+                continue;
+            }
+            plays.push(cards);
+        }
+        Ok(plays)
+    }
+
+    /// Under `BeatPolicy::MustBeatIfAble`, checks that the player beats the
+    /// currently-winning play with `cards` if they're able to. If there are
+    /// too many ways of following to check them all, only a winning play can
+    /// be accepted, and anything else gets `TrickError::DecompositionTooLarge`
+    /// rather than having the rule waived.
+    pub fn check_must_beat(
+        &self,
+        id: PlayerID,
+        hands: &Hands,
+        cards: &[Card],
+        trick_draw_policy: TrickDrawPolicy,
+        throw_eval_policy: ThrowEvaluationPolicy,
+    ) -> Result<(), TrickError> {
+        if self.trick_format.is_none() || self.would_win(id, cards, throw_eval_policy) {
+            return Ok(());
+        }
+        let plays = self.beating_plays(id, hands, trick_draw_policy, throw_eval_policy)?;
+        match plays.into_iter().next() {
+            Some(playable) => Err(PlayError::MustBeat { playable }.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// The legal plays for the player following the current trick, or `None`
+    /// if nobody has played yet, in which case any cards of one suit may be
+    /// led. For a lead spanning several suits, the groups for each suit
+    /// follow one another, in the order of `trick_format` and then
+    /// `side_formats`, and each covers only the cards played in its suit.
+    pub fn legal_plays(
+        &self,
+        id: PlayerID,
+        hands: &Hands,
+        trick_draw_policy: TrickDrawPolicy,
+    ) -> Result<Option<Vec<LegalPlays>>, TrickError> {
+        let hand = hands.get(id)?;
+        if self.trick_format.is_none() {
+            return Ok(None);
+        }
+        let mut groups = vec![];
+        for tf in self.trick_format.iter().chain(self.side_formats.iter()) {
+            groups.extend(tf.legal_plays(
+                hand,
+                trick_draw_policy,
+                tf.decomposition_strategy,
+                DecompositionLimits::default(),
+            )?);
         }
+        Ok(Some(groups))
     }
 
     ///
@@ -505,124 +1725,124 @@ impl Trick {
     ///
     pub fn play_cards(
         &mut self,
-        args: PlayCards<'_, '_, '_>,
+        args: PlayCards<'_, '_, '_, '_>,
     ) -> Result<Vec<PlayCardsMessage>, TrickError> {
         let PlayCards {
             id,
             hands,
             cards,
-            trick_draw_policy,
-            throw_eval_policy,
+            rules,
+            teammates,
             format_hint,
-            hide_throw_halting_player,
-            tractor_requirements,
         } = args;
 
         if self.player_queue.front().cloned() != Some(id) {
-            return Err(TrickError::OutOfOrder);
+            return Err(PlayError::OutOfTurn {
+                next_player: self.player_queue.front().cloned(),
+            }
+            .into());
+        }
+        self.can_play_cards_with_lead_policy(
+            id,
+            hands,
+            cards,
+            rules.trick_draw_policy,
+            rules.tractor_requirements.wild_card,
+            rules.multi_suit_lead_policy,
+        )?;
+        if rules.beat_policy == BeatPolicy::MustBeatIfAble {
+            self.check_must_beat(
+                id,
+                hands,
+                cards,
+                rules.trick_draw_policy,
+                rules.throw_eval_policy,
+            )?;
         }
-        self.can_play_cards(id, hands, cards, trick_draw_policy)?;
         let mut msgs = vec![];
         let mut cards = cards.to_vec();
         cards.sort_by(|a, b| self.trump.compare(*a, *b));
 
         let (cards, bad_throw_cards, better_player) = if self.trick_format.is_none() {
-            let mut tf =
-                TrickFormat::from_cards(self.trump, tractor_requirements, &cards, format_hint)?;
-            let mut invalid = None;
-            if tf.units.len() > 1 {
-                // This is a throw, let's see if any of the units can be strictly defeated by any
-                // other player.
-                'search: for player in self.player_queue.iter().skip(1) {
-                    let subset_hands = hands.get(*player)?.iter().filter_map(|(card, count)| {
-                        if self.trump.effective_suit(*card) == tf.suit {
-                            Some((
-                                OrderedCard {
-                                    card: *card,
-                                    trump: self.trump,
-                                },
-                                *count,
-                            ))
-                        } else {
-                            None
-                        }
-                    });
-
-                    for unit in &tf.units {
-                        match unit {
-                            TrickUnit::Repeated { count, card } => {
-                                for (c, ct) in subset_hands.clone() {
-                                    if ct >= *count && c.cmp_effective(*card) == Ordering::Greater {
-                                        invalid = Some((player, unit.clone()));
-                                        break 'search;
-                                    }
-                                }
-                            }
-                            TrickUnit::Tractor { count, members } => {
-                                let in_suit = subset_hands
-                                    .clone()
-                                    .collect::<BTreeMap<OrderedCard, usize>>();
-                                for (c, ct) in in_suit.range(members[1]..) {
-                                    let higher_tractors = find_tractors_from_start(
-                                        *c,
-                                        *ct,
-                                        &in_suit,
-                                        // Note: We base the
-                                        // tractor-requirements off of the
-                                        // tractor we found, rather than off of
-                                        // the requirements that are passed in,
-                                        // that way we only find "bigger"
-                                        // tractors.
-                                        TractorRequirements {
-                                            min_count: *count,
-                                            min_length: members.len(),
-                                        },
-                                    );
-                                    if !higher_tractors.is_empty() {
-                                        invalid = Some((player, unit.clone()));
-                                        break 'search;
-                                    }
-                                }
-                            }
-                        }
+            let multi_suit_lead = rules.multi_suit_lead_policy
+                == MultiSuitLeadPolicy::AllowWhenVoidElsewhere
+                && self.can_lead_multiple_suits(hands.get(id)?, &cards);
+            let mut formats = if multi_suit_lead {
+                // Each suit in the lead has its own format.
+                let mut groups: Vec<(EffectiveSuit, Vec<Card>)> = vec![];
+                for card in &cards {
+                    let suit = self.trump.effective_suit(*card);
+                    match groups.iter_mut().find(|(s, _)| *s == suit) {
+                        Some((_, group)) => group.push(*card),
+                        None => groups.push((suit, vec![*card])),
                     }
                 }
-            }
-
-            let (cards, bad_throw_cards, better_player) =
-                if let Some((better_player, forced_unit)) = invalid {
-                    let forced_cards: Vec<Card> = match forced_unit {
-                        TrickUnit::Repeated { card, count } => {
-                            (0..count).map(|_| card.card).collect()
-                        }
-                        TrickUnit::Tractor { ref members, count } => members
-                            .iter()
-                            .flat_map(|card| (0..count).map(move |_| card.card))
-                            .collect(),
-                    };
+                groups
+                    .into_iter()
+                    .map(|(_, group)| {
+                        TrickFormat::from_cards(
+                            self.trump,
+                            rules.tractor_requirements,
+                            &group,
+                            None,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+            } else {
+                vec![TrickFormat::from_cards(
+                    self.trump,
+                    rules.tractor_requirements,
+                    &cards,
+                    format_hint,
+                )?]
+            };
+            formats = formats
+                .into_iter()
+                .map(|tf| {
+                    tf.with_ruff_policy(rules.ruff_policy)
+                        .with_tie_break_policy(rules.tie_break_policy)
+                        .with_tractor_ruff_policy(rules.tractor_ruff_policy)
+                        .with_joker_pair_policy(rules.joker_pair_policy, &cards)
+                        .with_decomposition_strategy(rules.decomposition_strategy)
+                })
+                .collect();
 
-                    tf.units = vec![forced_unit];
+            let invalid = if rules.defer_throw_check {
+                None
+            } else {
+                self.find_throw_failure(id, &formats, hands, rules, teammates)?
+            };
 
+            let (cards, bad_throw_cards, better_player) =
+                if let Some((idx, better_player, forced_unit)) = invalid {
                     msgs.push(PlayCardsMessage::ThrowFailed {
                         original_cards: cards.clone(),
-                        better_player: if hide_throw_halting_player {
+                        better_player: if rules.hide_throw_halting_player {
                             None
                         } else {
-                            Some(*better_player)
+                            better_player
                         },
                     });
 
-                    for card in &forced_cards {
-                        let idx = cards.iter().position(|c| *c == *card).unwrap();
-                        cards.remove(idx);
-                    }
+                    let forced_cards = Self::take_forced_cards(
+                        &forced_unit,
+                        &mut cards,
+                        |c| *c,
+                        rules.tractor_requirements.wild_card,
+                    );
 
-                    (forced_cards, cards, Some(*better_player))
+                    let mut tf = formats.swap_remove(idx);
+                    tf.units = vec![forced_unit];
+                    formats = vec![tf];
+
+                    (forced_cards, cards, better_player)
                 } else {
                     (cards, vec![], None)
                 };
 
-            self.trick_format = Some(tf);
+            let mut formats = formats.into_iter();
+            self.trick_format = formats.next();
+            self.side_formats = formats.collect();
 
             msgs.push(PlayCardsMessage::PlayedCards {
                 cards: cards.clone(),
@@ -636,7 +1856,7 @@ impl Trick {
             (cards, vec![], None)
         };
 
-        hands.remove(id, cards.iter().cloned())?;
+        let deck_indices = hands.remove_from_decks(id, cards.iter().cloned())?;
 
         self.player_queue.pop_front();
 
@@ -652,22 +1872,174 @@ impl Trick {
             id,
             cards,
             bad_throw_cards,
-            better_player: if hide_throw_halting_player {
+            better_player: if rules.hide_throw_halting_player {
                 None
             } else {
                 better_player
             },
+            deck_indices,
         });
 
-        self.current_winner = Self::winner(
-            self.trick_format.as_ref(),
-            &self.played_cards,
-            throw_eval_policy,
-        );
+        self.update_winner(rules.throw_eval_policy);
 
         Ok(msgs)
     }
 
+    ///
+    /// Checks a throw which was led with `defer_throw_check`, before anyone
+    /// else has played, failing with `TrickError::NotAThrow` for any other
+    /// lead. If the throw can be beaten, the rest of it goes back
+    /// to the thrower's hand as though the check had been made when it was
+    /// led, and the returned messages describe the failure. Otherwise, nothing
+    /// changes and no messages are returned.
+    ///
+    pub fn challenge_throw(
+        &mut self,
+        hands: &mut Hands,
+        rules: &TrickRules,
+        teammates: &[PlayerID],
+    ) -> Result<Vec<PlayCardsMessage>, TrickError> {
+        if self.played_cards.len() != 1 {
+            return Err(TrickError::OutOfOrder);
+        }
+        let thrower = self.played_cards[0].id;
+        let mut formats = self
+            .trick_format
+            .iter()
+            .chain(self.side_formats.iter())
+            .cloned()
+            .collect::<Vec<_>>();
+        if formats.len() == 1 && formats[0].units.len() == 1 {
+            return Err(TrickError::NotAThrow);
+        }
+        let (idx, better_player, forced_unit) =
+            match self.find_throw_failure(thrower, &formats, hands, rules, teammates)? {
+                Some(invalid) => invalid,
+                None => return Ok(vec![]),
+            };
+        let better_player = if rules.hide_throw_halting_player {
+            None
+        } else {
+            better_player
+        };
+
+        let played = &mut self.played_cards[0];
+        let original_cards = played.cards.clone();
+        let mut cards = played
+            .cards
+            .iter()
+            .copied()
+            .zip(
+                played
+                    .deck_indices
+                    .iter()
+                    .copied()
+                    .chain(std::iter::repeat(None)),
+            )
+            .collect::<Vec<_>>();
+        let forced = Self::take_forced_cards(
+            &forced_unit,
+            &mut cards,
+            |(c, _)| *c,
+            rules.tractor_requirements.wild_card,
+        );
+        hands.add_from_decks(thrower, cards.iter().copied())?;
+        played.cards = forced.iter().map(|(c, _)| *c).collect();
+        played.deck_indices = forced.into_iter().map(|(_, d)| d).collect();
+        played.bad_throw_cards = cards.into_iter().map(|(c, _)| c).collect();
+        played.better_player = better_player;
+
+        let mut tf = formats.swap_remove(idx);
+        tf.units = vec![forced_unit];
+        self.played_card_mappings[0] = tf
+            .matches(&self.played_cards[0].cards)
+            .ok()
+            .and_then(|mut f| f.next());
+        self.trick_format = Some(tf);
+        self.side_formats.clear();
+        self.update_winner(rules.throw_eval_policy);
+
+        Ok(vec![
+            PlayCardsMessage::ThrowFailed {
+                original_cards,
+                better_player,
+            },
+            PlayCardsMessage::PlayedCards {
+                cards: self.played_cards[0].cards.clone(),
+            },
+        ])
+    }
+
+    ///
+    /// Validates the play and reports what would happen if it were made,
+    /// without modifying the trick or the hands in `args`.
+    ///
+    pub fn preview_play(
+        &self,
+        args: PlayCards<'_, '_, '_, '_>,
+        points: &PointSchedule,
+    ) -> Result<PlayPreview, TrickError> {
+        let id = args.id;
+        let mut hands = args.hands.clone();
+        let mut trick = self.clone();
+        trick.play_cards(PlayCards {
+            hands: &mut hands,
+            ..args
+        })?;
+
+        let played = trick.played_cards.last().expect("the play was just made");
+        let winning_cards = trick
+            .played_cards
+            .iter()
+            .find(|pc| Some(pc.id) == trick.current_winner)
+            .map(|pc| pc.cards.clone())
+            .unwrap_or_default();
+        Ok(PlayPreview {
+            cards: played.cards.clone(),
+            bad_throw_cards: played.bad_throw_cards.clone(),
+            winner: trick.current_winner,
+            takes_lead: trick.current_winner == Some(id),
+            winning_cards,
+            points: trick
+                .played_cards
+                .iter()
+                .flat_map(|pc| pc.cards.iter().copied())
+                .filter(|c| points.is_point_card(*c))
+                .collect(),
+        })
+    }
+
+    ///
+    /// Steps through the plays made so far, with the winner and the points
+    /// in the trick after each one.
+    ///
+    pub fn replay(
+        &self,
+        throw_eval_policy: ThrowEvaluationPolicy,
+        points: &PointSchedule,
+    ) -> Vec<TrickReplayStep> {
+        let winning_format = Self::winning_format(self.trick_format.as_ref(), &self.side_formats);
+        let mut points_so_far = vec![];
+        self.played_cards
+            .iter()
+            .enumerate()
+            .map(|(idx, pc)| {
+                points_so_far.extend(pc.cards.iter().filter(|c| points.is_point_card(**c)));
+                TrickReplayStep {
+                    id: pc.id,
+                    cards: pc.cards.clone(),
+                    bad_throw_cards: pc.bad_throw_cards.clone(),
+                    winner: Self::winner(
+                        winning_format.as_ref(),
+                        &self.played_cards[..=idx],
+                        throw_eval_policy,
+                    ),
+                    points: points_so_far.clone(),
+                }
+            })
+            .collect()
+    }
+
     /**
      * Takes back cards just played, e.g. in case of dispute.
      */
@@ -681,16 +2053,23 @@ impl Trick {
             let played = self.played_cards.pop().unwrap();
             self.played_card_mappings.pop();
 
-            hands.add(id, played.cards).unwrap();
+            hands
+                .add_from_decks(
+                    id,
+                    played.cards.into_iter().zip(
+                        played
+                            .deck_indices
+                            .into_iter()
+                            .chain(std::iter::repeat(None)),
+                    ),
+                )
+                .unwrap();
             self.player_queue.push_front(id);
             if self.played_cards.is_empty() {
                 self.trick_format = None;
+                self.side_formats.clear();
             }
-            self.current_winner = Self::winner(
-                self.trick_format.as_ref(),
-                &self.played_cards,
-                throw_eval_policy,
-            );
+            self.update_winner(throw_eval_policy);
             Ok(())
         } else {
             Err(TrickError::OutOfOrder)
@@ -701,6 +2080,11 @@ impl Trick {
      * Completes the trick and determines the winner. Returns the point cards that the winner won.
      */
     pub fn complete(&self) -> Result<TrickEnded, TrickError> {
+        self.complete_with_points(&PointSchedule::default())
+    }
+
+    /// Like `complete`, but with the point cards determined by `points`.
+    pub fn complete_with_points(&self, points: &PointSchedule) -> Result<TrickEnded, TrickError> {
         if !self.player_queue.is_empty() || self.played_cards.is_empty() {
             return Err(TrickError::OutOfOrder);
         }
@@ -708,13 +2092,29 @@ impl Trick {
             let all_card_points = self
                 .played_cards
                 .iter()
-                .flat_map(|pc| pc.cards.iter().filter(|c| c.points().is_some()).copied())
+                .flat_map(|pc| {
+                    pc.cards
+                        .iter()
+                        .filter(|c| points.is_point_card(**c))
+                        .copied()
+                })
                 .collect::<Vec<Card>>();
 
+            let played_units = |(idx, units): &(usize, Units)| PlayedUnits {
+                id: self.played_cards[*idx].id,
+                units: units.clone(),
+            };
+            let mut leaders = self.leaders.iter().rev().map(played_units);
             Ok(TrickEnded {
                 winner: self.current_winner.ok_or(TrickError::OutOfOrder)?,
+                winning_units: leaders.next().map(|p| p.units).unwrap_or_default(),
+                runner_up: leaders.next(),
                 points: all_card_points,
-                largest_trick_unit_size: tf.units.iter().map(|u| u.size()).max().unwrap_or(0),
+                largest_trick_unit_size: std::iter::once(tf)
+                    .chain(self.side_formats.iter())
+                    .flat_map(|tf| tf.units.iter().map(|u| u.size()))
+                    .max()
+                    .unwrap_or(0),
                 failed_throw_size: self
                     .played_cards
                     .get(0)
@@ -727,82 +2127,418 @@ impl Trick {
         }
     }
 
-    fn _defeats(m: &Units, winner: &Units, throw_eval_policy: ThrowEvaluationPolicy) -> bool {
-        match throw_eval_policy {
-            ThrowEvaluationPolicy::All => m
-                .iter()
-                .zip(winner.iter())
-                .all(|(n, w)| n.first_card().cmp_effective(w.first_card()) == Ordering::Greater),
-            ThrowEvaluationPolicy::Highest => {
-                let n_max = m
-                    .iter()
-                    .map(|u| u.last_card())
-                    .max()
-                    .expect("trick format cannot be empty");
-                let w_max = winner
-                    .iter()
-                    .map(|u| u.last_card())
-                    .max()
-                    .expect("trick format cannot be empty");
-                n_max.cmp_effective(w_max) == Ordering::Greater
+    /// Checks whether the thrower's lead, split into `formats` by suit, can
+    /// be beaten. If so, returns the index of the format containing the unit
+    /// which must be played instead, the player who could beat the throw if
+    /// it's known, and that unit.
+    fn find_throw_failure(
+        &self,
+        thrower: PlayerID,
+        formats: &[TrickFormat],
+        hands: &Hands,
+        rules: &TrickRules,
+        teammates: &[PlayerID],
+    ) -> Result<Option<(usize, Option<PlayerID>, TrickUnit)>, TrickError> {
+        if formats.len() == 1 && formats[0].units.len() == 1 {
+            return Ok(None);
+        }
+        // This is a throw, let's see if any of the units can be strictly
+        // defeated by any other player.
+        for (idx, tf) in formats.iter().enumerate() {
+            if let Some((player, unit)) =
+                self.find_beatable_unit(tf, thrower, hands, rules, teammates)?
+            {
+                return Ok(Some((idx, player, unit)));
             }
-            ThrowEvaluationPolicy::TrickUnitLength => {
-                // Don't worry about single cards if this is a throw with at
-                // least one unit that is longer than a single card, but do
-                // evaluate them if it isn't!
-                let skip_single_cards = m.len() > 1 && m.iter().any(|n| n.size() > 1);
+        }
+        if rules.trumped_throw_policy == TrumpedThrowPolicy::Fails {
+            for (idx, tf) in formats.iter().enumerate() {
+                if let Some(player) =
+                    self.find_trumping_player(tf, thrower, hands, rules, teammates)?
+                {
+                    // The whole throw is beaten, so only its smallest unit
+                    // may be played.
+                    let unit = tf
+                        .units
+                        .iter()
+                        .min_by_key(|u| u.size())
+                        .expect("formats have at least one unit")
+                        .clone();
+                    return Ok(Some((idx, player, unit)));
+                }
+            }
+        }
+        Ok(None)
+    }
 
-                let mut comparisons = m
-                    .iter()
+    /// Removes the cards making up `forced_unit` from `cards`, and returns
+    /// them. The forced unit might include cards which were stood in for by
+    /// a wild card, in which case the wild card is taken instead.
+    fn take_forced_cards<T>(
+        forced_unit: &TrickUnit,
+        cards: &mut Vec<T>,
+        card_of: impl Fn(&T) -> Card,
+        wild_card: Option<Card>,
+    ) -> Vec<T> {
+        let forced_cards: Vec<Card> = match forced_unit {
+            TrickUnit::Repeated { card, count } => (0..*count).map(|_| card.card).collect(),
+            TrickUnit::Tractor { members, count } => members
+                .iter()
+                .flat_map(|card| (0..*count).map(move |_| card.card))
+                .collect(),
+        };
+        forced_cards
+            .into_iter()
+            .map(|card| {
+                let idx = cards
+                    .iter()
+                    .position(|c| card_of(c) == card)
+                    .or_else(|| cards.iter().position(|c| Some(card_of(c)) == wild_card))
+                    .expect("forced unit is part of the throw");
+                cards.remove(idx)
+            })
+            .collect()
+    }
+
+    /// Finds a unit of the (possibly partial) lead `tf` which another player
+    /// could beat, and the player who could beat it if it's known.
+    fn find_beatable_unit(
+        &self,
+        tf: &TrickFormat,
+        thrower: PlayerID,
+        hands: &Hands,
+        rules: &TrickRules,
+        teammates: &[PlayerID],
+    ) -> Result<Option<(Option<PlayerID>, TrickUnit)>, TrickError> {
+        let tractor_requirements = rules.tractor_requirements;
+        // The cards which could be played in the trick's suit, including any
+        // wild cards.
+        let candidates = self.throw_candidates(thrower, hands, rules, teammates, |card| {
+            self.trump.effective_suit(card) == tf.suit
+                || Some(card) == tractor_requirements.wild_card
+        })?;
+
+        for (player, subset_hand) in candidates {
+            for reading in wild_card_readings(
+                self.trump,
+                tractor_requirements.wild_card,
+                tf.suit,
+                &subset_hand,
+                tractor_requirements.adjacency_policy,
+            ) {
+                let in_suit = OrderedCard::make_map(
+                    reading
+                        .into_iter()
+                        .filter(|card| self.trump.effective_suit(*card) == tf.suit),
+                    self.trump,
+                );
+                for unit in &tf.units {
+                    if Self::can_defeat(&in_suit, unit, tractor_requirements.adjacency_policy) {
+                        return Ok(Some((player, unit.clone())));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Finds a player who is void in the suit of the (possibly partial) lead
+    /// `tf` and could beat all of it by trumping, if there is one. The player
+    /// isn't known under `ThrowEvaluationScope::UnseenCards`.
+    fn find_trumping_player(
+        &self,
+        tf: &TrickFormat,
+        thrower: PlayerID,
+        hands: &Hands,
+        rules: &TrickRules,
+        teammates: &[PlayerID],
+    ) -> Result<Option<Option<PlayerID>>, TrickError> {
+        if tf.suit == EffectiveSuit::Trump {
+            return Ok(None);
+        }
+        let candidates = self.throw_candidates(thrower, hands, rules, teammates, |card| {
+            let suit = self.trump.effective_suit(card);
+            suit == tf.suit || suit == EffectiveSuit::Trump
+        })?;
+        for (player, cards) in candidates {
+            if cards
+                .iter()
+                .any(|c| self.trump.effective_suit(*c) == tf.suit)
+            {
+                continue;
+            }
+            let trump_cards = OrderedCard::make_map(cards.into_iter(), self.trump);
+            if UnitLike::check_play(
+                trump_cards,
+                tf.units.iter().map(UnitLike::from),
+                TrickDrawPolicy::NoProtections,
+                tf.adjacency_policy,
+            )
+            .next()
+            .is_some()
+            {
+                return Ok(Some(player));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The cards matching `filter` in each hand the thrower's throw is
+    /// checked against, according to the throw evaluation scope.
+    fn throw_candidates(
+        &self,
+        thrower: PlayerID,
+        hands: &Hands,
+        rules: &TrickRules,
+        teammates: &[PlayerID],
+        filter: impl Fn(Card) -> bool,
+    ) -> Result<ThrowCandidates, TrickError> {
+        let throw_eval_scope = rules.throw_eval_scope;
+        let mut candidates: ThrowCandidates = vec![];
+        for player in self.player_queue.iter().filter(|p| **p != thrower) {
+            if throw_eval_scope == ThrowEvaluationScope::OpponentsOnly && teammates.contains(player)
+            {
+                continue;
+            }
+            let subset_hand = hands
+                .get(*player)?
+                .iter()
+                .filter(|(card, _)| filter(**card))
+                .flat_map(|(card, count)| (0..*count).map(move |_| *card))
+                .collect::<Vec<Card>>();
+            if throw_eval_scope != ThrowEvaluationScope::UnseenCards {
+                candidates.push((Some(*player), subset_hand));
+            } else if let Some((_, pooled)) = candidates.first_mut() {
+                pooled.extend(subset_hand);
+            } else {
+                candidates.push((None, subset_hand));
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// Whether the cards in `in_suit` can form a unit which strictly beats
+    /// `unit`.
+    fn can_defeat(
+        in_suit: &BTreeMap<OrderedCard, usize>,
+        unit: &TrickUnit,
+        adjacency_policy: AdjacencyPolicy,
+    ) -> bool {
+        match unit {
+            TrickUnit::Repeated { count, card } => in_suit
+                .iter()
+                .any(|(c, ct)| *ct >= *count && c.cmp_effective(*card) == Ordering::Greater),
+            TrickUnit::Tractor { count, members } => {
+                in_suit.range(members[1]..).any(|(c, ct)| {
+                    !find_tractors_from_start(
+                        *c,
+                        *ct,
+                        in_suit,
+                        // Note: We base the tractor-requirements off of the
+                        // tractor we found, rather than off of the
+                        // requirements that are passed in, that way we only
+                        // find "bigger" tractors.
+                        TractorRequirements {
+                            min_count: *count,
+                            min_length: members.len(),
+                            adjacency_policy,
+                            wild_card: None,
+                        },
+                    )
+                    .is_empty()
+                })
+            }
+        }
+    }
+
+    /// `Greater` if `m` defeats `winner`, `Equal` if they tie, and `Less`
+    /// otherwise.
+    fn _compare(m: &Units, winner: &Units, throw_eval_policy: ThrowEvaluationPolicy) -> Ordering {
+        match throw_eval_policy {
+            ThrowEvaluationPolicy::All => {
+                let mut comparisons = m
+                    .iter()
+                    .zip(winner.iter())
+                    .map(|(n, w)| n.first_card().cmp_effective(w.first_card()));
+                let first = comparisons.next().expect("trick format cannot be empty");
+                if first != Ordering::Less && comparisons.all(|c| c == first) {
+                    first
+                } else {
+                    Ordering::Less
+                }
+            }
+            ThrowEvaluationPolicy::Highest => {
+                let n_max = m
+                    .iter()
+                    .map(|u| u.last_card())
+                    .max()
+                    .expect("trick format cannot be empty");
+                let w_max = winner
+                    .iter()
+                    .map(|u| u.last_card())
+                    .max()
+                    .expect("trick format cannot be empty");
+                n_max.cmp_effective(w_max)
+            }
+            ThrowEvaluationPolicy::TrickUnitLength => {
+                // Don't worry about single cards if this is a throw with at
+                // least one unit that is longer than a single card, but do
+                // evaluate them if it isn't!
+                let skip_single_cards = m.len() > 1 && m.iter().any(|n| n.size() > 1);
+
+                let mut comparisons = m
+                    .iter()
                     .zip(winner.iter())
                     .filter(|(n, _)| !skip_single_cards || n.size() > 1)
                     .map(|(n, w)| (n.size(), n.first_card().cmp_effective(w.first_card())))
                     .collect::<Vec<_>>();
                 // Compare by size first, then try to skip equal-comparisons.
                 comparisons.sort_by_key(|(s, c)| (-(*s as isize), *c == Ordering::Equal));
-                let mut iter = comparisons.into_iter().map(|(_, c)| c);
-                loop {
-                    match iter.next() {
-                        Some(Ordering::Equal) => {}
-                        Some(Ordering::Greater) => break true,
-                        Some(Ordering::Less) | None => break false,
-                    }
-                }
+                comparisons
+                    .into_iter()
+                    .map(|(_, c)| c)
+                    .find(|c| *c != Ordering::Equal)
+                    .unwrap_or(Ordering::Equal)
             }
         }
     }
 
+    fn update_winner(&mut self, throw_eval_policy: ThrowEvaluationPolicy) {
+        self.leaders = Self::leaders(
+            Self::winning_format(self.trick_format.as_ref(), &self.side_formats).as_ref(),
+            &self.played_cards,
+            throw_eval_policy,
+        );
+        self.current_winner = self
+            .leaders
+            .last()
+            .map(|(idx, _)| self.played_cards[*idx].id);
+    }
+
     fn winner(
         trick_format: Option<&'_ TrickFormat>,
         played_cards: &'_ [PlayedCards],
         throw_eval_policy: ThrowEvaluationPolicy,
     ) -> Option<PlayerID> {
+        Self::leaders(trick_format, played_cards, throw_eval_policy)
+            .pop()
+            .map(|(idx, _)| played_cards[idx].id)
+    }
+
+    /// The index in `played_cards` and the matching units of each play which
+    /// took the lead in turn, starting with the first play.
+    fn leaders(
+        trick_format: Option<&'_ TrickFormat>,
+        played_cards: &'_ [PlayedCards],
+        throw_eval_policy: ThrowEvaluationPolicy,
+    ) -> Vec<(usize, Units)> {
         match trick_format {
-            Some(tf) => {
-                let mut winner = (0, tf.units.to_vec());
+            Some(tf)
+                if tf.joker_pair_policy == JokerPairPolicy::Unbeatable
+                    && played_cards
+                        .first()
+                        .map(|pc| is_joker_pair(&pc.cards))
+                        .unwrap_or(false) =>
+            {
+                vec![(0, tf.units.to_vec())]
+            }
+            Some(tf) if !played_cards.is_empty() => {
+                let mut leaders = vec![(0, tf.units.to_vec())];
+                // Whether the current leader ruffed a tractor without matching
+                // its shape, under `TractorRuffPolicy::AnyTrump`.
+                let mut loose_ruff = false;
+                let wins = |ordering| match ordering {
+                    Ordering::Greater => true,
+                    Ordering::Equal => tf.tie_break_policy == TieBreakPolicy::LastPlayed,
+                    Ordering::Less => false,
+                };
 
                 for (idx, pc) in played_cards.iter().enumerate().skip(1) {
+                    let winner = &leaders[leaders.len() - 1].1;
                     if let Ok(mut mm) = tf.matches(&pc.cards) {
-                        let greater = mm.find(|m| Self::_defeats(m, &winner.1, throw_eval_policy));
+                        let greater = if loose_ruff {
+                            // Any trump play of the same shape beats a loose
+                            // ruff.
+                            mm.find(|_| {
+                                play_suit(tf.trump, tf.wild_card, &pc.cards) == EffectiveSuit::Trump
+                            })
+                        } else {
+                            mm.find(|m| wins(Self::_compare(m, winner, throw_eval_policy)))
+                        };
                         if let Some(m) = greater {
-                            winner = (idx, m);
+                            leaders.push((idx, m));
+                            loose_ruff = false;
+                        }
+                    } else if let Some(m) = tf.loose_ruff(&pc.cards) {
+                        let highest = |units: &Units| {
+                            units
+                                .iter()
+                                .map(|u| u.last_card())
+                                .max()
+                                .expect("trick format cannot be empty")
+                        };
+                        let takes_lead = if loose_ruff {
+                            wins(highest(&m).cmp_effective(highest(winner)))
+                        } else {
+                            // Loose ruffs only beat plays in the led suit.
+                            tf.trump.effective_suit(winner[0].first_card().card)
+                                != EffectiveSuit::Trump
+                        };
+                        if takes_lead {
+                            leaders.push((idx, m));
+                            loose_ruff = true;
                         }
                     }
                 }
-                Some(played_cards[winner.0].id)
+                leaders
             }
-            None => None,
+            _ => vec![],
         }
     }
 }
 
+/// A finished trick, stored compactly for the game's history: each play is
+/// kept as just the player and the one-byte codes of their cards.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CompactTrick {
+    pub plays: Vec<CompactPlay>,
+    pub winner: Option<PlayerID>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CompactPlay {
+    pub id: PlayerID,
+    /// The cards played, as given by `Card::as_u8`.
+    pub cards: Vec<u8>,
+}
+
+impl CompactPlay {
+    pub fn cards(&self) -> impl Iterator<Item = Card> + '_ {
+        self.cards
+            .iter()
+            .map(|c| Card::from_u8(*c).unwrap_or(Card::Unknown))
+    }
+}
+
 pub struct TrickEnded {
     pub winner: PlayerID,
+    /// The units with which `winner` won the trick.
+    pub winning_units: Vec<TrickUnit>,
+    /// The play which was winning until `winner` beat it, if the trick
+    /// changed hands.
+    pub runner_up: Option<PlayedUnits>,
     pub points: Vec<Card>,
     pub largest_trick_unit_size: usize,
     pub failed_throw_size: usize,
 }
 
+/// A player's play, split into units matching the trick-format.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PlayedUnits {
+    pub id: PlayerID,
+    pub units: Vec<TrickUnit>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct UnitLike {
     adjacent_tuples: AdjacentTupleSizes,
@@ -893,6 +2629,7 @@ impl UnitLike {
         counts: BTreeMap<OrderedCard, usize>,
         units: impl Iterator<Item = UnitLike>,
         trick_draw_policy: TrickDrawPolicy,
+        adjacency_policy: AdjacencyPolicy,
     ) -> impl Iterator<Item = Vec<MatchingCards>> {
         let counts_ = counts.clone();
         let filter_func = move |matching: &MatchingCardsRef| match trick_draw_policy {
@@ -908,7 +2645,7 @@ impl UnitLike {
             .map(|u| u.adjacent_tuples)
             .collect::<Vec<_>>();
 
-        crate::format_match::find_format_matches(units, counts)
+        crate::format_match::find_format_matches(units, counts, adjacency_policy)
             .filter(move |m| m.iter().all(|mm| filter_func(mm)))
     }
 }
@@ -944,6 +2681,13 @@ impl<'a> From<&'a MatchingCards> for UnitLike {
 
 type Units = Vec<TrickUnit>;
 
+/// Whether `cards` are exactly the small and big joker.
+fn is_joker_pair(cards: &[Card]) -> bool {
+    cards.len() == 2 && cards.contains(&Card::SmallJoker) && cards.contains(&Card::BigJoker)
+}
+/// The cards a throw is checked against, and whose they are if that's known.
+type ThrowCandidates = Vec<(Option<PlayerID>, Vec<Card>)>;
+
 fn without_trick_unit<T>(
     counts: &mut BTreeMap<OrderedCard, usize>,
     unit: &TrickUnit,
@@ -990,6 +2734,84 @@ fn without_trick_unit<T>(
     res
 }
 
+/// The effective suit of a play, ignoring any wild cards unless the play is
+/// made up entirely of them.
+fn play_suit(trump: Trump, wild_card: Option<Card>, cards: &[Card]) -> EffectiveSuit {
+    cards
+        .iter()
+        .find(|c| Some(**c) != wild_card)
+        .or_else(|| cards.first())
+        .map(|c| trump.effective_suit(*c))
+        .unwrap_or(EffectiveSuit::Unknown)
+}
+
+/// Every way of reading `cards` with each copy of the wild card standing in
+/// for some card in `suit`. If there aren't any wild cards to substitute, the
+/// only reading is `cards` itself.
+///
+/// A wild card only matters when it completes a pair or tractor, so it only
+/// stands in for cards which are within as many steps of the other cards as
+/// there are wild cards.
+fn wild_card_readings(
+    trump: Trump,
+    wild_card: Option<Card>,
+    suit: EffectiveSuit,
+    cards: &[Card],
+    adjacency_policy: AdjacencyPolicy,
+) -> Vec<Vec<Card>> {
+    let num_wild = cards.iter().filter(|c| Some(**c) == wild_card).count();
+    if num_wild == 0 || num_wild == cards.len() {
+        return vec![cards.to_vec()];
+    }
+    let mut nearby = cards
+        .iter()
+        .copied()
+        .filter(|c| Some(*c) != wild_card && trump.effective_suit(*c) == suit)
+        .collect::<HashSet<Card>>();
+    let mut frontier = nearby.iter().copied().collect::<Vec<Card>>();
+    for _ in 0..num_wild {
+        frontier = frontier
+            .into_iter()
+            .flat_map(|c| {
+                let mut adjacent = trump.successor(c, adjacency_policy);
+                adjacent.extend(trump.predecessor(c, adjacency_policy));
+                adjacent
+            })
+            .filter(|c| trump.effective_suit(*c) == suit && nearby.insert(*c))
+            .collect();
+    }
+    let candidates = FULL_DECK
+        .iter()
+        .copied()
+        .filter(|c| nearby.contains(c))
+        .collect::<Vec<Card>>();
+    let candidates = &candidates;
+
+    // Only substitute in non-decreasing order, so that each multiset of
+    // substitutions is generated exactly once.
+    let mut readings = vec![(
+        0,
+        cards
+            .iter()
+            .copied()
+            .filter(|c| Some(*c) != wild_card)
+            .collect::<Vec<Card>>(),
+    )];
+    for _ in 0..num_wild {
+        readings = readings
+            .into_iter()
+            .flat_map(|(start, reading)| {
+                (start..candidates.len()).map(move |idx| {
+                    let mut reading = reading.clone();
+                    reading.push(candidates[idx]);
+                    (idx, reading)
+                })
+            })
+            .collect();
+    }
+    readings.into_iter().map(|(_, reading)| reading).collect()
+}
+
 fn find_tractors_from_start(
     card: OrderedCard,
     count: usize,
@@ -1003,7 +2825,7 @@ fn find_tractors_from_start(
     }
 
     let mut next_cards: Vec<(OrderedCard, Members)> = card
-        .successor()
+        .successor(tractor_requirements.adjacency_policy)
         .into_iter()
         .map(|c| (c, vec![card]))
         .collect();
@@ -1024,8 +2846,12 @@ fn find_tractors_from_start(
                         count: min_count,
                     });
                 }
-                next_next_cards
-                    .extend(next_card.successor().into_iter().map(|n| (n, path.clone())));
+                next_next_cards.extend(
+                    next_card
+                        .successor(tractor_requirements.adjacency_policy)
+                        .into_iter()
+                        .map(|n| (n, path.clone())),
+                );
             }
         }
         next_cards = next_next_cards;
@@ -1100,20 +2926,31 @@ fn find_plays_inner(
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use std::collections::{BTreeMap, HashSet};
     use std::iter::FromIterator;
 
-    use crate::hands::Hands;
-    use crate::types::{cards::*, Card, EffectiveSuit, Number, PlayerID, Suit, Trump};
+    use crate::card_set::CardSet;
+    use crate::hands::{Hands, MissingCard};
+    use crate::multiset_iter::multiset_k_combination_iter;
+    use crate::scoring::PointSchedule;
+    use crate::types::{
+        cards::*, AdjacencyPolicy, Card, EffectiveSuit, Number, PermanentTrump, PlayerID, Suit,
+        Trump,
+    };
 
     use super::{
-        OrderedCard, PlayCards, ThrowEvaluationPolicy, TractorRequirements, Trick, TrickDrawPolicy,
-        TrickEnded, TrickError, TrickFormat, TrickUnit, UnitLike,
+        to_cards, wild_card_readings, BeatPolicy, DecompositionLimits, DecompositionStrategy,
+        FollowFailureReason, JokerPairPolicy, LegalPlays, MatchingContext, MultiSuitLeadPolicy,
+        OrderedCard, PlayCards, PlayError, PlayedUnits, RuffPolicy, ThrowEvaluationPolicy,
+        ThrowEvaluationScope, TieBreakPolicy, TractorRequirements, TractorRuffPolicy, Trick,
+        TrickDrawPolicy, TrickEnded, TrickError, TrickFormat, TrickRules, TrickUnit,
+        TrumpedThrowPolicy, UnitLike,
     };
 
     const TRUMP: Trump = Trump::Standard {
         number: Number::Four,
         suit: Suit::Spades,
+        permanent: PermanentTrump::None,
     };
     const P1: PlayerID = PlayerID(1);
     const P2: PlayerID = PlayerID(2);
@@ -1135,53 +2972,72 @@ mod tests {
         };
     }
 
+    const RULES: TrickRules = TrickRules {
+        trick_draw_policy: TrickDrawPolicy::NoProtections,
+        throw_eval_policy: ThrowEvaluationPolicy::All,
+        throw_eval_scope: ThrowEvaluationScope::AllHands,
+        ruff_policy: RuffPolicy::MayDiscard,
+        beat_policy: BeatPolicy::NoObligation,
+        tie_break_policy: TieBreakPolicy::FirstPlayed,
+        tractor_ruff_policy: TractorRuffPolicy::SameShape,
+        joker_pair_policy: JokerPairPolicy::Throw,
+        decomposition_strategy: DecompositionStrategy::MostOptionsFirst,
+        multi_suit_lead_policy: MultiSuitLeadPolicy::SingleSuitOnly,
+        trumped_throw_policy: TrumpedThrowPolicy::Stands,
+        defer_throw_check: false,
+        hide_throw_halting_player: false,
+        tractor_requirements: TractorRequirements {
+            min_count: 2,
+            min_length: 2,
+            adjacency_policy: AdjacencyPolicy {
+                off_suit_trump_number_connects: true,
+                skip_trump_number: true,
+                jokers_connect: true,
+            },
+            wild_card: None,
+        },
+    };
+
     macro_rules! pc {
         ($id:expr, $hands:expr, $cards:expr, $tdp:expr, $tep:expr, $fmt:expr, $h:expr) => {
             PlayCards {
                 id: $id,
                 hands: $hands,
                 cards: $cards,
-                trick_draw_policy: $tdp,
-                throw_eval_policy: $tep,
+                rules: &TrickRules {
+                    trick_draw_policy: $tdp,
+                    throw_eval_policy: $tep,
+                    hide_throw_halting_player: $h,
+                    ..RULES
+                },
+                teammates: &[],
                 format_hint: $fmt,
-                hide_throw_halting_player: $h,
-                tractor_requirements: TractorRequirements::default(),
             }
         };
         ($id:expr, $hands:expr, $cards:expr, $tdp:expr, $tep:expr) => {
-            PlayCards {
-                id: $id,
-                hands: $hands,
-                cards: $cards,
+            pc!($id, $hands, $cards; &TrickRules {
                 trick_draw_policy: $tdp,
                 throw_eval_policy: $tep,
-                format_hint: None,
-                hide_throw_halting_player: false,
-                tractor_requirements: TractorRequirements::default(),
-            }
+                ..RULES
+            })
         };
         ($id:expr, $hands:expr, $cards:expr, $tep:expr) => {
-            PlayCards {
-                id: $id,
-                hands: $hands,
-                cards: $cards,
-                trick_draw_policy: TrickDrawPolicy::NoProtections,
+            pc!($id, $hands, $cards; &TrickRules {
                 throw_eval_policy: $tep,
-                format_hint: None,
-                hide_throw_halting_player: false,
-                tractor_requirements: TractorRequirements::default(),
-            }
+                ..RULES
+            })
         };
         ($id:expr, $hands:expr, $cards:expr) => {
+            pc!($id, $hands, $cards; &RULES)
+        };
+        ($id:expr, $hands:expr, $cards:expr; $rules:expr) => {
             PlayCards {
                 id: $id,
                 hands: $hands,
                 cards: $cards,
-                trick_draw_policy: TrickDrawPolicy::NoProtections,
-                throw_eval_policy: ThrowEvaluationPolicy::All,
+                rules: $rules,
+                teammates: &[],
                 format_hint: None,
-                hide_throw_halting_player: false,
-                tractor_requirements: TractorRequirements::default(),
             }
         };
     }
@@ -1200,7 +3056,7 @@ mod tests {
                     HashSet::from_iter(vec![$(vec![$(vec![$($y),+]),+]),+])
                 );
                 for u in units {
-                    let mut iter = UnitLike::check_play(OrderedCard::make_map(cards.iter().copied(), TRUMP), u.iter().map(UnitLike::from), TrickDrawPolicy::NoProtections);
+                    let mut iter = UnitLike::check_play(OrderedCard::make_map(cards.iter().copied(), TRUMP), u.iter().map(UnitLike::from), TrickDrawPolicy::NoProtections, AdjacencyPolicy::default());
                     let play = iter.next().unwrap();
                     assert_eq!(
                         u.iter().map(UnitLike::from).collect::<HashSet<_>>(),
@@ -1216,22 +3072,34 @@ mod tests {
         test_eq!(H_2, H_2, H_3, H_3; [[H_3, H_3], [H_2, H_2]]; TractorRequirements {
             min_length: 3,
             min_count: 2,
+            ..Default::default()
         });
         test_eq!(H_2, H_2, H_3, H_3, H_5, H_5; [[H_2, H_2, H_3, H_3, H_5, H_5]]; TractorRequirements {
             min_length: 3,
             min_count: 2,
+            ..Default::default()
         });
         test_eq!(H_2, H_2, H_3, H_3; [[H_3, H_3], [H_2, H_2]]; TractorRequirements {
             min_length: 3,
             min_count: 3,
+            ..Default::default()
         });
         test_eq!(H_2, H_2, H_2, H_3, H_3, H_3; [[H_2, H_2, H_2, H_3, H_3, H_3]]; TractorRequirements {
             min_length: 2,
             min_count: 3,
+            ..Default::default()
         });
         test_eq!(H_2, H_2, H_2, H_3, H_3; [[H_2], [H_2, H_2, H_3, H_3]], [[H_3, H_3], [H_2, H_2, H_2]]; TractorRequirements::default());
         test_eq!(H_2, H_2, H_3, H_3, H_3; [[H_3], [H_2, H_2, H_3, H_3]], [[H_3, H_3, H_3], [H_2, H_2]]; TractorRequirements::default());
         test_eq!(H_4, H_4, S_4, S_4; [[H_4, H_4, S_4, S_4]]; TractorRequirements::default());
+        test_eq!(H_4, H_4, S_4, S_4; [[S_4, S_4], [H_4, H_4]]; TractorRequirements {
+            adjacency_policy: AdjacencyPolicy {
+                off_suit_trump_number_connects: false,
+                skip_trump_number: true,
+                jokers_connect: true,
+            },
+            ..Default::default()
+        });
         test_eq!(H_4, H_4, S_A, S_A; [[S_A, S_A, H_4, H_4]]; TractorRequirements::default());
         test_eq!(S_Q, S_Q, S_K, S_K, S_A; [[S_A], [S_Q, S_Q, S_K, S_K]]; TractorRequirements::default());
 
@@ -1376,104 +3244,870 @@ mod tests {
         run(ThrowEvaluationPolicy::TrickUnitLength);
     }
 
-    #[test]
-    fn test_play_throw_trick() {
-        let mut hands = Hands::new(vec![P1, P2, P3, P4]);
-        hands.add(P1, vec![H_8, H_8, H_7, H_2]).unwrap();
-        hands.add(P2, vec![H_2, S_2, S_2, S_2]).unwrap();
-        hands.add(P3, vec![S_2, S_2, S_3, S_4]).unwrap();
-        hands.add(P4, vec![S_4, S_4, S_4, S_4]).unwrap();
-        let mut trick = Trick::new(TRUMP, vec![P1, P2, P3, P4]);
+    #[test]
+    fn test_play_errors() {
+        let mut hands = Hands::new(vec![P1, P2]);
+        hands.add(P1, vec![H_7, H_7, S_2]).unwrap();
+        hands.add(P2, vec![H_3, H_5, H_5, C_3]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2]);
+        let tdp = TrickDrawPolicy::NoProtections;
+        let tep = ThrowEvaluationPolicy::All;
+        let mut play = |id, cards: &[Card]| {
+            trick
+                .play_cards(pc!(id, &mut hands, cards, tdp, tep))
+                .map(|_| ())
+                .map_err(|e| e.play_error().cloned().unwrap())
+        };
+
+        assert_eq!(
+            play(P2, &[H_3]).unwrap_err(),
+            PlayError::OutOfTurn {
+                next_player: Some(P1)
+            }
+        );
+        assert_eq!(play(P1, &[]).unwrap_err(), PlayError::NoCards);
+        assert_eq!(
+            play(P1, &[H_7, H_8]).unwrap_err(),
+            PlayError::CardsNotInHand {
+                missing: vec![MissingCard {
+                    card: H_8,
+                    required: 1,
+                    available: 0,
+                }]
+            }
+        );
+        assert_eq!(
+            play(P1, &[H_7, S_2]).unwrap_err(),
+            PlayError::MultipleSuits {
+                suits: vec![EffectiveSuit::Hearts, EffectiveSuit::Trump]
+            }
+        );
+        play(P1, &[H_7, H_7]).unwrap();
+
+        assert_eq!(
+            play(P2, &[H_5]).unwrap_err(),
+            PlayError::WrongNumberOfCards {
+                required: 2,
+                played: 1
+            }
+        );
+        assert_eq!(
+            play(P2, &[H_3, C_3]).unwrap_err(),
+            PlayError::MustFollowSuit {
+                suit: EffectiveSuit::Hearts,
+                played_in_suit: 1,
+                held_in_suit: 3,
+            }
+        );
+        assert_eq!(
+            play(P2, &[H_3, H_5]).unwrap_err(),
+            PlayError::MustMatchFormat {
+                required: vec![vec![2]],
+                playable: vec![H_5, H_5],
+            }
+        );
+        play(P2, &[H_5, H_5]).unwrap();
+    }
+
+    #[test]
+    fn test_play_throw_trick() {
+        let mut hands = Hands::new(vec![P1, P2, P3, P4]);
+        hands.add(P1, vec![H_8, H_8, H_7, H_2]).unwrap();
+        hands.add(P2, vec![H_2, S_2, S_2, S_2]).unwrap();
+        hands.add(P3, vec![S_2, S_2, S_3, S_4]).unwrap();
+        hands.add(P4, vec![S_4, S_4, S_4, S_4]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2, P3, P4]);
+        trick
+            .play_cards(pc!(P1, &mut hands, &[H_8, H_8, H_7, H_2]))
+            .unwrap();
+        trick
+            .play_cards(pc!(P2, &mut hands, &[H_2, S_2, S_2, S_2]))
+            .unwrap();
+        trick
+            .play_cards(pc!(P3, &mut hands, &[S_2, S_2, S_3, S_4]))
+            .unwrap();
+        trick
+            .play_cards(pc!(P4, &mut hands, &[S_4, S_4, S_4, S_4]))
+            .unwrap();
+        let TrickEnded {
+            winner: winner_id,
+            points,
+            largest_trick_unit_size,
+            ..
+        } = trick.complete().unwrap();
+        assert_eq!(largest_trick_unit_size, 2);
+        assert_eq!(winner_id, P3);
+        assert_eq!(points, vec![]);
+    }
+
+    #[test]
+    fn test_play_throw_trick_double_overflip() {
+        let p1_cards = vec![C_A, C_A, C_Q, C_Q, C_10, C_10];
+        let p2_cards = vec![S_8, S_8, H_9, H_9, H_3, H_3];
+        let p3_cards = vec![H_8, H_8, H_K, H_K, H_10, H_10];
+        let p4_cards = vec![Card::SmallJoker, Card::SmallJoker, H_8, H_8, H_K, H_K];
+        for tep in [
+            ThrowEvaluationPolicy::All,
+            ThrowEvaluationPolicy::Highest,
+            ThrowEvaluationPolicy::TrickUnitLength,
+        ] {
+            let mut hands = Hands::new(vec![P1, P2, P3, P4]);
+            hands.add(P1, p1_cards.clone()).unwrap();
+            hands.add(P2, p2_cards.clone()).unwrap();
+            hands.add(P3, p3_cards.clone()).unwrap();
+            hands.add(P4, p4_cards.clone()).unwrap();
+            let mut trick = Trick::new(
+                Trump::Standard {
+                    suit: Suit::Hearts,
+                    number: Number::Eight,
+                    permanent: PermanentTrump::None,
+                },
+                vec![P1, P2, P3, P4],
+            );
+            trick
+                .play_cards(pc!(P1, &mut hands, &p1_cards, tep))
+                .unwrap();
+            trick
+                .play_cards(pc!(P2, &mut hands, &p2_cards, tep))
+                .unwrap();
+            trick
+                .play_cards(pc!(P3, &mut hands, &p3_cards, tep))
+                .unwrap();
+            trick
+                .play_cards(pc!(P4, &mut hands, &p4_cards, tep))
+                .unwrap();
+            let TrickEnded {
+                winner: winner_id, ..
+            } = trick.complete().unwrap();
+            assert_eq!(winner_id, P4, "{tep:?}");
+        }
+    }
+
+    #[test]
+    fn test_play_throw_trick_failure() {
+        let mut hands = Hands::new(vec![P1, P2, P3, P4]);
+        hands.add(P1, vec![H_8, H_8, H_7, H_2]).unwrap();
+        hands.add(P2, vec![H_2, S_2, S_2, S_2]).unwrap();
+        hands.add(P3, vec![S_2, S_2, S_3, S_4]).unwrap();
+        hands.add(P4, vec![S_4, S_4, S_4, H_3]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2, P3, P4]);
+        trick
+            .play_cards(pc!(P1, &mut hands, &[H_8, H_8, H_7, H_2]))
+            .unwrap();
+        trick.play_cards(pc!(P2, &mut hands, &[H_2])).unwrap();
+        trick.play_cards(pc!(P3, &mut hands, &[S_3])).unwrap();
+        trick.play_cards(pc!(P4, &mut hands, &[H_3])).unwrap();
+        let TrickEnded {
+            winner: winner_id,
+            points,
+            largest_trick_unit_size,
+            failed_throw_size,
+            ..
+        } = trick.complete().unwrap();
+        assert_eq!(largest_trick_unit_size, 1);
+        assert_eq!(winner_id, P3);
+        assert_eq!(points, vec![]);
+        assert_eq!(failed_throw_size, 3);
+    }
+
+    #[test]
+    fn test_throw_evaluation_scope() {
+        let throw = |scope, teammates: &[PlayerID], p2, p3| {
+            let mut hands = Hands::new(vec![P1, P2, P3, P4]);
+            hands.add(P1, vec![H_Q, H_Q, H_A]).unwrap();
+            hands.add(P2, p2).unwrap();
+            hands.add(P3, p3).unwrap();
+            hands.add(P4, vec![H_5, H_6, H_7]).unwrap();
+            let mut trick = Trick::new(TRUMP, vec![P1, P2, P3, P4]);
+            trick
+                .play_cards(PlayCards {
+                    teammates,
+                    ..pc!(P1, &mut hands, &[H_Q, H_Q, H_A]; &TrickRules {
+                        throw_eval_scope: scope,
+                        ..RULES
+                    })
+                })
+                .unwrap();
+            trick.played_cards()[0].clone()
+        };
+
+        // Neither P2 nor P3 can beat the pair on their own, but together they
+        // hold a higher pair.
+        let split = || (vec![H_K, H_3, H_5], vec![H_K, H_3, H_6]);
+        let (p2, p3) = split();
+        let played = throw(ThrowEvaluationScope::AllHands, &[], p2, p3);
+        assert!(played.bad_throw_cards.is_empty());
+        let (p2, p3) = split();
+        let played = throw(ThrowEvaluationScope::UnseenCards, &[], p2, p3);
+        assert_eq!(played.cards, vec![H_Q, H_Q]);
+        assert_eq!(played.bad_throw_cards, vec![H_A]);
+        assert_eq!(played.better_player, None);
+
+        // Only P3 can beat the pair, and they're on the thrower's team.
+        let partner = || (vec![H_3, H_5, H_6], vec![H_K, H_K, H_3]);
+        let (p2, p3) = partner();
+        let played = throw(ThrowEvaluationScope::AllHands, &[P3], p2, p3);
+        assert_eq!(played.better_player, Some(P3));
+        let (p2, p3) = partner();
+        let played = throw(ThrowEvaluationScope::OpponentsOnly, &[P3], p2, p3);
+        assert!(played.bad_throw_cards.is_empty());
+    }
+
+    #[test]
+    fn test_must_beat() {
+        const MUST_BEAT: TrickRules = TrickRules {
+            beat_policy: BeatPolicy::MustBeatIfAble,
+            ..RULES
+        };
+        fn play<'a, 'b>(
+            id: PlayerID,
+            hands: &'a mut Hands,
+            cards: &'b [Card],
+        ) -> PlayCards<'a, 'b, 'static, 'static> {
+            pc!(id, hands, cards; &MUST_BEAT)
+        }
+        let mut hands = Hands::new(vec![P1, P2, P3, P4]);
+        hands.add(P1, vec![H_7]).unwrap();
+        hands.add(P2, vec![H_9]).unwrap();
+        hands.add(P3, vec![H_8, H_10]).unwrap();
+        hands.add(P4, vec![S_5, C_3]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2, P3, P4]);
+        trick.play_cards(play(P1, &mut hands, &[H_7])).unwrap();
+        trick.play_cards(play(P2, &mut hands, &[H_9])).unwrap();
+
+        assert_eq!(
+            trick
+                .beating_plays(
+                    P3,
+                    &hands,
+                    TrickDrawPolicy::NoProtections,
+                    ThrowEvaluationPolicy::All
+                )
+                .unwrap(),
+            vec![vec![H_10]]
+        );
+        assert!(matches!(
+            trick.play_cards(play(P3, &mut hands, &[H_8])),
+            Err(TrickError::InvalidPlay(PlayError::MustBeat { playable })) if playable == vec![H_10]
+        ));
+        // Without the rule, any legal play is fine.
+        trick
+            .can_play_cards(P3, &hands, &[H_8], TrickDrawPolicy::NoProtections)
+            .unwrap();
+        trick.play_cards(play(P3, &mut hands, &[H_10])).unwrap();
+
+        // A player who is out of the suit has to trump it.
+        assert!(matches!(
+            trick.play_cards(play(P4, &mut hands, &[C_3])),
+            Err(TrickError::InvalidPlay(PlayError::MustBeat { .. }))
+        ));
+        trick.play_cards(play(P4, &mut hands, &[S_5])).unwrap();
+        assert_eq!(trick.complete().unwrap().winner, P4);
+
+        // A wild card can complete the ruff which beats the trick.
+        fn wild<'a, 'b>(
+            id: PlayerID,
+            hands: &'a mut Hands,
+            cards: &'b [Card],
+        ) -> PlayCards<'a, 'b, 'static, 'static> {
+            const WILD: TrickRules = TrickRules {
+                tractor_requirements: TractorRequirements {
+                    wild_card: Some(C_3),
+                    ..RULES.tractor_requirements
+                },
+                ..MUST_BEAT
+            };
+            pc!(id, hands, cards; &WILD)
+        }
+        let mut hands = Hands::new(vec![P1, P2]);
+        hands.add(P1, vec![H_7, H_7]).unwrap();
+        hands.add(P2, vec![S_5, C_3, D_9]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2]);
+        trick.play_cards(wild(P1, &mut hands, &[H_7, H_7])).unwrap();
+        assert!(matches!(
+            trick.play_cards(wild(P2, &mut hands, &[C_3, D_9])),
+            Err(TrickError::InvalidPlay(PlayError::MustBeat { .. }))
+        ));
+        trick.play_cards(wild(P2, &mut hands, &[S_5, C_3])).unwrap();
+        assert_eq!(trick.complete().unwrap().winner, P2);
+
+        // If there are too many ways to follow to check them all, a play which
+        // doesn't win can't be accepted, rather than waiving the rule.
+        let mut hands = Hands::new(vec![P1, P2]);
+        let lead = [H_2, H_3, H_5, H_6, H_7, H_8, H_9, H_10];
+        hands.add(P1, lead.to_vec()).unwrap();
+        hands
+            .add(
+                P2,
+                [H_2, H_3, H_5, H_6, H_7, H_8, H_9, H_10, H_J, H_Q, H_K, H_A]
+                    .iter()
+                    .flat_map(|c| vec![*c; 3])
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2]);
+        trick
+            .play_cards(pc!(P1, &mut hands, &lead; &TrickRules {
+                defer_throw_check: true,
+                ..MUST_BEAT
+            }))
+            .unwrap();
+        assert!(matches!(
+            trick.beating_plays(
+                P2,
+                &hands,
+                TrickDrawPolicy::NoFormatBasedDraw,
+                ThrowEvaluationPolicy::All
+            ),
+            Err(TrickError::DecompositionTooLarge { .. })
+        ));
+        assert!(matches!(
+            trick.play_cards(pc!(
+                P2,
+                &mut hands,
+                &[H_2, H_2, H_2, H_3, H_3, H_3, H_5, H_5];
+                &TrickRules {
+                    trick_draw_policy: TrickDrawPolicy::NoFormatBasedDraw,
+                    ..MUST_BEAT
+                }
+            )),
+            Err(TrickError::DecompositionTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tie_break_policy() {
+        let winner = |tie_break_policy, plays: [Card; 3]| {
+            let mut hands = Hands::new(vec![P1, P2, P3]);
+            for (id, card) in [P1, P2, P3].into_iter().zip(plays) {
+                hands.add(id, vec![card]).unwrap();
+            }
+            let mut trick = Trick::new(TRUMP, vec![P1, P2, P3]);
+            for (id, card) in [P1, P2, P3].into_iter().zip(plays) {
+                trick
+                    .play_cards(pc!(id, &mut hands, &[card]; &TrickRules {
+                        tie_break_policy,
+                        ..RULES
+                    }))
+                    .unwrap();
+            }
+            trick.complete().unwrap().winner
+        };
+
+        assert_eq!(winner(TieBreakPolicy::FirstPlayed, [H_7, H_A, H_A]), P2);
+        assert_eq!(winner(TieBreakPolicy::LastPlayed, [H_7, H_A, H_A]), P3);
+        // Off-suit trump-rank cards are equal, too.
+        assert_eq!(winner(TieBreakPolicy::FirstPlayed, [H_7, H_4, D_4]), P2);
+        assert_eq!(winner(TieBreakPolicy::LastPlayed, [H_7, H_4, D_4]), P3);
+        // The leader can be tied as well, but a lower card never wins.
+        assert_eq!(winner(TieBreakPolicy::LastPlayed, [H_A, H_A, H_K]), P2);
+    }
+
+    #[test]
+    fn test_decomposition_strategy() {
+        let can_follow = |decomposition_strategy, follow: &[Card]| {
+            let mut hands = Hands::new(vec![P1, P2]);
+            hands
+                .add(P1, vec![H_5, H_5, H_6, H_6, H_Q, H_Q, H_Q])
+                .unwrap();
+            hands
+                .add(P2, vec![H_9, H_9, H_9, H_J, H_J, H_K, H_K, H_A])
+                .unwrap();
+            let mut trick = Trick::new(TRUMP, vec![P1, P2]);
+            trick
+                .play_cards(
+                    pc!(P1, &mut hands, &[H_5, H_5, H_6, H_6, H_Q, H_Q, H_Q]; &TrickRules {
+                        decomposition_strategy,
+                        ..RULES
+                    }),
+                )
+                .unwrap();
+            trick
+                .can_play_cards(P2, &hands, follow, TrickDrawPolicy::NoProtections)
+                .is_ok()
+        };
+
+        // Giving up the tractor first keeps the triple, so it has to be
+        // played...
+        let follow = [H_9, H_9, H_J, H_J, H_K, H_K, H_A];
+        assert!(can_follow(
+            DecompositionStrategy::MostOptionsFirst,
+            &[H_9, H_9, H_9, H_J, H_J, H_K, H_K]
+        ));
+        assert!(!can_follow(
+            DecompositionStrategy::MostOptionsFirst,
+            &follow
+        ));
+        // ...but giving up the triple first leaves only the pairs.
+        assert!(can_follow(DecompositionStrategy::PreserveTractors, &follow));
+    }
+
+    #[test]
+    fn test_multi_suit_lead() {
+        fn multi<'a, 'b>(
+            id: PlayerID,
+            hands: &'a mut Hands,
+            cards: &'b [Card],
+        ) -> PlayCards<'a, 'b, 'static, 'static> {
+            const MULTI: TrickRules = TrickRules {
+                multi_suit_lead_policy: MultiSuitLeadPolicy::AllowWhenVoidElsewhere,
+                ..RULES
+            };
+            pc!(id, hands, cards; &MULTI)
+        }
+
+        let mut hands = Hands::new(vec![P1, P2, P3]);
+        hands.add(P1, vec![H_A, C_A, S_K]).unwrap();
+        hands.add(P2, vec![H_3, H_5, C_3, D_9]).unwrap();
+        hands.add(P3, vec![S_5, S_6, D_5]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2, P3]);
+        assert!(matches!(
+            trick.play_cards(pc!(P1, &mut hands, &[H_A, C_A])),
+            Err(TrickError::InvalidPlay(PlayError::MultipleSuits { .. }))
+        ));
+        trick
+            .play_cards(multi(P1, &mut hands, &[H_A, C_A]))
+            .unwrap();
+        assert_eq!(trick.trick_format().unwrap().size(), 1);
+        assert_eq!(trick.side_formats().len(), 1);
+
+        // Each suit has to be followed separately.
+        assert_eq!(
+            trick
+                .can_play_cards(P2, &hands, &[H_3, D_9], TrickDrawPolicy::NoProtections)
+                .unwrap_err()
+                .play_error(),
+            Some(&PlayError::MustFollowSuit {
+                suit: EffectiveSuit::Clubs,
+                played_in_suit: 0,
+                held_in_suit: 1,
+            })
+        );
+        // Each suit's legal plays are listed in turn.
+        let legal = trick
+            .legal_plays(P2, &hands, TrickDrawPolicy::NoProtections)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            legal
+                .iter()
+                .map(|group| group.plays.clone())
+                .collect::<Vec<_>>(),
+            vec![vec![vec![H_3], vec![H_5]], vec![vec![C_3]]]
+        );
+        trick.play_cards(pc!(P2, &mut hands, &[H_3, C_3])).unwrap();
+
+        assert_eq!(trick.current_winner(), Some(P1));
+
+        // Trumping every suit beats the lead.
+        assert_eq!(
+            trick
+                .beating_plays(
+                    P3,
+                    &hands,
+                    TrickDrawPolicy::NoProtections,
+                    ThrowEvaluationPolicy::All
+                )
+                .unwrap(),
+            vec![vec![S_5, S_6]]
+        );
+        assert!(trick.would_win(P3, &[S_5, S_6], ThrowEvaluationPolicy::All));
+        assert!(!trick.would_win(P3, &[S_5, D_5], ThrowEvaluationPolicy::All));
+        trick.play_cards(pc!(P3, &mut hands, &[S_5, S_6])).unwrap();
+        assert_eq!(trick.complete().unwrap().winner, P3);
+
+        // Players who still hold another suit can't throw across suits.
+        let trick = Trick::new(TRUMP, vec![P1, P2, P3]);
+        assert!(
+            !trick.can_lead_multiple_suits(&CardSet::from_cards(vec![H_5, D_9, C_3]), &[H_5, D_9])
+        );
+        assert!(
+            trick.can_lead_multiple_suits(&CardSet::from_cards(vec![H_5, D_9, S_K]), &[H_5, D_9])
+        );
+
+        // A failed throw is reduced to a single unit in one suit.
+        let mut hands = Hands::new(vec![P1, P2]);
+        hands.add(P1, vec![H_Q, C_A]).unwrap();
+        hands.add(P2, vec![H_K, C_3]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2]);
+        trick
+            .play_cards(multi(P1, &mut hands, &[H_Q, C_A]))
+            .unwrap();
+        assert_eq!(trick.played_cards()[0].cards, vec![H_Q]);
+        assert_eq!(trick.played_cards()[0].bad_throw_cards, vec![C_A]);
+        assert!(trick.side_formats().is_empty());
+    }
+
+    #[test]
+    fn test_trumped_throw_policy() {
+        let lead = |trumped_throw_policy, p2: Vec<Card>| {
+            let mut hands = Hands::new(vec![P1, P2]);
+            hands.add(P1, vec![H_A, H_K]).unwrap();
+            hands.add(P2, p2).unwrap();
+            let mut trick = Trick::new(TRUMP, vec![P1, P2]);
+            trick
+                .play_cards(pc!(P1, &mut hands, &[H_A, H_K]; &TrickRules {
+                    trumped_throw_policy,
+                    ..RULES
+                }))
+                .unwrap();
+            trick.played_cards()[0].clone()
+        };
+
+        let played = lead(TrumpedThrowPolicy::Stands, vec![S_5, S_6]);
+        assert_eq!(played.cards.len(), 2);
+
+        let played = lead(TrumpedThrowPolicy::Fails, vec![S_5, S_6]);
+        assert_eq!(played.cards.len(), 1);
+        assert_eq!(played.bad_throw_cards.len(), 1);
+        assert_eq!(played.better_player, Some(P2));
+
+        // Players who can follow suit, or who can't trump all of the throw,
+        // don't count.
+        assert_eq!(
+            lead(TrumpedThrowPolicy::Fails, vec![S_5, H_3]).cards.len(),
+            2
+        );
+        assert_eq!(
+            lead(TrumpedThrowPolicy::Fails, vec![S_5, C_3]).cards.len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_tractor_ruff_policy() {
+        let winner = |tractor_ruff_policy, plays: [&[Card]; 3]| {
+            let mut hands = Hands::new(vec![P1, P2, P3]);
+            for (id, cards) in [P1, P2, P3].into_iter().zip(plays) {
+                hands.add(id, cards.to_vec()).unwrap();
+            }
+            let mut trick = Trick::new(TRUMP, vec![P1, P2, P3]);
+            for (id, cards) in [P1, P2, P3].into_iter().zip(plays) {
+                trick
+                    .play_cards(pc!(id, &mut hands, cards; &TrickRules {
+                        tractor_ruff_policy,
+                        ..RULES
+                    }))
+                    .unwrap();
+            }
+            trick.complete().unwrap().winner
+        };
+        let tractor: &[Card] = &[H_7, H_7, H_8, H_8];
+        let low: &[Card] = &[S_5, S_6, S_7, S_8];
+        let high: &[Card] = &[S_9, S_10, S_J, S_K];
+        let shaped: &[Card] = &[S_5, S_5, S_6, S_6];
+        let hearts: &[Card] = &[H_2, H_3, H_5, H_6];
+
+        assert_eq!(
+            winner(TractorRuffPolicy::SameShape, [tractor, low, hearts]),
+            P1
+        );
+        assert_eq!(
+            winner(TractorRuffPolicy::AnyTrump, [tractor, low, hearts]),
+            P2
+        );
+        assert_eq!(
+            winner(TractorRuffPolicy::AnyTrump, [tractor, low, high]),
+            P3
+        );
+        assert_eq!(
+            winner(TractorRuffPolicy::AnyTrump, [tractor, high, low]),
+            P2
+        );
+        // A ruff of the same shape wins, even with lower cards.
+        assert_eq!(
+            winner(TractorRuffPolicy::AnyTrump, [tractor, high, shaped]),
+            P3
+        );
+        assert_eq!(
+            winner(TractorRuffPolicy::AnyTrump, [tractor, shaped, high]),
+            P2
+        );
+        // Leads without a tractor still need to be matched.
+        assert_eq!(
+            winner(
+                TractorRuffPolicy::AnyTrump,
+                [&[H_7, H_7], &[S_5, S_6], &[H_2, H_3]]
+            ),
+            P1
+        );
+    }
+
+    #[test]
+    fn test_challenge_throw() {
+        let rules = TrickRules {
+            defer_throw_check: true,
+            ..RULES
+        };
+        let lead = |p2: Vec<Card>| {
+            let mut hands = Hands::new(vec![P1, P2]);
+            hands.add(P1, vec![H_A, H_Q]).unwrap();
+            hands.add(P2, p2).unwrap();
+            let mut trick = Trick::new(TRUMP, vec![P1, P2]);
+            trick
+                .play_cards(pc!(P1, &mut hands, &[H_A, H_Q]; &rules))
+                .unwrap();
+            assert_eq!(trick.played_cards()[0].cards.len(), 2);
+            (trick, hands)
+        };
+
+        let (mut trick, mut hands) = lead(vec![H_K]);
+        let msgs = trick.challenge_throw(&mut hands, &rules, &[]).unwrap();
+        assert_eq!(msgs.len(), 2);
+        assert_eq!(trick.played_cards()[0].cards, vec![H_Q]);
+        assert_eq!(trick.played_cards()[0].bad_throw_cards, vec![H_A]);
+        assert_eq!(trick.played_cards()[0].better_player, Some(P2));
+        assert_eq!(trick.trick_format().unwrap().size(), 1);
+        assert!(hands.contains(P1, vec![H_A]).is_ok());
+        trick.play_cards(pc!(P2, &mut hands, &[H_K])).unwrap();
+        assert_eq!(trick.complete().unwrap().winner, P2);
+
+        let (mut trick, mut hands) = lead(vec![H_3]);
+        let msgs = trick.challenge_throw(&mut hands, &rules, &[]).unwrap();
+        assert!(msgs.is_empty());
+        assert_eq!(trick.played_cards()[0].cards.len(), 2);
+
+        // Only the lead can be challenged.
+        let (mut trick, mut hands) = lead(vec![H_3, H_5]);
+        trick.play_cards(pc!(P2, &mut hands, &[H_3, H_5])).unwrap();
+        assert!(trick.challenge_throw(&mut hands, &rules, &[]).is_err());
+    }
+
+    #[test]
+    fn test_joker_pair_policy() {
+        let lead = |joker_pair_policy, p2: Vec<Card>| {
+            let mut hands = Hands::new(vec![P1, P2]);
+            hands
+                .add(P1, vec![Card::SmallJoker, Card::BigJoker])
+                .unwrap();
+            hands.add(P2, p2).unwrap();
+            let mut trick = Trick::new(TRUMP, vec![P1, P2]);
+            trick
+                .play_cards(
+                    pc!(P1, &mut hands, &[Card::SmallJoker, Card::BigJoker]; &TrickRules {
+                        joker_pair_policy,
+                        ..RULES
+                    }),
+                )
+                .unwrap();
+            (trick, hands)
+        };
+
+        // As a throw, any two trump cards follow it.
+        let (trick, hands) = lead(JokerPairPolicy::Throw, vec![S_5, S_5, S_6]);
+        assert_eq!(trick.trick_format().unwrap().units.len(), 2);
+        trick
+            .can_play_cards(P2, &hands, &[S_5, S_6], TrickDrawPolicy::NoProtections)
+            .unwrap();
+
+        // As a pair, it has to be followed with a pair.
+        let (trick, hands) = lead(JokerPairPolicy::Pair, vec![S_5, S_5, S_6]);
+        assert!(matches!(
+            trick
+                .can_play_cards(P2, &hands, &[S_5, S_6], TrickDrawPolicy::NoProtections)
+                .unwrap_err()
+                .play_error(),
+            Some(PlayError::MustMatchFormat { .. })
+        ));
         trick
-            .play_cards(pc!(P1, &mut hands, &[H_8, H_8, H_7, H_2]))
+            .can_play_cards(P2, &hands, &[S_5, S_5], TrickDrawPolicy::NoProtections)
             .unwrap();
+
+        // A pair of big jokers beats it, unless it's unbeatable.
+        let (mut trick, mut hands) =
+            lead(JokerPairPolicy::Pair, vec![Card::BigJoker, Card::BigJoker]);
         trick
-            .play_cards(pc!(P2, &mut hands, &[H_2, S_2, S_2, S_2]))
+            .play_cards(pc!(P2, &mut hands, &[Card::BigJoker, Card::BigJoker]))
             .unwrap();
+        assert_eq!(trick.complete().unwrap().winner, P2);
+
+        let (mut trick, mut hands) = lead(
+            JokerPairPolicy::Unbeatable,
+            vec![Card::BigJoker, Card::BigJoker],
+        );
         trick
-            .play_cards(pc!(P3, &mut hands, &[S_2, S_2, S_3, S_4]))
+            .play_cards(pc!(P2, &mut hands, &[Card::BigJoker, Card::BigJoker]))
             .unwrap();
+        assert_eq!(trick.complete().unwrap().winner, P1);
+    }
+
+    #[test]
+    fn test_preview_play() {
+        let mut hands = Hands::new(vec![P1, P2, P3]);
+        hands.add(P1, vec![H_5, H_Q]).unwrap();
+        hands.add(P2, vec![H_10, H_A]).unwrap();
+        hands.add(P3, vec![H_K, C_3]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2, P3]);
+        trick.play_cards(pc!(P1, &mut hands, &[H_5])).unwrap();
+
+        let before = hands.clone();
+        let preview = trick
+            .preview_play(pc!(P2, &mut hands, &[H_10]), &PointSchedule::default())
+            .unwrap();
+        assert!(preview.takes_lead);
+        assert_eq!(preview.winner, Some(P2));
+        assert_eq!(preview.winning_cards, vec![H_10]);
+        assert_eq!(preview.points, vec![H_5, H_10]);
+        assert_eq!(hands.get(P2).unwrap(), before.get(P2).unwrap());
+        assert_eq!(trick.played_cards().len(), 1);
+        assert_eq!(trick.next_player(), Some(P2));
+
         trick
-            .play_cards(pc!(P4, &mut hands, &[S_4, S_4, S_4, S_4]))
+            .preview_play(pc!(P2, &mut hands, &[C_3]), &PointSchedule::default())
+            .unwrap_err();
+        trick
+            .preview_play(pc!(P3, &mut hands, &[H_K]), &PointSchedule::default())
+            .unwrap_err();
+
+        // A failed throw only shows the forced part of the throw.
+        let mut trick = Trick::new(TRUMP, vec![P2, P3, P1]);
+        let preview = trick
+            .preview_play(pc!(P2, &mut hands, &[H_10, H_A]), &PointSchedule::default())
             .unwrap();
+        assert_eq!(preview.cards, vec![H_10]);
+        assert_eq!(preview.bad_throw_cards, vec![H_A]);
+        assert!(preview.takes_lead);
+        trick.play_cards(pc!(P2, &mut hands, &[H_A])).unwrap();
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut hands = Hands::new(vec![P1, P2, P3]);
+        hands.add(P1, vec![H_5]).unwrap();
+        hands.add(P2, vec![H_K]).unwrap();
+        hands.add(P3, vec![S_3]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2, P3]);
+        assert!(trick
+            .replay(ThrowEvaluationPolicy::All, &PointSchedule::default())
+            .is_empty());
+        trick.play_cards(pc!(P1, &mut hands, &[H_5])).unwrap();
+        trick.play_cards(pc!(P2, &mut hands, &[H_K])).unwrap();
+        trick.play_cards(pc!(P3, &mut hands, &[S_3])).unwrap();
+
+        let steps = trick.replay(ThrowEvaluationPolicy::All, &PointSchedule::default());
+        assert_eq!(
+            steps.iter().map(|s| (s.id, s.winner)).collect::<Vec<_>>(),
+            vec![(P1, Some(P1)), (P2, Some(P2)), (P3, Some(P3))]
+        );
+        assert_eq!(steps[0].points, vec![H_5]);
+        assert_eq!(steps[2].points, vec![H_5, H_K]);
+        assert_eq!(steps[2].winner, Some(trick.complete().unwrap().winner));
+    }
+
+    #[test]
+    fn test_winning_units() {
+        let mut hands = Hands::new(vec![P1, P2, P3]);
+        hands.add(P1, vec![H_5, H_5]).unwrap();
+        hands.add(P2, vec![H_K, H_K]).unwrap();
+        hands.add(P3, vec![H_7, H_8]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2, P3]);
+        trick.play_cards(pc!(P1, &mut hands, &[H_5, H_5])).unwrap();
+        trick.play_cards(pc!(P2, &mut hands, &[H_K, H_K])).unwrap();
+        trick.play_cards(pc!(P3, &mut hands, &[H_7, H_8])).unwrap();
         let TrickEnded {
-            winner: winner_id,
-            points,
-            largest_trick_unit_size,
+            winner,
+            winning_units,
+            runner_up,
             ..
         } = trick.complete().unwrap();
-        assert_eq!(largest_trick_unit_size, 2);
-        assert_eq!(winner_id, P3);
-        assert_eq!(points, vec![]);
+        assert_eq!(winner, P2);
+        assert_eq!(
+            winning_units,
+            vec![TrickUnit::Repeated {
+                count: 2,
+                card: oc!(H_K),
+            }]
+        );
+        assert_eq!(
+            runner_up,
+            Some(PlayedUnits {
+                id: P1,
+                units: vec![TrickUnit::Repeated {
+                    count: 2,
+                    card: oc!(H_5),
+                }],
+            })
+        );
+
+        // Nobody beat the leader.
+        let mut hands = Hands::new(vec![P1, P2]);
+        hands.add(P1, vec![H_K]).unwrap();
+        hands.add(P2, vec![H_5]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2]);
+        trick.play_cards(pc!(P1, &mut hands, &[H_K])).unwrap();
+        trick.play_cards(pc!(P2, &mut hands, &[H_5])).unwrap();
+        let ended = trick.complete().unwrap();
+        assert_eq!(ended.winner, P1);
+        assert_eq!(ended.runner_up, None);
     }
 
     #[test]
-    fn test_play_throw_trick_double_overflip() {
-        let p1_cards = vec![C_A, C_A, C_Q, C_Q, C_10, C_10];
-        let p2_cards = vec![S_8, S_8, H_9, H_9, H_3, H_3];
-        let p3_cards = vec![H_8, H_8, H_K, H_K, H_10, H_10];
-        let p4_cards = vec![Card::SmallJoker, Card::SmallJoker, H_8, H_8, H_K, H_K];
-        for tep in [
-            ThrowEvaluationPolicy::All,
-            ThrowEvaluationPolicy::Highest,
-            ThrowEvaluationPolicy::TrickUnitLength,
-        ] {
-            let mut hands = Hands::new(vec![P1, P2, P3, P4]);
-            hands.add(P1, p1_cards.clone()).unwrap();
-            hands.add(P2, p2_cards.clone()).unwrap();
-            hands.add(P3, p3_cards.clone()).unwrap();
-            hands.add(P4, p4_cards.clone()).unwrap();
-            let mut trick = Trick::new(
-                Trump::Standard {
-                    suit: Suit::Hearts,
-                    number: Number::Eight,
+    fn test_play_wild_card() {
+        fn play<'a, 'b>(
+            id: PlayerID,
+            hands: &'a mut Hands,
+            cards: &'b [Card],
+        ) -> PlayCards<'a, 'b, 'static, 'static> {
+            const WILD: TrickRules = TrickRules {
+                tractor_requirements: TractorRequirements {
+                    wild_card: Some(C_3),
+                    ..RULES.tractor_requirements
                 },
-                vec![P1, P2, P3, P4],
-            );
-            trick
-                .play_cards(pc!(P1, &mut hands, &p1_cards, tep))
-                .unwrap();
-            trick
-                .play_cards(pc!(P2, &mut hands, &p2_cards, tep))
-                .unwrap();
-            trick
-                .play_cards(pc!(P3, &mut hands, &p3_cards, tep))
-                .unwrap();
-            trick
-                .play_cards(pc!(P4, &mut hands, &p4_cards, tep))
-                .unwrap();
-            let TrickEnded {
-                winner: winner_id, ..
-            } = trick.complete().unwrap();
-            assert_eq!(winner_id, P4, "{tep:?}");
+                ..RULES
+            };
+            pc!(id, hands, cards; &WILD)
         }
-    }
 
-    #[test]
-    fn test_play_throw_trick_failure() {
-        let mut hands = Hands::new(vec![P1, P2, P3, P4]);
-        hands.add(P1, vec![H_8, H_8, H_7, H_2]).unwrap();
-        hands.add(P2, vec![H_2, S_2, S_2, S_2]).unwrap();
-        hands.add(P3, vec![S_2, S_2, S_3, S_4]).unwrap();
-        hands.add(P4, vec![S_4, S_4, S_4, H_3]).unwrap();
-        let mut trick = Trick::new(TRUMP, vec![P1, P2, P3, P4]);
+        // The wild card completes a tractor, both when leading and following.
+        let mut hands = Hands::new(vec![P1, P2, P3]);
+        hands.add(P1, vec![H_7, H_7, H_8, C_3]).unwrap();
+        hands.add(P2, vec![H_9, H_9, H_10, H_J]).unwrap();
+        hands.add(P3, vec![H_J, H_J, H_Q, C_3]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2, P3]);
         trick
-            .play_cards(pc!(P1, &mut hands, &[H_8, H_8, H_7, H_2]))
+            .play_cards(play(P1, &mut hands, &[H_7, H_7, H_8, C_3]))
             .unwrap();
-        trick.play_cards(pc!(P2, &mut hands, &[H_2])).unwrap();
-        trick.play_cards(pc!(P3, &mut hands, &[S_3])).unwrap();
-        trick.play_cards(pc!(P4, &mut hands, &[H_3])).unwrap();
-        let TrickEnded {
-            winner: winner_id,
-            points,
-            largest_trick_unit_size,
-            failed_throw_size,
-            ..
-        } = trick.complete().unwrap();
-        assert_eq!(largest_trick_unit_size, 1);
-        assert_eq!(winner_id, P3);
-        assert_eq!(points, vec![]);
-        assert_eq!(failed_throw_size, 3);
+        assert_eq!(
+            trick.trick_format().unwrap().units,
+            vec![TrickUnit::Tractor {
+                count: 2,
+                members: vec![oc!(H_7), oc!(H_8)],
+            }]
+        );
+        trick
+            .play_cards(play(P2, &mut hands, &[H_9, H_9, H_10, H_J]))
+            .unwrap();
+        trick
+            .play_cards(play(P3, &mut hands, &[H_J, H_J, H_Q, C_3]))
+            .unwrap();
+        assert_eq!(trick.complete().unwrap().winner, P3);
+
+        // A wild card can also help another player defeat a throw, and the
+        // wild card is played in place of the card it stood in for.
+        let mut hands = Hands::new(vec![P1, P2]);
+        hands.add(P1, vec![H_Q, C_3, H_A]).unwrap();
+        hands.add(P2, vec![H_K, C_3, H_2]).unwrap();
+        let mut trick = Trick::new(TRUMP, vec![P1, P2]);
+        trick
+            .play_cards(play(P1, &mut hands, &[H_Q, C_3, H_A]))
+            .unwrap();
+        assert_eq!(trick.played_cards()[0].cards, vec![H_Q, C_3]);
+        assert_eq!(trick.played_cards()[0].bad_throw_cards, vec![H_A]);
+
+        // Two wild cards only stand in for the cards within two steps of the
+        // seven, i.e. the five through the nine.
+        let readings = wild_card_readings(
+            TRUMP,
+            Some(C_3),
+            EffectiveSuit::Hearts,
+            &[H_7, C_3, C_3],
+            AdjacencyPolicy::default(),
+        );
+        assert_eq!(readings.len(), 15);
+        assert!(readings
+            .iter()
+            .flatten()
+            .all(|c| [H_5, H_6, H_7, H_8, H_9].contains(c)));
     }
 
     #[test]
@@ -1517,6 +4151,13 @@ mod tests {
         let expected_tf = TrickFormat {
             suit: EffectiveSuit::Trump,
             trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![TrickUnit::Repeated {
                 count: 3,
                 card: oc!(S_2),
@@ -1543,6 +4184,13 @@ mod tests {
         let expected_tf = TrickFormat {
             suit: EffectiveSuit::Trump,
             trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![TrickUnit::Tractor {
                 count: 3,
                 members: vec![oc!(S_2), oc!(S_3), oc!(S_5)],
@@ -1575,6 +4223,13 @@ mod tests {
         let expected_tf = TrickFormat {
             suit: EffectiveSuit::Trump,
             trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![
                 TrickUnit::Tractor {
                     count: 2,
@@ -1620,6 +4275,13 @@ mod tests {
         let expected_tf = TrickFormat {
             suit: EffectiveSuit::Trump,
             trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![
                 TrickUnit::Repeated {
                     count: 1,
@@ -1652,18 +4314,79 @@ mod tests {
             .is_ok());
     }
 
+    #[test]
+    fn test_legal_play_past_decomposition_limits() {
+        // A tractor of quadruples from four decks, which is too large to
+        // decompose in full.
+        let lead = [H_5, H_6, H_7, H_8, H_9]
+            .iter()
+            .flat_map(|c| vec![*c; 4])
+            .collect::<Vec<_>>();
+        let tf =
+            TrickFormat::from_cards(TRUMP, TractorRequirements::default(), &lead, None).unwrap();
+        assert_eq!(tf.size(), 20);
+
+        let mut hand = [H_2, H_5, H_7, H_9, H_J, H_K]
+            .iter()
+            .flat_map(|c| vec![*c; 3])
+            .collect::<Vec<_>>();
+        hand.extend(vec![H_3, H_6, H_8, H_Q, H_A]);
+        let hand = CardSet::from_cards(hand);
+        let check = |play: &[Card]| {
+            tf.try_is_legal_play(
+                &hand,
+                play,
+                TrickDrawPolicy::NoProtections,
+                DecompositionStrategy::default(),
+                DecompositionLimits::default(),
+            )
+        };
+
+        // Whether the play breaks the format up the right way can't be
+        // checked without decomposing it in full, so it's left to the caller
+        // rather than guessed at.
+        let mut play = [H_2, H_5, H_7, H_9, H_J]
+            .iter()
+            .flat_map(|c| vec![*c; 3])
+            .collect::<Vec<_>>();
+        play.extend(vec![H_3, H_6, H_8, H_K, H_K]);
+        assert!(matches!(
+            check(&play),
+            Err(TrickError::DecompositionTooLarge { num_cards: 20 })
+        ));
+        let mut play = [H_2, H_5, H_7, H_9]
+            .iter()
+            .flat_map(|c| vec![*c; 3])
+            .collect::<Vec<_>>();
+        play.extend(vec![H_J, H_J, H_K, H_K, H_3, H_6, H_8, H_Q]);
+        assert!(matches!(
+            check(&play),
+            Err(TrickError::DecompositionTooLarge { num_cards: 20 })
+        ));
+
+        // Playing the format outright doesn't need it decomposed.
+        assert!(check(&lead).unwrap());
+    }
+
     #[test]
     fn test_legal_play_pairs() {
         let tf = TrickFormat {
             suit: EffectiveSuit::Trump,
             trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![TrickUnit::Repeated {
                 count: 2,
                 card: oc!(S_3),
             }],
         };
 
-        let hand = Card::count(vec![S_2, S_2, S_3, S_3, S_5, S_5]);
+        let hand = CardSet::from_cards(vec![S_2, S_2, S_3, S_3, S_5, S_5]);
         assert!(tf.is_legal_play(&hand, &[S_2, S_2], TrickDrawPolicy::NoProtections));
         assert!(!tf.is_legal_play(&hand, &[S_2, S_3], TrickDrawPolicy::NoProtections));
         assert!(!tf.is_legal_play(&hand, &[S_2, S_3, S_3], TrickDrawPolicy::NoProtections));
@@ -1671,8 +4394,62 @@ mod tests {
         assert!(tf.is_legal_play(&hand, &[S_2, S_3], TrickDrawPolicy::NoFormatBasedDraw));
         assert!(!tf.is_legal_play(&hand, &[S_2, S_3, S_3], TrickDrawPolicy::NoFormatBasedDraw));
 
+        assert_eq!(
+            tf.explain_illegal_play(
+                &hand,
+                &[S_2, S_3],
+                TrickDrawPolicy::NoProtections,
+                DecompositionStrategy::default(),
+                DecompositionLimits::default()
+            )
+            .unwrap(),
+            Some(FollowFailureReason::DidNotMatchFormat {
+                required: vec![vec![2]],
+                playable: vec![S_5, S_5],
+            })
+        );
+        assert_eq!(
+            tf.explain_illegal_play(
+                &hand,
+                &[S_2, S_2, S_2],
+                TrickDrawPolicy::NoProtections,
+                DecompositionStrategy::default(),
+                DecompositionLimits::default()
+            )
+            .unwrap(),
+            Some(FollowFailureReason::WrongNumberOfCards {
+                required: 2,
+                played: 3,
+            })
+        );
+        assert_eq!(
+            tf.explain_illegal_play(
+                &hand,
+                &[S_2, S_2],
+                TrickDrawPolicy::NoProtections,
+                DecompositionStrategy::default(),
+                DecompositionLimits::default()
+            )
+            .unwrap(),
+            None
+        );
+        assert_eq!(
+            tf.explain_illegal_play(
+                &CardSet::from_cards(vec![S_2, S_3, H_7]),
+                &[S_2, H_7],
+                TrickDrawPolicy::NoProtections,
+                DecompositionStrategy::default(),
+                DecompositionLimits::default()
+            )
+            .unwrap(),
+            Some(FollowFailureReason::DidNotFollowSuit {
+                played_in_suit: 1,
+                held_in_suit: 2,
+            })
+        );
+
         // Check that we don't break longer tuples if that's not required
-        let hand = Card::count(vec![S_2, S_2, S_2, S_3, S_5]);
+        let hand = CardSet::from_cards(vec![S_2, S_2, S_2, S_3, S_5]);
         assert!(tf.is_legal_play(&hand, &[S_3, S_5], TrickDrawPolicy::LongerTuplesProtected));
         assert!(tf.is_legal_play(&hand, &[S_3, S_5], TrickDrawPolicy::NoFormatBasedDraw));
         assert!(!tf.is_legal_play(&hand, &[S_3, S_5], TrickDrawPolicy::NoProtections));
@@ -1680,13 +4457,20 @@ mod tests {
         let tf = TrickFormat {
             suit: EffectiveSuit::Trump,
             trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![TrickUnit::Repeated {
                 count: 3,
                 card: oc!(S_3),
             }],
         };
 
-        let hand = Card::count(vec![S_2, S_2, S_3, S_3, S_5, S_5]);
+        let hand = CardSet::from_cards(vec![S_2, S_2, S_3, S_3, S_5, S_5]);
         assert!(tf.is_legal_play(&hand, &[S_2, S_2, S_5], TrickDrawPolicy::NoProtections));
         assert!(!tf.is_legal_play(&hand, &[S_2, S_3, S_5], TrickDrawPolicy::NoProtections));
         assert!(tf.is_legal_play(&hand, &[S_2, S_2, S_5], TrickDrawPolicy::NoProtections));
@@ -1696,6 +4480,13 @@ mod tests {
         let tf = TrickFormat {
             suit: EffectiveSuit::Trump,
             trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![TrickUnit::Repeated {
                 count: 5,
                 card: oc!(S_3),
@@ -1717,7 +4508,7 @@ mod tests {
             TrickDrawPolicy::NoFormatBasedDraw
         ));
 
-        let hand = Card::count(vec![S_2, S_2, S_2, S_2, S_3, S_3, S_5, S_5]);
+        let hand = CardSet::from_cards(vec![S_2, S_2, S_2, S_2, S_3, S_3, S_5, S_5]);
         assert!(tf.is_legal_play(
             &hand,
             &[S_2, S_2, S_2, S_2, S_5],
@@ -1737,6 +4528,13 @@ mod tests {
         let tf = TrickFormat {
             suit: EffectiveSuit::Trump,
             trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![TrickUnit::Tractor {
                 count: 2,
                 members: vec![oc!(S_2), oc!(S_3)],
@@ -1776,7 +4574,7 @@ mod tests {
             TrickDrawPolicy::NoFormatBasedDraw
         ));
 
-        let hand = Card::count(vec![S_2, S_2, S_2, S_2, S_3, S_5, S_5]);
+        let hand = CardSet::from_cards(vec![S_2, S_2, S_2, S_2, S_3, S_5, S_5]);
         assert!(tf.is_legal_play(&hand, &[S_2, S_2, S_2, S_2], TrickDrawPolicy::NoProtections));
         assert!(tf.is_legal_play(&hand, &[S_2, S_2, S_5, S_5], TrickDrawPolicy::NoProtections));
         assert!(!tf.is_legal_play(&hand, &[S_2, S_2, S_5, S_3], TrickDrawPolicy::NoProtections));
@@ -1817,6 +4615,13 @@ mod tests {
         let tf = TrickFormat {
             suit: EffectiveSuit::Trump,
             trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![
                 TrickUnit::Repeated {
                     count: 2,
@@ -1828,7 +4633,7 @@ mod tests {
                 },
             ],
         };
-        let hand = Card::count(vec![S_2, S_2, S_2, S_5]);
+        let hand = CardSet::from_cards(vec![S_2, S_2, S_2, S_5]);
         assert!(tf.is_legal_play(&hand, &[S_2, S_2, S_2], TrickDrawPolicy::NoProtections));
         assert!(tf.is_legal_play(&hand, &[S_2, S_2, S_5], TrickDrawPolicy::NoProtections));
         assert!(tf.is_legal_play(&hand, &[S_2, S_2, S_2], TrickDrawPolicy::NoFormatBasedDraw));
@@ -1845,17 +4650,281 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_matching_context_reuse() {
+        let tf = TrickFormat {
+            suit: EffectiveSuit::Trump,
+            trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
+            units: vec![TrickUnit::Repeated {
+                count: 2,
+                card: oc!(S_3),
+            }],
+        };
+        let hand = CardSet::from_cards(vec![S_2, S_2, S_3, S_3, S_5, S_5, S_6]);
+
+        let mut ctx = MatchingContext::new();
+        assert!(ctx.is_empty());
+        for proposed in [&[S_2, S_2][..], &[S_2, S_3], &[S_5, S_6], &[S_3, S_3]] {
+            let with_ctx = tf
+                .explain_illegal_play_with_context(
+                    &mut ctx,
+                    &hand,
+                    proposed,
+                    TrickDrawPolicy::NoProtections,
+                    DecompositionStrategy::default(),
+                    DecompositionLimits::default(),
+                )
+                .unwrap();
+            let without_ctx = tf
+                .explain_illegal_play(
+                    &hand,
+                    proposed,
+                    TrickDrawPolicy::NoProtections,
+                    DecompositionStrategy::default(),
+                    DecompositionLimits::default(),
+                )
+                .unwrap();
+            assert_eq!(with_ctx, without_ctx);
+        }
+        // Only the hand is memoized, so every play shares the same entry.
+        assert_eq!(ctx.len(), 1);
+    }
+
+    #[test]
+    fn test_legal_plays() {
+        let tf = TrickFormat {
+            suit: EffectiveSuit::Trump,
+            trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
+            units: vec![TrickUnit::Repeated {
+                count: 2,
+                card: oc!(S_3),
+            }],
+        };
+        let legal_plays = |hand: &CardSet, policy| {
+            tf.legal_plays(
+                hand,
+                policy,
+                DecompositionStrategy::default(),
+                DecompositionLimits::default(),
+            )
+            .unwrap()
+        };
+
+        let hand = CardSet::from_cards(vec![S_2, S_2, S_3, S_5, S_5, S_5, S_6, H_7]);
+        assert_eq!(
+            legal_plays(&hand, TrickDrawPolicy::NoProtections),
+            vec![LegalPlays {
+                format: Some(vec![UnitLike {
+                    adjacent_tuples: vec![2]
+                }]),
+                plays: vec![vec![S_2, S_2], vec![S_5, S_5]],
+                num_other_cards: 0,
+                num_trump_cards: 0,
+            }]
+        );
+        // The triple is protected, so the pair of 2s is still required, but
+        // using two of the 5s is allowed.
+        assert_eq!(
+            legal_plays(&hand, TrickDrawPolicy::LongerTuplesProtected)[0].plays,
+            vec![vec![S_2, S_2], vec![S_5, S_5]]
+        );
+
+        // Every in-suit play is listed exactly when it's legal.
+        for policy in [
+            TrickDrawPolicy::NoProtections,
+            TrickDrawPolicy::LongerTuplesProtected,
+            TrickDrawPolicy::NoFormatBasedDraw,
+        ] {
+            for hand in [
+                hand.clone(),
+                CardSet::from_cards(vec![S_2, S_3, S_5, S_5, S_5]),
+                CardSet::from_cards(vec![S_2, S_3, S_6, H_7]),
+            ] {
+                let listed = legal_plays(&hand, policy)
+                    .into_iter()
+                    .flat_map(|g| g.plays)
+                    .collect::<Vec<_>>();
+                let in_suit = hand
+                    .iter()
+                    .filter(|(c, _)| TRUMP.is_trump(**c))
+                    .map(|(c, ct)| (oc!(*c), *ct))
+                    .collect::<BTreeMap<_, _>>();
+                let keys = in_suit.keys().copied().collect::<Vec<_>>();
+                for play in multiset_k_combination_iter(keys, |c| in_suit[c], 2) {
+                    let play = to_cards(play);
+                    assert_eq!(
+                        listed.contains(&play),
+                        tf.is_legal_play(&hand, &play, policy),
+                        "{:?} with {:?}",
+                        play,
+                        policy
+                    );
+                }
+            }
+        }
+
+        // When short of the suit, all of it has to be played.
+        assert_eq!(
+            legal_plays(
+                &CardSet::from_cards(vec![S_2, H_7, H_8]),
+                TrickDrawPolicy::NoProtections
+            ),
+            vec![LegalPlays {
+                format: None,
+                plays: vec![vec![S_2]],
+                num_other_cards: 1,
+                num_trump_cards: 0,
+            }]
+        );
+
+        // A wild card can make up the rest of the suit instead.
+        let wild_tf = TrickFormat {
+            wild_card: Some(C_3),
+            ..tf.clone()
+        };
+        let hand = CardSet::from_cards(vec![S_2, C_3, H_8]);
+        let groups = wild_tf
+            .legal_plays(
+                &hand,
+                TrickDrawPolicy::NoProtections,
+                DecompositionStrategy::default(),
+                DecompositionLimits::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            groups
+                .iter()
+                .map(|g| (g.plays.clone(), g.num_other_cards))
+                .collect::<Vec<_>>(),
+            vec![(vec![vec![S_2]], 1), (vec![vec![S_2, C_3]], 0)]
+        );
+        assert!(wild_tf.is_legal_play(&hand, &[S_2, C_3], TrickDrawPolicy::NoProtections));
+
+        // Listing every way of playing a large hand is bounded, too.
+        let hand = CardSet::from_cards(vec![S_2, S_3, S_5, S_6, S_7, S_8]);
+        let limits = DecompositionLimits {
+            max_partitions: usize::MAX,
+            max_decompositions: 10,
+        };
+        assert!(matches!(
+            tf.legal_plays(
+                &hand,
+                TrickDrawPolicy::NoFormatBasedDraw,
+                DecompositionStrategy::default(),
+                limits,
+            ),
+            Err(TrickError::DecompositionTooLarge { num_cards: 2 })
+        ));
+        let hand = CardSet::from_cards(
+            [S_2, S_3, S_5, S_6, S_7, S_8, S_9, S_10, S_J, S_Q, S_K]
+                .iter()
+                .flat_map(|c| vec![*c; 2])
+                .collect::<Vec<_>>(),
+        );
+        assert!(matches!(
+            tf.legal_plays(
+                &hand,
+                TrickDrawPolicy::NoProtections,
+                DecompositionStrategy::default(),
+                limits,
+            ),
+            Err(TrickError::DecompositionTooLarge { num_cards: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_forced_ruff() {
+        let tf = TrickFormat {
+            suit: EffectiveSuit::Hearts,
+            trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
+            units: vec![TrickUnit::Repeated {
+                count: 2,
+                card: oc!(H_7),
+            }],
+        };
+        let hand = CardSet::from_cards(vec![H_3, S_5, C_8, C_9]);
+        let explain = |tf: &TrickFormat, hand: &CardSet, play: &[Card]| {
+            tf.explain_illegal_play(
+                hand,
+                play,
+                TrickDrawPolicy::NoProtections,
+                DecompositionStrategy::default(),
+                DecompositionLimits::unlimited(),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(explain(&tf, &hand, &[H_3, C_8]), None);
+
+        let tf = tf.with_ruff_policy(RuffPolicy::MustRuff);
+        assert_eq!(
+            explain(&tf, &hand, &[H_3, C_8]),
+            Some(FollowFailureReason::DidNotRuff {
+                played_trump: 0,
+                held_trump: 1,
+            })
+        );
+        assert_eq!(explain(&tf, &hand, &[H_3, S_5]), None);
+        assert_eq!(
+            tf.legal_plays(
+                &hand,
+                TrickDrawPolicy::NoProtections,
+                DecompositionStrategy::default(),
+                DecompositionLimits::unlimited(),
+            )
+            .unwrap(),
+            vec![LegalPlays {
+                format: None,
+                plays: vec![vec![H_3]],
+                num_other_cards: 1,
+                num_trump_cards: 1,
+            }]
+        );
+
+        // Without any trump, anything may be played.
+        let hand = CardSet::from_cards(vec![C_8, C_9, D_3]);
+        assert_eq!(explain(&tf, &hand, &[C_8, D_3]), None);
+    }
+
     #[test]
     fn test_protected_tuple() {
         let tf = TrickFormat {
             suit: EffectiveSuit::Trump,
             trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![TrickUnit::Repeated {
                 card: oc!(S_3),
                 count: 3,
             }],
         };
-        let hand = Card::count(vec![S_2, S_2, S_2, S_2, S_5, S_6, S_7, S_8]);
+        let hand = CardSet::from_cards(vec![S_2, S_2, S_2, S_2, S_5, S_6, S_7, S_8]);
         assert!(!tf.is_legal_play(&hand, &[S_6, S_7, S_8], TrickDrawPolicy::NoProtections));
         assert!(tf.is_legal_play(&hand, &[S_6, S_7, S_8], TrickDrawPolicy::NoFormatBasedDraw));
         assert!(tf.is_legal_play(
@@ -1863,7 +4932,7 @@ mod tests {
             &[S_6, S_7, S_8],
             TrickDrawPolicy::LongerTuplesProtected
         ));
-        let hand = Card::count(vec![S_2, S_2, S_2, S_2, S_5, S_5, S_6, S_7, S_8]);
+        let hand = CardSet::from_cards(vec![S_2, S_2, S_2, S_2, S_5, S_5, S_6, S_7, S_8]);
         assert!(!tf.is_legal_play(&hand, &[S_5, S_5, S_6], TrickDrawPolicy::NoProtections));
         assert!(tf.is_legal_play(&hand, &[S_5, S_5, S_6], TrickDrawPolicy::NoFormatBasedDraw));
         assert!(tf.is_legal_play(
@@ -1883,12 +4952,19 @@ mod tests {
         let tf = TrickFormat {
             suit: EffectiveSuit::Trump,
             trump: TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![TrickUnit::Tractor {
                 members: vec![oc!(S_6), oc!(S_7)],
                 count: 2,
             }],
         };
-        let hand = Card::count(vec![S_2, S_2, S_2, S_3, S_3, S_3, S_5, S_6, S_7, S_8]);
+        let hand = CardSet::from_cards(vec![S_2, S_2, S_2, S_3, S_3, S_3, S_5, S_6, S_7, S_8]);
         assert!(!tf.is_legal_play(&hand, &[S_5, S_6, S_7, S_8], TrickDrawPolicy::NoProtections));
         assert!(tf.is_legal_play(
             &hand,
@@ -1907,10 +4983,18 @@ mod tests {
         const HEART_TRUMP: Trump = Trump::Standard {
             number: Number::Four,
             suit: Suit::Hearts,
+            permanent: PermanentTrump::None,
         };
         let tf = TrickFormat {
             suit: EffectiveSuit::Spades,
             trump: HEART_TRUMP,
+            adjacency_policy: AdjacencyPolicy::default(),
+            wild_card: None,
+            ruff_policy: RuffPolicy::MayDiscard,
+            tie_break_policy: TieBreakPolicy::FirstPlayed,
+            tractor_ruff_policy: TractorRuffPolicy::SameShape,
+            joker_pair_policy: JokerPairPolicy::Throw,
+            decomposition_strategy: DecompositionStrategy::default(),
             units: vec![
                 TrickUnit::Tractor {
                     members: vec![oc!(S_9, HEART_TRUMP), oc!(S_9, HEART_TRUMP)],
@@ -1922,7 +5006,7 @@ mod tests {
                 },
             ],
         };
-        let hand = Card::count(vec![S_3, S_5, S_10, S_J, S_Q, S_6, S_8, S_8, S_8]);
+        let hand = CardSet::from_cards(vec![S_3, S_5, S_10, S_J, S_Q, S_6, S_8, S_8, S_8]);
         assert!(!tf.is_legal_play(
             &hand,
             &[S_3, S_5, S_10, S_J, S_Q],
@@ -1955,6 +5039,7 @@ mod tests {
         let trump = Trump::Standard {
             number: Number::Four,
             suit: Suit::Hearts,
+            permanent: PermanentTrump::None,
         };
 
         let mut hands = Hands::new(vec![P1, P2, P3, P4]);
@@ -1990,6 +5075,7 @@ mod tests {
         let trump = Trump::Standard {
             number: Number::King,
             suit: Suit::Spades,
+            permanent: PermanentTrump::None,
         };
 
         let p1_hand = vec![S_7, S_7, S_8, S_8, S_9, S_9, C_4, C_4];
@@ -2037,13 +5123,15 @@ mod tests {
                 | TrickDrawPolicy::NoProtections
                 | TrickDrawPolicy::OnlyDrawTractorOnTractor => {
                     // This play should not succeed, because P2 also has S_K, S_K which is a pair.
-                    if let Err(TrickError::IllegalPlay) = trick.play_cards(pc!(
-                        P2,
-                        &mut hands,
-                        &[S_4, S_10, S_A, H_K, D_K, C_K],
-                        *policy,
-                        ThrowEvaluationPolicy::All
-                    )) {
+                    if let Err(TrickError::InvalidPlay(PlayError::MustMatchFormat { .. })) = trick
+                        .play_cards(pc!(
+                            P2,
+                            &mut hands,
+                            &[S_4, S_10, S_A, H_K, D_K, C_K],
+                            *policy,
+                            ThrowEvaluationPolicy::All
+                        ))
+                    {
                         trick
                             .play_cards(pc!(
                                 P2,
@@ -2084,6 +5172,7 @@ mod tests {
         let trump = Trump::Standard {
             number: Number::King,
             suit: Suit::Spades,
+            permanent: PermanentTrump::None,
         };
 
         let p1_hand = vec![C_4, C_6];
@@ -2130,6 +5219,7 @@ mod tests {
         let trump = Trump::Standard {
             number: Number::Two,
             suit: Suit::Spades,
+            permanent: PermanentTrump::None,
         };
 
         let p1_hand = vec![H_A, H_K, H_K, H_K, H_9, H_9];
@@ -2202,6 +5292,7 @@ mod tests {
             let trump = Trump::Standard {
                 number: Number::Five,
                 suit: Suit::Diamonds,
+                permanent: PermanentTrump::None,
             };
             let mut hands = Hands::new(vec![P1, P2, P3, P4]);
             hands.set_trump(trump);
@@ -2241,6 +5332,7 @@ mod tests {
             let trump = Trump::Standard {
                 number: Number::Two,
                 suit: Suit::Clubs,
+                permanent: PermanentTrump::None,
             };
             let mut hands = Hands::new(vec![P1, P2, P3, P4]);
             hands.set_trump(trump);
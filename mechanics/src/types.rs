@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
 
 use schemars::{
     gen::SchemaGenerator,
@@ -14,17 +15,99 @@ use serde::{Deserialize, Serialize};
 #[serde(transparent)]
 pub struct PlayerID(pub usize);
 
+/// Table rules about which cards are adjacent to one another, for the
+/// purposes of forming tractors.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema, Hash, Eq, PartialEq)]
+#[serde(default)]
+pub struct AdjacencyPolicy {
+    /// Whether the off-suit trump-number cards are adjacent to the trump-number
+    /// card in the trump suit. The same applies to a permanent trump number.
+    pub off_suit_trump_number_connects: bool,
+    /// Whether the cards on either side of the trump number are adjacent
+    /// within a suit, e.g. 3 and 5 when 4 is the trump number (or a permanent
+    /// trump). See `Trump::skips_trump_number`.
+    pub skip_trump_number: bool,
+    /// Whether the small joker is adjacent to the big joker.
+    pub jokers_connect: bool,
+}
+
+impl Default for AdjacencyPolicy {
+    fn default() -> Self {
+        Self {
+            off_suit_trump_number_connects: true,
+            skip_trump_number: true,
+            jokers_connect: true,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema, Hash, Eq, PartialEq)]
 pub enum Trump {
-    Standard { suit: Suit, number: Number },
-    NoTrump { number: Option<Number> },
+    Standard {
+        suit: Suit,
+        number: Number,
+        #[serde(default)]
+        permanent: PermanentTrump,
+    },
+    NoTrump {
+        number: Option<Number>,
+        #[serde(default)]
+        permanent: PermanentTrump,
+    },
 }
 
+/// Cards which are trump regardless of which suit was declared.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema, Hash, Eq, PartialEq, Default)]
+pub enum PermanentTrump {
+    #[default]
+    None,
+    /// Every card of this number is trump, ranking above the rest of the trump
+    /// suit but below the trump number. As with the trump number, the one in
+    /// the trump suit ranks above the others.
+    Number(Number),
+    /// This card (the "general") is trump, ranking above the trump number but
+    /// below the jokers.
+    Card(Card),
+}
+
+crate::impl_slog_value!(PermanentTrump);
+
 impl Trump {
+    pub fn permanent(self) -> PermanentTrump {
+        match self {
+            Trump::Standard { permanent, .. } | Trump::NoTrump { permanent, .. } => permanent,
+        }
+    }
+
+    /// The same trump, but with `permanent` as its permanent trump cards.
+    pub fn with_permanent(self, permanent: PermanentTrump) -> Trump {
+        match self {
+            Trump::Standard { suit, number, .. } => Trump::Standard {
+                suit,
+                number,
+                permanent,
+            },
+            Trump::NoTrump { number, .. } => Trump::NoTrump { number, permanent },
+        }
+    }
+
+    /// Whether the card is trump only because of the `PermanentTrump`. A
+    /// permanent number which is also the trump number is just treated as the
+    /// trump number.
+    pub fn is_permanent(self, card: Card) -> bool {
+        match (self.permanent(), card) {
+            (PermanentTrump::Number(n), Card::Suited { number, .. }) => {
+                number == n && self.number() != Some(n)
+            }
+            (PermanentTrump::Card(general @ Card::Suited { .. }), _) => card == general,
+            _ => false,
+        }
+    }
+
     pub fn number(self) -> Option<Number> {
         match self {
             Trump::Standard { number, .. } => Some(number),
-            Trump::NoTrump { number } => number,
+            Trump::NoTrump { number, .. } => number,
         }
     }
 
@@ -39,6 +122,7 @@ impl Trump {
         match (self, card) {
             (_, Card::Unknown) => EffectiveSuit::Unknown,
             (_, Card::SmallJoker) | (_, Card::BigJoker) => EffectiveSuit::Trump,
+            (_, _) if self.is_permanent(card) => EffectiveSuit::Trump,
 
             (
                 Trump::Standard { number, .. },
@@ -50,6 +134,7 @@ impl Trump {
             | (
                 Trump::NoTrump {
                     number: Some(number),
+                    ..
                 },
                 Card::Suited {
                     number: card_number,
@@ -62,22 +147,17 @@ impl Trump {
                     suit: trump_suit, ..
                 },
                 Card::Suited { suit, .. },
-            ) => match suit {
-                _ if suit == trump_suit => EffectiveSuit::Trump,
-                Suit::Clubs => EffectiveSuit::Clubs,
-                Suit::Diamonds => EffectiveSuit::Diamonds,
-                Suit::Spades => EffectiveSuit::Spades,
-                Suit::Hearts => EffectiveSuit::Hearts,
-            },
-            (Trump::NoTrump { .. }, Card::Suited { suit, .. }) => match suit {
-                Suit::Clubs => EffectiveSuit::Clubs,
-                Suit::Diamonds => EffectiveSuit::Diamonds,
-                Suit::Spades => EffectiveSuit::Spades,
-                Suit::Hearts => EffectiveSuit::Hearts,
-            },
+            ) if suit == trump_suit => EffectiveSuit::Trump,
+            (_, Card::Suited { suit, .. }) => suit.into(),
         }
     }
 
+    /// Whether the card is a trump, i.e. its effective suit is
+    /// `EffectiveSuit::Trump`.
+    pub fn is_trump(self, card: Card) -> bool {
+        self.effective_suit(card) == EffectiveSuit::Trump
+    }
+
     pub fn suit_ordinal(self, card: Card) -> impl Ord {
         let effective_suit = self.effective_suit(card);
         match self {
@@ -133,101 +213,134 @@ impl Trump {
         }
     }
 
-    pub fn successor(self, card: Card) -> Vec<Card> {
+    /// The trump cards which rank above the rest of the trump suit, grouped
+    /// from lowest to highest, ending with the small joker. Each group is the
+    /// successor of the one before it.
+    fn trump_ladder(self) -> Vec<Vec<Card>> {
+        let numbered = |number: Number, in_trump_suit: bool| -> Vec<Card> {
+            Suit::iter()
+                .filter(|s| (Some(*s) == self.suit()) == in_trump_suit)
+                .map(|suit| Card::Suited { suit, number })
+                .filter(|c| self.permanent() != PermanentTrump::Card(*c))
+                .collect()
+        };
+        let mut ladder = vec![];
+        match self.permanent() {
+            PermanentTrump::Number(n) if self.number() != Some(n) => {
+                ladder.push(numbered(n, false));
+                ladder.push(numbered(n, true));
+            }
+            _ => (),
+        }
+        if let Some(n) = self.number() {
+            ladder.push(numbered(n, false));
+            ladder.push(numbered(n, true));
+        }
+        if let PermanentTrump::Card(general @ Card::Suited { .. }) = self.permanent() {
+            ladder.push(vec![general]);
+        }
+        ladder.push(vec![Card::SmallJoker]);
+        ladder.retain(|group| !group.is_empty());
+        ladder
+    }
+
+    pub fn successor(self, card: Card, adjacency_policy: AdjacencyPolicy) -> Vec<Card> {
         match card {
             Card::Unknown => vec![],
             Card::BigJoker => vec![],
-            Card::SmallJoker => vec![Card::BigJoker],
-            // If this is the trump number, it's part of trump and we need to
-            // handle it specially.
+            Card::SmallJoker if adjacency_policy.jokers_connect => vec![Card::BigJoker],
+            Card::SmallJoker => vec![],
+            // If this is the trump number or a permanent trump, it's ranked
+            // above the rest of trump and we need to handle it specially.
             Card::Suited { suit, number }
-                if self.number().map(|n| number == n).unwrap_or(false) =>
+                if self.number() == Some(number) || self.is_permanent(card) =>
             {
-                match self {
-                    Trump::Standard {
-                        suit: trump_suit,
-                        number: trump_number,
-                    } => {
-                        if suit == trump_suit {
-                            vec![Card::SmallJoker]
-                        } else {
-                            vec![Card::Suited {
-                                suit: trump_suit,
-                                number: trump_number,
-                            }]
-                        }
+                let ladder = self.trump_ladder();
+                let next = ladder
+                    .iter()
+                    .position(|group| group.contains(&card))
+                    .and_then(|idx| ladder.get(idx + 1));
+                match next.map(|group| group.as_slice()) {
+                    // The off-suit cards connect to the same number in the
+                    // trump suit only if the table allows it.
+                    Some(
+                        [Card::Suited {
+                            suit: next_suit,
+                            number: next_number,
+                        }],
+                    ) if !adjacency_policy.off_suit_trump_number_connects
+                        && *next_number == number
+                        && Some(*next_suit) == self.suit()
+                        && suit != *next_suit =>
+                    {
+                        vec![]
                     }
-                    Trump::NoTrump { .. } => vec![Card::SmallJoker],
+                    Some(group) => group.to_vec(),
+                    None => vec![],
                 }
             }
-            // If the _next_ number is the trump number, we need to skip it.
-            Card::Suited { suit, number }
-                if number.successor().is_some() && number.successor() == self.number() =>
-            {
-                match number.successor().and_then(|n| n.successor()) {
-                    // If there's a valid suited card afterwards, try that.
-                    Some(n) => vec![Card::Suited { suit, number: n }],
-                    // Otherwise, if we're in trump *and* there's a trump
-                    // number, the next higher cards are the non-trump trump
-                    // numbers.
-                    None if self.effective_suit(card) == EffectiveSuit::Trump
-                        && self.number().is_some() =>
-                    {
-                        ALL_SUITS
-                            .iter()
-                            .flat_map(|s| {
-                                if Some(*s) != self.suit() {
-                                    Some(Card::Suited {
-                                        suit: *s,
-                                        number: self
-                                            .number()
-                                            .expect("already checked in match branch"),
-                                    })
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect()
+            Card::Suited { suit, number } => {
+                // Skip over the numbers which have been moved into trump.
+                let mut skipped = false;
+                let mut next = number.successor();
+                while let Some(n) = next {
+                    let next_card = Card::Suited { suit, number: n };
+                    if self.number() == Some(n) || self.is_permanent(next_card) {
+                        skipped = true;
+                        next = n.successor();
+                    } else {
+                        break;
                     }
-                    // Otherwise, if we're in trump and there _isn't_ a trump
-                    // number, the next highest card is the small joker.
-                    None if self.effective_suit(card) == EffectiveSuit::Trump => {
-                        vec![Card::SmallJoker]
+                }
+                match next {
+                    // If there's a valid suited card afterwards, try that --
+                    // unless the table doesn't allow skipping the trump number.
+                    Some(n) if !skipped || adjacency_policy.skip_trump_number => {
+                        vec![Card::Suited { suit, number: n }]
                     }
+                    Some(_) => vec![],
+                    // Otherwise, if we're in trump, the next higher cards are
+                    // the bottom of the trump ladder: the permanent trumps, the
+                    // trump numbers, or the small joker.
+                    None if self.is_trump(card) => self.trump_ladder().swap_remove(0),
                     // Otherwise, there's no successor.
                     None => vec![],
                 }
             }
-            Card::Suited { suit, number } => match number.successor() {
-                // If there's a valid suited card afterwards, try that.
-                Some(n) => vec![Card::Suited { suit, number: n }],
-                // Otherwise, if we're in trump *and* there's a trump number,
-                // the next higher cards are the non-trump trump numbers.
-                None if self.effective_suit(card) == EffectiveSuit::Trump
-                    && self.number().is_some() =>
-                {
-                    ALL_SUITS
-                        .iter()
-                        .flat_map(|s| {
-                            if Some(*s) != self.suit() {
-                                Some(Card::Suited {
-                                    suit: *s,
-                                    number: self.number().expect("already checked in match branch"),
-                                })
-                            } else {
-                                None
-                            }
-                        })
-                        .collect()
-                }
-                // Otherwise, if we're in trump and there _isn't_ a trump
-                // number, the next highest card is the small joker.
-                None if self.effective_suit(card) == EffectiveSuit::Trump => {
-                    vec![Card::SmallJoker]
-                }
-                // Otherwise, there's no successor.
-                None => vec![],
-            },
+        }
+    }
+
+    /// Whether the next number after `card` in its suit is the trump number
+    /// (or a permanent trump), so that `successor` has to skip over it
+    /// (subject to the `AdjacencyPolicy`).
+    pub fn skips_trump_number(self, card: Card) -> bool {
+        match card {
+            Card::Suited { suit, number } => number.successor().is_some_and(|n| {
+                self.number() == Some(n) || self.is_permanent(Card::Suited { suit, number: n })
+            }),
+            _ => false,
+        }
+    }
+
+    /// The cards which have `card` as a successor; i.e. the inverse of
+    /// `successor`.
+    pub fn predecessor(self, card: Card, adjacency_policy: AdjacencyPolicy) -> Vec<Card> {
+        FULL_DECK
+            .iter()
+            .copied()
+            .filter(|c| self.successor(*c, adjacency_policy).contains(&card))
+            .collect()
+    }
+
+    /// Within trump, the general ranks above the trump number, which ranks
+    /// above the permanent trump number, which ranks above the rest of the
+    /// trump suit.
+    fn trump_tier(self, card: Card) -> usize {
+        match card {
+            Card::Suited { .. } if self.permanent() == PermanentTrump::Card(card) => 3,
+            Card::Suited { number, .. } if self.number() == Some(number) => 2,
+            Card::Suited { .. } if self.is_permanent(card) => 1,
+            _ => 0,
         }
     }
 
@@ -260,34 +373,86 @@ impl Trump {
                         suit: suit_2,
                     },
                 ) => {
-                    let trump_number = self.number();
-                    if Some(number_1) == trump_number && Some(number_2) == trump_number {
-                        if let Trump::Standard {
-                            suit: trump_suit, ..
-                        } = self
-                        {
-                            if suit_1 == trump_suit && suit_2 == trump_suit {
-                                Ordering::Equal
-                            } else if suit_1 == trump_suit {
+                    let (tier_1, tier_2) = (self.trump_tier(card1), self.trump_tier(card2));
+                    match tier_1.cmp(&tier_2) {
+                        Ordering::Equal if tier_1 == 0 => number_1.cmp(&number_2),
+                        Ordering::Equal => match self.suit() {
+                            Some(trump_suit) if suit_1 == trump_suit && suit_2 != trump_suit => {
                                 Ordering::Greater
-                            } else if suit_2 == trump_suit {
+                            }
+                            Some(trump_suit) if suit_2 == trump_suit && suit_1 != trump_suit => {
                                 Ordering::Less
-                            } else {
-                                Ordering::Equal
                             }
-                        } else {
-                            Ordering::Equal
-                        }
-                    } else if Some(number_1) == trump_number {
-                        Ordering::Greater
-                    } else if Some(number_2) == trump_number {
-                        Ordering::Less
-                    } else {
-                        number_1.cmp(&number_2)
+                            _ => Ordering::Equal,
+                        },
+                        ordering => ordering,
                     }
                 }
             })
     }
+
+    /// Like `compare_effective`, but also explains why the cards are ordered
+    /// the way they are.
+    pub fn compare_detailed(self, card1: Card, card2: Card) -> Comparison {
+        let ordering = self.compare_effective(card1, card2);
+        let reason = if card1 == card2 {
+            ComparisonReason::Identical
+        } else if card1 == Card::Unknown || card2 == Card::Unknown {
+            ComparisonReason::Unknown
+        } else if self.effective_suit(card1) != self.effective_suit(card2) {
+            if self.is_trump(card1) || self.is_trump(card2) {
+                ComparisonReason::TrumpOverOffSuit
+            } else {
+                ComparisonReason::DifferentSuits
+            }
+        } else if card1.is_joker() || card2.is_joker() {
+            ComparisonReason::Joker
+        } else {
+            match self.trump_tier(card1).max(self.trump_tier(card2)) {
+                _ if ordering == Ordering::Equal => ComparisonReason::EquallyRanked,
+                3 => ComparisonReason::General,
+                2 => ComparisonReason::TrumpNumber,
+                1 => ComparisonReason::PermanentNumber,
+                _ => ComparisonReason::HigherNumber,
+            }
+        };
+        Comparison { ordering, reason }
+    }
+}
+
+/// The result of `Trump::compare_detailed`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Comparison {
+    /// The same as `Trump::compare_effective`.
+    pub ordering: Ordering,
+    pub reason: ComparisonReason,
+}
+
+/// Why one card ranks above the other, or why they rank the same.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema, Hash, Eq, PartialEq)]
+pub enum ComparisonReason {
+    /// They're the same card.
+    Identical,
+    /// At least one of the cards is unknown.
+    Unknown,
+    /// One card is trump and the other isn't.
+    TrumpOverOffSuit,
+    /// The cards are in different non-trump suits, so they're only ordered by
+    /// suit and neither can beat the other.
+    DifferentSuits,
+    /// At least one of the cards is a joker.
+    Joker,
+    /// At least one of the cards is the general.
+    General,
+    /// At least one of the cards is the trump number, i.e. a rank card.
+    TrumpNumber,
+    /// At least one of the cards is a permanent trump number.
+    PermanentNumber,
+    /// The cards are in the same suit, and one has a higher number.
+    HigherNumber,
+    /// The cards are different, but rank the same, e.g. two off-suit trump
+    /// numbers.
+    EquallyRanked,
 }
 
 #[derive(
@@ -302,6 +467,30 @@ pub enum EffectiveSuit {
     Trump,
 }
 
+impl EffectiveSuit {
+    /// The underlying suit, if this isn't trump (or unknown).
+    pub fn suit(self) -> Option<Suit> {
+        match self {
+            EffectiveSuit::Clubs => Some(Suit::Clubs),
+            EffectiveSuit::Diamonds => Some(Suit::Diamonds),
+            EffectiveSuit::Spades => Some(Suit::Spades),
+            EffectiveSuit::Hearts => Some(Suit::Hearts),
+            EffectiveSuit::Unknown | EffectiveSuit::Trump => None,
+        }
+    }
+}
+
+impl From<Suit> for EffectiveSuit {
+    fn from(suit: Suit) -> Self {
+        match suit {
+            Suit::Clubs => EffectiveSuit::Clubs,
+            Suit::Diamonds => EffectiveSuit::Diamonds,
+            Suit::Spades => EffectiveSuit::Spades,
+            Suit::Hearts => EffectiveSuit::Hearts,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub struct CardInfo {
     pub value: char,
@@ -309,6 +498,28 @@ pub struct CardInfo {
     pub typ: char,
     pub number: Option<&'static str>,
     pub points: usize,
+    /// The color in the standard two-color scheme.
+    pub color: CardColor,
+    /// The color in the four-color scheme.
+    pub four_color: CardColor,
+}
+
+/// The color a card is printed in.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
+pub enum CardColor {
+    Black,
+    Red,
+    Blue,
+    Green,
+}
+
+/// How the suits are colored. The four-color scheme gives each suit its own
+/// color, which is easier to tell apart for colorblind players.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash, Default)]
+pub enum ColorScheme {
+    #[default]
+    TwoColor,
+    FourColor,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
@@ -347,6 +558,12 @@ impl<'d> Deserialize<'d> for Card {
 }
 
 impl Card {
+    /// Every card in a single full deck, including both jokers, in
+    /// `FULL_DECK` order.
+    pub fn all() -> impl Iterator<Item = Card> {
+        FULL_DECK.iter().copied()
+    }
+
     pub fn count(iter: impl IntoIterator<Item = Card>) -> HashMap<Card, usize> {
         let mut counts = HashMap::new();
         for card in iter {
@@ -373,6 +590,18 @@ impl Card {
             number: self.number().map(|n| n.as_str()),
             typ: self.suit().map(|s| s.as_char()).unwrap_or(value),
             points: self.points().unwrap_or(0),
+            color: self.color(ColorScheme::TwoColor),
+            four_color: self.color(ColorScheme::FourColor),
+        }
+    }
+
+    /// The color of the card in the given scheme. The jokers are the same in
+    /// every scheme, and the back of an unknown card is black.
+    pub fn color(self, scheme: ColorScheme) -> CardColor {
+        match self {
+            Card::Suited { suit, .. } => suit.color(scheme),
+            Card::BigJoker => CardColor::Red,
+            Card::SmallJoker | Card::Unknown => CardColor::Black,
         }
     }
 
@@ -522,7 +751,114 @@ impl Card {
         }
     }
 }
+/// The order of suits and numbers used by `Card::as_u8`. These must not be
+/// reordered, since the codes may be persisted or sent over the wire.
+const CODE_SUITS: [Suit; 4] = [Suit::Diamonds, Suit::Clubs, Suit::Hearts, Suit::Spades];
+const CODE_NUMBERS: [Number; 13] = [
+    Number::Two,
+    Number::Three,
+    Number::Four,
+    Number::Five,
+    Number::Six,
+    Number::Seven,
+    Number::Eight,
+    Number::Nine,
+    Number::Ten,
+    Number::Jack,
+    Number::Queen,
+    Number::King,
+    Number::Ace,
+];
+
+impl Card {
+    /// A stable one-byte code for the card: 0 for `Unknown`, 1 through 52 for
+    /// the suited cards (by suit, then by number), and 53 and 54 for the small
+    /// and big jokers.
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Card::Unknown => 0,
+            Card::Suited { suit, number } => {
+                let suit_idx = CODE_SUITS.iter().position(|s| *s == suit).unwrap();
+                let number_idx = CODE_NUMBERS.iter().position(|n| *n == number).unwrap();
+                (1 + suit_idx * CODE_NUMBERS.len() + number_idx) as u8
+            }
+            Card::SmallJoker => 53,
+            Card::BigJoker => 54,
+        }
+    }
+
+    pub fn from_u8(code: u8) -> Option<Card> {
+        CARDS_BY_CODE.get(code as usize).copied()
+    }
+}
+
+/// Every card, indexed by its `Card::as_u8` code.
+pub(crate) const CARDS_BY_CODE: [Card; 55] = {
+    let mut cards = [Card::Unknown; 55];
+    let mut idx = 0;
+    while idx < 52 {
+        cards[1 + idx] = Card::Suited {
+            suit: CODE_SUITS[idx / CODE_NUMBERS.len()],
+            number: CODE_NUMBERS[idx % CODE_NUMBERS.len()],
+        };
+        idx += 1;
+    }
+    cards[53] = Card::SmallJoker;
+    cards[54] = Card::BigJoker;
+    cards
+};
+
+/// Serde helpers which represent cards by their `Card::as_u8` codes rather
+/// than their unicode characters, for use with `#[serde(with = "...")]`.
+#[cfg(feature = "compact-cards")]
+pub mod compact_cards {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::Card;
+
+    pub fn serialize<S: Serializer>(card: &Card, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(card.as_u8())
+    }
+
+    pub fn deserialize<'d, D: Deserializer<'d>>(deserializer: D) -> Result<Card, D::Error> {
+        let code = u8::deserialize(deserializer)?;
+        Card::from_u8(code).ok_or_else(|| D::Error::custom(format!("Unexpected card code {code}")))
+    }
+
+    /// Like the parent module, but for a list of cards.
+    pub mod vec {
+        use serde::de::Error;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        use super::Card;
+
+        pub fn serialize<S: Serializer>(cards: &[Card], serializer: S) -> Result<S::Ok, S::Error> {
+            let codes = cards.iter().map(|c| c.as_u8()).collect::<Vec<_>>();
+            serializer.serialize_bytes(&codes)
+        }
+
+        pub fn deserialize<'d, D: Deserializer<'d>>(
+            deserializer: D,
+        ) -> Result<Vec<Card>, D::Error> {
+            Vec::<u8>::deserialize(deserializer)?
+                .into_iter()
+                .map(|code| {
+                    Card::from_u8(code)
+                        .ok_or_else(|| D::Error::custom(format!("Unexpected card code {code}")))
+                })
+                .collect()
+        }
+    }
+}
+
 impl fmt::Debug for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl fmt::Display for Card {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Card::Suited { suit, number } => write!(f, "{}{}", number.as_str(), suit.as_char()),
@@ -533,6 +869,41 @@ impl fmt::Debug for Card {
     }
 }
 
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid card {0:?}")]
+pub struct ParseCardError(pub String);
+
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    /// Parses a card written as its number and suit in either order, e.g.
+    /// `SA`, `10H`, or `♥J`, or one of the jokers `LJ` and `HJ`. Suits can be
+    /// letters or either the filled or outlined symbols. This round-trips
+    /// with the `Display` representation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseCardError(s.to_string());
+        let upper = s.trim().to_uppercase();
+        match upper.as_str() {
+            "LJ" => return Ok(Card::SmallJoker),
+            "HJ" => return Ok(Card::BigJoker),
+            "[]" => return Ok(Card::Unknown),
+            _ => (),
+        }
+
+        let first = upper.chars().next().ok_or_else(err)?;
+        let last = upper.chars().next_back().ok_or_else(err)?;
+        let (suit, number) = if let Some(suit) = Suit::from_any_char(first) {
+            (suit, &upper[first.len_utf8()..])
+        } else if let Some(suit) = Suit::from_any_char(last) {
+            (suit, &upper[..upper.len() - last.len_utf8()])
+        } else {
+            return Err(err());
+        };
+        let number = Number::from_str(number).ok_or_else(err)?;
+        Ok(Card::Suited { suit, number })
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Number {
     Two,
@@ -660,6 +1031,23 @@ impl Number {
         }
     }
 
+    /// The next number, skipping over the trump number if there is one; e.g.
+    /// 3 is followed by 5 when 4 is the trump number.
+    pub fn successor_skipping(self, trump_number: Option<Number>) -> Option<Self> {
+        match self.successor() {
+            Some(n) if Some(n) == trump_number => n.successor(),
+            next => next,
+        }
+    }
+
+    /// The previous number, skipping over the trump number if there is one.
+    pub fn predecessor_skipping(self, trump_number: Option<Number>) -> Option<Self> {
+        match self.predecessor() {
+            Some(n) if Some(n) == trump_number => n.predecessor(),
+            prev => prev,
+        }
+    }
+
     pub fn as_str(self) -> &'static str {
         match self {
             Number::Two => "2",
@@ -747,6 +1135,11 @@ impl<'d> Deserialize<'d> for Suit {
 }
 
 impl Suit {
+    /// All of the suits, in `ALL_SUITS` order.
+    pub fn iter() -> impl Iterator<Item = Suit> {
+        ALL_SUITS.iter().copied()
+    }
+
     pub fn unicode_offset(self) -> char {
         match self {
             Suit::Spades => '\u{1f0a0}',
@@ -765,6 +1158,17 @@ impl Suit {
         }
     }
 
+    pub fn color(self, scheme: ColorScheme) -> CardColor {
+        match (scheme, self) {
+            (_, Suit::Spades) => CardColor::Black,
+            (_, Suit::Hearts) => CardColor::Red,
+            (ColorScheme::TwoColor, Suit::Diamonds) => CardColor::Red,
+            (ColorScheme::TwoColor, Suit::Clubs) => CardColor::Black,
+            (ColorScheme::FourColor, Suit::Diamonds) => CardColor::Blue,
+            (ColorScheme::FourColor, Suit::Clubs) => CardColor::Green,
+        }
+    }
+
     pub fn from_char(c: char) -> Option<Self> {
         match c {
             '♡' => Some(Suit::Hearts),
@@ -774,6 +1178,18 @@ impl Suit {
             _ => None,
         }
     }
+
+    /// Like `from_char`, but also accepts the uppercase letter or the filled
+    /// symbol for the suit.
+    pub fn from_any_char(c: char) -> Option<Self> {
+        match c {
+            'S' | '♠' | '♤' => Some(Suit::Spades),
+            'H' | '♥' | '♡' => Some(Suit::Hearts),
+            'D' | '♦' | '♢' => Some(Suit::Diamonds),
+            'C' | '♣' | '♧' => Some(Suit::Clubs),
+            _ => None,
+        }
+    }
 }
 impl fmt::Debug for Suit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1100,6 +1516,13 @@ impl Rank {
         }
     }
 
+    pub fn predecessor(self) -> Option<Rank> {
+        match self {
+            Rank::Number(n) => n.predecessor().map(Rank::Number),
+            Rank::NoTrump => Some(Rank::Number(Number::Ace)),
+        }
+    }
+
     pub fn as_str(self) -> &'static str {
         match self {
             Rank::Number(n) => n.as_str(),
@@ -1117,7 +1540,13 @@ impl Rank {
 
 #[cfg(test)]
 mod tests {
-    use super::{cards, Card, Number, Rank, Suit, Trump, FULL_DECK};
+    use std::cmp::Ordering;
+
+    use super::{
+        cards, AdjacencyPolicy, Card, CardColor, ColorScheme, Comparison, ComparisonReason,
+        EffectiveSuit, Number, ParseCardError, PermanentTrump, Rank, Suit, Trump, ALL_SUITS,
+        FULL_DECK,
+    };
 
     #[test]
     fn test_char_roundtrip() {
@@ -1126,6 +1555,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_str_roundtrip() {
+        for card in FULL_DECK.iter().chain(std::iter::once(&Card::Unknown)) {
+            assert_eq!(*card, card.to_string().parse::<Card>().unwrap());
+        }
+        assert_eq!("SA".parse::<Card>().unwrap(), cards::S_A);
+        assert_eq!("10H".parse::<Card>().unwrap(), cards::H_10);
+        assert_eq!("♥J".parse::<Card>().unwrap(), cards::H_J);
+        assert_eq!("jh".parse::<Card>().unwrap(), cards::H_J);
+        assert_eq!("d10".parse::<Card>().unwrap(), cards::D_10);
+        assert_eq!("LJ".parse::<Card>().unwrap(), Card::SmallJoker);
+        assert_eq!("HJ".parse::<Card>().unwrap(), Card::BigJoker);
+        assert_eq!(cards::C_2.to_string(), "2♧");
+
+        for invalid in ["", "S", "1S", "S11", "XA", "AJ"] {
+            assert_eq!(
+                invalid.parse::<Card>(),
+                Err(ParseCardError(invalid.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_u8_roundtrip() {
+        for card in FULL_DECK.iter().chain(std::iter::once(&Card::Unknown)) {
+            assert_eq!(*card, Card::from_u8(card.as_u8()).unwrap());
+        }
+        assert_eq!(Card::Unknown.as_u8(), 0);
+        assert_eq!(cards::D_2.as_u8(), 1);
+        assert_eq!(cards::S_A.as_u8(), 52);
+        assert_eq!(Card::BigJoker.as_u8(), 54);
+        assert_eq!(Card::from_u8(55), None);
+    }
+
+    #[cfg(feature = "compact-cards")]
+    #[test]
+    fn test_compact_cards_serde() {
+        #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+        struct Hand {
+            #[serde(with = "super::compact_cards")]
+            card: Card,
+            #[serde(with = "super::compact_cards::vec")]
+            cards: Vec<Card>,
+        }
+        let hand = Hand {
+            card: cards::S_A,
+            cards: vec![cards::D_2, Card::BigJoker],
+        };
+        let json = serde_json::to_string(&hand).unwrap();
+        assert_eq!(json, r#"{"card":52,"cards":[1,54]}"#);
+        assert_eq!(serde_json::from_str::<Hand>(&json).unwrap(), hand);
+        assert!(serde_json::from_str::<Hand>(r#"{"card":55,"cards":[]}"#).is_err());
+    }
+
     #[test]
     fn test_deck_completeness() {
         assert_eq!(
@@ -1154,6 +1637,7 @@ mod tests {
         let trump = Trump::Standard {
             number: Number::Two,
             suit: Suit::Spades,
+            permanent: PermanentTrump::None,
         };
         hand.sort_by(|a, b| trump.compare(*a, *b));
         assert_eq!(
@@ -1167,9 +1651,15 @@ mod tests {
         let trump = Trump::Standard {
             number: Number::Four,
             suit: Suit::Spades,
+            permanent: PermanentTrump::None,
         };
 
-        let s = |c| trump.successor(c).into_iter().collect::<Vec<_>>();
+        let s = |c| {
+            trump
+                .successor(c, AdjacencyPolicy::default())
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
         assert_eq!(s(cards::S_3), vec![cards::S_5]);
         assert_eq!(s(cards::S_4), vec![Card::SmallJoker]);
         assert!(s(cards::H_4).contains(&cards::S_4));
@@ -1178,16 +1668,30 @@ mod tests {
 
         let no_trump = Trump::NoTrump {
             number: Some(Number::Four),
+            permanent: PermanentTrump::None,
+        };
+        let s = |c| {
+            no_trump
+                .successor(c, AdjacencyPolicy::default())
+                .into_iter()
+                .collect::<Vec<_>>()
         };
-        let s = |c| no_trump.successor(c).into_iter().collect::<Vec<_>>();
         assert_eq!(s(cards::S_3), vec![cards::S_5]);
         assert_eq!(s(cards::S_4), vec![Card::SmallJoker]);
         assert_eq!(s(cards::H_4), vec![Card::SmallJoker]);
         assert!(s(cards::S_A).is_empty());
         assert!(s(cards::H_A).is_empty());
 
-        let no_trump_2 = Trump::NoTrump { number: None };
-        let s = |c| no_trump_2.successor(c).into_iter().collect::<Vec<_>>();
+        let no_trump_2 = Trump::NoTrump {
+            number: None,
+            permanent: PermanentTrump::None,
+        };
+        let s = |c| {
+            no_trump_2
+                .successor(c, AdjacencyPolicy::default())
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
         assert_eq!(s(cards::S_3), vec![cards::S_4]);
         assert!(s(cards::S_A).is_empty());
         assert!(s(cards::H_A).is_empty());
@@ -1195,8 +1699,14 @@ mod tests {
         let trump_ace = Trump::Standard {
             number: Number::Ace,
             suit: Suit::Spades,
+            permanent: PermanentTrump::None,
+        };
+        let s = |c| {
+            trump_ace
+                .successor(c, AdjacencyPolicy::default())
+                .into_iter()
+                .collect::<Vec<_>>()
         };
-        let s = |c| trump_ace.successor(c).into_iter().collect::<Vec<_>>();
         assert_eq!(s(cards::S_3), vec![cards::S_4]);
         assert_eq!(s(cards::S_A), vec![Card::SmallJoker]);
         assert_eq!(s(cards::H_A), vec![cards::S_A]);
@@ -1205,8 +1715,14 @@ mod tests {
 
         let no_trump_ace = Trump::NoTrump {
             number: Some(Number::Ace),
+            permanent: PermanentTrump::None,
+        };
+        let s = |c| {
+            no_trump_ace
+                .successor(c, AdjacencyPolicy::default())
+                .into_iter()
+                .collect::<Vec<_>>()
         };
-        let s = |c| no_trump_ace.successor(c).into_iter().collect::<Vec<_>>();
         assert_eq!(s(cards::S_3), vec![cards::S_4]);
         assert_eq!(s(cards::S_A), vec![Card::SmallJoker]);
         assert_eq!(s(cards::H_A), vec![Card::SmallJoker]);
@@ -1214,6 +1730,232 @@ mod tests {
         assert!(s(cards::H_K).is_empty());
     }
 
+    #[test]
+    fn test_effective_suit() {
+        let trump = Trump::Standard {
+            number: Number::Four,
+            suit: Suit::Spades,
+            permanent: PermanentTrump::None,
+        };
+        assert_eq!(trump.effective_suit(cards::H_4), EffectiveSuit::Trump);
+        assert_eq!(trump.effective_suit(cards::S_7), EffectiveSuit::Trump);
+        assert_eq!(trump.effective_suit(Card::SmallJoker), EffectiveSuit::Trump);
+        assert_eq!(trump.effective_suit(cards::H_7), EffectiveSuit::Hearts);
+        assert_eq!(trump.effective_suit(Card::Unknown), EffectiveSuit::Unknown);
+        assert!(trump.is_trump(cards::D_4));
+        assert!(!trump.is_trump(cards::D_5));
+
+        let no_trump = Trump::NoTrump {
+            number: None,
+            permanent: PermanentTrump::None,
+        };
+        assert_eq!(no_trump.effective_suit(cards::S_7), EffectiveSuit::Spades);
+        assert!(no_trump.is_trump(Card::BigJoker));
+        assert!(!no_trump.is_trump(cards::S_2));
+
+        for suit in ALL_SUITS {
+            assert_eq!(EffectiveSuit::from(suit).suit(), Some(suit));
+        }
+        assert_eq!(EffectiveSuit::Trump.suit(), None);
+    }
+
+    #[test]
+    fn test_adjacency_policy() {
+        let trump = Trump::Standard {
+            number: Number::Four,
+            suit: Suit::Spades,
+            permanent: PermanentTrump::None,
+        };
+        let strict = AdjacencyPolicy {
+            off_suit_trump_number_connects: false,
+            skip_trump_number: false,
+            jokers_connect: false,
+        };
+        let s = |c| trump.successor(c, strict);
+        assert!(s(cards::H_4).is_empty());
+        assert!(s(Card::SmallJoker).is_empty());
+        assert_eq!(s(cards::S_4), vec![Card::SmallJoker]);
+        assert!(s(cards::S_3).is_empty());
+        assert!(s(cards::H_3).is_empty());
+        assert_eq!(s(cards::H_5), vec![cards::H_6]);
+
+        assert!(trump.skips_trump_number(cards::H_3));
+        assert!(trump.skips_trump_number(cards::S_3));
+        assert!(!trump.skips_trump_number(cards::S_4));
+        assert!(!trump.skips_trump_number(Card::SmallJoker));
+        assert!(!Trump::NoTrump {
+            number: None,
+            permanent: PermanentTrump::None
+        }
+        .skips_trump_number(cards::S_3));
+
+        let no_trump = Trump::NoTrump {
+            number: Some(Number::Four),
+            permanent: PermanentTrump::None,
+        };
+        assert_eq!(
+            no_trump.successor(cards::H_4, strict),
+            vec![Card::SmallJoker]
+        );
+    }
+
+    #[test]
+    fn test_predecessor() {
+        let trump = Trump::Standard {
+            number: Number::Four,
+            suit: Suit::Spades,
+            permanent: PermanentTrump::None,
+        };
+        let p = |c| trump.predecessor(c, AdjacencyPolicy::default());
+        assert_eq!(p(cards::S_5), vec![cards::S_3]);
+        assert_eq!(p(Card::SmallJoker), vec![cards::S_4]);
+        assert_eq!(p(Card::BigJoker), vec![Card::SmallJoker]);
+        assert_eq!(p(cards::H_4), vec![cards::S_A]);
+        let mut off_suit = p(cards::S_4);
+        off_suit.sort_by(|a, b| trump.compare(*a, *b));
+        assert_eq!(off_suit, vec![cards::H_4, cards::D_4, cards::C_4]);
+        assert!(p(cards::S_2).is_empty());
+        assert!(p(cards::H_2).is_empty());
+    }
+
+    #[test]
+    fn test_permanent_trump() {
+        let policy = AdjacencyPolicy::default();
+        let trump = Trump::Standard {
+            number: Number::Four,
+            suit: Suit::Hearts,
+            permanent: PermanentTrump::Number(Number::Two),
+        };
+        assert!(trump.is_trump(cards::S_2));
+        assert!(trump.is_permanent(cards::C_2));
+        assert!(!trump.is_permanent(cards::S_4));
+        assert_eq!(trump.compare(cards::H_4, cards::S_4), Ordering::Greater);
+        assert_eq!(trump.compare(cards::S_4, cards::H_2), Ordering::Greater);
+        assert_eq!(trump.compare(cards::H_2, cards::S_2), Ordering::Greater);
+        assert_eq!(trump.compare(cards::S_2, cards::H_A), Ordering::Greater);
+        assert_eq!(
+            trump.compare_effective(cards::S_2, cards::C_2),
+            Ordering::Equal
+        );
+
+        assert_eq!(
+            trump.successor(cards::H_A, policy),
+            vec![cards::S_2, cards::D_2, cards::C_2]
+        );
+        assert_eq!(trump.successor(cards::S_2, policy), vec![cards::H_2]);
+        assert_eq!(
+            trump.successor(cards::H_2, policy),
+            vec![cards::S_4, cards::D_4, cards::C_4]
+        );
+        assert!(trump.predecessor(cards::S_3, policy).is_empty());
+        assert!(trump
+            .successor(
+                cards::S_2,
+                AdjacencyPolicy {
+                    off_suit_trump_number_connects: false,
+                    ..policy
+                }
+            )
+            .is_empty());
+
+        let trump = trump.with_permanent(PermanentTrump::Card(cards::S_5));
+        assert!(trump.is_trump(cards::S_5));
+        assert!(!trump.is_trump(cards::S_2));
+        assert_eq!(trump.compare(cards::S_5, cards::H_4), Ordering::Greater);
+        assert_eq!(
+            trump.compare(Card::SmallJoker, cards::S_5),
+            Ordering::Greater
+        );
+        assert_eq!(trump.successor(cards::H_4, policy), vec![cards::S_5]);
+        assert_eq!(trump.successor(cards::S_5, policy), vec![Card::SmallJoker]);
+        assert_eq!(trump.successor(cards::S_3, policy), vec![cards::S_6]);
+        assert!(trump.skips_trump_number(cards::S_3));
+        assert_eq!(trump.successor(cards::D_3, policy), vec![cards::D_5]);
+
+        let no_trump = Trump::NoTrump {
+            number: Some(Number::Four),
+            permanent: PermanentTrump::Number(Number::Two),
+        };
+        assert_eq!(no_trump.effective_suit(cards::D_2), EffectiveSuit::Trump);
+        assert_eq!(
+            no_trump.successor(cards::D_2, policy),
+            vec![cards::S_4, cards::H_4, cards::D_4, cards::C_4]
+        );
+    }
+
+    #[test]
+    fn test_compare_detailed() {
+        let trump = Trump::Standard {
+            number: Number::Four,
+            suit: Suit::Spades,
+            permanent: PermanentTrump::None,
+        };
+        let detailed = |card1, card2| {
+            let comparison = trump.compare_detailed(card1, card2);
+            assert_eq!(comparison.ordering, trump.compare_effective(card1, card2));
+            (comparison.ordering, comparison.reason)
+        };
+        assert_eq!(
+            detailed(cards::S_3, cards::H_A),
+            (Ordering::Greater, ComparisonReason::TrumpOverOffSuit)
+        );
+        assert_eq!(
+            detailed(cards::H_3, cards::D_A).1,
+            ComparisonReason::DifferentSuits
+        );
+        assert_eq!(
+            detailed(cards::H_K, cards::H_A),
+            (Ordering::Less, ComparisonReason::HigherNumber)
+        );
+        assert_eq!(
+            detailed(cards::H_4, cards::S_A),
+            (Ordering::Greater, ComparisonReason::TrumpNumber)
+        );
+        assert_eq!(
+            detailed(cards::S_4, cards::H_4),
+            (Ordering::Greater, ComparisonReason::TrumpNumber)
+        );
+        assert_eq!(
+            detailed(cards::D_4, cards::H_4),
+            (Ordering::Equal, ComparisonReason::EquallyRanked)
+        );
+        assert_eq!(
+            detailed(Card::SmallJoker, cards::S_4),
+            (Ordering::Greater, ComparisonReason::Joker)
+        );
+        assert_eq!(
+            detailed(cards::S_4, cards::S_4),
+            (Ordering::Equal, ComparisonReason::Identical)
+        );
+
+        let trump = trump.with_permanent(PermanentTrump::Card(cards::H_5));
+        assert_eq!(
+            trump.compare_detailed(cards::H_5, cards::S_4),
+            Comparison {
+                ordering: Ordering::Greater,
+                reason: ComparisonReason::General
+            }
+        );
+    }
+
+    #[test]
+    fn test_card_colors() {
+        assert_eq!(cards::D_5.color(ColorScheme::TwoColor), CardColor::Red);
+        assert_eq!(cards::D_5.color(ColorScheme::FourColor), CardColor::Blue);
+        assert_eq!(cards::C_5.color(ColorScheme::FourColor), CardColor::Green);
+        assert_eq!(Card::BigJoker.color(ColorScheme::FourColor), CardColor::Red);
+
+        let info = cards::C_K.as_info();
+        assert_eq!(info.color, CardColor::Black);
+        assert_eq!(info.four_color, CardColor::Green);
+        // Every suit is distinct in the four-color scheme.
+        let colors = ALL_SUITS
+            .iter()
+            .map(|s| s.color(ColorScheme::FourColor))
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(colors.len(), 4);
+    }
+
     #[test]
     fn test_serde() {
         let mut r = Rank::Number(Number::Two);
@@ -1253,4 +1995,16 @@ mod tests {
         assert_eq!(n, Rank::NoTrump);
         assert!(n.successor().is_none());
     }
+
+    #[test]
+    fn test_number_skipping() {
+        let four = Some(Number::Four);
+        assert_eq!(Number::Three.successor_skipping(four), Some(Number::Five));
+        assert_eq!(Number::Five.predecessor_skipping(four), Some(Number::Three));
+        assert_eq!(Number::Four.successor_skipping(four), Some(Number::Five));
+        assert_eq!(Number::Three.successor_skipping(None), Some(Number::Four));
+        assert_eq!(Number::King.successor_skipping(Some(Number::Ace)), None);
+        assert_eq!(Number::Three.predecessor_skipping(Some(Number::Two)), None);
+        assert_eq!(Suit::iter().collect::<Vec<_>>(), ALL_SUITS.to_vec());
+    }
 }